@@ -4,6 +4,7 @@ extern crate test;
 
 use contour::ContourBuilder;
 use contour::contour_rings;
+use contour::testing::noise;
 use test::{black_box, Bencher};
 
 static VALUES: [f64; 110] = [
@@ -67,3 +68,55 @@ fn bench_build_isoring(b: &mut Bencher) {
 fn bench_build_isoring_values2(b: &mut Bencher) {
     b.iter(|| black_box(contour_rings(&VALUES2, 1.5, 14, 17)));
 }
+
+// A 4096 x 4096 grid of pseudo-random noise, giving a large number of small rings.
+fn noise_grid() -> Vec<f64> {
+    noise(4096 * 4096, 0x2545_f491_4f6c_dd1d)
+}
+
+#[bench]
+fn bench_build_isoring_noise_4096(b: &mut Bencher) {
+    let values = noise_grid();
+    b.iter(|| black_box(contour_rings(&values, 0.5, 4096, 4096)));
+}
+
+#[bench]
+fn bench_build_geojson_contours_noise_4096(b: &mut Bencher) {
+    let values = noise_grid();
+    let c = ContourBuilder::new(4096, 4096, true);
+    b.iter(|| black_box(c.contours(&values, &[0.5])));
+}
+
+// To be compared with the previous one, using `--features bench,fast-unchecked`.
+#[cfg(feature = "fast-unchecked")]
+#[bench]
+fn bench_build_geojson_contours_noise_4096_unchecked(b: &mut Bencher) {
+    let values = noise_grid();
+    let c = ContourBuilder::new(4096, 4096, true);
+    b.iter(|| black_box(unsafe { c.contours_unchecked(&values, &[0.5]) }));
+}
+
+// A 2048 x 2048 grid, 60% of which is covered by one disc.
+fn blob_grid() -> Vec<f64> {
+    let radius2 = 0.6 * 2048. * 2048. / std::f64::consts::PI;
+    (0..2048 * 2048)
+        .map(|i| {
+            let (x, y) = ((i % 2048) as f64 - 1024., (i / 2048) as f64 - 1024.);
+            if x * x + y * y < radius2 { 1. } else { 0. }
+        })
+        .collect()
+}
+
+#[bench]
+fn bench_build_geojson_contours_blob(b: &mut Bencher) {
+    let values = blob_grid();
+    let c = ContourBuilder::new(2048, 2048, true);
+    b.iter(|| black_box(c.contours(&values, &[0.5])));
+}
+
+#[bench]
+fn bench_build_geojson_contours_blob_boundary_trace(b: &mut Bencher) {
+    let values = blob_grid();
+    let c = ContourBuilder::new(2048, 2048, true).algorithm(contour::Algorithm::BoundaryTrace);
+    b.iter(|| black_box(c.contours(&values, &[0.5])));
+}
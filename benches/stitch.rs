@@ -0,0 +1,30 @@
+//! Compares the stitching of the rings with the dense tables of `IsoRingBuilder::new` and
+//! with the hash maps of `IsoRingBuilder::with_hash_index`, on a 4096 x 4096 noise grid
+//! giving a large number of small rings (`cargo bench --bench stitch`).
+
+use contour::IsoRingBuilder;
+use contour::testing::noise;
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+const SIZE: u32 = 4096;
+
+fn bench_stitch(c: &mut Criterion) {
+    let values = noise((SIZE * SIZE) as usize, 0x2545_f491_4f6c_dd1d);
+    let mut group = c.benchmark_group("stitch_noise_4096");
+    group.sample_size(10);
+    group.bench_function("dense", |b| {
+        let mut isoring = IsoRingBuilder::new(SIZE, SIZE);
+        let mut rings = Vec::new();
+        b.iter(|| isoring.compute_into(black_box(&values), 0.5, &mut rings).unwrap());
+    });
+    group.bench_function("hashed", |b| {
+        let mut isoring = IsoRingBuilder::with_hash_index(SIZE, SIZE);
+        let mut rings = Vec::new();
+        b.iter(|| isoring.compute_into(black_box(&values), 0.5, &mut rings).unwrap());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_stitch);
+criterion_main!(benches);
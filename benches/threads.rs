@@ -0,0 +1,27 @@
+//! Compares the computation of the contours of a 4096 x 4096 noise grid in 1, 2, 4 and 8
+//! bands of rows, on a rayon thread pool of as many threads
+//! (`cargo bench --features rayon --bench threads`).
+
+use contour::ContourBuilder;
+use contour::testing::noise;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+const SIZE: u32 = 4096;
+
+fn bench_threads(c: &mut Criterion) {
+    let values = noise((SIZE * SIZE) as usize, 0x2545_f491_4f6c_dd1d);
+    let mut group = c.benchmark_group("threads_noise_4096");
+    group.sample_size(10);
+    for threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let builder = ContourBuilder::new(SIZE, SIZE, true).threads(threads);
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, _| {
+            b.iter(|| pool.install(|| builder.contours(black_box(&values), &[0.5]).unwrap()));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_threads);
+criterion_main!(benches);
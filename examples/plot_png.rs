@@ -0,0 +1,141 @@
+//! Draws the filled contours of a synthetic gaussian bump into a PNG image.
+//!
+//! Usage: `cargo run --example plot_png [output.png]`
+
+use contour::ContourBuilder;
+use contour::plot::{Canvas, DrawOptions, Rgb, draw_contours};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+const SIZE: u32 = 256;
+const GRID: u32 = 64;
+
+/// An RGB image filling polygons with the even-odd rule.
+struct Image {
+    pixels: Vec<Rgb>,
+}
+
+impl Canvas for Image {
+    fn fill_polygon(&mut self, points: &[(f64, f64)], color: Rgb) {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                if even_odd_contains(points, x as f64 + 0.5, y as f64 + 0.5) {
+                    self.pixels[(y * SIZE + x) as usize] = color;
+                }
+            }
+        }
+    }
+
+    fn stroke_polyline(&mut self, points: &[(f64, f64)], color: Rgb) {
+        for segment in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.);
+            for i in 0..=steps as u32 {
+                let t = i as f64 / steps;
+                let (x, y) = (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+                if x >= 0. && y >= 0. && x < SIZE as f64 && y < SIZE as f64 {
+                    self.pixels[(y as u32 * SIZE + x as u32) as usize] = color;
+                }
+            }
+        }
+    }
+}
+
+fn even_odd_contains(points: &[(f64, f64)], x: f64, y: f64) -> bool {
+    let mut inside = false;
+    let mut j = points.len().wrapping_sub(1);
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        let (xj, yj) = points[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+// Writes the image as an 8-bit RGB PNG, using uncompressed deflate blocks.
+fn write_png<W: Write>(mut writer: W, image: &Image) -> io::Result<()> {
+    let mut raw = Vec::with_capacity((SIZE * (3 * SIZE + 1)) as usize);
+    for row in image.pixels.chunks(SIZE as usize) {
+        raw.push(0);
+        raw.extend(row.iter().flat_map(|&(r, g, b)| [r, g, b]));
+    }
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(0xffff).peekable();
+    while let Some(block) = blocks.next() {
+        let len = block.len() as u16;
+        zlib.push(blocks.peek().is_none() as u8);
+        zlib.extend(len.to_le_bytes().iter().chain((!len).to_le_bytes().iter()));
+        zlib.extend(block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes().iter());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend(SIZE.to_be_bytes().iter().chain(SIZE.to_be_bytes().iter()));
+    header.extend([8, 2, 0, 0, 0].iter());
+
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+    write_chunk(&mut writer, b"IHDR", &header)?;
+    write_chunk(&mut writer, b"IDAT", &zlib)?;
+    write_chunk(&mut writer, b"IEND", &[])
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let mut crc = !0u32;
+    for byte in kind.iter().chain(data) {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    writer.write_all(&(!crc).to_be_bytes())
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn run(output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let center = GRID as f64 / 2.;
+    let values: Vec<f64> = (0..GRID * GRID)
+        .map(|i| {
+            let (x, y) = ((i % GRID) as f64 - center, (i / GRID) as f64 - center);
+            (-(x * x + y * y) / (2. * 12. * 12.)).exp()
+        })
+        .collect();
+    let thresholds = [0.1, 0.3, 0.5, 0.7, 0.9];
+    let contours = ContourBuilder::new(GRID, GRID, true).contours_geoarrow(&values, &thresholds)?;
+
+    let palette = [(254, 235, 226), (251, 180, 185), (247, 104, 161), (197, 27, 138), (122, 1, 119)];
+    let scale = SIZE as f64 / GRID as f64;
+    let options = DrawOptions { x_step: scale, y_step: scale, stroke: Some((0, 0, 0)), ..Default::default() };
+    let mut image = Image { pixels: vec![(255, 255, 255); (SIZE * SIZE) as usize] };
+    draw_contours(&mut image, &contours, &palette, &options);
+    write_png(File::create(output)?, &image)?;
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::env::args().nth(1).unwrap_or_else(|| String::from("contours.png"));
+    run(Path::new(&output))
+}
+
+#[test]
+fn writes_a_png() {
+    let output = std::env::temp_dir().join(format!("contour-plot-{}.png", std::process::id()));
+    run(&output).unwrap();
+    let bytes = std::fs::read(&output).unwrap();
+    assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+    assert!(bytes.ends_with(b"IEND\xae\x42\x60\x82"));
+    std::fs::remove_file(output).unwrap();
+}
@@ -0,0 +1,38 @@
+#![no_main]
+
+// Run with `cargo fuzz run contours` (the computation must fail rather than panic).
+
+use contour::{Algorithm, ContourBuilder, contour_rings};
+use libfuzzer_sys::fuzz_target;
+use std::convert::TryInto;
+
+// The input is a header (the dimensions of the grid, the options and the number
+// of thresholds) followed by the thresholds and the values, as little-endian `f64`s.
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 10 {
+        return;
+    }
+    let (header, data) = data.split_at(10);
+    let mut dx = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    let mut dy = u32::from_le_bytes(header[4..8].try_into().unwrap());
+    let flags = header[8];
+    if flags & 8 != 0 {
+        // Mostly grids whose dimensions match the values.
+        dx %= 64;
+        dy %= 64;
+    }
+    let floats = data
+        .chunks_exact(8)
+        .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    let (thresholds, values) = floats.split_at((header[9] as usize % 8).min(floats.len()));
+
+    let _ = contour_rings(values, thresholds.first().cloned().unwrap_or(0.), dx, dy);
+    let algorithm = if flags & 2 != 0 { Algorithm::BoundaryTrace } else { Algorithm::MarchingSquares };
+    let builder = ContourBuilder::new(dx, dy, flags & 1 != 0)
+        .algorithm(algorithm)
+        .threads(if flags & 4 != 0 { 3 } else { 1 })
+        .zonal_stats(flags & 16 != 0)
+        .bbox(flags & 32 != 0);
+    let _ = builder.contours(values, thresholds);
+});
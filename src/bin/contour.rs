@@ -0,0 +1,256 @@
+//! Command-line tool computing contour polygons of a grid
+//! and writing them as a GeoJSON FeatureCollection.
+//!
+//! Run `contour --help` for usage.
+
+use contour::ContourBuilder;
+use geojson::{FeatureCollection, GeoJson};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+const USAGE: &str = "\
+Usage: contour [OPTIONS] <INPUT>
+
+Computes contour polygons of a grid and writes them as a GeoJSON FeatureCollection.
+
+The grid is read from <INPUT> (use - for stdin), either as CSV (one row of the grid
+per line) or as raw little-endian floats (requires --width and --height).
+
+Options:
+    --format <csv|f32|f64>    Format of the input (default: csv)
+    --width <N>               Number of columns of the grid
+    --height <N>              Number of rows of the grid
+    --thresholds <T1,T2,..>   Comma separated threshold values
+    --levels <N>              Number of evenly spaced thresholds between the grid extent
+    --smooth                  Smooth the contours using linear interpolation
+    --origin <X,Y>            Coordinates of the origin of the grid (default: 0,0)
+    --step <SX,SY>            Size of a grid cell (default: 1,1)
+    --output <PATH>           Write the output to PATH instead of stdout
+    -h, --help                Print this help
+";
+
+#[derive(Debug)]
+struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<contour::Error> for CliError {
+    fn from(err: contour::Error) -> CliError {
+        CliError(err.to_string())
+    }
+}
+
+macro_rules! cli_error {
+    ($($arg:tt)*) => { CliError(format!($($arg)*)) }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    F32,
+    F64,
+}
+
+enum Levels {
+    Thresholds(Vec<f64>),
+    Count(usize),
+}
+
+struct Args {
+    input: String,
+    format: Format,
+    width: Option<usize>,
+    height: Option<usize>,
+    levels: Levels,
+    smooth: bool,
+    origin: (f64, f64),
+    step: (f64, f64),
+    output: Option<String>,
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T, CliError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| cli_error!("invalid value '{}' for {}", value, option))
+}
+
+fn parse_pair(option: &str, value: &str) -> Result<(f64, f64), CliError> {
+    let parts = value.split(',').collect::<Vec<_>>();
+    if parts.len() != 2 {
+        return Err(cli_error!("{} expects two comma separated values, got '{}'", option, value));
+    }
+    Ok((parse_number(option, parts[0])?, parse_number(option, parts[1])?))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, CliError> {
+    let mut input = None;
+    let mut format = Format::Csv;
+    let (mut width, mut height) = (None, None);
+    let mut levels = None;
+    let mut smooth = false;
+    let (mut origin, mut step) = ((0., 0.), (1., 1.));
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| cli_error!("missing value for {}", arg));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--format" => {
+                format = match value()?.as_str() {
+                    "csv" => Format::Csv,
+                    "f32" => Format::F32,
+                    "f64" => Format::F64,
+                    other => return Err(cli_error!("unknown format '{}' (expected csv, f32 or f64)", other)),
+                }
+            }
+            "--width" => width = Some(parse_number(&arg, &value()?)?),
+            "--height" => height = Some(parse_number(&arg, &value()?)?),
+            "--thresholds" => {
+                levels = Some(Levels::Thresholds(
+                    value()?
+                        .split(',')
+                        .map(|t| parse_number(&arg, t))
+                        .collect::<Result<_, _>>()?,
+                ))
+            }
+            "--levels" => levels = Some(Levels::Count(parse_number(&arg, &value()?)?)),
+            "--smooth" => smooth = true,
+            "--origin" => origin = parse_pair(&arg, &value()?)?,
+            "--step" => step = parse_pair(&arg, &value()?)?,
+            "--output" => output = Some(value()?),
+            _ if arg.starts_with("--") => return Err(cli_error!("unknown option {}", arg)),
+            _ if input.is_none() => input = Some(arg),
+            _ => return Err(cli_error!("unexpected argument '{}'", arg)),
+        }
+    }
+
+    Ok(Some(Args {
+        input: input.ok_or_else(|| cli_error!("missing input file"))?,
+        format,
+        width,
+        height,
+        levels: levels.ok_or_else(|| cli_error!("one of --thresholds or --levels is required"))?,
+        smooth,
+        origin,
+        step,
+        output,
+    }))
+}
+
+fn read_input(path: &str) -> Result<Vec<u8>, CliError> {
+    if path == "-" {
+        let mut buf = Vec::new();
+        io::Read::read_to_end(&mut io::stdin(), &mut buf).map_err(|e| cli_error!("cannot read stdin: {}", e))?;
+        Ok(buf)
+    } else {
+        fs::read(path).map_err(|e| cli_error!("cannot read '{}': {}", path, e))
+    }
+}
+
+fn parse_raw(data: &[u8], format: Format) -> Vec<f64> {
+    match format {
+        Format::F32 => data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+            .collect(),
+        _ => data
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))
+            .collect(),
+    }
+}
+
+fn evenly_spaced_levels(values: &[f64], count: usize) -> Vec<f64> {
+    let (min, max) = values
+        .iter()
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(*v), max.max(*v)));
+    if min > max {
+        return Vec::new();
+    }
+    (1..=count)
+        .map(|i| min + (max - min) * i as f64 / (count + 1) as f64)
+        .collect()
+}
+
+fn run(args: Args) -> Result<(), CliError> {
+    let data = read_input(&args.input)?;
+    let (values, width, height) = match args.format {
+        Format::Csv => {
+            let (values, width, height) = contour::io::read_csv_grid(&data[..])?;
+            (values, args.width.unwrap_or(width), args.height.unwrap_or(height))
+        }
+        format => {
+            let size = if format == Format::F32 { 4 } else { 8 };
+            let (width, height) = match (args.width, args.height) {
+                (Some(w), Some(h)) => (w, h),
+                _ => return Err(cli_error!("--width and --height are required for raw input")),
+            };
+            if data.len() != width * height * size {
+                return Err(cli_error!(
+                    "the input holds {} bytes but a {}x{} grid of {}-byte floats needs {}",
+                    data.len(),
+                    width,
+                    height,
+                    size,
+                    width * height * size
+                ));
+            }
+            (parse_raw(&data, format), width, height)
+        }
+    };
+    if values.len() != width * height {
+        return Err(cli_error!(
+            "the grid holds {} values, which doesn't match the {}x{} dimensions",
+            values.len(),
+            width,
+            height
+        ));
+    }
+    let thresholds = match args.levels {
+        Levels::Thresholds(thresholds) => thresholds,
+        Levels::Count(count) => evenly_spaced_levels(&values, count),
+    };
+
+    let features = ContourBuilder::new(width as u32, height as u32, args.smooth)
+        .x_origin(args.origin.0)
+        .y_origin(args.origin.1)
+        .x_step(args.step.0)
+        .y_step(args.step.1)
+        .contours(&values, &thresholds)?;
+    let geojson = GeoJson::from(FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    });
+
+    match args.output {
+        Some(path) => fs::write(&path, geojson.to_string()).map_err(|e| cli_error!("cannot write '{}': {}", path, e)),
+        None => {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            writeln!(handle, "{}", geojson).map_err(|e| cli_error!("cannot write to stdout: {}", e))
+        }
+    }
+}
+
+fn main() {
+    let result = parse_args(std::env::args().skip(1)).and_then(|args| match args {
+        Some(args) => run(args),
+        None => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+    });
+    if let Err(err) = result {
+        eprintln!("error: {}\n\nRun 'contour --help' for usage.", err);
+        process::exit(1);
+    }
+}
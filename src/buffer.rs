@@ -0,0 +1,163 @@
+//! Buffering (offsetting) of the polygons of contours by a distance, e.g. for safety
+//! margins around a flood extent (only available with the `buffer` feature).
+//!
+//! The buffered polygons are the contours of the signed distance to the polygons (negative
+//! inside them), sampled on a grid around them: growing the polygons rounds their convex
+//! corners with arcs and shrinks their holes, while shrinking them (with a negative distance)
+//! rounds their concave corners. The polygons merging or vanishing in the process are handled
+//! like any other contours, so that the output is always valid. The arcs are approximated
+//! by the segments of the contours of the distance, sampled every eighth of the distance
+//! (or every 1/1024 of the largest side of the bounding box of the polygons, if larger).
+//!
+//! The sampling bounds the precision: the buffered rings are within about a step of the
+//! exact offset, and the parts of the polygons (or the gaps between them) thinner than a
+//! step may be lost. It also bounds the work, to at most 1024 samples along each side of
+//! the grid: the distance of each sample is computed from the segments in the cells of
+//! side `|distance|` around it only (the farther samples being far enough from the
+//! buffered rings for their exact distance not to matter), and whether it is inside of
+//! the polygons from the crossings of its row, computed once per row.
+//!
+//! See [`ContourBuilder::buffer`] to buffer the contours as they're computed.
+//!
+//! [`ContourBuilder::buffer`]: ../struct.ContourBuilder.html#method.buffer
+
+use crate::contour::{ContourBuilder, LinearRing};
+use crate::error::Result;
+use geojson::Value;
+
+// The number of samples of the distance along the buffer distance.
+const SAMPLES_PER_DISTANCE: f64 = 8.;
+// The maximum number of samples along the largest side of the bounding box of the polygons.
+const MAX_SAMPLES: f64 = 1024.;
+
+/// Buffers the `polygons` (exterior rings followed by their holes, closed) by `distance`,
+/// growing them if it is positive and shrinking them if it is negative, and returns the
+/// resulting polygons, the ones overlapping each other being merged.
+///
+/// ```
+/// # use contour::buffer::buffer_polygons;
+/// # use contour::geom::area;
+/// let square = vec![vec![0., 0.], vec![0., 4.], vec![4., 4.], vec![4., 0.], vec![0., 0.]];
+/// let shrunk = buffer_polygons(&[vec![square]], -1.)?;
+/// assert_eq!(shrunk.len(), 1);
+/// // `area` being twice the signed area, the square of side 2 has an area of 8.
+/// assert!((area(&shrunk[0][0]).abs() - 8.).abs() < 0.1);
+/// # Ok::<(), contour::Error>(())
+/// ```
+pub fn buffer_polygons(polygons: &[Vec<LinearRing>], distance: f64) -> Result<Vec<Vec<LinearRing>>> {
+    let segments = polygons
+        .iter()
+        .flatten()
+        .flat_map(|ring| ring.windows(2).map(|s| [s[0][0], s[0][1], s[1][0], s[1][1]]))
+        .collect::<Vec<_>>();
+    if distance == 0. || !distance.is_finite() || segments.is_empty() {
+        return Ok(polygons.to_vec());
+    }
+    let (min_x, min_y, max_x, max_y) = segments.iter().fold(
+        (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        |(min_x, min_y, max_x, max_y), s| (min_x.min(s[0]), min_y.min(s[1]), max_x.max(s[0]), max_y.max(s[1])),
+    );
+    let step = (distance.abs() / SAMPLES_PER_DISTANCE).max((max_x - min_x).max(max_y - min_y) / MAX_SAMPLES);
+    // The cells along the border of the grid are outside of the buffered polygons.
+    let margin = distance.max(0.) + 2. * step;
+    let (x_origin, y_origin) = (min_x - margin, min_y - margin);
+    let dx = ((max_x - min_x + 2. * margin) / step).ceil() as usize;
+    let dy = ((max_y - min_y + 2. * margin) / step).ceil() as usize;
+    // The samples farther than `reach` from the rings only need to be on the right side
+    // of the threshold, their neighbors being too far from the buffered rings for them
+    // to be interpolated.
+    let reach = distance.abs() + 2. * step;
+    let cells = Cells::new(&segments, x_origin, y_origin, reach);
+    // The values are the opposite of the signed distance, the buffered polygons being
+    // the cells where it is at most `distance`.
+    let mut values = Vec::with_capacity(dx * dy);
+    let mut crossings = Vec::new();
+    for row in 0..dy {
+        let y = y_origin + (row as f64 + 0.5) * step;
+        // The positions where the rings cross the row, the samples after an odd number
+        // of them being inside of the polygons (by the even-odd rule).
+        crossings.clear();
+        crossings.extend(
+            segments
+                .iter()
+                .filter(|&&[_, y0, _, y1]| (y0 > y) != (y1 > y))
+                .map(|&[x0, y0, x1, y1]| x0 + (y - y0) * (x1 - x0) / (y1 - y0)),
+        );
+        crossings.sort_by(f64::total_cmp);
+        let mut crossed = 0;
+        for column in 0..dx {
+            let x = x_origin + (column as f64 + 0.5) * step;
+            while crossed < crossings.len() && crossings[crossed] <= x {
+                crossed += 1;
+            }
+            let distance = cells.distance(&segments, x, y).min(reach);
+            values.push(if crossed % 2 == 1 { distance } else { -distance });
+        }
+    }
+    let builder = ContourBuilder::new(dx as u32, dy as u32, true).x_origin(x_origin).y_origin(y_origin).x_step(step).y_step(step);
+    let feature = builder.contours(&values, &[-distance])?.swap_remove(0);
+    Ok(match feature.geometry.map(|g| g.value) {
+        Some(Value::MultiPolygon(polygons)) => polygons,
+        _ => Vec::new(),
+    })
+}
+
+// The indices of the segments (`[x0, y0, x1, y1]`) of the rings in each of the square cells
+// they cross, of side `side`, for the segments near a position to be looked up in the cells
+// around it only.
+struct Cells {
+    x_origin: f64,
+    y_origin: f64,
+    side: f64,
+    columns: usize,
+    rows: usize,
+    segments: Vec<Vec<usize>>,
+}
+
+impl Cells {
+    fn new(segments: &[[f64; 4]], x_origin: f64, y_origin: f64, side: f64) -> Self {
+        let (max_x, max_y) = segments
+            .iter()
+            .fold((x_origin, y_origin), |(max_x, max_y), s| (max_x.max(s[0]).max(s[2]), max_y.max(s[1]).max(s[3])));
+        let columns = ((max_x - x_origin) / side) as usize + 1;
+        let rows = ((max_y - y_origin) / side) as usize + 1;
+        let mut cells = Cells { x_origin, y_origin, side, columns, rows, segments: vec![Vec::new(); columns * rows] };
+        for (i, &[x0, y0, x1, y1]) in segments.iter().enumerate() {
+            // The cells of the bounding box of the segment.
+            let (c0, r0) = cells.cell(x0.min(x1), y0.min(y1));
+            let (c1, r1) = cells.cell(x0.max(x1), y0.max(y1));
+            for r in r0..=r1 {
+                for c in c0..=c1 {
+                    cells.segments[r * columns + c].push(i);
+                }
+            }
+        }
+        cells
+    }
+
+    fn cell(&self, x: f64, y: f64) -> (usize, usize) {
+        let column = ((x - self.x_origin) / self.side).max(0.) as usize;
+        let row = ((y - self.y_origin) / self.side).max(0.) as usize;
+        (column.min(self.columns - 1), row.min(self.rows - 1))
+    }
+
+    // The distance from `(x, y)` to the nearest of the segments, if it is at most the side
+    // of the cells (infinite otherwise).
+    fn distance(&self, segments: &[[f64; 4]], x: f64, y: f64) -> f64 {
+        let (column, row) = self.cell(x, y);
+        let mut squared = f64::INFINITY;
+        for r in row.saturating_sub(1)..=(row + 1).min(self.rows - 1) {
+            for c in column.saturating_sub(1)..=(column + 1).min(self.columns - 1) {
+                for &i in &self.segments[r * self.columns + c] {
+                    let [x0, y0, x1, y1] = segments[i];
+                    let (sx, sy) = (x1 - x0, y1 - y0);
+                    let length = sx * sx + sy * sy;
+                    let t = if length > 0. { (((x - x0) * sx + (y - y0) * sy) / length).clamp(0., 1.) } else { 0. };
+                    let (ex, ey) = (x0 + t * sx - x, y0 + t * sy - y);
+                    squared = squared.min(ex * ex + ey * ey);
+                }
+            }
+        }
+        squared.sqrt()
+    }
+}
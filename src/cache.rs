@@ -0,0 +1,176 @@
+//! Caching of the contours of grids computed repeatedly, e.g. by a service requested
+//! for the same unchanged grids (see [`ContourBuilder::contours_cached`]).
+//!
+//! The Feature of each threshold is cached under a hash of the values of the grid (their
+//! 64-bit XXH3 hash) and the threshold, along with the options of the builder which computed it: it is only
+//! reused by a builder with the same options (which include the dimensions of the grid).
+//! The least recently used Features are evicted once the cache is full.
+//!
+//! [`ContourBuilder::contours_cached`]: ../struct.ContourBuilder.html#method.contours_cached
+
+use crate::contour::ContourOptions;
+use geojson::Feature;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use xxhash_rust::xxh3::Xxh3;
+
+// The number of values hashed at once.
+const HASH_CHUNK: usize = 512;
+
+/// A bounded cache of the Features computed by [`ContourBuilder::contours_cached`],
+/// evicting the least recently used ones. It can be shared between threads.
+///
+/// ```
+/// # use contour::ContourBuilder;
+/// # use contour::cache::ContourCache;
+/// let cache = ContourCache::new(100);
+/// let c = ContourBuilder::new(2, 2, true);
+/// let features = c.contours_cached(&[0., 1., 1., 2.], &[0.5, 1.5], &cache)?;
+/// assert_eq!(c.contours_cached(&[0., 1., 1., 2.], &[0.5, 1.5], &cache)?, features);
+/// assert_eq!((cache.hits(), cache.misses(), cache.len()), (2, 2, 2));
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`ContourBuilder::contours_cached`]: ../struct.ContourBuilder.html#method.contours_cached
+#[derive(Debug)]
+pub struct ContourCache {
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    entries: HashMap<Key, Vec<Entry>>,
+    len: usize,
+    // Incremented on each use of an entry, to find the least recently used one.
+    clock: u64,
+    hits: usize,
+    misses: usize,
+}
+
+#[derive(Debug)]
+struct Entry {
+    options: ContourOptions,
+    feature: Feature,
+    used: u64,
+}
+
+// The hash of the values of a grid and the bits of a threshold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Key {
+    values: u64,
+    threshold: u64,
+}
+
+impl Key {
+    pub(crate) fn new(values_hash: u64, threshold: f64) -> Self {
+        Key { values: values_hash, threshold: threshold.to_bits() }
+    }
+}
+
+// Hashes the number of `values` and their bits (little-endian) with XXH3.
+pub(crate) fn hash_values(values: &[f64]) -> u64 {
+    let mut hasher = Xxh3::new();
+    hasher.update(&(values.len() as u64).to_le_bytes());
+    let mut bytes = [0; 8 * HASH_CHUNK];
+    for chunk in values.chunks(HASH_CHUNK) {
+        for (value, out) in chunk.iter().zip(bytes.chunks_exact_mut(8)) {
+            out.copy_from_slice(&value.to_bits().to_le_bytes());
+        }
+        hasher.update(&bytes[..8 * chunk.len()]);
+    }
+    hasher.digest()
+}
+
+impl ContourCache {
+    /// Creates a cache holding at most `capacity` Features (one per threshold of a grid).
+    pub fn new(capacity: usize) -> Self {
+        ContourCache { capacity, state: Mutex::new(State::default()) }
+    }
+
+    /// Returns the number of cached Features.
+    pub fn len(&self) -> usize {
+        self.state().len
+    }
+
+    /// Returns whether no Feature is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of Features found in the cache.
+    pub fn hits(&self) -> usize {
+        self.state().hits
+    }
+
+    /// Returns the number of Features not found in the cache, which were computed.
+    pub fn misses(&self) -> usize {
+        self.state().misses
+    }
+
+    /// Removes all the cached Features, keeping the counts of hits and misses.
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.entries.clear();
+        state.len = 0;
+    }
+
+    // Returns a clone of the cached Feature of `key` computed with `options`, if any,
+    // counting a hit or a miss.
+    pub(crate) fn get(&self, options: &ContourOptions, key: Key) -> Option<Feature> {
+        let mut state = self.state();
+        state.clock += 1;
+        let clock = state.clock;
+        let found = state.entries.get_mut(&key).and_then(|entries| entries.iter_mut().find(|entry| entry.options == *options)).map(|entry| {
+            entry.used = clock;
+            entry.feature.clone()
+        });
+        if found.is_some() {
+            state.hits += 1;
+        } else {
+            state.misses += 1;
+        }
+        found
+    }
+
+    // Caches the `feature` of `key` computed with `options`, evicting the least recently
+    // used Feature if the cache is full.
+    pub(crate) fn insert(&self, options: &ContourOptions, key: Key, feature: Feature) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state();
+        if state.len == self.capacity {
+            state.evict();
+        }
+        state.clock += 1;
+        let used = state.clock;
+        state.entries.entry(key).or_default().push(Entry { options: options.clone(), feature, used });
+        state.len += 1;
+    }
+
+    // Locks the state, even if another thread panicked while holding it (it stays consistent).
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl State {
+    // Removes the least recently used entry.
+    fn evict(&mut self) {
+        let oldest = self
+            .entries
+            .iter()
+            .flat_map(|(key, entries)| entries.iter().enumerate().map(move |(i, entry)| (entry.used, *key, i)))
+            .min_by_key(|(used, _, _)| *used);
+        if let Some((_, key, i)) = oldest {
+            if let Some(entries) = self.entries.get_mut(&key) {
+                entries.swap_remove(i);
+                if entries.is_empty() {
+                    self.entries.remove(&key);
+                }
+                self.len -= 1;
+            }
+        }
+    }
+}
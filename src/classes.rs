@@ -0,0 +1,70 @@
+//! Vectorization of classified rasters (such as land-cover codes): the polygons of the
+//! region of each distinct value of the grid.
+//!
+//! The cells are squares, the cell of the value at column `x` and row `y` being
+//! `[x, x + 1] * [y, y + 1]` (in grid coordinates): the boundaries follow the sides of the
+//! cells, and the boundary between the regions of two classes is the same in both (with
+//! the same positions, in opposite directions). The regions of the classes tile the grid.
+
+use crate::contour::{IsoRingBuilder, LinearRing, check_dimensions, group_holes};
+use crate::error::Result;
+use crate::geom::merge_collinear;
+
+/// Computes the polygons (the exterior ring followed by its holes) of the region of each
+/// distinct value of the `dx` * `dy` grid of `values`, in the order of their first cell.
+///
+/// The values not equal to themselves (NaN) belong to no class: their cells are left out.
+/// The polygons of a class may touch each other (or themselves) at a corner of the cells,
+/// where the class only touches itself diagonally.
+///
+/// ```
+/// # use contour::classes::vectorize_classes;
+/// # use contour::geom::area;
+/// let classes = vectorize_classes(&['a', 'a', 'b', 'a'], 2, 2)?;
+/// assert_eq!(classes.iter().map(|(class, _)| *class).collect::<Vec<_>>(), ['a', 'b']);
+/// // The cell of `b`, from (0, 1) to (1, 2), and the rest of the grid.
+/// let (a, b) = (&classes[0].1[0][0], &classes[1].1[0][0]);
+/// assert_eq!((a.len(), area(a) / 2.), (7, 3.));
+/// assert_eq!((b.len(), area(b) / 2.), (5, 1.));
+/// # Ok::<(), contour::Error>(())
+/// ```
+pub fn vectorize_classes<T: PartialEq + Copy>(values: &[T], dx: u32, dy: u32) -> Result<Vec<(T, Vec<Vec<LinearRing>>)>> {
+    check_dimensions(dx, dy, values.len())?;
+    let mut classes: Vec<T> = Vec::new();
+    for value in values {
+        #[allow(clippy::eq_op)]
+        let is_class = value == value;
+        if is_class && !classes.contains(value) {
+            classes.push(*value);
+        }
+    }
+    let mut isoring = IsoRingBuilder::new(dx, dy);
+    classes
+        .into_iter()
+        .map(|class| {
+            let mut rings = isoring.compute_by(&|i| if values[i] == class { 1. } else { 0. }, 0.5)?;
+            rings.iter_mut().for_each(square);
+            Ok((class, group_holes(rings)))
+        })
+        .collect()
+}
+
+// Turns the (unsmoothed) ring of the marching squares, whose positions are at the middle
+// of the sides of the cells, into the one following the sides: the segments joining two
+// perpendicular sides are replaced by the two half sides meeting at their common corner.
+fn square(ring: &mut LinearRing) {
+    let integer = |v: f64| v.fract() == 0.;
+    let mut squared = Vec::with_capacity(ring.len() * 2);
+    for w in ring.windows(2) {
+        squared.push(w[0].clone());
+        let (a, b) = (&w[0], &w[1]);
+        if integer(a[0]) && integer(b[1]) && !integer(a[1]) {
+            squared.push(vec![a[0], b[1]]);
+        } else if integer(a[1]) && integer(b[0]) && !integer(a[0]) {
+            squared.push(vec![b[0], a[1]]);
+        }
+    }
+    squared.extend(ring.last().cloned());
+    merge_collinear(&mut squared);
+    *ring = squared;
+}
@@ -0,0 +1,113 @@
+use crate::contour::LinearRing;
+#[cfg(feature = "clip")]
+use crate::error::{ErrorKind, Result, new_error};
+#[cfg(feature = "clip")]
+use geo::{BooleanOps, Coord, Intersects, LineString, MultiPolygon, Point, Polygon};
+
+// Builds the region given by its rings (the exterior ring, then the holes, not closed),
+// failing when one of them has less than 3 distinct positions.
+#[cfg(feature = "clip")]
+pub fn clip_region(rings: &[Vec<(f64, f64)>]) -> Result<Polygon<f64>> {
+    for (i, ring) in rings.iter().enumerate() {
+        let mut distinct = ring.iter().map(|&(x, y)| (x.to_bits(), y.to_bits())).collect::<Vec<_>>();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() < 3 {
+            return Err(new_error(ErrorKind::DegenerateClipRegion { ring: i }));
+        }
+    }
+    let line_string = |ring: &Vec<(f64, f64)>| LineString::from(ring.clone());
+    Ok(Polygon::new(line_string(&rings[0]), rings[1..].iter().map(line_string).collect()))
+}
+
+// Clips the given polygons (exterior ring first, then the holes) to the region, the
+// rings of the output keeping the orientation of the input ones.
+#[cfg(feature = "clip")]
+pub fn clip_polygons(polygons: Vec<Vec<LinearRing>>, region: &Polygon<f64>) -> Vec<Vec<LinearRing>> {
+    let orientation = match polygons.first() {
+        Some(polygon) => signed_area(polygon[0].iter().map(|p| (p[0], p[1]))).signum(),
+        None => return polygons,
+    };
+    let line_string = |ring: &LinearRing| ring.iter().map(|p| Coord { x: p[0], y: p[1] }).collect::<LineString<f64>>();
+    let subject = polygons
+        .iter()
+        .map(|polygon| Polygon::new(line_string(&polygon[0]), polygon[1..].iter().map(line_string).collect()))
+        .collect::<MultiPolygon<f64>>();
+    // The exterior ring has the orientation of the input exterior rings, the holes the other one.
+    let ring = |line_string: &LineString<f64>, orientation: f64| {
+        let mut ring = line_string.coords().map(|c| vec![c.x, c.y]).collect::<LinearRing>();
+        if signed_area(ring.iter().map(|p| (p[0], p[1]))).signum() != orientation {
+            ring.reverse();
+        }
+        ring
+    };
+    subject
+        .intersection(&MultiPolygon::new(vec![region.clone()]))
+        .into_iter()
+        .map(|polygon| {
+            let mut rings = vec![ring(polygon.exterior(), orientation)];
+            rings.extend(polygon.interiors().iter().map(|hole| ring(hole, -orientation)));
+            rings
+        })
+        .collect()
+}
+
+// Whether the given point is in the region (or on its boundary).
+#[cfg(feature = "clip")]
+pub fn clip_contains(region: &Polygon<f64>, point: (f64, f64)) -> bool {
+    region.intersects(&Point::new(point.0, point.1))
+}
+
+// Clips the given ring to the vertical strip between `x_min` and `x_max`, the positions
+// added on the sides of the strip having exactly `x_min` or `x_max` as x coordinate.
+pub fn clip_ring_to_strip(ring: &[Vec<f64>], x_min: f64, x_max: f64) -> Option<LinearRing> {
+    let at = |x: f64| move |p: (f64, f64), q: (f64, f64)| (x, p.1 + (x - p.0) / (q.0 - p.0) * (q.1 - p.1));
+    let points = clip_half_plane(open(ring), |p| p.0 >= x_min, at(x_min));
+    close(clip_half_plane(points, |p| p.0 <= x_max, at(x_max)))
+}
+
+// One step of the Sutherland–Hodgman algorithm: keeps the part of the (open) ring
+// made of `points` which is `inside` the half-plane.
+fn clip_half_plane<I, X>(points: Vec<(f64, f64)>, inside: I, intersection: X) -> Vec<(f64, f64)>
+where
+    I: Fn((f64, f64)) -> bool,
+    X: Fn((f64, f64), (f64, f64)) -> (f64, f64),
+{
+    let mut output = Vec::with_capacity(points.len());
+    for (j, current) in points.iter().enumerate() {
+        let previous = points[(j + points.len() - 1) % points.len()];
+        match (inside(previous), inside(*current)) {
+            (true, true) => output.push(*current),
+            (true, false) => output.push(intersection(previous, *current)),
+            (false, true) => {
+                output.push(intersection(previous, *current));
+                output.push(*current);
+            }
+            (false, false) => {}
+        }
+    }
+    output
+}
+
+fn open(ring: &[Vec<f64>]) -> Vec<(f64, f64)> {
+    let mut points: Vec<(f64, f64)> = ring.iter().map(|p| (p[0], p[1])).collect();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
+
+// Closes the ring made of `points`, unless it is empty or degenerate.
+fn close(mut points: Vec<(f64, f64)>) -> Option<LinearRing> {
+    if points.len() < 3 || signed_area(points.iter().cloned()) == 0. {
+        return None;
+    }
+    points.push(points[0]);
+    Some(points.into_iter().map(|(x, y)| vec![x, y]).collect())
+}
+
+fn signed_area<I: Iterator<Item = (f64, f64)> + Clone>(points: I) -> f64 {
+    let first = points.clone().next();
+    let next = points.clone().skip(1).chain(first);
+    points.zip(next).map(|(a, b)| a.0 * b.1 - b.0 * a.1).sum::<f64>() / 2.
+}
@@ -1,18 +1,82 @@
-use crate::area::{area, contains};
+#[cfg(feature = "buffer")]
+use crate::buffer::buffer_polygons;
+use crate::cache::{ContourCache, Key, hash_values};
+use crate::geom::{Metric, area, contains, densify, merge_collinear, ring_contains};
+use crate::clip::clip_ring_to_strip;
+#[cfg(feature = "clip")]
+use crate::clip::{clip_contains, clip_polygons, clip_region};
+use crate::diff::ContourDiff;
 use crate::error::{ErrorKind, Result, new_error};
-use geojson::Value::MultiPolygon;
-use geojson::{Feature, Geometry};
+use crate::extrema::{ExtremaOptions, Extremum, ExtremumKind, find_extrema};
+use crate::geoarrow::MultiPolygonArray;
+use crate::histogram::{Extent, bin_points};
+use crate::instrument::{SpanKind, enter, enter_contours};
+use crate::kml::{KmlOptions, to_kml};
+use crate::mesh::Mesh;
+#[cfg(feature = "mmap")]
+use crate::mmap::{Endianness, MappedGrid, RawType};
+#[cfg(feature = "png")]
+use crate::plot::Rgb;
+#[cfg(feature = "png")]
+use crate::png::{PngOptions, render_png};
+use crate::polyline::{EncodedContour, EncodedRing, encode};
+use crate::raster::rasterize;
+use crate::pad::{PadMode, pad_grid};
+use crate::resample::resample_bilinear;
+use crate::ring::{Ring, RingDirection, Side, Winding, higher_side, normalize, open, tangent_angles};
+use crate::smooth::{GridView, LinearSmoother, VertexSmoother, smooth_linear};
+use crate::source::GridSource;
+use crate::svg::{SvgStyle, to_svg, write_svg};
+use crate::stats::{GridStats, ThresholdDiagnostics, ValidationReport};
+#[cfg(feature = "stream")]
+use crate::stream::ContourStream;
+#[cfg(feature = "stream")]
+use crate::style::apply_simplestyle_over;
+use crate::style::{SimpleStyle, apply_simplestyle};
+use crate::trace::trace_rings;
+use crate::validate::{Intersection, SegmentId, find_intersections, repair};
+use crate::wkb::ewkb_multipolygon;
+use crate::zonal::ZonalStats;
+#[cfg(feature = "arrow")]
+use arrow_array::RecordBatch;
+use geojson::{Feature, Geometry, Value};
 use lazy_static::lazy_static;
-use rustc_hash::FxHashMap;
+#[cfg(feature = "nalgebra")]
+use nalgebra::DMatrix;
+use serde::Serialize;
+use serde_json::json;
 use serde_json::map::Map;
 use serde_json::to_value;
+#[cfg(feature = "proj")]
+use proj4rs::{Proj, adaptors::transform_xy};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use rustc_hash::FxHashMap;
 use slab::Slab;
+use smallvec::SmallVec;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+#[cfg(feature = "rayon")]
+use std::ops::Range;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+use std::result;
+use std::sync::Arc;
+#[cfg(feature = "rayon")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "stream")]
+use tokio::runtime::Handle;
+#[cfg(feature = "stream")]
+use tokio::sync::mpsc;
 
 pub type Pt = Vec<f64>;
-pub type Ring = Vec<Pt>;
+pub type LinearRing = Vec<Pt>;
 
 lazy_static! {
-    static ref CASES: Vec<Vec<Vec<Vec<f64>>>> = vec![
+    pub(crate) static ref CASES: Vec<Vec<Vec<Vec<f64>>>> = vec![
         vec![],
         vec![vec![vec![1.0, 1.5], vec![0.5, 1.0]]],
         vec![vec![vec![1.5, 1.0], vec![1.0, 1.5]]],
@@ -38,127 +102,2736 @@ lazy_static! {
     ];
 }
 
+// The positions of the fragments are stored inline, and only converted to `Pt`
+// (one allocation each) once their ring is closed.
+type FragmentPt = [f64; 2];
+
+// The positions of a fragment, without allocation up to `MIN_FRAGMENT_CAPACITY` of them
+// (enough for most rings of speckled grids).
+type FragmentRing = SmallVec<[FragmentPt; MIN_FRAGMENT_CAPACITY]>;
+
 #[derive(Clone, Debug)]
 struct Fragment {
     start: usize,
     end: usize,
-    ring: Ring,
+    ring: FragmentRing,
+}
+
+/// The kind of geometry used for the Features generated by [`ContourBuilder`].
+///
+/// [`ContourBuilder`]: struct.ContourBuilder.html
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GeometryMode {
+    /// Always emit a `MultiPolygon` (default).
+    #[default]
+    MultiPolygon,
+    /// Emit a `Polygon` when the threshold has exactly one polygon
+    /// (with its holes) and a `MultiPolygon` otherwise.
+    Auto,
+}
+
+/// How a threshold without any polygon is represented in the generated Features.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EmptyGeometry {
+    /// Emit an empty `MultiPolygon` (default).
+    #[default]
+    MultiPolygon,
+    /// Emit a Feature with `geometry: None`.
+    Null,
+}
+
+/// The algorithm finding the rings of each threshold (see [`ContourBuilder::algorithm`]).
+///
+/// [`ContourBuilder::algorithm`]: struct.ContourBuilder.html#method.algorithm
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Algorithm {
+    /// Classify every cell of the grid and stitch their segments into rings (default).
+    #[default]
+    MarchingSquares,
+    /// Find a cell of each ring by scanning the rows of the grid, and walk along the ring
+    /// from there, only classifying the cells on the boundaries: faster on grids made
+    /// of large regions above or below the threshold.
+    BoundaryTrace,
+}
+
+/// What to do with the positions that can't be reprojected
+/// (see [`ContourBuilder::reprojection_failure`]).
+///
+/// [`ContourBuilder::reprojection_failure`]: struct.ContourBuilder.html#method.reprojection_failure
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReprojectionFailure {
+    /// Fail with an [`ErrorKind::Reprojection`] error (default).
+    ///
+    /// [`ErrorKind::Reprojection`]: enum.ErrorKind.html#variant.Reprojection
+    #[default]
+    Error,
+    /// Drop the position, without error. The rings left with fewer than 4 positions
+    /// (the closing one included, i.e. with less than three distinct positions) are
+    /// silently dropped as well, along with the polygons whose exterior ring is dropped:
+    /// only [`ThresholdDiagnostics::dropped`] counts them (see
+    /// [`ContourBuilder::contours_with_diagnostics`]).
+    ///
+    /// [`ThresholdDiagnostics::dropped`]: stats/struct.ThresholdDiagnostics.html#structfield.dropped
+    /// [`ContourBuilder::contours_with_diagnostics`]: struct.ContourBuilder.html#method.contours_with_diagnostics
+    Drop,
+}
+
+/// How the polygons are validated once smoothed (see [`ContourBuilder::validate_output`]).
+///
+/// [`ContourBuilder::validate_output`]: struct.ContourBuilder.html#method.validate_output
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Validation {
+    /// No validation (default).
+    #[default]
+    Off,
+    /// Fail with an [`ErrorKind::InvalidGeometry`] error listing the [`Intersection`]s
+    /// of the first threshold whose polygons aren't valid.
+    ///
+    /// [`ErrorKind::InvalidGeometry`]: enum.ErrorKind.html#variant.InvalidGeometry
+    /// [`Intersection`]: struct.Intersection.html
+    Check,
+    /// Move the positions of the intersecting segments back to their unsmoothed
+    /// positions (the middle of their edge of the grid), until the polygons are valid.
+    Repair,
+}
+
+/// The directions of the rings written to the properties of the Features
+/// (see [`ContourBuilder::ring_directions`]).
+///
+/// [`ContourBuilder::ring_directions`]: struct.ContourBuilder.html#method.ring_directions
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RingDirections {
+    /// No direction (default).
+    #[default]
+    Off,
+    /// The side of the higher values of each ring, in the `higher_side` property.
+    HigherSide,
+    /// The side of the higher values of each ring, and the tangent angles at its
+    /// positions in the `tangent_angles` property.
+    WithTangentAngles,
+}
+
+/// How the values within the epsilon of a threshold are classified
+/// (see [`ContourBuilder::threshold_epsilon`]).
+///
+/// [`ContourBuilder::threshold_epsilon`]: struct.ContourBuilder.html#method.threshold_epsilon
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EpsilonRounding {
+    /// Take them as equal to the threshold, inside the polygons (default).
+    #[default]
+    Up,
+    /// Take them as right below the threshold, outside the polygons.
+    Down,
+}
+
+/// Whether the rings end with a repetition of their first position
+/// (see [`ContourBuilder::ring_closure`]).
+///
+/// [`ContourBuilder::ring_closure`]: struct.ContourBuilder.html#method.ring_closure
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RingClosure {
+    /// Repeat the first position at the end of the rings, as required by GeoJSON (default).
+    #[default]
+    Closed,
+    /// Leave out the repeated position, the last position being implicitly joined to the first one.
+    Open,
+}
+
+/// The options of a [`ContourBuilder`], all in one place: the methods of the builder
+/// set them, and a builder can be created from them with `ContourBuilder::from`.
+///
+/// The options are built from their default values (a `0` by `0` smoothed grid,
+/// the defaults of the methods of the builder otherwise), the fields being set one by one:
+///
+/// ```
+/// # use contour::{ContourBuilder, ContourOptions};
+/// let mut options = ContourOptions::default();
+/// options.dx = 10;
+/// options.dy = 20;
+/// options.bbox = true;
+/// let c = ContourBuilder::from(options);
+/// assert_eq!(c, ContourBuilder::new(10, 20, true).bbox(true));
+/// ```
+///
+/// The [`reproject_with`] transformation, the [`smoother`] and the [`value_transform`] can only be set
+/// with the methods of the builder; the options sharing them compare equal.
+///
+/// [`ContourBuilder`]: struct.ContourBuilder.html
+/// [`reproject_with`]: struct.ContourBuilder.html#method.reproject_with
+/// [`smoother`]: struct.ContourBuilder.html#method.smoother
+/// [`value_transform`]: struct.ContourBuilder.html#method.value_transform
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContourOptions {
+    /// The number of columns of the grid.
+    pub dx: u32,
+    /// The number of rows of the grid.
+    pub dy: u32,
+    /// Whether the rings are smoothed using linear interpolation.
+    pub smooth: bool,
+    /// See [`ContourBuilder::geometry_mode`](struct.ContourBuilder.html#method.geometry_mode).
+    pub geometry_mode: GeometryMode,
+    /// See [`ContourBuilder::empty_geometry`](struct.ContourBuilder.html#method.empty_geometry).
+    pub empty_geometry: EmptyGeometry,
+    /// See [`ContourBuilder::z`](struct.ContourBuilder.html#method.z).
+    pub z: bool,
+    /// See [`ContourBuilder::crs`](struct.ContourBuilder.html#method.crs).
+    pub crs: Option<String>,
+    /// See [`ContourBuilder::x_origin`](struct.ContourBuilder.html#method.x_origin).
+    pub x_origin: f64,
+    /// See [`ContourBuilder::y_origin`](struct.ContourBuilder.html#method.y_origin).
+    pub y_origin: f64,
+    /// See [`ContourBuilder::x_step`](struct.ContourBuilder.html#method.x_step).
+    pub x_step: f64,
+    /// See [`ContourBuilder::y_step`](struct.ContourBuilder.html#method.y_step).
+    pub y_step: f64,
+    /// See [`ContourBuilder::bbox`](struct.ContourBuilder.html#method.bbox).
+    pub bbox: bool,
+    /// See [`ContourBuilder::zonal_stats`](struct.ContourBuilder.html#method.zonal_stats).
+    pub zonal_stats: bool,
+    /// See [`ContourBuilder::area`](struct.ContourBuilder.html#method.area).
+    pub area: bool,
+    /// See [`ContourBuilder::perimeter`](struct.ContourBuilder.html#method.perimeter).
+    pub perimeter: bool,
+    /// See [`ContourBuilder::metric`](struct.ContourBuilder.html#method.metric).
+    pub metric: Metric,
+    /// See [`ContourBuilder::reprojection_failure`](struct.ContourBuilder.html#method.reprojection_failure).
+    pub reprojection_failure: ReprojectionFailure,
+    /// The rings of the region set by
+    /// [`ContourBuilder::clip_to_polygon`](struct.ContourBuilder.html#method.clip_to_polygon)
+    /// (the exterior ring, then the holes), not closed (only applied with the `clip` feature).
+    pub clip: Option<Vec<Vec<(f64, f64)>>>,
+    /// See [`ContourBuilder::antimeridian`](struct.ContourBuilder.html#method.antimeridian).
+    pub antimeridian: bool,
+    /// The expected number of rings per threshold and number of positions per ring, see
+    /// [`ContourBuilder::with_capacity_hint`](struct.ContourBuilder.html#method.with_capacity_hint).
+    pub capacity_hint: (usize, usize),
+    /// See [`ContourBuilder::threads`](struct.ContourBuilder.html#method.threads)
+    /// (only applied with the `rayon` feature).
+    pub threads: usize,
+    /// See [`ContourBuilder::algorithm`](struct.ContourBuilder.html#method.algorithm).
+    pub algorithm: Algorithm,
+    /// See [`ContourBuilder::max_rings_per_threshold`](struct.ContourBuilder.html#method.max_rings_per_threshold).
+    pub max_rings_per_threshold: Option<usize>,
+    /// See [`ContourBuilder::max_total_vertices`](struct.ContourBuilder.html#method.max_total_vertices).
+    pub max_total_vertices: Option<usize>,
+    /// See [`ContourBuilder::normalize_rings`](struct.ContourBuilder.html#method.normalize_rings).
+    pub normalize_rings: bool,
+    /// See [`ContourBuilder::union_touching`](struct.ContourBuilder.html#method.union_touching).
+    pub union_touching: bool,
+    /// See [`ContourBuilder::merge_collinear`](struct.ContourBuilder.html#method.merge_collinear).
+    pub merge_collinear: bool,
+    /// The maximum length of the segments, see
+    /// [`ContourBuilder::densify`](struct.ContourBuilder.html#method.densify).
+    pub densify: Option<f64>,
+    /// See [`ContourBuilder::validate_output`](struct.ContourBuilder.html#method.validate_output).
+    pub validation: Validation,
+    /// See [`ContourBuilder::strict_input`](struct.ContourBuilder.html#method.strict_input).
+    pub strict_input: bool,
+    /// See [`ContourBuilder::verify_nesting`](struct.ContourBuilder.html#method.verify_nesting).
+    pub verify_nesting: bool,
+    /// See [`ContourBuilder::simplestyle`](struct.ContourBuilder.html#method.simplestyle).
+    pub simplestyle: Option<SimpleStyle>,
+    /// See [`ContourBuilder::ring_directions`](struct.ContourBuilder.html#method.ring_directions).
+    pub ring_directions: RingDirections,
+    /// See [`ContourBuilder::threshold_epsilon`](struct.ContourBuilder.html#method.threshold_epsilon).
+    pub threshold_epsilon: f64,
+    /// See [`ContourBuilder::epsilon_rounding`](struct.ContourBuilder.html#method.epsilon_rounding).
+    pub epsilon_rounding: EpsilonRounding,
+    /// The period of circular values, see
+    /// [`ContourBuilder::circular`](struct.ContourBuilder.html#method.circular).
+    pub period: Option<f64>,
+    /// See [`ContourBuilder::buffer`](struct.ContourBuilder.html#method.buffer)
+    /// (only applied with the `buffer` feature).
+    pub buffer: Option<f64>,
+    /// See [`ContourBuilder::ring_closure`](struct.ContourBuilder.html#method.ring_closure).
+    pub ring_closure: RingClosure,
+    /// See [`ContourBuilder::enclosures`](struct.ContourBuilder.html#method.enclosures).
+    pub enclosures: bool,
+    /// See [`ContourBuilder::replace_properties`](struct.ContourBuilder.html#method.replace_properties).
+    pub replace_properties: bool,
+    transformer: Option<Reprojection>,
+    smoother: Option<Smoother>,
+    value_transform: Option<ValueTransform>,
+}
+
+impl Default for ContourOptions {
+    fn default() -> Self {
+        ContourOptions {
+            dx: 0,
+            dy: 0,
+            smooth: true,
+            geometry_mode: GeometryMode::default(),
+            empty_geometry: EmptyGeometry::default(),
+            z: false,
+            crs: None,
+            x_origin: 0.,
+            y_origin: 0.,
+            x_step: 1.,
+            y_step: 1.,
+            bbox: false,
+            zonal_stats: false,
+            area: false,
+            perimeter: false,
+            metric: Metric::default(),
+            reprojection_failure: ReprojectionFailure::default(),
+            clip: None,
+            antimeridian: false,
+            capacity_hint: (0, 0),
+            threads: 1,
+            algorithm: Algorithm::default(),
+            max_rings_per_threshold: None,
+            max_total_vertices: None,
+            normalize_rings: false,
+            union_touching: false,
+            merge_collinear: false,
+            densify: None,
+            validation: Validation::default(),
+            strict_input: false,
+            verify_nesting: false,
+            simplestyle: None,
+            ring_directions: RingDirections::default(),
+            threshold_epsilon: 0.,
+            epsilon_rounding: EpsilonRounding::default(),
+            period: None,
+            buffer: None,
+            ring_closure: RingClosure::default(),
+            enclosures: false,
+            replace_properties: false,
+            transformer: None,
+            smoother: None,
+            value_transform: None,
+        }
+    }
+}
+
+// The factory of the transformation set by `ContourBuilder::reproject_with`, compared by identity.
+#[derive(Clone)]
+struct Reprojection(TransformerFactory);
+
+impl PartialEq for Reprojection {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const () == Arc::as_ptr(&other.0) as *const ()
+    }
+}
+
+impl fmt::Debug for Reprojection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Reprojection(..)")
+    }
+}
+
+// The smoother set by `ContourBuilder::smoother`, compared by identity.
+#[derive(Clone)]
+struct Smoother(Arc<dyn VertexSmoother>);
+
+impl PartialEq for Smoother {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const () == Arc::as_ptr(&other.0) as *const ()
+    }
+}
+
+impl fmt::Debug for Smoother {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Smoother(..)")
+    }
+}
+
+// The function set by `ContourBuilder::value_transform`, compared by identity.
+#[derive(Clone)]
+struct ValueTransform(Arc<dyn Fn(f64) -> f64 + Send + Sync>);
+
+impl PartialEq for ValueTransform {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0) as *const () == Arc::as_ptr(&other.0) as *const ()
+    }
+}
+
+impl fmt::Debug for ValueTransform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ValueTransform(..)")
+    }
+}
+
+/// Contours generator to
+/// be used on a rectangular `Slice` of values to
+/// get a `Vec` of Features of MultiPolygon (use [`contour_rings`] internally).
+///
+/// Its options are stored in a [`ContourOptions`] (see [`options`]).
+///
+/// [`contour_rings`]: fn.contour_rings.html
+/// [`ContourOptions`]: struct.ContourOptions.html
+/// [`options`]: #method.options
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContourBuilder {
+    options: ContourOptions,
+    thresholds: Vec<f64>,
+    // The `properties` of the Features of each of the stored thresholds.
+    threshold_properties: Vec<Map<String, serde_json::Value>>,
+}
+
+impl From<ContourOptions> for ContourBuilder {
+    fn from(options: ContourOptions) -> Self {
+        ContourBuilder {
+            options,
+            thresholds: Vec::new(),
+            threshold_properties: Vec::new(),
+        }
+    }
+}
+
+impl ContourBuilder {
+    /// Constructs a new contours generator for a grid with `dx` * `dy` dimension.
+    ///
+    /// # Arguments
+    ///
+    /// * `dx` - The number of columns in the grid.
+    /// * `dy` - The number of rows in the grid.
+    /// * `smooth` - Whether or not the generated rings will be smoothed using linear interpolation.
+    pub fn new(dx: u32, dy: u32, smooth: bool) -> Self {
+        ContourBuilder::from(ContourOptions { dx, dy, smooth, ..ContourOptions::default() })
+    }
+
+    /// Returns the options of the builder.
+    pub fn options(&self) -> &ContourOptions {
+        &self.options
+    }
+
+    /// Sets whether the bounding box (`[min x, min y, max x, max y]`) of the geometry
+    /// of each generated Feature is computed and stored in its `bbox` member
+    /// (default: `false`). Features without polygons don't get a bounding box.
+    pub fn bbox(mut self, bbox: bool) -> Self {
+        self.options.bbox = bbox;
+        self
+    }
+
+    /// Sets whether statistics of the grid values inside the polygons of each generated
+    /// Feature are computed (default: `false`): the number of cells in the polygons
+    /// (their holes excluded) is stored in the `cells` property and the mean, minimum and
+    /// maximum of their values in the `mean`, `min` and `max` properties (`null` without cells).
+    ///
+    /// A cell is in a polygon when its center is, so the smoothing of the rings may make
+    /// cells whose value is below the threshold count.
+    pub fn zonal_stats(mut self, zonal_stats: bool) -> Self {
+        self.options.zonal_stats = zonal_stats;
+        self
+    }
+
+    /// Sets whether the area of the polygons of each generated Feature (their holes
+    /// excluded) is computed and stored in its `area` property (default: `false`),
+    /// in output units (see [`x_step`]) or as set by [`metric`].
+    ///
+    /// The area of the cells above the threshold, without the polygons, is computed by
+    /// [`area_above_thresholds`].
+    ///
+    /// [`x_step`]: #method.x_step
+    /// [`metric`]: #method.metric
+    /// [`area_above_thresholds`]: fn.area_above_thresholds.html
+    pub fn area(mut self, area: bool) -> Self {
+        self.options.area = area;
+        self
+    }
+
+    /// Sets whether the length of the rings of the polygons of each generated Feature
+    /// (their holes included) is computed and stored in its `perimeter` property
+    /// (default: `false`), in output units or as set by [`metric`].
+    ///
+    /// [`metric`]: #method.metric
+    pub fn perimeter(mut self, perimeter: bool) -> Self {
+        self.options.perimeter = perimeter;
+        self
+    }
+
+    /// Sets how the `area` and `perimeter` properties (see [`area`] and [`perimeter`])
+    /// are computed from the output coordinates (default: [`Metric::Planar`]): for
+    /// longitudes and latitudes, [`Metric::Spherical`] or [`Metric::Wgs84`] give them
+    /// in meters (and square meters).
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// # use contour::geom::Metric;
+    /// // A diamond of half a square degree at the equator, in square meters.
+    /// let c = ContourBuilder::new(3, 3, false).x_origin(-1).y_origin(-1).area(true).metric(Metric::Wgs84);
+    /// let features = c.contours(&[0., 0., 0., 0., 1., 0., 0., 0., 0.], &[0.5])?;
+    /// let area = features[0].properties.as_ref().unwrap()["area"].as_f64().unwrap();
+    /// assert!((area / 1e6 - 6_154.).abs() < 1.);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`area`]: #method.area
+    /// [`perimeter`]: #method.perimeter
+    /// [`Metric::Planar`]: geom/enum.Metric.html#variant.Planar
+    /// [`Metric::Spherical`]: geom/enum.Metric.html#variant.Spherical
+    /// [`Metric::Wgs84`]: geom/enum.Metric.html#variant.Wgs84
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.options.metric = metric;
+        self
+    }
+
+    /// Sets the x coordinate of the origin of the grid (default: `0`).
+    ///
+    /// Output coordinates are computed as `x * x_step + x_origin`
+    /// and `y * y_step + y_origin`, `x` and `y` being the grid coordinates.
+    pub fn x_origin(mut self, x_origin: impl Into<f64>) -> Self {
+        self.options.x_origin = x_origin.into();
+        self
+    }
+
+    /// Sets the y coordinate of the origin of the grid (default: `0`).
+    pub fn y_origin(mut self, y_origin: impl Into<f64>) -> Self {
+        self.options.y_origin = y_origin.into();
+        self
+    }
+
+    /// Sets the size of a grid cell along the x axis (default: `1`).
+    pub fn x_step(mut self, x_step: impl Into<f64>) -> Self {
+        self.options.x_step = x_step.into();
+        self
+    }
+
+    /// Sets the size of a grid cell along the y axis (default: `1`).
+    /// Use a negative value for grids whose first row is the northernmost one.
+    pub fn y_step(mut self, y_step: impl Into<f64>) -> Self {
+        self.options.y_step = y_step.into();
+        self
+    }
+
+    /// Reprojects every output position from the `from` coordinate reference system to the
+    /// `to` one, after the origin and the step of the grid have been applied.
+    ///
+    /// The reference systems are given as PROJ.4 strings (like
+    /// `"+proj=utm +zone=33 +datum=WGS84"`, or `"WGS84"` for longitudes and latitudes)
+    /// understood by [proj4rs](https://docs.rs/proj4rs), a pure Rust port of PROJ.4:
+    /// EPSG codes aren't. Longitudes and latitudes are in degrees. The projections are
+    /// created once per computation (e.g. per call to [`contours`]), and an invalid string
+    /// fails it with an [`ErrorKind::Reprojection`] error. What to do with the positions
+    /// outside the domain of a projection is set by [`reprojection_failure`].
+    ///
+    /// ```
+    /// # #[cfg(feature = "proj")]
+    /// # {
+    /// use contour::ContourBuilder;
+    ///
+    /// let c = ContourBuilder::new(3, 3, false)
+    ///     .x_origin(500_000.)
+    ///     .y_origin(4_649_776.)
+    ///     .x_step(1_000.)
+    ///     .y_step(1_000.)
+    ///     .reproject("+proj=utm +zone=33 +datum=WGS84", "WGS84");
+    /// let res = c.contours(&[0., 0., 0., 0., 1., 0., 0., 0., 0.], &[0.5]).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// Only available with the `proj` feature (see [`reproject_with`] for other
+    /// transformations, e.g. with the [`proj`](https://docs.rs/proj) crate).
+    ///
+    /// [`contours`]: #method.contours
+    /// [`reprojection_failure`]: #method.reprojection_failure
+    /// [`reproject_with`]: #method.reproject_with
+    /// [`ErrorKind::Reprojection`]: enum.ErrorKind.html#variant.Reprojection
+    #[cfg(feature = "proj")]
+    pub fn reproject(self, from: &str, to: &str) -> Self {
+        let (from, to) = (from.to_string(), to.to_string());
+        self.reproject_with(move || {
+            let (from, to) = (Proj::from_user_string(&from)?, Proj::from_user_string(&to)?);
+            Ok::<_, proj4rs::errors::Error>(move |x: f64, y: f64| {
+                let (x, y) = if from.is_latlong() { (x.to_radians(), y.to_radians()) } else { (x, y) };
+                let (x, y) = transform_xy(&from, &to, x, y)?;
+                Ok::<_, proj4rs::errors::Error>(if to.is_latlong() { (x.to_degrees(), y.to_degrees()) } else { (x, y) })
+            })
+        })
+    }
+
+    /// Sets a transformation (typically a reprojection) applied to every output position,
+    /// after the origin and the step of the grid have been applied.
+    ///
+    /// `factory` is called once per computation (e.g. per call to [`contours`]) to create
+    /// the function transforming the `(x, y)` positions, so that expensive state is only
+    /// created once. For instance, with the [`proj`](https://docs.rs/proj) crate:
+    ///
+    /// ```ignore
+    /// use contour::ContourBuilder;
+    /// use proj::Proj;
+    ///
+    /// let c = ContourBuilder::new(dx, dy, true)
+    ///     .x_origin(x_origin)
+    ///     .y_origin(y_origin)
+    ///     .x_step(x_step)
+    ///     .y_step(y_step)
+    ///     .reproject_with(|| {
+    ///         let proj = Proj::new_known_crs("EPSG:32633", "EPSG:4326", None)?;
+    ///         Ok::<_, proj::ProjCreateError>(move |x, y| proj.convert((x, y)))
+    ///     });
+    /// ```
+    ///
+    /// Errors returned by `factory` (and by the transformation, unless the positions are
+    /// dropped, see [`reprojection_failure`]) are returned as [`ErrorKind::Reprojection`] errors.
+    ///
+    /// [`contours`]: #method.contours
+    /// [`reprojection_failure`]: #method.reprojection_failure
+    /// [`ErrorKind::Reprojection`]: enum.ErrorKind.html#variant.Reprojection
+    pub fn reproject_with<F, T, E1, E2>(mut self, factory: F) -> Self
+    where
+        F: Fn() -> result::Result<T, E1> + Send + Sync + 'static,
+        T: FnMut(f64, f64) -> result::Result<(f64, f64), E2> + 'static,
+        E1: Into<BoxError>,
+        E2: Into<BoxError>,
+    {
+        self.options.transformer = Some(Reprojection(Arc::new(move || {
+            let mut transform = factory().map_err(Into::into)?;
+            Ok(Box::new(move |x, y| transform(x, y).map_err(Into::into)) as Transformer)
+        })));
+        self
+    }
+
+    /// Sets what to do with the positions the [`reproject`] or [`reproject_with`]
+    /// transformation fails to transform (default: [`ReprojectionFailure::Error`]).
+    ///
+    /// [`reproject`]: #method.reproject
+    /// [`reproject_with`]: #method.reproject_with
+    /// [`ReprojectionFailure::Error`]: enum.ReprojectionFailure.html#variant.Error
+    pub fn reprojection_failure(mut self, on_failure: ReprojectionFailure) -> Self {
+        self.options.reprojection_failure = on_failure;
+        self
+    }
+
+    /// Sets a region the output polygons are clipped to, given by its exterior ring
+    /// in output coordinates (closed or not), see [`clip_to_polygon`].
+    ///
+    /// Only available with the `clip` feature.
+    ///
+    /// [`clip_to_polygon`]: #method.clip_to_polygon
+    #[cfg(feature = "clip")]
+    pub fn clip_to(self, ring: &[(f64, f64)]) -> Self {
+        self.clip_to_polygon(ring, &[])
+    }
+
+    /// Sets a region the output polygons are clipped to, given by its exterior ring and
+    /// its holes in output coordinates (closed or not, in any orientation). The region
+    /// can be concave, the polygons it splits giving several polygons.
+    ///
+    /// Clipping happens after the origin, the step and the [`reproject_with`] transformation
+    /// have been applied, with the boolean operations of the geo crate. Polygons outside
+    /// of the region are dropped, and the holes of the region cut the polygons. Computing
+    /// the contours fails with an [`ErrorKind::DegenerateClipRegion`] error if one of the
+    /// rings of the region has less than 3 distinct positions.
+    ///
+    /// Only available with the `clip` feature.
+    ///
+    /// [`reproject_with`]: #method.reproject_with
+    /// [`ErrorKind::DegenerateClipRegion`]: enum.ErrorKind.html#variant.DegenerateClipRegion
+    #[cfg(feature = "clip")]
+    pub fn clip_to_polygon(mut self, exterior: &[(f64, f64)], holes: &[Vec<(f64, f64)>]) -> Self {
+        let open = |ring: &[(f64, f64)]| {
+            let mut ring = ring.to_vec();
+            if ring.len() > 1 && ring.first() == ring.last() {
+                ring.pop();
+            }
+            ring
+        };
+        let mut rings = vec![open(exterior)];
+        rings.extend(holes.iter().map(|hole| open(hole)));
+        self.options.clip = Some(rings);
+        self
+    }
+
+    /// Sets whether the output x coordinates are longitudes, in degrees, to be kept
+    /// within `[-180, 180]` (default: `false`).
+    ///
+    /// As recommended by RFC 7946, polygons crossing the antimeridian are then split
+    /// into one polygon on each side of it, the positions added on the antimeridian
+    /// having exactly `180` or `-180` as longitude. Polygons lying beyond it
+    /// (e.g. for a grid covering longitudes from `0` to `360`) are shifted back.
+    pub fn antimeridian(mut self, antimeridian: bool) -> Self {
+        self.options.antimeridian = antimeridian;
+        self
+    }
+
+    /// Sets the name of the coordinate reference system (such as `"EPSG:3857"`)
+    /// written in the legacy named-CRS `crs` member of every generated Feature.
+    pub fn crs(mut self, crs: impl Into<String>) -> Self {
+        self.options.crs = Some(crs.into());
+        self
+    }
+
+    /// Sets whether the threshold value is appended as a third (z) element
+    /// to every position of the generated geometries (default: `false`).
+    pub fn z(mut self, z: bool) -> Self {
+        self.options.z = z;
+        self
+    }
+
+    /// Sets the kind of geometry of the generated Features
+    /// (default: [`GeometryMode::MultiPolygon`]).
+    ///
+    /// [`GeometryMode::MultiPolygon`]: enum.GeometryMode.html#variant.MultiPolygon
+    pub fn geometry_mode(mut self, geometry_mode: GeometryMode) -> Self {
+        self.options.geometry_mode = geometry_mode;
+        self
+    }
+
+    /// Sets how a threshold without any polygon is represented
+    /// (default: [`EmptyGeometry::MultiPolygon`]).
+    ///
+    /// [`EmptyGeometry::MultiPolygon`]: enum.EmptyGeometry.html#variant.MultiPolygon
+    pub fn empty_geometry(mut self, empty_geometry: EmptyGeometry) -> Self {
+        self.options.empty_geometry = empty_geometry;
+        self
+    }
+
+    /// Sets the expected number of rings per threshold and number of positions per ring,
+    /// used to preallocate the buffers of the computation of the contours (default: `(0, 0)`,
+    /// the buffers growing as needed).
+    ///
+    /// When computing contours of similar grids repeatedly, the hint can be measured once
+    /// with [`capacity_hint_for`].
+    ///
+    /// The pieces of rings being stitched store up to 16 positions without allocating: with
+    /// more `expected_ring_len` positions, they are allocated with this capacity instead.
+    ///
+    /// [`capacity_hint_for`]: #method.capacity_hint_for
+    pub fn with_capacity_hint(mut self, expected_rings: usize, expected_ring_len: usize) -> Self {
+        self.options.capacity_hint = (expected_rings, expected_ring_len);
+        self
+    }
+
+    /// Sets the number of bands of rows whose rings are computed in parallel for each
+    /// threshold (default: `1`, computing them on the calling thread), for large grids.
+    ///
+    /// The bands are computed on the current rayon thread pool (the global one, unless
+    /// called within `ThreadPool::install`). The result is the same as with a single band.
+    #[cfg(feature = "rayon")]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.options.threads = threads;
+        self
+    }
+
+    /// Sets the algorithm finding the rings of each threshold (default:
+    /// [`Algorithm::MarchingSquares`]). Both give the same rings, in the same order;
+    /// the [`threads`] option only applies to the marching squares.
+    ///
+    /// [`Algorithm::MarchingSquares`]: enum.Algorithm.html#variant.MarchingSquares
+    /// [`threads`]: #method.threads
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.options.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the maximum number of rings of one threshold (default: unlimited).
+    ///
+    /// The computation fails with an [`ErrorKind::OutputLimitExceeded`] error as soon as
+    /// the limit is exceeded, to guard against grids giving huge outputs (such as noise).
+    ///
+    /// [`ErrorKind::OutputLimitExceeded`]: enum.ErrorKind.html#variant.OutputLimitExceeded
+    pub fn max_rings_per_threshold(mut self, max_rings: usize) -> Self {
+        self.options.max_rings_per_threshold = Some(max_rings);
+        self
+    }
+
+    /// Sets the maximum number of positions of the rings of all the thresholds of one
+    /// computation (default: unlimited), like [`max_rings_per_threshold`] does.
+    ///
+    /// [`max_rings_per_threshold`]: #method.max_rings_per_threshold
+    pub fn max_total_vertices(mut self, max_vertices: usize) -> Self {
+        self.options.max_total_vertices = Some(max_vertices);
+        self
+    }
+
+    /// Sets whether each ring is rotated to start at its lexicographically smallest
+    /// position (by x, then y, in output coordinates), keeping its orientation and its
+    /// closing position (default: `false`). The output then only depends on the rings
+    /// as cycles, e.g. for diffing it or caching it by content hash (see [`Ring::normalize`]).
+    ///
+    /// [`Ring::normalize`]: struct.Ring.html#method.normalize
+    pub fn normalize_rings(mut self, normalize_rings: bool) -> Self {
+        self.options.normalize_rings = normalize_rings;
+        self
+    }
+
+    /// Sets whether the polygons of a threshold sharing positions are merged into single
+    /// polygons (default: `false`): the segments they share are dissolved, and the polygons
+    /// only meeting at a position get a ring going through it twice. The polygons of
+    /// marching squares don't touch each other, but a [`reproject_with`] transformation, a
+    /// [`smoother`] or the clipping can make them meet, which double-counts the shared
+    /// boundaries and makes some validity checkers reject the output.
+    ///
+    /// The positions must be exactly the same to be shared. This happens once the positions
+    /// are transformed and clipped, before the rings are [`normalize_rings`]d.
+    ///
+    /// [`reproject_with`]: #method.reproject_with
+    /// [`smoother`]: #method.smoother
+    /// [`normalize_rings`]: #method.normalize_rings
+    pub fn union_touching(mut self, union_touching: bool) -> Self {
+        self.options.union_touching = union_touching;
+        self
+    }
+
+    /// Sets whether the positions lying exactly on the segment between their neighbours
+    /// are removed from the rings, after smoothing (default: `false`). Without smoothing,
+    /// the runs of positions along the lines of the grid are merged, so that a rectangle
+    /// of values only keeps its corners. See [`geom::merge_collinear`].
+    ///
+    /// [`geom::merge_collinear`]: geom/fn.merge_collinear.html
+    pub fn merge_collinear(mut self, merge_collinear: bool) -> Self {
+        self.options.merge_collinear = merge_collinear;
+        self
+    }
+
+    /// Sets the maximum length of the segments of the rings, in grid coordinates (the
+    /// width of a cell being 1): the longer ones are subdivided into pieces of equal length
+    /// (see [`geom::densify`]). This happens after smoothing (and merging collinear positions),
+    /// before the origin, the step and the [`reproject_with`] transformation are applied, so that
+    /// the transformation is sampled densely enough along the rings.
+    ///
+    /// [`geom::densify`]: geom/fn.densify.html
+    /// [`reproject_with`]: #method.reproject_with
+    pub fn densify(mut self, max_segment_length: f64) -> Self {
+        self.options.densify = Some(max_segment_length);
+        self
+    }
+
+    /// Sets the [`VertexSmoother`] placing the positions of the rings along the edges of the
+    /// grid when smoothing them (default: [`LinearSmoother`]), e.g. to experiment with other
+    /// interpolations. It's only used with smoothing enabled (see [`new`]).
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// # use contour::smooth::{GridView, VertexSmoother};
+    /// # use contour::Ring;
+    /// // Leaves the positions in the middle of the edges, like without smoothing.
+    /// struct Middle;
+    ///
+    /// impl VertexSmoother for Middle {
+    ///     fn smooth(&self, _: &mut Ring, _: &GridView, _: f64) {}
+    /// }
+    ///
+    /// let values = [0., 0., 0., 0., 0.75, 0., 0., 0., 0.];
+    /// let c = ContourBuilder::new(3, 3, true).smoother(Middle);
+    /// assert_eq!(c.contours(&values, &[0.5])?, ContourBuilder::new(3, 3, false).contours(&values, &[0.5])?);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`VertexSmoother`]: smooth/trait.VertexSmoother.html
+    /// [`LinearSmoother`]: smooth/struct.LinearSmoother.html
+    /// [`new`]: #method.new
+    pub fn smoother<S: VertexSmoother + 'static>(mut self, smoother: S) -> Self {
+        self.options.smoother = Some(Smoother(Arc::new(smoother)));
+        self
+    }
+
+    /// Sets a function applied to every value of the grid as it's read to classify the cells
+    /// and to smooth the rings, e.g. `f64::log10`, the thresholds being then expressed in the
+    /// transformed domain, without transforming a copy of the grid. The NaN and infinite
+    /// results are handled like NaN and infinite values (see [`contours`]), and are the ones
+    /// reported by [`strict_input`], which then checks a transformed copy of the values.
+    /// The other uses of the values, like the [`zonal_stats`] or the [`extrema`], read them
+    /// as given.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let values = [0., 1., 4., 9.];
+    /// let c = ContourBuilder::new(2, 2, true).value_transform(f64::sqrt);
+    /// let roots = ContourBuilder::new(2, 2, true).contours(&[0., 1., 2., 3.], &[1.5])?;
+    /// assert_eq!(c.contours(&values, &[1.5])?, roots);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`strict_input`]: #method.strict_input
+    /// [`zonal_stats`]: #method.zonal_stats
+    /// [`extrema`]: #method.extrema
+    pub fn value_transform<F: Fn(f64) -> f64 + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.options.value_transform = Some(ValueTransform(Arc::new(f)));
+        self
+    }
+
+    /// Sets how the polygons are validated (default: [`Validation::Off`]), once smoothed:
+    /// when the values equal to a threshold are next to each other, the smoothed rings
+    /// may touch themselves or collapse, which makes the polygons invalid (as defined
+    /// by the OGC Simple Features specification) and rejected by some databases.
+    ///
+    /// [`Validation::Check`] finds the segments of different rings crossing or overlapping
+    /// each other, and the segments of a ring meeting anywhere else than at their common
+    /// position. [`Validation::Repair`] only changes the polygons when smoothing them.
+    ///
+    /// [`Validation::Off`]: enum.Validation.html#variant.Off
+    /// [`Validation::Check`]: enum.Validation.html#variant.Check
+    /// [`Validation::Repair`]: enum.Validation.html#variant.Repair
+    pub fn validate_output(mut self, validation: Validation) -> Self {
+        self.options.validation = validation;
+        self
+    }
+
+    /// Sets whether [`contours`], [`compute`] and the other methods reading a grid (apart
+    /// from [`validate_input`] and the unchecked ones) check the input values with
+    /// [`validate_input`] first, failing with an [`ErrorKind::InvalidInput`] error
+    /// describing the problems found, if any (default: `false`): the NaN and infinite
+    /// values and the constant grids are then rejected. The values read in place (by
+    /// [`contours_source`] or [`contours_strided`], for instance) are copied to be checked.
+    ///
+    /// [`contours`]: #method.contours
+    /// [`compute`]: #method.compute
+    /// [`validate_input`]: #method.validate_input
+    /// [`ErrorKind::InvalidInput`]: enum.ErrorKind.html#variant.InvalidInput
+    /// [`contours_source`]: #method.contours_source
+    /// [`contours_strided`]: #method.contours_strided
+    pub fn strict_input(mut self, strict_input: bool) -> Self {
+        self.options.strict_input = strict_input;
+        self
+    }
+
+    /// Sets whether [`contours`] checks that the polygons of each threshold are within
+    /// the ones of the next lower threshold, failing with an [`ErrorKind::NotNested`] error
+    /// otherwise (default: `false`). Every position of the higher polygons must be inside
+    /// the lower ones or exactly on their boundary, the tests being exact (see [`geom::ring_contains`]).
+    ///
+    /// The smoothed positions are computed the same way for every threshold, so that the
+    /// contours of adjacent thresholds meeting on a cell of the grid share the exact same
+    /// coordinates there, and the contours are nested unless the output is transformed
+    /// (e.g. by [`simplify`] or [`reproject_with`]).
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let values = [0., 1., 2., 1., 0.];
+    /// let c = ContourBuilder::new(5, 1, true).verify_nesting(true);
+    /// assert_eq!(c.contours(&values, &[1.5, 0.5])?.len(), 2);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`ErrorKind::NotNested`]: enum.ErrorKind.html#variant.NotNested
+    /// [`geom::ring_contains`]: geom/fn.ring_contains.html
+    /// [`simplify`]: #method.simplify
+    /// [`reproject_with`]: #method.reproject_with
+    pub fn verify_nesting(mut self, verify_nesting: bool) -> Self {
+        self.options.verify_nesting = verify_nesting;
+        self
+    }
+
+    /// Sets whether the kind of extremum enclosed by each ring is written to the `encloses`
+    /// property of the Features (default: `false`), in arrays of polygons (even for a `Polygon`
+    /// geometry) of rings, e.g. to draw the tick marks of the depression contours. The kind
+    /// comes from the local extrema of the grid (see [`extrema`]) each ring directly encloses,
+    /// i.e. not within another ring of the same threshold: `"maximum"` for a ring around
+    /// maxima at or above the threshold only, `"minimum"` for a ring around minima below it
+    /// only, and `null` for the rings around none or both, and for the rings reaching the
+    /// border of the grid, which aren't closed contours.
+    ///
+    /// [`extrema`]: #method.extrema
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let values = [
+    ///     1., 1., 1., 1., 1.,
+    ///     1., 0., 1., 2., 1.,
+    ///     1., 1., 1., 1., 1.,
+    /// ];
+    /// let c = ContourBuilder::new(5, 3, false).enclosures(true);
+    /// let features = c.contours(&values, &[0.5, 1.5])?;
+    /// let encloses = |i: usize| features[i].properties.as_ref().unwrap()["encloses"].clone();
+    /// assert_eq!(encloses(0), serde_json::json!([[null, "minimum"]]));
+    /// assert_eq!(encloses(1), serde_json::json!([["maximum"]]));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    pub fn enclosures(mut self, enclosures: bool) -> Self {
+        self.options.enclosures = enclosures;
+        self
+    }
+
+    /// Sets whether the properties given to [`contours_with_props`] replace the default
+    /// properties of the Features, `value` included, instead of being merged over them
+    /// (default: `false`).
+    ///
+    /// [`contours_with_props`]: #method.contours_with_props
+    pub fn replace_properties(mut self, replace_properties: bool) -> Self {
+        self.options.replace_properties = replace_properties;
+        self
+    }
+
+    /// Sets the simplestyle properties written to the Features (see
+    /// [`style::apply_simplestyle`]), the colors being spread over the range of
+    /// the thresholds (default: none).
+    ///
+    /// [`style::apply_simplestyle`]: style/fn.apply_simplestyle.html
+    pub fn simplestyle(mut self, style: SimpleStyle) -> Self {
+        self.options.simplestyle = Some(style);
+        self
+    }
+
+    /// Sets whether the directions of the rings are written to the properties of the
+    /// Features (default: [`RingDirections::Off`]), for hachures or the orientation of
+    /// labels: the `higher_side` property holds the [`Side`] of the higher values of each
+    /// ring (`"left"` or `"right"`, `null` for the rings of zero area) and the
+    /// `tangent_angles` property the [`Ring::tangent_angles`] of each ring, in arrays
+    /// of polygons (even for a `Polygon` geometry) of rings.
+    ///
+    /// The directions are the ones of the output rings: the higher values are on the
+    /// right of the rings in grid coordinates, but on their left when the origin and step
+    /// (or the reprojection) flip one of the axes, e.g. with a negative `y_step`.
+    ///
+    /// [`RingDirections::Off`]: enum.RingDirections.html#variant.Off
+    /// [`Side`]: enum.Side.html
+    /// [`Ring::tangent_angles`]: struct.Ring.html#method.tangent_angles
+    pub fn ring_directions(mut self, ring_directions: RingDirections) -> Self {
+        self.options.ring_directions = ring_directions;
+        self
+    }
+
+    /// Sets the tolerance of the comparisons of the values with the thresholds (default: `0`):
+    /// the values within `epsilon` of a threshold are taken as equal to it, or as right below it
+    /// (see [`epsilon_rounding`]), both when classifying the cells and when smoothing the rings.
+    /// This removes the tiny rings and slivers left around values like `0.4999999999` which
+    /// went through float round trips but were meant to be `0.5`.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let values = [1., 1., 1., 1., 0.5 - 1e-12, 1., 1., 1., 1.];
+    /// let c = ContourBuilder::new(3, 3, true);
+    /// // A hole around the center cell, without tolerance.
+    /// let p = c.contours(&values, &[0.5])?[0].geometry.clone().unwrap();
+    /// assert!(matches!(p.value, geojson::Value::MultiPolygon(ref p) if p[0].len() == 2));
+    /// let p = c.threshold_epsilon(1e-9).contours(&values, &[0.5])?[0].geometry.clone().unwrap();
+    /// assert!(matches!(p.value, geojson::Value::MultiPolygon(ref p) if p[0].len() == 1));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`epsilon_rounding`]: #method.epsilon_rounding
+    pub fn threshold_epsilon(mut self, epsilon: f64) -> Self {
+        self.options.threshold_epsilon = epsilon;
+        self
+    }
+
+    /// Sets how the values within the [`threshold_epsilon`] of a threshold are classified
+    /// (default: [`EpsilonRounding::Up`]).
+    ///
+    /// [`threshold_epsilon`]: #method.threshold_epsilon
+    /// [`EpsilonRounding::Up`]: enum.EpsilonRounding.html#variant.Up
+    pub fn epsilon_rounding(mut self, rounding: EpsilonRounding) -> Self {
+        self.options.epsilon_rounding = rounding;
+        self
+    }
+
+    /// Sets the values as circular, e.g. directions or phases, repeating every `period`
+    /// (`360` for degrees): a value is above a threshold when it is less than half a period
+    /// after it, and the rings are smoothed along the shorter arc between the values, so that
+    /// going from `359` to `1` crosses the threshold `0`, but not the threshold `180`.
+    /// The values can be in any range; the thresholds must be in `[0, period)`, or the
+    /// computation fails with an [`ErrorKind::ThresholdOutOfPeriod`] error.
+    ///
+    /// The region above a threshold being the half of the circle after it, its polygons are
+    /// also bounded by the values half a period away from the threshold, if any.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let c = ContourBuilder::new(4, 1, true).bbox(true).circular(360.);
+    /// let features = c.contours(&[340., 350., 10., 20.], &[0.])?;
+    /// assert_eq!(features[0].bbox, Some(vec![2., 0., 4., 1.]));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`ErrorKind::ThresholdOutOfPeriod`]: enum.ErrorKind.html#variant.ThresholdOutOfPeriod
+    pub fn circular(mut self, period: f64) -> Self {
+        self.options.period = Some(period);
+        self
+    }
+
+    /// Sets the distance the polygons are buffered by (see [`buffer::buffer_polygons`]):
+    /// they grow by `distance` if it is positive and shrink if it is negative, their holes
+    /// shrinking or growing accordingly. This happens in the coordinates given by the origin
+    /// and the step, before the [`reproject_with`] transformation and the clipping.
+    ///
+    /// Only available with the `buffer` feature.
+    ///
+    /// [`buffer::buffer_polygons`]: buffer/fn.buffer_polygons.html
+    /// [`reproject_with`]: #method.reproject_with
+    #[cfg(feature = "buffer")]
+    pub fn buffer(mut self, distance: f64) -> Self {
+        self.options.buffer = Some(distance);
+        self
+    }
+
+    /// Sets whether the rings of the output end with a repetition of their first position
+    /// (default: [`RingClosure::Closed`], as required by GeoJSON). With [`RingClosure::Open`],
+    /// the repeated position is left out of the Features, the [`contours_geoarrow`] columns and
+    /// the [`contours_encoded_polylines`] rings. The properties (e.g. [`area`]) are the same
+    /// either way, and the KML documents keep closed rings, as required by KML.
+    ///
+    /// ```
+    /// # use contour::{ContourBuilder, RingClosure};
+    /// let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+    /// let c = ContourBuilder::new(3, 3, false).ring_closure(RingClosure::Open);
+    /// let polylines = c.contours_encoded_polylines(&values, &[0.5], 0)?;
+    /// assert_eq!(polylines[0].rings.len(), 1);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`RingClosure::Closed`]: enum.RingClosure.html#variant.Closed
+    /// [`RingClosure::Open`]: enum.RingClosure.html#variant.Open
+    /// [`contours_geoarrow`]: #method.contours_geoarrow
+    /// [`contours_encoded_polylines`]: #method.contours_encoded_polylines
+    /// [`area`]: #method.area
+    pub fn ring_closure(mut self, ring_closure: RingClosure) -> Self {
+        self.options.ring_closure = ring_closure;
+        self
+    }
+
+    // Removes the closing positions of the rings of the polygons, if they must be open.
+    fn close_rings(&self, polygons: &mut [Vec<LinearRing>]) {
+        if self.options.ring_closure == RingClosure::Open {
+            polygons.iter_mut().flatten().for_each(open);
+        }
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`.
+    /// Returns a `Vec` of Features of MultiPolygon.
+    /// The threshold value of each Feature is stored in its `value` property.
+    /// Any values and thresholds are accepted, NaN ones included (a NaN value is outside
+    /// every polygon, a NaN threshold gives no polygon): the only error caused by the input
+    /// is a wrong length of `values`, the computation never panics.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        self.contours_by(|i| values[i], thresholds)
+    }
+
+    /// Computes the geometries of the contours like [`contours`] computes their Features,
+    /// without building the Features nor their properties, for pipelines only using the
+    /// coordinates. The geometry of a threshold without polygons is an empty `MultiPolygon`,
+    /// whatever the [`empty_geometry`] option.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+    /// let c = ContourBuilder::new(3, 3, true);
+    /// let features = c.contours(&values, &[0.5])?;
+    /// assert_eq!(c.contours_geometries(&values, &[0.5])?, [features[0].geometry.clone().unwrap()]);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`empty_geometry`]: #method.empty_geometry
+    pub fn contours_geometries(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Geometry>> {
+        self.check_input(values)?;
+        let _span = enter_contours(self.options.dx, self.options.dy);
+        let mut pass = self.pass()?;
+        thresholds
+            .iter()
+            .map(|threshold| {
+                let mut span = enter(SpanKind::Threshold, Some(*threshold));
+                let polygons = self.contour_polygons(&|i| values[i], *threshold, &mut pass, None)?;
+                span.rings(polygons.iter().map(|polygon| polygon.len()).sum());
+                Ok(self.geometry(polygons).unwrap_or_else(|| Geometry::new(Value::MultiPolygon(Vec::new()))))
+            })
+            .collect()
+    }
+
+    /// Computes the contours like [`contours`], as the extended WKB (EWKB) of their MultiPolygon
+    /// with the SRID `srid`, paired with their threshold, ready to be loaded into PostGIS (see
+    /// the [`wkb`] module, [`wkb::to_hex`] and [`wkb::write_copy_rows`]). The rings are written
+    /// closed whatever the [`ring_closure`] option, and a threshold without polygons gives an
+    /// empty MultiPolygon.
+    ///
+    /// [`contours`]: #method.contours
+    /// [`ring_closure`]: #method.ring_closure
+    /// [`wkb`]: wkb/index.html
+    /// [`wkb::to_hex`]: wkb/fn.to_hex.html
+    /// [`wkb::write_copy_rows`]: wkb/fn.write_copy_rows.html
+    pub fn contours_ewkb(&self, values: &[f64], thresholds: &[f64], srid: u32) -> Result<Vec<(f64, Vec<u8>)>> {
+        self.check_input(values)?;
+        let _span = enter_contours(self.options.dx, self.options.dy);
+        let mut pass = self.pass()?;
+        thresholds
+            .iter()
+            .map(|threshold| {
+                let mut span = enter(SpanKind::Threshold, Some(*threshold));
+                let polygons = self.contour_polygons(&|i| values[i], *threshold, &mut pass, None)?;
+                span.rings(polygons.iter().map(|polygon| polygon.len()).sum());
+                Ok((*threshold, ewkb_multipolygon(&polygons, Some(srid))))
+            })
+            .collect()
+    }
+
+    /// Computes contours like [`contours`], for `values` stored in column-major order
+    /// (the value at column `x` and row `y` being at index `x * dy + y`), as done by
+    /// `nalgebra`'s `DMatrix` (see [`contours_matrix`]).
+    /// The values are read in place and the output coordinates are (column, row)
+    /// coordinates, as with [`contours`].
+    ///
+    /// [`contours`]: #method.contours
+    /// [`contours_matrix`]: #method.contours_matrix
+    pub fn contours_column_major(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Feature>> {
+        check_dimensions(self.options.dx, self.options.dy, values.len())?;
+        let (dx, dy) = (self.options.dx as usize, self.options.dy as usize);
+        let value = |i| values[(i % dx) * dy + i / dx];
+        self.check_input_by(value)?;
+        self.contours_by(value, thresholds)
+    }
+
+    /// Computes contours like [`contours`], of the values of a `nalgebra` matrix, read in
+    /// place like [`contours_column_major`] does: the builder has `matrix.ncols()` columns
+    /// and `matrix.nrows()` rows (e.g. `ContourBuilder::new(ncols, nrows, smooth)`), the
+    /// value at column `x` and row `y` being `matrix[(y, x)]`.
+    ///
+    /// Returns an [`ErrorKind::BadDimension`] error if the dimensions of the matrix don't
+    /// match the ones of the grid.
+    ///
+    /// Only available with the `nalgebra` feature.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// # use nalgebra::DMatrix;
+    /// let matrix = DMatrix::from_row_slice(2, 3, &[0., 1., 0., 0., 1., 1.]);
+    /// let c = ContourBuilder::new(3, 2, false);
+    /// assert_eq!(c.contours_matrix(&matrix, &[0.5])?, c.contours(&[0., 1., 0., 0., 1., 1.], &[0.5])?);
+    /// assert!(ContourBuilder::new(2, 3, false).contours_matrix(&matrix, &[0.5]).is_err());
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`contours_column_major`]: #method.contours_column_major
+    /// [`ErrorKind::BadDimension`]: enum.ErrorKind.html#variant.BadDimension
+    #[cfg(feature = "nalgebra")]
+    pub fn contours_matrix(&self, matrix: &DMatrix<f64>, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        if matrix.ncols() != self.options.dx as usize || matrix.nrows() != self.options.dy as usize {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        self.contours_column_major(matrix.as_slice(), thresholds)
+    }
+
+    /// Computes contours like [`contours`], for `values` stored with rows of `row_stride`
+    /// values, e.g. rows padded to an alignment boundary by an image library: the value at
+    /// column `x` and row `y` is at index `y * row_stride + x`, the padding being ignored.
+    /// The values are read in place; `values` needs to hold the `dx` first values of the
+    /// last row, but not its padding.
+    ///
+    /// Returns an [`ErrorKind::InvalidStride`] error if `row_stride` is smaller than `dx`,
+    /// or an [`ErrorKind::BadDimension`] error if `values` is too short.
+    ///
+    /// [`contours`]: #method.contours
+    /// [`ErrorKind::InvalidStride`]: enum.ErrorKind.html#variant.InvalidStride
+    /// [`ErrorKind::BadDimension`]: enum.ErrorKind.html#variant.BadDimension
+    pub fn contours_strided(&self, values: &[f64], row_stride: usize, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        let (dx, dy) = (self.options.dx as usize, self.options.dy as usize);
+        if row_stride < dx {
+            return Err(new_error(ErrorKind::InvalidStride { row_stride, dx: self.options.dx }));
+        }
+        let len = match dy {
+            0 => Some(0),
+            _ => (dy - 1).checked_mul(row_stride).and_then(|n| n.checked_add(dx)),
+        };
+        match len {
+            Some(len) if len <= values.len() => check_dimensions(self.options.dx, self.options.dy, dx * dy)?,
+            _ => return Err(new_error(ErrorKind::BadDimension)),
+        }
+        let value = |i| values[(i / dx) * row_stride + i % dx];
+        self.check_input_by(value)?;
+        self.contours_by(value, thresholds)
+    }
+
+    /// Computes contours like [`contours`], only over the window of `width` columns and
+    /// `height` rows of the grid starting at column `x` and row `y`: the values outside
+    /// of it are ignored, as if they were below every threshold, so that the rings crossing
+    /// the edges of the window are closed along them. The values are read in place (with
+    /// the row stride of the whole grid) and the output coordinates are the ones of the whole
+    /// grid, the origin and the step applying to it.
+    ///
+    /// Returns an [`ErrorKind::InvalidWindow`] error if the window doesn't fit in the grid.
+    ///
+    /// [`contours`]: #method.contours
+    /// [`ErrorKind::InvalidWindow`]: enum.ErrorKind.html#variant.InvalidWindow
+    pub fn contours_window(&self, values: &[f64], thresholds: &[f64], x: u32, y: u32, width: u32, height: u32) -> Result<Vec<Feature>> {
+        check_dimensions(self.options.dx, self.options.dy, values.len())?;
+        if u64::from(x) + u64::from(width) > u64::from(self.options.dx) || u64::from(y) + u64::from(height) > u64::from(self.options.dy) {
+            return Err(new_error(ErrorKind::InvalidWindow { x, y, width, height }));
+        }
+        let mut window = self.clone();
+        window.options.dx = width;
+        window.options.dy = height;
+        window.options.x_origin += f64::from(x) * self.options.x_step;
+        window.options.y_origin += f64::from(y) * self.options.y_step;
+        let (dx, x, y, width) = (self.options.dx as usize, x as usize, y as usize, width as usize);
+        let value = |i| values[(y + i / width) * dx + x + i % width];
+        // Only the values of the window are checked.
+        window.check_input_by(value)?;
+        window.contours_by(value, thresholds)
+    }
+
+    /// Computes contours like [`contours`], of the grid padded with `width` cells on each
+    /// side (see [`pad_grid`]), the output coordinates being shifted back by the width of
+    /// the padding: they are the ones of the grid of `values`, the padding cells being
+    /// outside of it. With `PadMode::Constant(f64::NEG_INFINITY)`, the contours are the ones
+    /// computed by [`contours`]; with `PadMode::Constant(f64::INFINITY)`, the polygons touching
+    /// the border of the grid stay open to it, extending over the padding, instead of being
+    /// closed half a cell away from the centers of the border cells; with [`PadMode::Edge`]
+    /// or [`PadMode::Reflect`], the contours are continued beyond the border.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// # use contour::pad::PadMode;
+    /// let c = ContourBuilder::new(3, 3, false).bbox(true);
+    /// let features = c.contours_padded(&[1., 0., 0., 0., 0., 0., 0., 0., 0.], &[0.5], 1, PadMode::Edge)?;
+    /// // The polygon of the corner cell extends over the padding.
+    /// assert_eq!(features[0].bbox, Some(vec![-1., -1., 1., 1.]));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`pad_grid`]: pad/fn.pad_grid.html
+    /// [`PadMode::Edge`]: pad/enum.PadMode.html#variant.Edge
+    /// [`PadMode::Reflect`]: pad/enum.PadMode.html#variant.Reflect
+    pub fn contours_padded(&self, values: &[f64], thresholds: &[f64], width: u32, mode: PadMode) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let (padded, dx, dy) = pad_grid(values, self.options.dx, self.options.dy, width, mode)?;
+        let mut builder = self.clone();
+        builder.options.dx = dx;
+        builder.options.dy = dy;
+        builder.options.x_origin -= f64::from(width) * self.options.x_step;
+        builder.options.y_origin -= f64::from(width) * self.options.y_step;
+        builder.contours_by(|i| padded[i], thresholds)
+    }
+
+    /// Computes contours like [`contours`], of the grid of the counts of the `points` (or the
+    /// sums of their `weights`) binned into the `dx` * `dy` cells of the `extent` (see
+    /// [`histogram::bin_points`]). The origin and the step are set to the ones of the extent,
+    /// so that the output coordinates are the ones of the points.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// # use contour::histogram::Extent;
+    /// let extent = Extent { x_min: 10., y_min: 20., x_max: 14., y_max: 24. };
+    /// let points = vec![(11.5, 21.5), (11.6, 21.7), (11.9, 21.1)];
+    /// let c = ContourBuilder::new(4, 4, false).bbox(true);
+    /// let features = c.contours_binned(points.into_iter(), extent, None, &[2.])?;
+    /// // The cell holding the three points.
+    /// assert_eq!(features[0].bbox, Some(vec![11., 21., 12., 22.]));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`histogram::bin_points`]: histogram/fn.bin_points.html
+    pub fn contours_binned(&self, points: impl Iterator<Item = (f64, f64)>, extent: Extent, weights: Option<&[f64]>, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        let counts = bin_points(points, extent, self.options.dx, self.options.dy, weights)?;
+        let mut builder = self.clone();
+        builder.options.x_origin = extent.x_min;
+        builder.options.y_origin = extent.y_min;
+        builder.options.x_step = (extent.x_max - extent.x_min) / f64::from(self.options.dx);
+        builder.options.y_step = (extent.y_max - extent.y_min) / f64::from(self.options.dy);
+        builder.contours(&counts, thresholds)
+    }
+
+    /// Computes contours like [`contours`], of the grid interpolated linearly between the
+    /// grids `a` and `b` (of the same dimensions): `(1 - t) * a + t * b`, `t` going from `0`
+    /// (the contours of `a`) to `1` (the contours of `b`), for animations morphing the
+    /// contours of a grid into the ones of the next one. The values are blended on the fly,
+    /// without allocating the interpolated grid.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let (a, b) = ([0., 0., 0., 2.], [2., 0., 0., 0.]);
+    /// let c = ContourBuilder::new(2, 2, true);
+    /// assert_eq!(c.contours_blended(&a, &b, 0., &[1.])?, c.contours(&a, &[1.])?);
+    /// assert_eq!(c.contours_blended(&a, &b, 0.5, &[1.])?, c.contours(&[1., 0., 0., 1.], &[1.])?);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    pub fn contours_blended(&self, a: &[f64], b: &[f64], t: f64, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        self.check_input(a)?;
+        self.check_input(b)?;
+        self.blended_by(a, b, t, thresholds)
+    }
+
+    /// Computes the contours of `steps` grids interpolated between the grids `a` and `b`
+    /// (see [`contours_blended`]), `t` going from `0` to `1` by steps of `1 / (steps - 1)`:
+    /// returns the Features of each of them, the first ones being the contours of `a` and
+    /// the last ones the contours of `b` (when `steps` is at least `2`).
+    ///
+    /// [`contours_blended`]: #method.contours_blended
+    pub fn contours_sequence(&self, a: &[f64], b: &[f64], thresholds: &[f64], steps: usize) -> Result<Vec<Vec<Feature>>> {
+        self.check_input(a)?;
+        self.check_input(b)?;
+        let last = steps.saturating_sub(1).max(1) as f64;
+        (0..steps).map(|step| self.blended_by(a, b, step as f64 / last, thresholds)).collect()
+    }
+
+    // Computes the contours of `(1 - t) * a + t * b`, the grids themselves for `t`
+    // equal to `0` and `1` (whatever the values of the other one).
+    fn blended_by(&self, a: &[f64], b: &[f64], t: f64, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        if t == 0. {
+            self.contours_by(|i| a[i], thresholds)
+        } else if t == 1. {
+            self.contours_by(|i| b[i], thresholds)
+        } else {
+            self.contours_by(|i| (1. - t) * a[i] + t * b[i], thresholds)
+        }
+    }
+
+    /// Computes contours like [`contours`], of the regions where the `values` exceed the
+    /// `reference` grid (of the same dimensions) by each of the `offsets`: the contour of the
+    /// offset `o` covers the cells where `values[i] - reference[i] >= o`, e.g. the anomalies
+    /// of a field relative to its local climatological norm. The differences are computed
+    /// on the fly, without allocating the grid of the differences, and the rings are smoothed
+    /// by interpolating them. The offset of each Feature is stored in its `value` property.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let (values, reference) = ([1., 2., 3., 4.], [0., 2., 0., 2.]);
+    /// let c = ContourBuilder::new(2, 2, true);
+    /// assert_eq!(c.contours_vs_grid(&values, &reference, &[1.])?, c.contours(&[1., 0., 3., 2.], &[1.])?);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    pub fn contours_vs_grid(&self, values: &[f64], reference: &[f64], offsets: &[f64]) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        self.check_input(reference)?;
+        self.contours_by(|i| values[i] - reference[i], offsets)
+    }
+
+    /// Computes contours like [`contours`], reading the values from the given [`GridSource`],
+    /// which must hold the `dx * dy` values of the grid.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let values: [f32; 4] = [0., 1., 1., 0.];
+    /// let c = ContourBuilder::new(2, 2, true);
+    /// assert_eq!(c.contours_source(&values[..], &[0.5])?, c.contours(&[0., 1., 1., 0.], &[0.5])?);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`GridSource`]: source/trait.GridSource.html
+    pub fn contours_source<S: GridSource + ?Sized>(&self, source: &S, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        check_dimensions(self.options.dx, self.options.dy, source.len())?;
+        self.check_input_by(|i| source.value(i))?;
+        self.contours_by(|i| source.value(i), thresholds)
+    }
+
+    /// Computes contours like [`contours`], reading the values in place from the raw file
+    /// at `path` (see [`mmap::MappedGrid::open`]), without copying them.
+    ///
+    /// Only available with the `mmap` feature.
+    ///
+    /// [`contours`]: #method.contours
+    /// [`mmap::MappedGrid::open`]: mmap/struct.MappedGrid.html#method.open
+    #[cfg(feature = "mmap")]
+    pub fn contours_from_raw_file<P: AsRef<Path>>(&self, path: P, dtype: RawType, endianness: Endianness, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        let grid = MappedGrid::open(path, self.options.dx, self.options.dy, dtype, endianness)?;
+        self.contours_source(&grid, thresholds)
+    }
+
+    /// Computes contours like [`contours`], on the grid resampled to `new_dx` columns
+    /// and `new_dy` rows with [`resample::resample_bilinear`]: the step is scaled
+    /// accordingly, so that the output coordinates still are the ones of the grid
+    /// of `values`.
+    ///
+    /// [`contours`]: #method.contours
+    /// [`resample::resample_bilinear`]: resample/fn.resample_bilinear.html
+    pub fn contours_resampled(&self, values: &[f64], thresholds: &[f64], new_dx: u32, new_dy: u32) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let resampled = resample_bilinear(values, self.options.dx, self.options.dy, new_dx, new_dy)?;
+        let mut builder = self.clone();
+        builder.options.dx = new_dx;
+        builder.options.dy = new_dy;
+        builder.options.x_step *= f64::from(self.options.dx) / f64::from(new_dx);
+        builder.options.y_step *= f64::from(self.options.dy) / f64::from(new_dy);
+        builder.contours(&resampled, thresholds)
+    }
+
+    /// Computes contours like [`contours`], without checking that the length of `values`
+    /// matches the dimensions of the grid and without bounds checks when reading them.
+    ///
+    /// Only available with the `fast-unchecked` feature.
+    ///
+    /// # Safety
+    ///
+    /// The length of `values` must be `dx * dy`: reading them is undefined behavior otherwise.
+    ///
+    /// [`contours`]: #method.contours
+    #[cfg(feature = "fast-unchecked")]
+    pub unsafe fn contours_unchecked(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Feature>> {
+        self.contours_by(|i| *values.get_unchecked(i), thresholds)
+    }
+
+    /// Computes contours like [`contours_unchecked`], for values known to be valid:
+    /// panics if the length of `values` doesn't match the dimensions of the grid,
+    /// and, in debug builds only, if some of them aren't finite.
+    ///
+    /// Only available with the `fast-unchecked` feature.
+    ///
+    /// [`contours_unchecked`]: #method.contours_unchecked
+    #[cfg(feature = "fast-unchecked")]
+    pub fn contours_trusted(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Feature>> {
+        assert_eq!(values.len(), self.options.dx as usize * self.options.dy as usize, "the length of values doesn't match the dimensions of the grid");
+        debug_assert!(values.iter().all(|v| v.is_finite()), "values aren't all finite");
+        unsafe { self.contours_unchecked(values, thresholds) }
+    }
+
+    /// Computes contours like [`contours`], along with the [`ThresholdDiagnostics`] of each
+    /// threshold: the numbers of rings computed, of exterior rings, holes and dropped rings,
+    /// and the number of positions of the output, to tune the thresholds or understand
+    /// surprising contours. The diagnostics are only gathered by this method.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+    /// let (features, diagnostics) = ContourBuilder::new(3, 3, false).contours_with_diagnostics(&values, &[0.5, 2.])?;
+    /// assert_eq!(features.len(), 2);
+    /// assert_eq!((diagnostics[0].rings, diagnostics[0].shells, diagnostics[0].vertices), (1, 1, 5));
+    /// assert_eq!(diagnostics[1].rings, 0);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`ThresholdDiagnostics`]: struct.ThresholdDiagnostics.html
+    pub fn contours_with_diagnostics(&self, values: &[f64], thresholds: &[f64]) -> Result<(Vec<Feature>, Vec<ThresholdDiagnostics>)> {
+        self.check_input(values)?;
+        let _span = enter_contours(self.options.dx, self.options.dy);
+        let mut pass = self.pass()?;
+        pass.diagnostics = Some(Vec::with_capacity(thresholds.len()));
+        let features = thresholds
+            .iter()
+            .map(|value| self.contour(&|i| values[i], *value, &mut pass))
+            .collect::<Result<Vec<Feature>>>()?;
+        Ok((self.styled(features), pass.diagnostics.unwrap_or_default()))
+    }
+
+    /// Computes contours like [`contours`], reusing the Features of the thresholds found in
+    /// the `cache` (computed for the same values and thresholds by a builder with the same
+    /// options) and caching the ones computed (see the [`cache`] module). The output is
+    /// the same as the one of [`contours`].
+    ///
+    /// [`contours`]: #method.contours
+    /// [`cache`]: cache/index.html
+    pub fn contours_cached(&self, values: &[f64], thresholds: &[f64], cache: &ContourCache) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let hash = hash_values(values);
+        let mut pass = None;
+        let features = thresholds
+            .iter()
+            .map(|threshold| {
+                let key = Key::new(hash, *threshold);
+                if let Some(feature) = cache.get(&self.options, key) {
+                    return Ok(feature);
+                }
+                let pass = match pass {
+                    Some(ref mut p) => p,
+                    None => pass.insert(self.pass()?),
+                };
+                let feature = self.contour(&|i| values[i], *threshold, pass)?;
+                cache.insert(&self.options, key, feature.clone());
+                Ok(feature)
+            })
+            .collect::<Result<Vec<Feature>>>()?;
+        Ok(self.styled(features))
+    }
+
+    /// Computes the regions gained and lost by the polygons of each threshold between the
+    /// Features `before` and `after` of the same thresholds, in the same order, computed by
+    /// a builder with the same dimensions, origin and step (without [`reproject_with`]): see the
+    /// [`diff`] module. The regions are computed like the contours of the grid of the cells
+    /// gained or lost (with the options of the builder), in the same coordinates.
+    ///
+    /// Returns an [`ErrorKind::MismatchedThresholds`] error if there isn't the same number
+    /// of Features in both outputs.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let c = ContourBuilder::new(3, 1, true).x_origin(10.).bbox(true);
+    /// let before = c.contours(&[1., 1., 0.], &[0.5])?;
+    /// let after = c.contours(&[0., 1., 1.], &[0.5])?;
+    /// let diff = c.contours_diff(&before, &after)?;
+    /// assert_eq!(diff[0].gained.bbox, Some(vec![12., 0., 13., 1.]));
+    /// assert_eq!(diff[0].lost.bbox, Some(vec![10., 0., 11., 1.]));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`reproject_with`]: #method.reproject_with
+    /// [`diff`]: diff/index.html
+    /// [`ErrorKind::MismatchedThresholds`]: enum.ErrorKind.html#variant.MismatchedThresholds
+    pub fn contours_diff(&self, before: &[Feature], after: &[Feature]) -> Result<Vec<ContourDiff>> {
+        if before.len() != after.len() {
+            return Err(new_error(ErrorKind::MismatchedThresholds { before: before.len(), after: after.len() }));
+        }
+        let mut pass = self.pass()?;
+        before
+            .iter()
+            .zip(after)
+            .map(|(before, after)| {
+                let threshold = before.properties.as_ref().and_then(|p| p.get("value")).and_then(|v| v.as_f64()).unwrap_or(f64::NAN);
+                let (before, after) = (self.grid_mask(before), self.grid_mask(after));
+                let mut region = |inside: &[bool], outside: &[bool]| -> Result<Feature> {
+                    let mut feature = self.contour(&|i| if inside[i] && !outside[i] { 1. } else { 0. }, 0.5, &mut pass)?;
+                    if let Some(ref mut properties) = feature.properties {
+                        properties.insert(String::from("value"), serde_json::Value::from(threshold));
+                    }
+                    Ok(feature)
+                };
+                let gained = region(&after, &before)?;
+                let lost = region(&before, &after)?;
+                Ok(ContourDiff { threshold, gained, lost })
+            })
+            .collect()
+    }
+
+    // Rasterizes the polygons of the `feature` onto the grid, converting them back from
+    // output coordinates to grid coordinates.
+    fn grid_mask(&self, feature: &Feature) -> Vec<bool> {
+        let rings: Vec<LinearRing> = match feature.geometry.as_ref().map(|g| &g.value) {
+            Some(Value::Polygon(polygon)) => polygon.clone(),
+            Some(Value::MultiPolygon(polygons)) => polygons.iter().flatten().cloned().collect(),
+            _ => Vec::new(),
+        };
+        let rings = rings
+            .into_iter()
+            .map(|ring| {
+                ring.into_iter()
+                    .map(|p| vec![(p[0] - self.options.x_origin) / self.options.x_step, (p[1] - self.options.y_origin) / self.options.y_step])
+                    .collect::<LinearRing>()
+            })
+            .collect::<Vec<_>>();
+        rasterize(&rings, self.options.dx, self.options.dy)
+    }
+
+    /// Computes contours like [`contours`], for thresholds given along with their names
+    /// (e.g. `(2.5, "minor flood")`): the name of each threshold is stored in the `name`
+    /// property of its Feature, next to its `value`, the Features being in the order of
+    /// the thresholds.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let c = ContourBuilder::new(2, 2, true);
+    /// let features = c.contours_named(&[0., 1., 2., 3.], &[(0.5, "low"), (2.5, "high")])?;
+    /// let properties = features[1].properties.as_ref().unwrap();
+    /// assert_eq!((&properties["name"], &properties["value"]), (&"high".into(), &2.5.into()));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    pub fn contours_named(&self, values: &[f64], thresholds: &[(f64, &str)]) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let _span = enter_contours(self.options.dx, self.options.dy);
+        let mut pass = self.pass()?;
+        let features = thresholds
+            .iter()
+            .map(|(value, name)| {
+                let mut feature = self.contour(&|i| values[i], *value, &mut pass)?;
+                if let Some(ref mut properties) = feature.properties {
+                    properties.insert(String::from("name"), serde_json::Value::from(*name));
+                }
+                Ok(feature)
+            })
+            .collect::<Result<Vec<Feature>>>()?;
+        Ok(self.styled(features))
+    }
+
+    /// Computes contours like [`contours`], with the properties of the Feature of each threshold
+    /// given by `make`, called with the index of the threshold and its value, whose result must
+    /// be serialized to a JSON object (failing with an [`ErrorKind::PropertiesNotObject`] error
+    /// otherwise). Its entries are merged over the default properties (`value`, and the ones
+    /// computed by the options like [`area`]), or replace them with [`replace_properties`].
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// # use std::collections::BTreeMap;
+    /// let c = ContourBuilder::new(2, 2, true);
+    /// let features = c.contours_with_props(&[0., 1., 2., 3.], &[0.5, 2.5], |i, _| {
+    ///     vec![("rank", i)].into_iter().collect::<BTreeMap<_, _>>()
+    /// })?;
+    /// let properties = features[1].properties.as_ref().unwrap();
+    /// assert_eq!((&properties["rank"], &properties["value"]), (&1.into(), &2.5.into()));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`contours`]: #method.contours
+    /// [`ErrorKind::PropertiesNotObject`]: enum.ErrorKind.html#variant.PropertiesNotObject
+    /// [`area`]: #method.area
+    /// [`replace_properties`]: #method.replace_properties
+    pub fn contours_with_props<P: Serialize, F: Fn(usize, f64) -> P>(&self, values: &[f64], thresholds: &[f64], make: F) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let _span = enter_contours(self.options.dx, self.options.dy);
+        let mut pass = self.pass()?;
+        let features = thresholds
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let mut feature = self.contour(&|i| values[i], *value, &mut pass)?;
+                let props = match to_value(make(index, *value))? {
+                    serde_json::Value::Object(props) => props,
+                    value => return Err(new_error(ErrorKind::PropertiesNotObject { index, value })),
+                };
+                match feature.properties {
+                    Some(ref mut properties) if !self.options.replace_properties => properties.extend(props),
+                    _ => feature.properties = Some(props),
+                }
+                Ok(feature)
+            })
+            .collect::<Result<Vec<Feature>>>()?;
+        Ok(self.styled(features))
+    }
+
+    /// Computes contours like [`contours`], as a [`ContourStream`] of the Features of the
+    /// `thresholds`, in order, each one being computed on the blocking threads of the current
+    /// tokio runtime (see [`contours_stream_on`]), so that async code can send them as soon
+    /// as they're ready.
+    ///
+    /// Only available with the `stream` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    ///
+    /// [`contours`]: #method.contours
+    /// [`ContourStream`]: stream/struct.ContourStream.html
+    /// [`contours_stream_on`]: #method.contours_stream_on
+    #[cfg(feature = "stream")]
+    pub fn contours_stream(&self, values: Arc<[f64]>, thresholds: Vec<f64>, ahead: usize) -> Result<ContourStream> {
+        self.contours_stream_on(&Handle::current(), values, thresholds, ahead)
+    }
+
+    /// Computes contours like [`contours_stream`], on the blocking threads of the runtime
+    /// of the given `handle`. At most `ahead` thresholds (at least one) are computed ahead of
+    /// the consumption of the stream; dropping the stream stops the computation once the
+    /// threshold being computed, if any, is done. The stream ends after the first error.
+    ///
+    /// Only available with the `stream` feature.
+    ///
+    /// [`contours_stream`]: #method.contours_stream
+    #[cfg(feature = "stream")]
+    pub fn contours_stream_on(&self, handle: &Handle, values: Arc<[f64]>, thresholds: Vec<f64>, ahead: usize) -> Result<ContourStream> {
+        self.check_input(&values)?;
+        let (sender, receiver) = mpsc::channel(ahead.max(1));
+        let (builder, runtime) = (self.clone(), handle.clone());
+        handle.spawn_blocking(move || builder.send_contours(&runtime, &values, &thresholds, sender));
+        Ok(ContourStream::new(receiver))
+    }
+
+    // Computes the Features of the thresholds one by one for `contours_stream_on`, sending
+    // them through `sender` until they're all sent, an error is sent or the stream is dropped.
+    #[cfg(feature = "stream")]
+    fn send_contours(&self, handle: &Handle, values: &[f64], thresholds: &[f64], sender: mpsc::Sender<Result<Feature>>) {
+        let _span = enter_contours(self.options.dx, self.options.dy);
+        // The colors are spread over all the thresholds, like in `contours`.
+        let range = thresholds.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), t| (min.min(*t), max.max(*t)));
+        let mut pass = match self.pass() {
+            Ok(pass) => pass,
+            Err(err) => {
+                let _ = sender.blocking_send(Err(err));
+                return;
+            }
+        };
+        for threshold in thresholds {
+            // Waiting for room in the stream before computing a threshold bounds the number
+            // of thresholds computed ahead.
+            let permit = match handle.block_on(sender.reserve()) {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+            let feature = self.contour(&|i| values[i], *threshold, &mut pass);
+            let failed = feature.is_err();
+            permit.send(feature.map(|mut feature| {
+                if let Some(ref style) = self.options.simplestyle {
+                    apply_simplestyle_over(std::slice::from_mut(&mut feature), style, range);
+                }
+                feature
+            }));
+            if failed {
+                return;
+            }
+        }
+    }
+
+    // Computes contours of the grid whose value at (row-major) index `i` is `values(i)`.
+    fn contours_by<V: Fn(usize) -> f64 + Sync>(&self, values: V, thresholds: &[f64]) -> Result<Vec<Feature>> {
+        let _span = enter_contours(self.options.dx, self.options.dy);
+        let mut pass = self.pass()?;
+        let features = thresholds
+            .iter()
+            .map(|value| self.contour(&values, *value, &mut pass))
+            .collect::<Result<Vec<Feature>>>()?;
+        if self.options.verify_nesting {
+            check_nesting(&features, thresholds)?;
+        }
+        Ok(self.styled(features))
+    }
+
+    // Writes the simplestyle properties of the features, if set.
+    fn styled(&self, mut features: Vec<Feature>) -> Vec<Feature> {
+        if let Some(ref style) = self.options.simplestyle {
+            apply_simplestyle(&mut features, style);
+        }
+        features
+    }
+
+    /// Stores the thresholds used by [`compute`], sorted in ascending order.
+    /// Returns an error, leaving the stored thresholds unchanged, if one of them isn't finite.
+    ///
+    /// [`compute`]: #method.compute
+    pub fn set_thresholds(&mut self, thresholds: &[f64]) -> Result<()> {
+        if let Some(value) = thresholds.iter().find(|t| !t.is_finite()) {
+            return Err(new_error(ErrorKind::InvalidThreshold(*value)));
+        }
+        let mut thresholds = thresholds.to_vec();
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        self.threshold_properties = thresholds
+            .iter()
+            .map(|threshold| {
+                let mut properties = Map::with_capacity(1);
+                properties.insert(String::from("value"), to_value(threshold)?);
+                Ok(properties)
+            })
+            .collect::<Result<_>>()?;
+        self.thresholds = thresholds;
+        Ok(())
+    }
+
+    /// Returns the thresholds stored by [`set_thresholds`].
+    ///
+    /// [`set_thresholds`]: #method.set_thresholds
+    pub fn thresholds(&self) -> &[f64] {
+        &self.thresholds
+    }
+
+    /// Computes contours according the given input `values` and the thresholds
+    /// stored by [`set_thresholds`], like [`contours`] does.
+    ///
+    /// [`set_thresholds`]: #method.set_thresholds
+    /// [`contours`]: #method.contours
+    pub fn compute(&self, values: &[f64]) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let mut pass = self.pass()?;
+        let features = self
+            .thresholds
+            .iter()
+            .zip(&self.threshold_properties)
+            .map(|(threshold, properties)| {
+                let mut polygons = self.grid_polygons(&|i| values[i], *threshold, &mut pass)?;
+                let mut properties = properties.clone();
+                self.insert_grid_properties(&|i| values[i], *threshold, &polygons, &mut pass, &mut properties)?;
+                self.georeference(&mut polygons, *threshold, &mut pass)?;
+                Ok(self.feature(polygons, properties))
+            })
+            .collect::<Result<Vec<Feature>>>()?;
+        Ok(self.styled(features))
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`,
+    /// emitting one Feature per polygon (each polygon keeping its holes) instead of
+    /// one Feature per threshold.
+    /// The threshold value of each Feature is stored in its `value` property and the
+    /// index of the polygon within its threshold is stored in its `part` property.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours_exploded(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let mut pass = self.pass()?;
+        let mut features = Vec::new();
+        for threshold in thresholds {
+            let polygons = self.grid_polygons(&|i| values[i], *threshold, &mut pass)?;
+            let value = serde_json::Value::from(*threshold);
+            // The enclosed extrema depend on the other polygons, nested in the holes.
+            let encloses = self.encloses(&|i| values[i], *threshold, &polygons, &mut pass);
+            for (part, polygon) in polygons.into_iter().enumerate() {
+                let mut polygons = vec![polygon];
+                let mut properties = Map::with_capacity(2);
+                properties.insert(String::from("value"), value.clone());
+                properties.insert(String::from("part"), serde_json::Value::from(part));
+                self.insert_zonal_stats(&|i| values[i], &polygons, &mut properties)?;
+                if let Some(ref encloses) = encloses {
+                    properties.insert(String::from("encloses"), json!([encloses[part]]));
+                }
+                self.georeference(&mut polygons, *threshold, &mut pass)?;
+                features.push(self.feature(polygons, properties));
+            }
+        }
+        Ok(self.styled(features))
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`,
+    /// each Feature covering only the band between its threshold and the next higher one
+    /// instead of all the values above its threshold: the polygons of the next threshold
+    /// are cut out of its polygons, their exterior rings becoming holes (and their holes
+    /// exterior rings). The Features are sorted by threshold, the same thresholds being
+    /// kept once, and get the bounds of their band in their `min` and `max` properties
+    /// (`null` for the last one), replacing the ones of the [`zonal_stats`].
+    ///
+    /// Filling the bands with semi-transparent colors doesn't stack them, unlike the
+    /// polygons of [`contours`].
+    ///
+    /// [`contours`]: #method.contours
+    /// [`zonal_stats`]: #method.zonal_stats
+    pub fn contours_banded(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let mut thresholds = thresholds.to_vec();
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        thresholds.dedup();
+        let mut pass = self.pass()?;
+        let mut features = Vec::with_capacity(thresholds.len());
+        // The polygons of the next higher threshold, if any.
+        let mut higher: Option<Vec<Vec<LinearRing>>> = None;
+        for (k, threshold) in thresholds.iter().enumerate().rev() {
+            let polygons = self.grid_polygons(&|i| values[i], *threshold, &mut pass)?;
+            let mut band = match higher {
+                Some(ref higher) => cut_out(&polygons, higher),
+                None => polygons.clone(),
+            };
+            let mut properties = Map::with_capacity(3);
+            properties.insert(String::from("value"), serde_json::Value::from(*threshold));
+            self.insert_grid_properties(&|i| values[i], *threshold, &band, &mut pass, &mut properties)?;
+            properties.insert(String::from("min"), serde_json::Value::from(*threshold));
+            properties.insert(String::from("max"), thresholds.get(k + 1).map_or(serde_json::Value::Null, |max| serde_json::Value::from(*max)));
+            self.georeference(&mut band, *threshold, &mut pass)?;
+            features.push(self.feature(band, properties));
+            higher = Some(polygons);
+        }
+        features.reverse();
+        Ok(self.styled(features))
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// and returns them as a KML document (see [`kml::to_kml`]).
+    ///
+    /// [`kml::to_kml`]: kml/fn.to_kml.html
+    pub fn contours_kml(&self, values: &[f64], thresholds: &[f64], options: &KmlOptions) -> Result<String> {
+        let features = if options.per_polygon {
+            self.contours_exploded(values, thresholds)?
+        } else {
+            self.contours(values, thresholds)?
+        };
+        // `to_kml` writes the rings closed whatever the `ring_closure` option.
+        Ok(to_kml(&features, options))
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// and returns them as a standalone SVG document (see [`svg::to_svg`]), whose `viewBox`
+    /// is the extent of the grid (extended to the positions outside of it, if any). The y axis
+    /// is flipped with a negative [`y_step`], for the north to be up.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// # use contour::svg::SvgStyle;
+    /// let c = ContourBuilder::new(3, 3, true).x_origin(10.).y_origin(23.).y_step(-1.);
+    /// let svg = c.render_svg(&[0., 0., 0., 0., 1., 0., 0., 0., 0.], &[0.5], &SvgStyle::default())?;
+    /// assert!(svg.contains(r#"viewBox="10 20 3 3""#));
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`svg::to_svg`]: svg/fn.to_svg.html
+    /// [`y_step`]: #method.y_step
+    pub fn render_svg(&self, values: &[f64], thresholds: &[f64], style: &SvgStyle) -> Result<String> {
+        let features = self.contours(values, thresholds)?;
+        Ok(to_svg(&features, self.output_extent(&features), self.options.y_step < 0., style))
+    }
+
+    /// Computes contours like [`render_svg`] does, writing the SVG document to `writer`.
+    ///
+    /// [`render_svg`]: #method.render_svg
+    pub fn write_svg<W: io::Write>(&self, writer: W, values: &[f64], thresholds: &[f64], style: &SvgStyle) -> Result<()> {
+        let features = self.contours(values, thresholds)?;
+        Ok(write_svg(writer, &features, self.output_extent(&features), self.options.y_step < 0., style)?)
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// and renders them to a PNG image of `width` x `height` pixels covering the extent
+    /// of the grid (see [`render_svg`]), the polygons of each threshold being filled with
+    /// the colors of `palette` (see [`png::render_png`]), over a transparent background.
+    ///
+    /// [`render_svg`]: #method.render_svg
+    /// [`png::render_png`]: png/fn.render_png.html
+    #[cfg(feature = "png")]
+    pub fn render_png(&self, values: &[f64], thresholds: &[f64], width: u32, height: u32, palette: &[Rgb]) -> Result<Vec<u8>> {
+        self.render_png_with(values, thresholds, width, height, palette, &PngOptions::default())
+    }
+
+    /// Renders the contours like [`render_png`] does, with the given background, isolines
+    /// and anti-aliasing.
+    ///
+    /// [`render_png`]: #method.render_png
+    #[cfg(feature = "png")]
+    pub fn render_png_with(&self, values: &[f64], thresholds: &[f64], width: u32, height: u32, palette: &[Rgb], options: &PngOptions) -> Result<Vec<u8>> {
+        let features = self.contours(values, thresholds)?;
+        let extent = self.output_extent(&features);
+        render_png(&features, extent, self.options.y_step < 0., (width, height), palette, options)
+    }
+
+    // The extent of the grid, extended to the positions of the features outside of it.
+    fn output_extent(&self, features: &[Feature]) -> Extent {
+        let o = &self.options;
+        let (x0, x1) = (o.x_origin, o.x_origin + f64::from(o.dx) * o.x_step);
+        let (y0, y1) = (o.y_origin, o.y_origin + f64::from(o.dy) * o.y_step);
+        let mut extent = Extent { x_min: x0.min(x1), y_min: y0.min(y1), x_max: x0.max(x1), y_max: y0.max(y1) };
+        let positions = features.iter().filter_map(|feature| feature.geometry.as_ref()).flat_map(|geometry| match geometry.value {
+            Value::Polygon(ref polygon) => polygon.iter().flatten().collect::<Vec<_>>(),
+            Value::MultiPolygon(ref polygons) => polygons.iter().flatten().flatten().collect(),
+            _ => Vec::new(),
+        });
+        for position in positions {
+            extent.x_min = extent.x_min.min(position[0]);
+            extent.y_min = extent.y_min.min(position[1]);
+            extent.x_max = extent.x_max.max(position[0]);
+            extent.y_max = extent.y_max.max(position[1]);
+        }
+        extent
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`,
+    /// storing them directly in the buffers of a geoarrow MultiPolygon column
+    /// (one row per threshold) without building any GeoJSON object.
+    ///
+    /// Only the first two coordinates of each vertex are stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The slice of values to be used.
+    /// * `thresholds` - The slice of thresholds values to be used.
+    pub fn contours_geoarrow(&self, values: &[f64], thresholds: &[f64]) -> Result<MultiPolygonArray> {
+        self.check_input(values)?;
+        let mut pass = self.pass()?;
+        let mut array = MultiPolygonArray::new();
+        for threshold in thresholds {
+            let mut polygons = self.polygons(&|i| values[i], *threshold, &mut pass)?;
+            self.close_rings(&mut polygons);
+            array.push(*threshold, polygons);
+        }
+        Ok(array)
+    }
+
+    /// Computes contours like [`contours_geoarrow`] and returns them as an arrow
+    /// `RecordBatch` with a `geometry` GeoArrow MultiPolygon column and a `value` column
+    /// holding the thresholds (see [`MultiPolygonArray::into_record_batch`]).
+    ///
+    /// Only available with the `arrow` feature.
+    ///
+    /// [`contours_geoarrow`]: #method.contours_geoarrow
+    /// [`MultiPolygonArray::into_record_batch`]: geoarrow/struct.MultiPolygonArray.html#method.into_record_batch
+    #[cfg(feature = "arrow")]
+    pub fn contours_record_batch(&self, values: &[f64], thresholds: &[f64]) -> Result<RecordBatch> {
+        self.contours_geoarrow(values, thresholds)?.into_record_batch()
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// and returns, for each threshold, its rings encoded as polylines
+    /// (see the [`polyline`] module) with `precision` decimals.
+    ///
+    /// [`polyline`]: polyline/index.html
+    pub fn contours_encoded_polylines(&self, values: &[f64], thresholds: &[f64], precision: u32) -> Result<Vec<EncodedContour>> {
+        self.check_input(values)?;
+        let mut pass = self.pass()?;
+        thresholds
+            .iter()
+            .map(|threshold| {
+                let mut polygons = self.polygons(&|i| values[i], *threshold, &mut pass)?;
+                self.close_rings(&mut polygons);
+                let rings = polygons
+                    .iter()
+                    .flat_map(|polygon| {
+                        polygon.iter().enumerate().map(|(i, ring)| {
+                            Ok(EncodedRing {
+                                polyline: encode(ring, precision)?,
+                                hole: i > 0,
+                            })
+                        })
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(EncodedContour { value: *threshold, rings })
+            })
+            .collect()
+    }
+
+    /// Computes contours according the given input `values` and the given `thresholds`
+    /// and triangulates them, returning one indexed triangle [`Mesh`] per threshold.
+    ///
+    /// The vertices have a third (z) coordinate, the threshold value, when
+    /// the [`z`] option is set. Degenerate polygons are skipped.
+    ///
+    /// [`Mesh`]: mesh/struct.Mesh.html
+    /// [`z`]: #method.z
+    pub fn contours_mesh(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Mesh>> {
+        self.check_input(values)?;
+        let mut pass = self.pass()?;
+        thresholds
+            .iter()
+            .map(|threshold| {
+                let mut mesh = Mesh::new(*threshold, if self.options.z { 3 } else { 2 });
+                for polygon in self.polygons(&|i| values[i], *threshold, &mut pass)? {
+                    mesh.push_polygon(&polygon);
+                }
+                Ok(mesh)
+            })
+            .collect()
+    }
+
+    /// Computes the statistics of the given input `values` (see [`GridStats`]),
+    /// checking them like [`contours`] does.
+    ///
+    /// [`GridStats`]: struct.GridStats.html
+    /// [`contours`]: #method.contours
+    pub fn stats(&self, values: &[f64]) -> Result<GridStats> {
+        self.check_input(values)?;
+        Ok(GridStats::compute(values))
+    }
+
+    /// Checks the given input `values` before computing contours (see [`ValidationReport`]):
+    /// their length, their NaN and infinite values, whether the grid is constant,
+    /// and their finite range.
+    ///
+    /// ```
+    /// # use contour::ContourBuilder;
+    /// let report = ContourBuilder::new(2, 2, true).validate_input(&[1., f64::NAN, 2., 3.]);
+    /// assert!(!report.is_valid());
+    /// assert_eq!(report.nan_indices, [1]);
+    /// assert_eq!((report.min, report.max), (Some(1.), Some(3.)));
+    /// ```
+    ///
+    /// [`ValidationReport`]: struct.ValidationReport.html
+    pub fn validate_input(&self, values: &[f64]) -> ValidationReport {
+        ValidationReport::compute(values, self.options.dx, self.options.dy)
+    }
+
+    // Checks the length of the input values, and the values themselves in strict mode.
+    fn check_input(&self, values: &[f64]) -> Result<()> {
+        if self.options.strict_input {
+            let report = match self.options.value_transform {
+                Some(ValueTransform(ref f)) => self.validate_input(&values.iter().map(|value| f(*value)).collect::<Vec<_>>()),
+                None => self.validate_input(values),
+            };
+            if !report.is_valid() {
+                return Err(new_error(ErrorKind::InvalidInput(report)));
+            }
+        }
+        check_dimensions(self.options.dx, self.options.dy, values.len())
+    }
+
+    // Checks the input values like `check_input` does, for values read in place (the value
+    // at row-major index `i` being `values(i)`) whose dimensions are already checked: they
+    // are only copied in strict mode.
+    fn check_input_by<V: Fn(usize) -> f64>(&self, values: V) -> Result<()> {
+        if self.options.strict_input {
+            let len = self.options.dx as usize * self.options.dy as usize;
+            self.check_input(&(0..len).map(values).collect::<Vec<_>>())?;
+        }
+        Ok(())
+    }
+
+    /// Returns the highest number of rings of one threshold and the highest number of
+    /// positions of one ring among the contours of the given input `values` and `thresholds`,
+    /// to be given to [`with_capacity_hint`].
+    ///
+    /// [`with_capacity_hint`]: #method.with_capacity_hint
+    pub fn capacity_hint_for(&self, values: &[f64], thresholds: &[f64]) -> Result<(usize, usize)> {
+        self.check_input(values)?;
+        let mut pass = self.pass()?;
+        let (mut rings, mut ring_len) = (0, 0);
+        for threshold in thresholds {
+            let polygons = self.grid_polygons(&|i| values[i], *threshold, &mut pass)?;
+            rings = rings.max(polygons.iter().map(|polygon| polygon.len()).sum());
+            ring_len = polygons.iter().flatten().map(|ring| ring.len()).fold(ring_len, usize::max);
+        }
+        Ok((rings, ring_len))
+    }
+
+    /// Finds the local maxima and minima of the given input `values` (see [`ExtremaOptions`])
+    /// and returns them as Features of Point, located at the center of their cell,
+    /// with the same coordinates as the polygons returned by [`contours`]
+    /// (the origin, step, reprojection, clipping, antimeridian and z options apply).
+    /// The value of each extremum is stored in its `value` property and its kind
+    /// in its `kind` property (`"max"` or `"min"`).
+    ///
+    /// [`ExtremaOptions`]: extrema/struct.ExtremaOptions.html
+    /// [`contours`]: #method.contours
+    pub fn extrema(&self, values: &[f64], options: &ExtremaOptions) -> Result<Vec<Feature>> {
+        self.check_input(values)?;
+        let mut pass = self.pass()?;
+        let mut features = Vec::new();
+        for extremum in find_extrema(&|i| values[i], self.options.dx as usize, self.options.dy as usize, options) {
+            let mut point = vec![extremum.x as f64 + 0.5, extremum.y as f64 + 0.5];
+            if !self.georeference_point(&mut point, extremum.value, &mut pass)? {
+                continue;
+            }
+            let mut properties = Map::with_capacity(2);
+            properties.insert(String::from("value"), to_value(extremum.value)?);
+            properties.insert(String::from("kind"), to_value(extremum.kind.as_str())?);
+            features.push(Feature {
+                bbox: if self.options.bbox { Some(vec![point[0], point[1], point[0], point[1]]) } else { None },
+                geometry: Some(Geometry::new(Value::Point(point))),
+                properties: Some(properties),
+                id: None,
+                foreign_members: self.foreign_members(),
+            });
+        }
+        Ok(features)
+    }
+
+    fn contour<V: Fn(usize) -> f64 + Sync>(&self, values: &V, threshold: f64, pass: &mut Pass) -> Result<Feature> {
+        let mut span = enter(SpanKind::Threshold, Some(threshold));
+        let mut properties = Map::with_capacity(1);
+        properties.insert(String::from("value"), serde_json::Value::from(threshold));
+        let polygons = self.contour_polygons(values, threshold, pass, Some(&mut properties))?;
+        span.rings(polygons.iter().map(|polygon| polygon.len()).sum());
+        let _span = enter(SpanKind::Feature, Some(threshold));
+        Ok(self.feature(polygons, properties))
+    }
+
+    // Computes the polygons of the Feature of the threshold in output coordinates, inserting
+    // the properties computed in grid coordinates into `properties`, if any.
+    fn contour_polygons<V: Fn(usize) -> f64 + Sync>(
+        &self,
+        values: &V,
+        threshold: f64,
+        pass: &mut Pass,
+        properties: Option<&mut Map<String, serde_json::Value>>,
+    ) -> Result<Vec<Vec<LinearRing>>> {
+        let mut polygons = self.grid_polygons(values, threshold, pass)?;
+        if let Some(properties) = properties {
+            self.insert_grid_properties(values, threshold, &polygons, pass, properties)?;
+        }
+        {
+            let _span = enter(SpanKind::Georeference, Some(threshold));
+            self.georeference(&mut polygons, threshold, pass)?;
+        }
+        if let Some(diagnostics) = pass.diagnostics() {
+            diagnostics.vertices = polygons.iter().flatten().map(|ring| ring.len()).sum();
+        }
+        Ok(polygons)
+    }
+
+    // Computes the rings for the given threshold and groups them
+    // into polygons (the exterior ring followed by its holes).
+    fn polygons<V: Fn(usize) -> f64 + Sync>(&self, values: &V, threshold: f64, pass: &mut Pass) -> Result<Vec<Vec<LinearRing>>> {
+        let mut polygons = self.grid_polygons(values, threshold, pass)?;
+        self.georeference(&mut polygons, threshold, pass)?;
+        Ok(polygons)
+    }
+
+    // Computes the polygons like `polygons` does, keeping them in grid coordinates.
+    fn grid_polygons<V: Fn(usize) -> f64 + Sync>(&self, values: &V, threshold: f64, pass: &mut Pass) -> Result<Vec<Vec<LinearRing>>> {
+        let (epsilon, rounding, period) = (self.options.threshold_epsilon, self.options.epsilon_rounding, self.options.period);
+        if let Some(period) = period {
+            if !(0. ..period).contains(&threshold) {
+                return Err(new_error(ErrorKind::ThresholdOutOfPeriod { threshold, period }));
+            }
+        }
+        let transform = self.options.value_transform.as_ref().map(|ValueTransform(f)| &**f);
+        if epsilon > 0. || period.is_some() || transform.is_some() {
+            let mapped = |i| {
+                let value = match transform {
+                    Some(f) => f(values(i)),
+                    None => values(i),
+                };
+                let value = match period {
+                    Some(period) => unwrap_angle(value, threshold, period),
+                    None => value,
+                };
+                if epsilon > 0. { snap(value, threshold, epsilon, rounding) } else { value }
+            };
+            self.snapped_polygons(&mapped, threshold, pass)
+        } else {
+            self.snapped_polygons(values, threshold, pass)
+        }
+    }
+
+    // Computes the polygons like `grid_polygons` does, the values being already unwrapped
+    // around the threshold and snapped to it, so that the cells are classified and the rings
+    // smoothed alike.
+    fn snapped_polygons<V: Fn(usize) -> f64 + Sync>(&self, values: &V, threshold: f64, pass: &mut Pass) -> Result<Vec<Vec<LinearRing>>> {
+        let mut result = if self.options.dx == 0 || self.options.dy == 0 {
+            Vec::new()
+        } else if self.options.algorithm == Algorithm::BoundaryTrace {
+            let (dx, dy) = (self.options.dx as usize, self.options.dy as usize);
+            trace_rings(values, threshold, dx, dy, &self.limits(), &mut pass.isoring.vertices)?
+        } else if cfg!(feature = "rayon") && self.options.threads > 1 {
+            self.parallel_rings(values, threshold, &mut pass.isoring.vertices)?
+        } else {
+            pass.isoring.compute_by(values, threshold)?
+        };
+        if let Some(diagnostics) = pass.diagnostics.as_mut() {
+            diagnostics.push(ThresholdDiagnostics { threshold, rings: result.len(), ..ThresholdDiagnostics::default() });
+        }
+
+        if self.options.smooth {
+            let _span = enter(SpanKind::Smooth, Some(threshold));
+            let original = if self.options.validation == Validation::Repair { Some(result.clone()) } else { None };
+            let grid = GridView::from_fn(values, self.options.dx, self.options.dy);
+            let smoother: &dyn VertexSmoother = match self.options.smoother {
+                Some(Smoother(ref smoother)) => &**smoother,
+                None => &LinearSmoother,
+            };
+            for points in result.iter_mut() {
+                let mut ring = Ring::from(std::mem::take(points));
+                smoother.smooth(&mut ring, &grid, threshold);
+                *points = ring.into();
+            }
+            if let Some(original) = original {
+                let repaired = repair(&mut result, &original);
+                if let Some(diagnostics) = pass.diagnostics() {
+                    diagnostics.repaired_rings = repaired;
+                }
+            }
+        }
+
+        if self.options.merge_collinear {
+            result.iter_mut().for_each(merge_collinear);
+        }
+        if let Some(max_segment_length) = self.options.densify {
+            result.iter_mut().for_each(|ring| densify(ring, max_segment_length));
+        }
+
+        let mut span = enter(SpanKind::GroupHoles, Some(threshold));
+        let polygons = pass.grouping.group(result);
+        span.rings(polygons.len());
+        if let Some(diagnostics) = pass.diagnostics() {
+            diagnostics.shells = polygons.len();
+            diagnostics.holes = polygons.iter().map(|polygon| polygon.len() - 1).sum();
+            diagnostics.orphans = diagnostics.rings - diagnostics.shells - diagnostics.holes;
+        }
+
+        if self.options.validation == Validation::Check {
+            let rings = polygons.iter().flatten().collect::<Vec<_>>();
+            let found = find_intersections(&rings);
+            if !found.is_empty() {
+                let ids = polygons.iter().enumerate().flat_map(|(p, polygon)| (0..polygon.len()).map(move |r| (p, r))).collect::<Vec<_>>();
+                let segment = |(r, segment): (usize, usize)| SegmentId { polygon: ids[r].0, ring: ids[r].1, segment };
+                let intersections = found.into_iter().map(|[a, b]| Intersection { threshold, first: segment(a), second: segment(b) });
+                return Err(new_error(ErrorKind::InvalidGeometry(intersections.collect())));
+            }
+        }
+
+        Ok(polygons)
+    }
+
+    // Computes the rings in `threads` bands of rows, see `compute_rings_parallel`.
+    #[cfg(feature = "rayon")]
+    fn parallel_rings<V: Fn(usize) -> f64 + Sync>(&self, values: &V, threshold: f64, vertices: &mut usize) -> Result<Vec<LinearRing>> {
+        let (dx, dy) = (self.options.dx, self.options.dy);
+        compute_rings_parallel(values, threshold, dx, dy, self.options.threads, &self.limits(), vertices)
+    }
+
+    // Without the `rayon` feature, the `threads` option is ignored (and this is never called).
+    #[cfg(not(feature = "rayon"))]
+    fn parallel_rings<V: Fn(usize) -> f64 + Sync>(&self, values: &V, threshold: f64, vertices: &mut usize) -> Result<Vec<LinearRing>> {
+        let mut isoring = IsoRingBuilder::new(self.options.dx, self.options.dy);
+        isoring.limits = self.limits();
+        isoring.vertices = *vertices;
+        let result = isoring.compute_by(values, threshold)?;
+        *vertices = isoring.vertices;
+        Ok(result)
+    }
+
+    // The limits of the size of the output.
+    fn limits(&self) -> Limits {
+        Limits {
+            rings: self.options.max_rings_per_threshold,
+            vertices: self.options.max_total_vertices,
+        }
+    }
+
+    // Creates the state used while computing the contours of one grid.
+    fn pass(&self) -> Result<Pass> {
+        let transformer = match self.options.transformer {
+            Some(Reprojection(ref factory)) => Some(factory().map_err(|err| new_error(ErrorKind::Reprojection(err)))?),
+            None => None,
+        };
+        let mut isoring = IsoRingBuilder::with_capacity(self.options.dx, self.options.dy, self.options.capacity_hint.0, self.options.capacity_hint.1, false);
+        isoring.limits = self.limits();
+        Ok(Pass {
+            isoring,
+            transformer,
+            #[cfg(feature = "clip")]
+            clip: self.options.clip.as_deref().map(clip_region).transpose()?,
+            diagnostics: None,
+            grouping: Grouping::default(),
+            extrema: None,
+        })
+    }
+
+    // Converts the given polygons from grid coordinates to output coordinates.
+    fn georeference(&self, polygons: &mut Vec<Vec<LinearRing>>, threshold: f64, pass: &mut Pass) -> Result<()> {
+        if self.options.x_origin != 0. || self.options.y_origin != 0. || self.options.x_step != 1. || self.options.y_step != 1. {
+            polygons.iter_mut().flatten().flatten().for_each(|point| {
+                point[0] = point[0] * self.options.x_step + self.options.x_origin;
+                point[1] = point[1] * self.options.y_step + self.options.y_origin;
+            });
+        }
+        #[cfg(feature = "buffer")]
+        {
+            if let Some(distance) = self.options.buffer {
+                *polygons = buffer_polygons(polygons, distance)?;
+            }
+        }
+
+        let count = |polygons: &[Vec<LinearRing>]| polygons.iter().map(|polygon| polygon.len()).sum::<usize>();
+        let before = if pass.diagnostics.is_some() { count(polygons) } else { 0 };
+        if let Some(ref mut transformer) = pass.transformer {
+            for polygon in polygons.iter_mut() {
+                for ring in polygon.iter_mut() {
+                    let mut projected: LinearRing = Vec::with_capacity(ring.len());
+                    for point in ring.drain(..) {
+                        match transformer(point[0], point[1]) {
+                            Ok((x, y)) => projected.push(vec![x, y]),
+                            Err(err) => {
+                                if self.options.reprojection_failure == ReprojectionFailure::Error {
+                                    return Err(new_error(ErrorKind::Reprojection(err)));
+                                }
+                            }
+                        }
+                    }
+                    if projected.len() > 1 && projected.first() != projected.last() {
+                        projected.push(projected[0].clone());
+                    }
+                    *ring = projected;
+                }
+                // Rings left with less than 3 distinct positions are dropped,
+                // along with the holes of a dropped exterior ring.
+                if polygon[0].len() < 4 {
+                    polygon.clear();
+                }
+                polygon.retain(|ring| ring.len() >= 4);
+            }
+            polygons.retain(|polygon| !polygon.is_empty());
+        }
+
+        #[cfg(feature = "clip")]
+        {
+            if let Some(ref region) = pass.clip {
+                *polygons = clip_polygons(std::mem::take(polygons), region);
+            }
+        }
+        if let Some(diagnostics) = pass.diagnostics() {
+            // Clipping can split rings, giving more rings than it drops.
+            diagnostics.dropped = before.saturating_sub(count(polygons));
+        }
+
+        if self.options.union_touching {
+            *polygons = union_touching(std::mem::take(polygons));
+        }
+
+        if self.options.antimeridian {
+            *polygons = polygons.drain(..).flat_map(split_at_antimeridian).collect();
+        }
+
+        if self.options.normalize_rings {
+            polygons.iter_mut().flatten().for_each(normalize);
+        }
+
+        if self.options.z {
+            polygons
+                .iter_mut()
+                .flatten()
+                .flatten()
+                .for_each(|point| point.push(threshold));
+        }
+        Ok(())
+    }
+
+    // Converts the given point from grid coordinates to output coordinates like `georeference`
+    // does, returning whether it is kept.
+    fn georeference_point(&self, point: &mut Pt, value: f64, pass: &mut Pass) -> Result<bool> {
+        point[0] = point[0] * self.options.x_step + self.options.x_origin;
+        point[1] = point[1] * self.options.y_step + self.options.y_origin;
+        if let Some(ref mut transformer) = pass.transformer {
+            match transformer(point[0], point[1]) {
+                Ok((x, y)) => *point = vec![x, y],
+                Err(err) if self.options.reprojection_failure == ReprojectionFailure::Error => {
+                    return Err(new_error(ErrorKind::Reprojection(err)));
+                }
+                Err(_) => return Ok(false),
+            }
+        }
+        #[cfg(feature = "clip")]
+        {
+            if matches!(pass.clip, Some(ref region) if !clip_contains(region, (point[0], point[1]))) {
+                return Ok(false);
+            }
+        }
+        if self.options.antimeridian && !(-180. ..=180.).contains(&point[0]) {
+            point[0] -= 360. * ((point[0] + 180.) / 360.).floor();
+        }
+        if self.options.z {
+            point.push(value);
+        }
+        Ok(true)
+    }
+
+    // Inserts the properties computed from the polygons in grid coordinates
+    // (the zonal statistics and the enclosed extrema).
+    fn insert_grid_properties<V: Fn(usize) -> f64>(
+        &self,
+        values: &V,
+        threshold: f64,
+        polygons: &[Vec<LinearRing>],
+        pass: &mut Pass,
+        properties: &mut Map<String, serde_json::Value>,
+    ) -> Result<()> {
+        self.insert_zonal_stats(values, polygons, properties)?;
+        if let Some(encloses) = self.encloses(values, threshold, polygons, pass) {
+            properties.insert(String::from("encloses"), json!(encloses));
+        }
+        Ok(())
+    }
+
+    fn insert_zonal_stats<V: Fn(usize) -> f64>(&self, values: &V, polygons: &[Vec<LinearRing>], properties: &mut Map<String, serde_json::Value>) -> Result<()> {
+        if self.options.zonal_stats {
+            let mut stats = ZonalStats::default();
+            for polygon in polygons {
+                stats.add_polygon(values, self.options.dx, self.options.dy, polygon);
+            }
+            stats.insert_into(properties)?;
+        }
+        Ok(())
+    }
+
+    // The kind of extremum enclosed by each ring of the polygons (of one threshold), when requested.
+    fn encloses<V: Fn(usize) -> f64>(&self, values: &V, threshold: f64, polygons: &[Vec<LinearRing>], pass: &mut Pass) -> Option<Vec<Vec<Option<&'static str>>>> {
+        if !self.options.enclosures {
+            return None;
+        }
+        let (dx, dy) = (self.options.dx, self.options.dy);
+        let extrema = pass.extrema.get_or_insert_with(|| find_extrema(values, dx as usize, dy as usize, &ExtremaOptions::default()));
+        Some(enclosures(extrema, threshold, polygons, dx, dy))
+    }
+
+    fn feature(&self, polygons: Vec<Vec<LinearRing>>, mut properties: Map<String, serde_json::Value>) -> Feature {
+        if self.options.ring_directions != RingDirections::Off {
+            let sides = polygons
+                .iter()
+                .map(|polygon| polygon.iter().enumerate().map(|(i, ring)| higher_side(ring, i == 0).map(|side| side.as_str())).collect())
+                .collect::<Vec<Vec<_>>>();
+            properties.insert(String::from("higher_side"), json!(sides));
+        }
+        if self.options.ring_directions == RingDirections::WithTangentAngles {
+            let angles = polygons
+                .iter()
+                .map(|polygon| {
+                    polygon
+                        .iter()
+                        .map(|ring| {
+                            let mut angles = tangent_angles(ring);
+                            if self.options.ring_closure == RingClosure::Open {
+                                angles.pop();
+                            }
+                            angles
+                        })
+                        .collect()
+                })
+                .collect::<Vec<Vec<_>>>();
+            properties.insert(String::from("tangent_angles"), json!(angles));
+        }
+        let metric = self.options.metric;
+        if self.options.area {
+            let area = polygons
+                .iter()
+                .filter_map(|polygon| polygon.split_first())
+                .map(|(shell, holes)| metric.area(shell) - holes.iter().map(|hole| metric.area(hole)).sum::<f64>())
+                .sum::<f64>();
+            properties.insert(String::from("area"), json!(area));
+        }
+        if self.options.perimeter {
+            let perimeter = polygons.iter().flatten().map(|ring| metric.perimeter(ring)).sum::<f64>();
+            properties.insert(String::from("perimeter"), json!(perimeter));
+        }
+        let bbox = if self.options.bbox { bbox(&polygons) } else { None };
+        Feature {
+            geometry: self.geometry(polygons),
+            properties: Some(properties),
+            bbox,
+            id: None,
+            foreign_members: self.foreign_members(),
+        }
+    }
+
+    fn foreign_members(&self) -> Option<Map<String, serde_json::Value>> {
+        match self.options.crs {
+            Some(ref name) => {
+                let mut foreign_members = Map::with_capacity(1);
+                foreign_members.insert(
+                    String::from("crs"),
+                    json!({ "type": "name", "properties": { "name": name } }),
+                );
+                Some(foreign_members)
+            }
+            None => None,
+        }
+    }
+
+    // Assembles the geometry of the polygons (`None` for no polygon with `EmptyGeometry::Null`).
+    fn geometry(&self, mut polygons: Vec<Vec<LinearRing>>) -> Option<Geometry> {
+        self.close_rings(&mut polygons);
+        let value = match (polygons.len(), self.options.geometry_mode, self.options.empty_geometry) {
+            (0, _, EmptyGeometry::Null) => return None,
+            (1, GeometryMode::Auto, _) => Value::Polygon(polygons.pop().unwrap()),
+            _ => Value::MultiPolygon(polygons),
+        };
+        Some(Geometry {
+            value,
+            bbox: None,
+            foreign_members: None,
+        })
+    }
 }
 
-/// Contours generator to
-/// be used on a rectangular `Slice` of values to
-/// get a `Vec` of Features of MultiPolygon (use [`contour_rings`] internally).
-///
-/// [`contour_rings`]: fn.contour_rings.html
-pub struct ContourBuilder {
-    dx: u32,
-    dy: u32,
-    smooth: bool,
-}
+// Merges the polygons sharing positions: the segments of their rings going back and forth
+// between the same two positions are dropped, and the other ones are stitched into rings,
+// going on along another polygon at the shared positions. The other polygons are left as is.
+fn union_touching(polygons: Vec<Vec<LinearRing>>) -> Vec<Vec<LinearRing>> {
+    let key = |point: &Pt| ((point[0] + 0.).to_bits(), (point[1] + 0.).to_bits());
+    // The groups of polygons sharing positions, by union-find.
+    let mut parent = (0..polygons.len()).collect::<Vec<_>>();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut owners = HashMap::new();
+    for (i, polygon) in polygons.iter().enumerate() {
+        for point in polygon.iter().flatten() {
+            let owner = *owners.entry(key(point)).or_insert(i);
+            let (a, b) = (root(&mut parent, owner), root(&mut parent, i));
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+    let mut groups: Vec<Vec<Vec<LinearRing>>> = Vec::new();
+    let mut group_of = HashMap::new();
+    for (i, polygon) in polygons.into_iter().enumerate() {
+        let r = root(&mut parent, i);
+        let g = *group_of.entry(r).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[g].push(polygon);
+    }
 
-impl ContourBuilder {
-    /// Constructs a new contours generator for a grid with `dx` * `dy` dimension.
-    ///
-    /// # Arguments
-    ///
-    /// * `dx` - The number of columns in the grid.
-    /// * `dy` - The number of rows in the grid.
-    /// * `smooth` - Whether or not the generated rings will be smoothed using linear interpolation.
-    pub fn new(dx: u32, dy: u32, smooth: bool) -> Self {
-        ContourBuilder { dx, dy, smooth }
-    }
-
-    fn smoooth_linear(&self, ring: &mut Ring, values: &[f64], value: f64) {
-        let dx = self.dx;
-        let dy = self.dy;
-        let len_values = values.len();
-
-        ring.iter_mut()
-            .map(|point| {
-                let x = point[0];
-                let y = point[1];
-                let xt = x.trunc() as u32;
-                let yt = y.trunc() as u32;
-                let mut v0;
-                let ix = (yt * dx + xt) as usize;
-                if ix < len_values {
-                    let v1 = values[ix];
-                    if x > 0.0 && x < (dx as f64) && (xt as f64 - x).abs() < std::f64::EPSILON {
-                        v0 = values[(yt * dx + xt - 1) as usize];
-                        point[0] = x + (value - v0) / (v1 - v0) - 0.5;
-                    }
-                    if y > 0.0 && y < (dy as f64) && (yt as f64 - y).abs() < std::f64::EPSILON {
-                        v0 = values[((yt - 1) * dx + xt) as usize];
-                        point[1] = y + (value - v0) / (v1 - v0) - 0.5;
-                    }
+    let mut merged = Vec::with_capacity(groups.len());
+    for group in groups {
+        if group.len() == 1 {
+            merged.extend(group);
+            continue;
+        }
+        // The segments, along with the index of their ring.
+        let segments = group
+            .iter()
+            .flatten()
+            .enumerate()
+            .flat_map(|(r, ring)| ring.windows(2).filter(|s| key(&s[0]) != key(&s[1])).map(move |s| (&s[0], &s[1], r)))
+            .collect::<Vec<_>>();
+        let mut used = vec![false; segments.len()];
+        let mut by_ends: HashMap<_, Vec<usize>> = HashMap::new();
+        for (i, (a, b, _)) in segments.iter().enumerate() {
+            by_ends.entry((key(a), key(b))).or_default().push(i);
+        }
+        for (i, (a, b, _)) in segments.iter().enumerate() {
+            if used[i] {
+                continue;
+            }
+            if let Some(j) = by_ends.get(&(key(b), key(a))).and_then(|reverse| reverse.iter().find(|j| !used[**j])) {
+                used[i] = true;
+                used[*j] = true;
+            }
+        }
+        let mut starting: HashMap<_, Vec<usize>> = HashMap::new();
+        for (i, (a, _, _)) in segments.iter().enumerate().filter(|(i, _)| !used[*i]) {
+            starting.entry(key(a)).or_default().push(i);
+        }
+        let mut rings = Vec::new();
+        for first in 0..segments.len() {
+            if used[first] {
+                continue;
+            }
+            let mut ring = vec![segments[first].0.clone()];
+            let mut i = first;
+            loop {
+                used[i] = true;
+                let (_, end, r) = segments[i];
+                ring.push(end.clone());
+                // Goes on along another ring at the shared positions, for the polygons only
+                // meeting there to be merged too.
+                let candidates = starting.get(&key(end)).map(|c| c.iter().filter(|j| !used[**j]).collect::<Vec<_>>()).unwrap_or_default();
+                match candidates.iter().find(|j| segments[***j].2 != r).or_else(|| candidates.first()) {
+                    Some(j) if key(end) != key(segments[first].0) || segments[**j].2 != r => i = **j,
+                    _ => break,
                 }
-            })
-            .for_each(drop);
+            }
+            if ring.len() >= 4 {
+                rings.push(ring);
+            }
+        }
+        merged.extend(group_holes(rings));
     }
+    merged
+}
 
-    /// Computes contours according the given input `values` and the given `thresholds`.
-    /// Returns a `Vec` of Features of MultiPolygon.
-    /// The threshold value of each Feature is stored in its `value` property.
-    ///
-    /// # Arguments
-    ///
-    /// * `values` - The slice of values to be used.
-    /// * `thresholds` - The slice of thresholds values to be used.
-    pub fn contours(&self, values: &[f64], thresholds: &[f64]) -> Result<Vec<Feature>> {
-        if values.len() as u32 != self.dx * self.dy {
-            return Err(new_error(ErrorKind::BadDimension));
+// Cuts the polygons `higher` out of the polygons `lower` they are nested in: the exterior
+// rings of `higher` become holes and their holes exterior rings, each hole going to the
+// smallest exterior ring containing it.
+fn cut_out(lower: &[Vec<LinearRing>], higher: &[Vec<LinearRing>]) -> Vec<Vec<LinearRing>> {
+    let reversed = |ring: &LinearRing| ring.iter().rev().cloned().collect::<LinearRing>();
+    let (mut shells, mut holes) = (Vec::new(), Vec::new());
+    for polygon in lower {
+        if let Some((shell, rest)) = polygon.split_first() {
+            shells.push(shell.clone());
+            holes.extend(rest.iter().cloned());
         }
-        let mut isoring = IsoRingBuilder::new(self.dx, self.dy);
-        thresholds
+    }
+    for polygon in higher {
+        if let Some((shell, rest)) = polygon.split_first() {
+            holes.push(reversed(shell));
+            shells.extend(rest.iter().map(reversed));
+        }
+    }
+    let mut polygons = shells.into_iter().map(|shell| vec![shell]).collect::<Vec<_>>();
+    for hole in holes {
+        let smallest = polygons
             .iter()
-            .map(|value| self.contour(values, *value, &mut isoring))
-            .collect::<Result<Vec<Feature>>>()
+            .enumerate()
+            .filter(|(_, polygon)| contains(&polygon[0], &hole) != -1)
+            .min_by(|(_, a), (_, b)| area(&a[0]).partial_cmp(&area(&b[0])).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(i, _)| i);
+        if let Some(i) = smallest {
+            polygons[i].push(hole);
+        }
     }
+    polygons
+}
 
-    fn contour(&self, values: &[f64], threshold: f64, isoring: &mut IsoRingBuilder) -> Result<Feature> {
-        let (mut polygons, mut holes) = (Vec::new(), Vec::new());
-        let mut result = isoring.compute(values, threshold)?;
-
-        result
-            .drain(..)
-            .map(|mut ring| {
-                if self.smooth {
-                    self.smoooth_linear(&mut ring, values, threshold);
-                }
-                if area(&ring) > 0.0 {
-                    polygons.push(vec![ring]);
-                } else {
-                    holes.push(ring);
-                }
-            })
-            .for_each(drop);
-
-        holes
-            .drain(..)
-            .map(|hole| {
-                for polygon in &mut polygons {
-                    if contains(&polygon[0], &hole) != -1 {
-                        polygon.push(hole);
-                        return;
-                    }
-                }
-            })
-            .for_each(drop);
+// Splits the given polygon into the parts lying between two successive antimeridians,
+// shifted to have longitudes within [-180, 180].
+fn split_at_antimeridian(polygon: Vec<LinearRing>) -> Vec<Vec<LinearRing>> {
+    let (min, max) = polygon[0]
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| (min.min(p[0]), max.max(p[0])));
+    let (first, last) = (((min + 180.) / 360.).floor() as i64, ((max + 180.) / 360.).floor() as i64);
+    if first == 0 && (last == 0 || max == 180.) {
+        return vec![polygon];
+    }
+    (first..=last)
+        .filter_map(|k| {
+            let shift = 360. * k as f64;
+            let mut rings = polygon.iter().map(|ring| clip_ring_to_strip(ring, shift - 180., shift + 180.));
+            let mut part = vec![rings.next()??];
+            part.extend(rings.flatten());
+            part.iter_mut().flatten().for_each(|p| p[0] -= shift);
+            Some(part)
+        })
+        .collect()
+}
 
-        let mut properties = Map::with_capacity(1);
-        properties.insert(String::from("value"), to_value(threshold)?);
-        Ok(Feature {
-            geometry: Some(Geometry {
-                value: MultiPolygon(polygons),
-                bbox: None,
-                foreign_members: None,
-            }),
-            properties: Some(properties),
-            bbox: None,
-            id: None,
-            foreign_members: None,
+// Computes the bounding box of the given polygons (using only their exterior rings).
+fn bbox(polygons: &[Vec<LinearRing>]) -> Option<Vec<f64>> {
+    polygons
+        .iter()
+        .filter_map(|polygon| polygon.first())
+        .flatten()
+        .fold(None, |bbox, p| match bbox {
+            None => Some(vec![p[0], p[1], p[0], p[1]]),
+            Some(b) => Some(vec![b[0].min(p[0]), b[1].min(p[1]), b[2].max(p[0]), b[3].max(p[1])]),
         })
-    }
 }
 
 /// Computes isoring for the given `Slice` of `values` according to the `threshold` value
@@ -171,19 +2844,551 @@ impl ContourBuilder {
 /// * `threshold` - The threshold value.
 /// * `dx` - The number of columns in the grid.
 /// * `dy` - The number of rows in the grid.
+///
+/// The exterior rings are [`Winding::Clockwise`] (see [`Ring::orientation`]) and the holes
+/// [`Winding::CounterClockwise`].
+///
+/// [`Winding::Clockwise`]: enum.Winding.html#variant.Clockwise
+/// [`Winding::CounterClockwise`]: enum.Winding.html#variant.CounterClockwise
+/// [`Ring::orientation`]: struct.Ring.html#method.orientation
 pub fn contour_rings(values: &[f64], threshold: f64, dx: u32, dy: u32) -> Result<Vec<Ring>> {
+    check_dimensions(dx, dy, values.len())?;
+    let mut isoring = IsoRingBuilder::new(dx, dy);
+    Ok(isoring.compute(values, threshold)?.into_iter().map(Ring::from).collect())
+}
+
+/// Computes isoring like [`contour_rings`] does, the positions being linearly interpolated
+/// between the values of the grid like the ones of the smoothed [`ContourBuilder`] are.
+///
+/// ```
+/// # use contour::contour_rings_smooth;
+/// let values = [0., 0., 0., 0., 0.75, 0., 0., 0., 0.];
+/// let rings = contour_rings_smooth(&values, 0.5, 3, 3)?;
+/// // Two thirds of the way from the value above to the one in the middle.
+/// assert!(rings[0].iter_points().any(|p| p[0] == 1.5 && (p[1] - 7. / 6.).abs() < 1e-12));
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`contour_rings`]: fn.contour_rings.html
+/// [`ContourBuilder`]: struct.ContourBuilder.html
+pub fn contour_rings_smooth(values: &[f64], threshold: f64, dx: u32, dy: u32) -> Result<Vec<Ring>> {
+    check_dimensions(dx, dy, values.len())?;
     let mut isoring = IsoRingBuilder::new(dx, dy);
-    isoring.compute(values, threshold)
+    let mut rings = isoring.compute(values, threshold)?;
+    let grid = GridView::new(values, dx, dy)?;
+    for ring in rings.iter_mut() {
+        smooth_linear(ring.iter_mut(), &grid, threshold);
+    }
+    Ok(rings.into_iter().map(Ring::from).collect())
+}
+
+// Checks that the polygons of each Feature are within the ones of the Feature of the next
+// lower threshold: every position inside or on the boundary of an exterior ring, and not
+// strictly inside one of its holes.
+fn check_nesting(features: &[Feature], thresholds: &[f64]) -> Result<()> {
+    let polygons = |feature: &Feature| match feature.geometry.as_ref().map(|geometry| &geometry.value) {
+        Some(Value::Polygon(polygon)) => vec![polygon.clone()],
+        Some(Value::MultiPolygon(polygons)) => polygons.clone(),
+        _ => Vec::new(),
+    };
+    // The NaN thresholds, below or above no other one, are left out.
+    let mut order = (0..features.len().min(thresholds.len())).filter(|i| !thresholds[*i].is_nan()).collect::<Vec<_>>();
+    order.sort_by(|a, b| thresholds[*a].total_cmp(&thresholds[*b]));
+    for pair in order.windows(2) {
+        let (lower, higher) = (pair[0], pair[1]);
+        if thresholds[lower] == thresholds[higher] {
+            continue;
+        }
+        let region = polygons(&features[lower]);
+        let inside = |point: &Vec<f64>| {
+            region.iter().any(|polygon| {
+                polygon.split_first().is_some_and(|(shell, holes)| {
+                    ring_contains(shell, point) >= 0 && holes.iter().all(|hole| ring_contains(hole, point) <= 0)
+                })
+            })
+        };
+        if !polygons(&features[higher]).iter().flatten().flatten().all(inside) {
+            return Err(new_error(ErrorKind::NotNested {
+                lower: thresholds[lower],
+                higher: thresholds[higher],
+            }));
+        }
+    }
+    Ok(())
+}
+
+// Groups the rings into polygons (the exterior ring, clockwise, followed by its holes),
+// the holes outside of every exterior ring being left out.
+pub(crate) fn group_holes(rings: Vec<LinearRing>) -> Vec<Vec<LinearRing>> {
+    Grouping::default().group(rings)
+}
+
+// The buffers used to group the rings into polygons, reused across thresholds.
+#[derive(Default)]
+struct Grouping {
+    shells: Vec<LinearRing>,
+    // The holes, along with the index of their exterior ring (`usize::MAX` if none).
+    holes: Vec<(usize, LinearRing)>,
+}
+
+impl Grouping {
+    // Groups the rings like `group_holes` does, each polygon being allocated once
+    // with its holes.
+    fn group(&mut self, rings: Vec<LinearRing>) -> Vec<Vec<LinearRing>> {
+        for ring in rings.into_iter().map(Ring::from) {
+            if ring.orientation() == Winding::Clockwise {
+                self.shells.push(ring.into());
+            } else {
+                self.holes.push((usize::MAX, ring.into()));
+            }
+        }
+        let shells = &self.shells;
+        for (shell, hole) in self.holes.iter_mut() {
+            *shell = shells.iter().position(|ring| contains(ring, hole) != -1).unwrap_or(usize::MAX);
+        }
+        // Stable, keeping the holes of each polygon in their order.
+        self.holes.sort_by_key(|(shell, _)| *shell);
+
+        let mut polygons = Vec::with_capacity(self.shells.len());
+        let mut start = 0;
+        for (i, shell) in self.shells.drain(..).enumerate() {
+            let end = start + self.holes[start..].iter().take_while(|(s, _)| *s == i).count();
+            let mut polygon = Vec::with_capacity(1 + end - start);
+            polygon.push(shell);
+            polygon.extend(self.holes[start..end].iter_mut().map(|(_, hole)| std::mem::take(hole)));
+            polygons.push(polygon);
+            start = end;
+        }
+        self.holes.clear();
+        polygons
+    }
+}
+
+/// Computes isorings like [`contour_rings`], along with their directions: the side of
+/// their higher values, always [`Side::Right`] (in grid coordinates, the y axis pointing
+/// up), and their tangent angles when `tangent_angles` is set (see [`Ring::tangent_angles`]).
+///
+/// [`contour_rings`]: fn.contour_rings.html
+/// [`Side::Right`]: enum.Side.html#variant.Right
+/// [`Ring::tangent_angles`]: struct.Ring.html#method.tangent_angles
+pub fn contour_rings_with_directions(values: &[f64], threshold: f64, dx: u32, dy: u32, tangent_angles: bool) -> Result<(Vec<Ring>, Vec<RingDirection>)> {
+    let rings = contour_rings(values, threshold, dx, dy)?;
+    let directions = rings
+        .iter()
+        .map(|ring| RingDirection {
+            higher_side: Side::Right,
+            tangent_angles: if tangent_angles { ring.tangent_angles() } else { Vec::new() },
+        })
+        .collect();
+    Ok((rings, directions))
+}
+
+/// Computes the marching squares case code of every cell for the given `Slice`
+/// of `values` according to the `threshold` value.
+///
+/// Cells are formed by four adjacent grid values and include the synthetic
+/// border cells (values outside the grid are below any threshold), so the returned
+/// `Vec` holds `(dx + 1) * (dy + 1)` codes in row-major order, the first one being
+/// the cell whose upper-right corner is the first value of the grid.
+/// Each code has bit 0 set for the value at `(x, y + 1)`, bit 1 for `(x + 1, y + 1)`,
+/// bit 2 for `(x + 1, y)` and bit 3 for `(x, y)` when they are greater than or equal
+/// to the threshold.
+///
+/// # Arguments
+///
+/// * `values` - The slice of values to be used.
+/// * `threshold` - The threshold value.
+/// * `dx` - The number of columns in the grid.
+/// * `dy` - The number of rows in the grid.
+pub fn case_indices(values: &[f64], threshold: f64, dx: u32, dy: u32) -> Result<Vec<u8>> {
+    check_dimensions(dx, dy, values.len())?;
+    let mut cases = Vec::new();
+    fill_cases(dx as usize, dy as usize, |i| values[i] >= threshold, &mut cases);
+    Ok(cases)
+}
+
+// Returns the value taken in place of `value` for the given `threshold`: the threshold itself,
+// or the float right below it, when `value` is within `epsilon` of it.
+fn snap(value: f64, threshold: f64, epsilon: f64, rounding: EpsilonRounding) -> f64 {
+    if (value - threshold).abs() > epsilon || value.is_nan() {
+        return value;
+    }
+    match rounding {
+        EpsilonRounding::Up => threshold,
+        EpsilonRounding::Down => threshold.next_down(),
+    }
+}
+
+// Returns the circular `value` moved by a multiple of `period` within half a period
+// of the `threshold`, in `[threshold - period / 2, threshold + period / 2)`.
+fn unwrap_angle(value: f64, threshold: f64, period: f64) -> f64 {
+    let offset = (value - threshold).rem_euclid(period);
+    threshold + if offset < period / 2. { offset } else { offset - period }
+}
+
+// Checks that `len` values make a grid of `dx` columns and `dy` rows,
+// whose edges can be indexed (see `edge_index`) without overflow.
+pub(crate) fn check_dimensions(dx: u32, dy: u32, len: usize) -> Result<()> {
+    let (dx, dy) = (dx as usize, dy as usize);
+    match (dx.checked_mul(dy), edge_count(dx, dy)) {
+        (Some(n), Some(_)) if n == len => Ok(()),
+        _ => Err(new_error(ErrorKind::BadDimension)),
+    }
+}
+
+// Classifies every cell of the grid (including the border cells) using
+// `above` to tell if the value at a given index is inside the isoring.
+fn fill_cases<F: Fn(usize) -> bool>(dx: usize, dy: usize, above: F, cases: &mut Vec<u8>) {
+    cases.clear();
+    cases.reserve((dx + 1) * (dy + 1));
+    for y in 0..=dy {
+        let (mut t0, mut t3) = (0, 0);
+        for x in 0..=dx {
+            let t1 = (y < dy && x < dx && above(y * dx + x)) as u8;
+            let t2 = (y > 0 && x < dx && above((y - 1) * dx + x)) as u8;
+            cases.push(t0 | t1 << 1 | t2 << 2 | t3 << 3);
+            t0 = t1;
+            t3 = t2;
+        }
+    }
+}
+
+// The limits of the size of the output.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Limits {
+    rings: Option<usize>,
+    vertices: Option<usize>,
+}
+
+impl Limits {
+    // Checks the number of rings of the current threshold and the total number of positions.
+    pub(crate) fn check(&self, rings: usize, vertices: usize) -> Result<()> {
+        if matches!(self.rings, Some(max) if rings > max) || matches!(self.vertices, Some(max) if vertices > max) {
+            return Err(new_error(ErrorKind::OutputLimitExceeded { rings, vertices }));
+        }
+        Ok(())
+    }
+}
+
+// The output of `IsoRingBuilder::compute_band`.
+#[cfg(feature = "rayon")]
+struct Band {
+    rings: Vec<(usize, LinearRing)>,
+    fragments: Vec<FragmentRing>,
+}
+
+// Computes the rings like `IsoRingBuilder::compute_by` does, splitting the grid into
+// (at most) `threads` bands of rows computed in parallel by rayon, the fragments crossing
+// the seams between the bands being joined afterwards.
+//
+// The rings are the same, in the same order: the sequential computation closes a ring
+// when stitching its last segment (in the order of the cells, see `segment_key`),
+// the ring starting at the end of this segment.
+//
+// The limits are checked as soon as a band closes a ring, against the rings closed by all
+// the bands (and the `vertices` positions of the rings computed before), every band
+// stopping at its next row once they are exceeded.
+#[cfg(feature = "rayon")]
+fn compute_rings_parallel<V: Fn(usize) -> f64 + Sync>(
+    values: &V,
+    threshold: f64,
+    dx: u32,
+    dy: u32,
+    threads: usize,
+    limits: &Limits,
+    vertices: &mut usize,
+) -> Result<Vec<LinearRing>> {
+    let (dx, dy) = (dx as usize, dy as usize);
+    let (closed, positions) = (AtomicUsize::new(0), AtomicUsize::new(*vertices));
+    // Adds a ring of `len` positions (if any) to the output, checking the limits.
+    let check = |ring: Option<usize>| {
+        let rings = closed.fetch_add(ring.is_some() as usize, Ordering::Relaxed) + ring.is_some() as usize;
+        let len = ring.unwrap_or(0);
+        limits.check(rings, positions.fetch_add(len, Ordering::Relaxed) + len)
+    };
+    let band_len = (dy + 1).div_ceil(threads.clamp(1, dy + 1));
+    let rows = (0..=dy).collect::<Vec<_>>();
+    let outputs = rows
+        .par_chunks(band_len)
+        .map(|rows| {
+            let rows = rows[0]..rows[rows.len() - 1] + 1;
+            let mut isoring = IsoRingBuilder::new(dx as u32, rows.len() as u32 + 1);
+            let offset = rows.start as f64 - 1.;
+            let mut band = isoring.compute_band(values, threshold, dy, rows, &check)?;
+            band.rings.iter_mut().flat_map(|(_, ring)| ring.iter_mut()).for_each(|p| p[1] += offset);
+            band.fragments.iter_mut().flatten().for_each(|p| p[1] += offset);
+            Ok(band)
+        })
+        .collect::<Result<Vec<Band>>>()?;
+
+    let mut rings = Vec::new();
+    let mut fragments = Vec::new();
+    for band in outputs {
+        rings.extend(band.rings);
+        fragments.extend(band.fragments);
+    }
+
+    // Joins the fragments left open into rings.
+    let by_start = fragments
+        .iter()
+        .enumerate()
+        .map(|(i, fragment)| (edge_index(dx, dy, &fragment[0]), i))
+        .collect::<HashMap<_, _>>();
+    let mut joined = vec![false; fragments.len()];
+    for first in 0..fragments.len() {
+        if joined[first] {
+            continue;
+        }
+        let mut points = fragments[first].clone();
+        let mut current = first;
+        loop {
+            joined[current] = true;
+            let next = *by_start
+                .get(&edge_index(dx, dy, &fragments[current][fragments[current].len() - 1]))
+                .ok_or_else(|| new_error(ErrorKind::Unexpected))?;
+            if next == first {
+                break;
+            }
+            if joined[next] {
+                return Err(new_error(ErrorKind::Unexpected));
+            }
+            points.extend_from_slice(&fragments[next][1..]);
+            current = next;
+        }
+        // The ring starts at the end of its closing segment.
+        points.pop();
+        let (key, last) = points
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (segment_key(values, threshold, dx, dy, a, &points[(i + 1) % points.len()]), i))
+            .max()
+            .ok_or_else(|| new_error(ErrorKind::Unexpected))?;
+        let n = points.len();
+        let ring = (0..=n).map(|i| points[(last + 1 + i) % n].to_vec()).collect();
+        rings.push((key, ring));
+        check(Some(n + 1))?;
+    }
+
+    *vertices = positions.into_inner();
+    rings.sort_by_key(|(key, _)| *key);
+    Ok(rings.into_iter().map(|(_, ring)| ring).collect())
+}
+
+// The order in which the sequential computation stitches the segment going from `a` to `b`:
+// the index of its cell in the cells of `fill_cases`, and its index in the lines of the case.
+#[cfg(feature = "rayon")]
+fn segment_key<V: Fn(usize) -> f64>(values: &V, threshold: f64, dx: usize, dy: usize, a: &[f64], b: &[f64]) -> usize {
+    // The middle of the segment is strictly inside its cell.
+    let x = ((a[0] + b[0]) / 2. - 0.5).floor() as i64 + 1;
+    let r = ((a[1] + b[1]) / 2. - 0.5).floor() as i64 + 1;
+    let (x, r) = (x as usize, r as usize);
+    let above = |x: usize, r: usize| r < dy && x < dx && values(r * dx + x) >= threshold;
+    let (t1, t2) = (above(x, r), r > 0 && above(x, r - 1));
+    let (t0, t3) = (x > 0 && above(x - 1, r), x > 0 && r > 0 && above(x - 1, r - 1));
+    let case = t0 as usize | (t1 as usize) << 1 | (t2 as usize) << 2 | (t3 as usize) << 3;
+    let line = CASES[case]
+        .iter()
+        .position(|line| line[0][0] + x as f64 - 1. == a[0] && line[0][1] + r as f64 - 1. == a[1])
+        .unwrap_or(0);
+    (r * (dx + 1) + x) * 2 + line
+}
+
+// The index of the given end of a segment, a point in the middle of an edge of the grid:
+// the points on horizontal edges come first, followed by the ones on vertical edges.
+fn edge_index(dx: usize, dy: usize, point: &[f64]) -> usize {
+    let row_len = dx + 1;
+    let (x, y) = (point[0] * 2., point[1] * 2.);
+    if (y as usize).is_multiple_of(2) {
+        (y as usize / 2) * row_len + x as usize / 2
+    } else {
+        (dy + 1) * row_len + (y as usize / 2) * row_len + x as usize / 2
+    }
+}
+
+// The number of possible ends of segments (see `edge_index`) in a grid of `dx` columns
+// and `dy` rows, unless it overflows.
+fn edge_count(dx: usize, dy: usize) -> Option<usize> {
+    dx.checked_add(1)?.checked_mul(dy.checked_add(1)?)?.checked_mul(2)
+}
+
+// The kind of extremum enclosed by each ring of the polygons of a threshold (see
+// `ContourBuilder::enclosures`): each maximum above the threshold and each minimum below it
+// is enclosed by the innermost ring around it, the rings enclosing only maxima (or only
+// minima) getting their kind.
+fn enclosures(extrema: &[Extremum], threshold: f64, polygons: &[Vec<LinearRing>], dx: u32, dy: u32) -> Vec<Vec<Option<&'static str>>> {
+    // The positions on the border of the grid stay there when smoothed.
+    let on_border = |p: &Pt| p[0] <= 0. || p[1] <= 0. || p[0] >= f64::from(dx) || p[1] >= f64::from(dy);
+    // A ring, with its bounding box and its area.
+    fn measured(ring: &LinearRing) -> (&LinearRing, [f64; 4], f64) {
+        let bbox = ring.iter().fold([f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY], |b, p| {
+            [b[0].min(p[0]), b[1].min(p[1]), b[2].max(p[0]), b[3].max(p[1])]
+        });
+        (ring, bbox, area(ring).abs())
+    }
+    let rings = polygons.iter().flatten().map(measured).collect::<Vec<_>>();
+    // Whether each ring encloses maxima and minima.
+    let mut enclosed = vec![(false, false); rings.len()];
+    for extremum in extrema {
+        let is_max = match extremum.kind {
+            ExtremumKind::Max if extremum.value >= threshold => true,
+            ExtremumKind::Min if extremum.value < threshold => false,
+            _ => continue,
+        };
+        let point = [extremum.x as f64 + 0.5, extremum.y as f64 + 0.5];
+        let innermost = rings
+            .iter()
+            .enumerate()
+            .filter(|(_, (ring, b, _))| b[0] <= point[0] && point[0] <= b[2] && b[1] <= point[1] && point[1] <= b[3] && ring_contains(ring, &point) != -1)
+            .min_by(|a, b| a.1.2.total_cmp(&b.1.2));
+        if let Some((i, _)) = innermost {
+            if is_max {
+                enclosed[i].0 = true;
+            } else {
+                enclosed[i].1 = true;
+            }
+        }
+    }
+    let mut enclosed = enclosed.into_iter().zip(&rings);
+    polygons
+        .iter()
+        .map(|polygon| {
+            polygon
+                .iter()
+                .map(|_| match enclosed.next() {
+                    Some(((true, false), (ring, _, _))) if !ring.iter().any(on_border) => Some("maximum"),
+                    Some(((false, true), (ring, _, _))) if !ring.iter().any(on_border) => Some("minimum"),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+type BoxError = Box<dyn StdError + Send + Sync>;
+type Transformer = Box<dyn FnMut(f64, f64) -> result::Result<(f64, f64), BoxError>>;
+type TransformerFactory = Arc<dyn Fn() -> result::Result<Transformer, BoxError> + Send + Sync>;
+
+// The state used while computing the contours of one grid.
+struct Pass {
+    isoring: IsoRingBuilder,
+    transformer: Option<Transformer>,
+    // The region set by `ContourBuilder::clip_to_polygon`, if any.
+    #[cfg(feature = "clip")]
+    clip: Option<geo::Polygon<f64>>,
+    // The diagnostics of the thresholds computed, when requested.
+    diagnostics: Option<Vec<ThresholdDiagnostics>>,
+    grouping: Grouping,
+    // The extrema of the grid, found by the first threshold when the enclosures are requested.
+    extrema: Option<Vec<Extremum>>,
+}
+
+impl Pass {
+    // The diagnostics of the threshold being computed, when requested.
+    fn diagnostics(&mut self) -> Option<&mut ThresholdDiagnostics> {
+        self.diagnostics.as_mut()?.last_mut()
+    }
+}
+
+// The number of positions of the fragments stored inline.
+const MIN_FRAGMENT_CAPACITY: usize = 16;
+
+// The maximum number of buffers of closed fragments kept for reuse, across thresholds.
+const MAX_POOLED_FRAGMENTS: usize = 1024;
+
+// The highest number of slots of the dense tables of `FragmentIndex` (256 MiB each), the
+// fragments of larger grids being indexed by hash maps.
+const MAX_DENSE_SLOTS: usize = 1 << 26;
+
+// Fragments indexed by one of their ends (the midpoints of the edges of the grid).
+enum FragmentIndex {
+    // A dense table with one slot per possible end among the `len` ones, holding the index
+    // of the fragment or `EMPTY`, allocated on the first insertion.
+    Dense { len: usize, slots: Vec<u32> },
+    // A hash map, for the grids with too many possible ends, the builders created by
+    // `IsoRingBuilder::with_hash_index`, and once a fragment index doesn't fit in a slot.
+    Hashed(FxHashMap<usize, usize>),
+}
+
+impl FragmentIndex {
+    const EMPTY: u32 = u32::MAX;
+
+    fn new(len: Option<usize>, hashed: bool) -> Self {
+        match len {
+            Some(len) if !hashed && len <= MAX_DENSE_SLOTS => FragmentIndex::Dense { len, slots: Vec::new() },
+            _ => FragmentIndex::Hashed(FxHashMap::default()),
+        }
+    }
+
+    fn contains_key(&self, key: usize) -> bool {
+        match self {
+            FragmentIndex::Dense { slots, .. } => slots.get(key).is_some_and(|slot| *slot != Self::EMPTY),
+            FragmentIndex::Hashed(map) => map.contains_key(&key),
+        }
+    }
+
+    fn insert(&mut self, key: usize, fragment: usize) -> Result<()> {
+        if let FragmentIndex::Dense { len, slots } = self {
+            match u32::try_from(fragment) {
+                Ok(slot) if slot != Self::EMPTY => {
+                    if slots.is_empty() {
+                        slots.resize(*len, Self::EMPTY);
+                    }
+                    *slots.get_mut(key).ok_or_else(|| new_error(ErrorKind::Unexpected))? = slot;
+                    return Ok(());
+                }
+                _ => {
+                    let map = slots
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, slot)| **slot != Self::EMPTY)
+                        .map(|(key, slot)| (key, *slot as usize))
+                        .collect();
+                    *self = FragmentIndex::Hashed(map);
+                }
+            }
+        }
+        if let FragmentIndex::Hashed(map) = self {
+            map.insert(key, fragment);
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, key: usize) -> Option<usize> {
+        match self {
+            FragmentIndex::Dense { slots, .. } => match slots.get_mut(key).map(|slot| std::mem::replace(slot, Self::EMPTY)) {
+                None | Some(Self::EMPTY) => None,
+                Some(fragment) => Some(fragment as usize),
+            },
+            FragmentIndex::Hashed(map) => map.remove(&key),
+        }
+    }
 }
 
 /// Isoring generator to compute marching squares with isolines stitched into rings.
-struct IsoRingBuilder {
-    fragment_by_start: FxHashMap<usize, usize>,
-    fragment_by_end: FxHashMap<usize, usize>,
+///
+/// The builder keeps its buffers from one computation to the next: computing the rings
+/// of many grids of the same dimensions (such as the frames of an animation) with the
+/// same builder, and with [`compute_into`], avoids most of the allocations.
+///
+/// [`compute_into`]: #method.compute_into
+pub struct IsoRingBuilder {
+    fragment_by_start: FragmentIndex,
+    fragment_by_end: FragmentIndex,
     f: Slab<Fragment>,
+    // The buffers of the fragments which outgrew their inline positions.
+    pool: Vec<FragmentRing>,
+    // The rings handed back to `compute_into`, reused for the next rings.
+    rings_pool: Vec<LinearRing>,
+    cases: Vec<u8>,
     dx: u32,
     dy: u32,
     is_empty: bool,
+    rings_capacity: usize,
+    ring_capacity: usize,
+    limits: Limits,
+    // The number of positions of the rings computed since the builder was created.
+    vertices: usize,
+    ring_closure: RingClosure,
 }
 
 impl IsoRingBuilder {
@@ -193,13 +3398,44 @@ impl IsoRingBuilder {
     /// * `dx` - The number of columns in the grid.
     /// * `dy` - The number of rows in the grid.
     pub fn new(dx: u32, dy: u32) -> Self {
+        Self::with_capacity(dx, dy, 0, 0, false)
+    }
+
+    /// Constructs a new IsoRing generator like [`new`], indexing the ends of the pieces of
+    /// rings being stitched with hash maps rather than with tables of one slot per edge of
+    /// the grid (allocated by the first computation): slower on large grids, but using
+    /// memory in proportion to the rings rather than to the grid.
+    ///
+    /// The grids with more than 2^25 cells are always indexed with hash maps.
+    ///
+    /// [`new`]: #method.new
+    pub fn with_hash_index(dx: u32, dy: u32) -> Self {
+        Self::with_capacity(dx, dy, 0, 0, true)
+    }
+
+    // Constructs a new IsoRing generator preallocating its buffers for the given
+    // number of rings and number of positions per ring.
+    fn with_capacity(dx: u32, dy: u32, rings: usize, ring_len: usize, hashed: bool) -> Self {
+        // The rings of an empty grid are always empty: its indexes aren't needed.
+        let len = match (dx, dy) {
+            (0, _) | (_, 0) => Some(0),
+            _ => edge_count(dx as usize, dy as usize),
+        };
         IsoRingBuilder {
-            fragment_by_start: FxHashMap::default(),
-            fragment_by_end: FxHashMap::default(),
-            f: Slab::new(),
+            fragment_by_start: FragmentIndex::new(len, hashed),
+            fragment_by_end: FragmentIndex::new(len, hashed),
+            f: Slab::with_capacity(rings),
+            pool: Vec::new(),
+            rings_pool: Vec::new(),
+            cases: Vec::new(),
             dx,
             dy,
             is_empty: true,
+            rings_capacity: rings,
+            ring_capacity: ring_len,
+            limits: Limits::default(),
+            vertices: 0,
+            ring_closure: RingClosure::default(),
         }
     }
 
@@ -211,159 +3447,272 @@ impl IsoRingBuilder {
     ///
     /// * `values` - The slice of values to be used.
     /// * `threshold` - The threshold value to use.
-    pub fn compute(&mut self, values: &[f64], threshold: f64) -> Result<Vec<Ring>> {
-        macro_rules! case_stitch {
-            ($ix:expr, $x:ident, $y:ident, $result:expr) => {
-                CASES[$ix]
-                    .iter()
-                    .map(|ring| {
-                        self.stitch(&ring, $x, $y, $result)
-                    })
-                    .collect::<Result<Vec<()>>>()?;
-            }
+    pub fn compute(&mut self, values: &[f64], threshold: f64) -> Result<Vec<LinearRing>> {
+        let mut result = Vec::with_capacity(self.rings_capacity);
+        self.compute_into(values, threshold, &mut result)?;
+        Ok(result)
+    }
+
+    /// Computes isoring like [`compute`] does, into `out`: its rings are cleared first and
+    /// kept by the builder, their buffers (and the ones of their positions) being reused
+    /// for the next rings computed.
+    ///
+    /// ```
+    /// # use contour::IsoRingBuilder;
+    /// let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+    /// let mut isoring = IsoRingBuilder::new(3, 3);
+    /// let mut rings = Vec::new();
+    /// for threshold in &[0.25, 0.5, 0.75] {
+    ///     isoring.compute_into(&values, *threshold, &mut rings)?;
+    ///     assert_eq!(rings.len(), 1);
+    /// }
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`compute`]: #method.compute
+    pub fn compute_into(&mut self, values: &[f64], threshold: f64, out: &mut Vec<LinearRing>) -> Result<()> {
+        // Reversed so that the rings are reused in the same order.
+        self.rings_pool.extend(out.drain(..).rev());
+        check_dimensions(self.dx, self.dy, values.len())?;
+        self.compute_by_into(&|i| values[i], threshold, out)?;
+        if self.ring_closure == RingClosure::Open {
+            out.iter_mut().for_each(open);
         }
+        Ok(())
+    }
+
+    /// Sets whether the rings computed by [`compute`] and [`compute_into`] end with
+    /// a repetition of their first position (default: [`RingClosure::Closed`]).
+    ///
+    /// ```
+    /// # use contour::{IsoRingBuilder, RingClosure};
+    /// let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+    /// let closed = IsoRingBuilder::new(3, 3).compute(&values, 0.5)?;
+    /// let open = IsoRingBuilder::new(3, 3).ring_closure(RingClosure::Open).compute(&values, 0.5)?;
+    /// assert_eq!(open[0].len(), closed[0].len() - 1);
+    /// assert_eq!(open[0][..], closed[0][..open[0].len()]);
+    /// # Ok::<(), contour::Error>(())
+    /// ```
+    ///
+    /// [`compute`]: #method.compute
+    /// [`compute_into`]: #method.compute_into
+    /// [`RingClosure::Closed`]: enum.RingClosure.html#variant.Closed
+    pub fn ring_closure(mut self, ring_closure: RingClosure) -> Self {
+        self.ring_closure = ring_closure;
+        self
+    }
 
+    // Computes isoring for the grid whose value at (row-major) index `i` is `values(i)`.
+    pub(crate) fn compute_by<V: Fn(usize) -> f64>(&mut self, values: &V, threshold: f64) -> Result<Vec<LinearRing>> {
+        let mut result = Vec::with_capacity(self.rings_capacity);
+        self.compute_by_into(values, threshold, &mut result)?;
+        Ok(result)
+    }
+
+    // Computes isoring like `compute_by` does, pushing the rings to the (empty) `result`.
+    fn compute_by_into<V: Fn(usize) -> f64>(&mut self, values: &V, threshold: f64, result: &mut Vec<LinearRing>) -> Result<()> {
         if !self.is_empty {
             self.clear();
         }
-        let mut result = Vec::new();
-        let dx = self.dx as i32;
-        let dy = self.dy as i32;
-        let mut x = -1;
-        let mut y = -1;
-        let mut t0;
-        let mut t1;
-        let mut t2;
-        let mut t3;
-
-        // Special case for the first row (y = -1, t2 = t3 = 0).
-        t1 = (values[0] >= threshold) as u32;
-        case_stitch!((t1 << 1) as usize, x, y, &mut result);
-        x += 1;
-        while x < dx - 1 {
-            t0 = t1;
-            t1 = (values[(x + 1) as usize] >= threshold) as u32;
-            case_stitch!((t0 | t1 << 1) as usize, x, y, &mut result);
-            x += 1;
-        }
-        case_stitch!((t1 << 0) as usize, x, y, &mut result);
-
-        // General case for the intermediate rows.
-        y += 1;
-        while y < dy - 1 {
-            x = -1;
-            t1 = (values[(y * dx + dx) as usize] >= threshold) as u32;
-            t2 = (values[(y * dx) as usize] >= threshold) as u32;
-            case_stitch!((t1 << 1 | t2 << 2) as usize, x, y, &mut result);
-            x += 1;
-            while x < dx - 1 {
-                t0 = t1;
-                t1 = (values[(y * dx + dx + x + 1) as usize] >= threshold) as u32;
-                t3 = t2;
-                t2 = (values[(y * dx + x + 1) as usize] >= threshold) as u32;
-                case_stitch!((t0 | t1 << 1 | t2 << 2 | t3 << 3) as usize, x, y, &mut result);
-                x += 1;
+        if self.dx == 0 || self.dy == 0 {
+            return Ok(());
+        }
+        if edge_count(self.dx as usize, self.dy as usize).is_none() {
+            return Err(new_error(ErrorKind::BadDimension));
+        }
+        let mut cases = std::mem::take(&mut self.cases);
+        {
+            let _span = enter(SpanKind::Classify, Some(threshold));
+            fill_cases(self.dx as usize, self.dy as usize, |i| values(i) >= threshold, &mut cases);
+        }
+        let mut span = enter(SpanKind::Stitch, Some(threshold));
+        let row_len = self.dx as usize + 1;
+        let mut closed = 0;
+        for (i, case) in cases.iter().enumerate() {
+            let x = (i % row_len) as i32 - 1;
+            let y = (i / row_len) as i32 - 1;
+            for line in CASES[*case as usize].iter() {
+                self.stitch(line, x, y, result)?;
+            }
+            if result.len() > closed {
+                self.vertices += result[closed..].iter().map(|ring| ring.len()).sum::<usize>();
+                closed = result.len();
+                self.limits.check(closed, self.vertices)?;
             }
-            case_stitch!((t1 | t2 << 3) as usize, x, y, &mut result);
-            y += 1;
         }
+        span.rings(result.len());
+        self.cases = cases;
+        self.is_empty = false;
+        Ok(())
+    }
 
-        // Special case for the last row (y = dy - 1, t0 = t1 = 0).
-        x = -1;
-        t2 = (values[(y * dx) as usize] >= threshold) as u32;
-        case_stitch!((t2 << 2) as usize, x, y, &mut result);
-        x += 1;
-        while x < dx - 1 {
-            t3 = t2;
-            t2 = (values[(y * dx + x + 1) as usize] >= threshold) as u32;
-            case_stitch!((t2 << 2 | t3 << 3) as usize, x, y, &mut result);
-            x += 1;
+    // Stitches the segments of the cells of the rows `rows` only (see `fill_cases`)
+    // of the grid of `dy` rows, positions being relative to the row `rows.start - 1`
+    // (the builder must have `rows.len() + 1` rows). Returns the rings closed within
+    // these rows, along with the key of their closing segment (see `segment_key`),
+    // and the positions of the fragments left open. `check` is called with the length of each
+    // ring closed, and with `None` before each row, failing once the limits are exceeded.
+    #[cfg(feature = "rayon")]
+    fn compute_band<V, C>(&mut self, values: &V, threshold: f64, dy: usize, rows: Range<usize>, check: &C) -> Result<Band>
+    where
+        V: Fn(usize) -> f64,
+        C: Fn(Option<usize>) -> Result<()>,
+    {
+        let dx = self.dx as usize;
+        let above = |i: usize| values(i) >= threshold;
+        let (mut rings, mut keys): (Vec<LinearRing>, _) = (Vec::new(), Vec::new());
+        for r in rows.clone() {
+            check(None)?;
+            let (mut t0, mut t3) = (0, 0);
+            for x in 0..=dx {
+                let t1 = (r < dy && x < dx && above(r * dx + x)) as u8;
+                let t2 = (r > 0 && x < dx && above((r - 1) * dx + x)) as u8;
+                for (k, line) in CASES[(t0 | t1 << 1 | t2 << 2 | t3 << 3) as usize].iter().enumerate() {
+                    let closed = rings.len();
+                    self.stitch(line, x as i32 - 1, (r - rows.start) as i32, &mut rings)?;
+                    if rings.len() > closed {
+                        keys.push((r * (dx + 1) + x) * 2 + k);
+                        check(Some(rings[closed].len()))?;
+                    }
+                }
+                t0 = t1;
+                t3 = t2;
+            }
         }
-        case_stitch!((t2 << 3) as usize, x, y, &mut result);
         self.is_empty = false;
-        Ok(result)
+        Ok(Band {
+            rings: keys.into_iter().zip(rings).collect(),
+            fragments: self.f.drain().map(|fragment| fragment.ring).collect(),
+        })
     }
 
     fn index(&self, point: &[f64]) -> usize {
-        (point[0] * 2.0 + point[1] * (self.dx as f64 + 1.) * 4.) as usize
+        edge_index(self.dx as usize, self.dy as usize, point)
     }
 
     // Stitchs segments to rings.
-    fn stitch(&mut self, line: &[Vec<f64>], x: i32, y: i32, result: &mut Vec<Ring>) -> Result<()> {
-        let start = vec![line[0][0] + x as f64, line[0][1] + y as f64];
-        let end = vec![line[1][0] + x as f64, line[1][1] + y as f64];
+    fn stitch(&mut self, line: &[Vec<f64>], x: i32, y: i32, result: &mut Vec<LinearRing>) -> Result<()> {
+        let start = [line[0][0] + x as f64, line[0][1] + y as f64];
+        let end = [line[1][0] + x as f64, line[1][1] + y as f64];
         let start_index = self.index(&start);
         let end_index = self.index(&end);
-        if self.fragment_by_end.contains_key(&start_index) {
-            if self.fragment_by_start.contains_key(&end_index) {
-                let f_ix = self.fragment_by_end.remove(&start_index).ok_or(new_error(ErrorKind::Unexpected))?;
-                let g_ix = self.fragment_by_start.remove(&end_index).ok_or(new_error(ErrorKind::Unexpected))?;
+        if self.fragment_by_end.contains_key(start_index) {
+            if self.fragment_by_start.contains_key(end_index) {
+                let f_ix = self.fragment_by_end.remove(start_index).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
+                let g_ix = self.fragment_by_start.remove(end_index).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
                 if f_ix == g_ix {
-                    let mut f = self.f.remove(f_ix);
+                    let mut f = self.remove(f_ix)?;
                     f.ring.push(end);
-                    result.push(f.ring);
+                    let ring = self.close(&f.ring);
+                    result.push(ring);
+                    self.recycle(f.ring);
                 } else {
-                    let mut f = self.f.remove(f_ix);
-                    let g = self.f.remove(g_ix);
-                    f.ring.extend(g.ring);
+                    let mut f = self.remove(f_ix)?;
+                    let g = self.remove(g_ix)?;
+                    f.ring.extend_from_slice(&g.ring);
+                    self.recycle(g.ring);
                     let ix = self.f.insert(Fragment {
                         start: f.start,
                         end: g.end,
                         ring: f.ring,
                     });
-                    self.fragment_by_start.insert(f.start, ix);
-                    self.fragment_by_end.insert(g.end, ix);
+                    self.fragment_by_start.insert(f.start, ix)?;
+                    self.fragment_by_end.insert(g.end, ix)?;
                 }
             } else {
-                let f_ix = self.fragment_by_end.remove(&start_index).ok_or(new_error(ErrorKind::Unexpected))?;
-                let mut f = self.f.get_mut(f_ix).ok_or(new_error(ErrorKind::Unexpected))?;
+                let f_ix = self.fragment_by_end.remove(start_index).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
+                let f = self.f.get_mut(f_ix).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
                 f.ring.push(end);
                 f.end = end_index;
-                self.fragment_by_end.insert(end_index, f_ix);
+                self.fragment_by_end.insert(end_index, f_ix)?;
             }
-        } else if self.fragment_by_start.contains_key(&end_index) {
-            if self.fragment_by_end.contains_key(&start_index) {
-                let f_ix = self.fragment_by_start.remove(&end_index).ok_or(new_error(ErrorKind::Unexpected))?;
-                let g_ix = self.fragment_by_end.remove(&start_index).ok_or(new_error(ErrorKind::Unexpected))?;
+        } else if self.fragment_by_start.contains_key(end_index) {
+            if self.fragment_by_end.contains_key(start_index) {
+                let f_ix = self.fragment_by_start.remove(end_index).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
+                let g_ix = self.fragment_by_end.remove(start_index).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
                 if f_ix == g_ix {
-                    let mut f = self.f.remove(f_ix);
+                    let mut f = self.remove(f_ix)?;
                     f.ring.push(end);
-                    result.push(f.ring);
+                    let ring = self.close(&f.ring);
+                    result.push(ring);
+                    self.recycle(f.ring);
                 } else {
-                    let f = self.f.remove(f_ix);
-                    let mut g = self.f.remove(g_ix);
-                    g.ring.extend(f.ring);
+                    let f = self.remove(f_ix)?;
+                    let mut g = self.remove(g_ix)?;
+                    g.ring.extend_from_slice(&f.ring);
+                    self.recycle(f.ring);
                     let ix = self.f.insert(Fragment {
                         start: g.start,
                         end: f.end,
                         ring: g.ring,
                     });
-                    self.fragment_by_start.insert(g.start, ix);
-                    self.fragment_by_end.insert(f.end, ix);
+                    self.fragment_by_start.insert(g.start, ix)?;
+                    self.fragment_by_end.insert(f.end, ix)?;
                 }
             } else {
-                let f_ix = self.fragment_by_start.remove(&end_index).ok_or(new_error(ErrorKind::Unexpected))?;
-                let mut f = self.f.get_mut(f_ix).ok_or(new_error(ErrorKind::Unexpected))?;
+                let f_ix = self.fragment_by_start.remove(end_index).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
+                let f = self.f.get_mut(f_ix).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
                 f.ring.insert(0, start);
                 f.start = start_index;
-                self.fragment_by_start.insert(start_index, f_ix);
+                self.fragment_by_start.insert(start_index, f_ix)?;
             }
         } else {
+            let mut ring = match self.pool.pop() {
+                Some(ring) => ring,
+                None => FragmentRing::with_capacity(self.ring_capacity),
+            };
+            ring.push(start);
+            ring.push(end);
             let ix = self.f.insert(Fragment {
                 start: start_index,
                 end: end_index,
-                ring: vec![start, end],
+                ring,
             });
-            self.fragment_by_start.insert(start_index, ix);
-            self.fragment_by_end.insert(end_index, ix);
+            self.fragment_by_start.insert(start_index, ix)?;
+            self.fragment_by_end.insert(end_index, ix)?;
         }
         Ok(())
     }
 
+    // Removes the fragment `ix` (failing instead of panicking if it doesn't exist).
+    fn remove(&mut self, ix: usize) -> Result<Fragment> {
+        if !self.f.contains(ix) {
+            return Err(new_error(ErrorKind::Unexpected));
+        }
+        Ok(self.f.remove(ix))
+    }
+
+    // Converts the positions of a closed fragment to a ring, reusing a pooled ring if any.
+    fn close(&mut self, points: &[FragmentPt]) -> LinearRing {
+        let mut ring = self.rings_pool.pop().unwrap_or_default();
+        ring.truncate(points.len());
+        for (pt, p) in ring.iter_mut().zip(points) {
+            pt.clear();
+            pt.extend_from_slice(p);
+        }
+        let reused = ring.len();
+        ring.extend(points[reused..].iter().map(|p| p.to_vec()));
+        ring
+    }
+
+    // Keeps the (emptied) buffer of a fragment for the next ones, up to `MAX_POOLED_FRAGMENTS`,
+    // unless its positions are inline.
+    fn recycle(&mut self, mut ring: FragmentRing) {
+        if ring.spilled() && self.pool.len() < MAX_POOLED_FRAGMENTS {
+            ring.clear();
+            self.pool.push(ring);
+        }
+    }
+
+    /// Clears the state left by the previous computation (done by the next one otherwise).
     pub fn clear(&mut self) {
+        // All the fragments are closed into rings (emptying the indexes) when the computation
+        // succeeds: only the ones left by a failed computation need to be removed.
+        for (_, fragment) in self.f.iter() {
+            self.fragment_by_start.remove(fragment.start);
+            self.fragment_by_end.remove(fragment.end);
+        }
         self.f.clear();
-        self.fragment_by_end.clear();
-        self.fragment_by_start.clear();
         self.is_empty = true;
     }
 }
@@ -0,0 +1,363 @@
+use crate::contour::ContourBuilder;
+use crate::error::Result;
+use crate::stats::GridStats;
+use geojson::Feature;
+use std::fmt;
+
+/// The function computing the thresholds of [`ThresholdSpec::Fn`] from the values.
+///
+/// [`ThresholdSpec::Fn`]: enum.ThresholdSpec.html#variant.Fn
+pub type ThresholdFn = Box<dyn Fn(&[f64]) -> Vec<f64>>;
+
+/// The thresholds used by [`Contours`].
+///
+/// [`Contours`]: struct.Contours.html
+pub enum ThresholdSpec {
+    /// The given thresholds (sorted in ascending order before use).
+    List(Vec<f64>),
+    /// About this number of uniformly-spaced, rounded thresholds covering the extent
+    /// of the values, computed like d3-contour does (the actual number may differ).
+    Count(usize),
+    /// The thresholds computed by the given function from the values.
+    Fn(ThresholdFn),
+}
+
+impl ThresholdSpec {
+    /// Returns the thresholds, in ascending order, for the given `values`.
+    pub fn thresholds(&self, values: &[f64]) -> Vec<f64> {
+        let mut thresholds = match self {
+            ThresholdSpec::List(thresholds) => thresholds.clone(),
+            ThresholdSpec::Count(count) => {
+                let stats = GridStats::compute(values);
+                match (stats.min, stats.max) {
+                    (Some(min), Some(max)) => {
+                        let step = tick_step(min, max, *count as f64);
+                        if !step.is_finite() || step == 0. {
+                            return Vec::new();
+                        }
+                        ticks((min / step).floor() * step, ((max / step).floor() - 1.) * step, *count as f64)
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            ThresholdSpec::Fn(f) => f(values),
+        };
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        thresholds
+    }
+}
+
+impl fmt::Debug for ThresholdSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThresholdSpec::List(thresholds) => f.debug_tuple("List").field(thresholds).finish(),
+            ThresholdSpec::Count(count) => f.debug_tuple("Count").field(count).finish(),
+            ThresholdSpec::Fn(_) => f.write_str("Fn(..)"),
+        }
+    }
+}
+
+impl From<Vec<f64>> for ThresholdSpec {
+    fn from(thresholds: Vec<f64>) -> Self {
+        ThresholdSpec::List(thresholds)
+    }
+}
+
+impl From<&[f64]> for ThresholdSpec {
+    fn from(thresholds: &[f64]) -> Self {
+        ThresholdSpec::List(thresholds.to_vec())
+    }
+}
+
+impl From<usize> for ThresholdSpec {
+    fn from(count: usize) -> Self {
+        ThresholdSpec::Count(count)
+    }
+}
+
+/// Contours generator configured with chained method calls,
+/// mirroring the API of d3-contour:
+///
+/// ```
+/// # use contour::contours;
+/// # let values = vec![0.; 100];
+/// let features = contours().size((10, 10)).thresholds(5).smooth(false).compute(&values)?;
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// The generator is a thin layer over [`ContourBuilder`], which exposes the other options.
+///
+/// [`ContourBuilder`]: struct.ContourBuilder.html
+#[derive(Debug)]
+pub struct Contours {
+    size: (usize, usize),
+    smooth: bool,
+    area: bool,
+    thresholds: ThresholdSpec,
+}
+
+/// Constructs a new contours generator, with a size of `(1, 1)`, smoothing enabled
+/// and a number of thresholds following Sturges' formula (as d3-contour does).
+pub fn contours() -> Contours {
+    let sturges = ThresholdSpec::Fn(Box::new(|values: &[f64]| {
+        let count = (values.len() as f64).log2().ceil() as usize + 1;
+        ThresholdSpec::Count(count).thresholds(values)
+    }));
+    Contours {
+        size: (1, 1),
+        smooth: true,
+        area: false,
+        thresholds: sturges,
+    }
+}
+
+impl Contours {
+    /// Sets the number of columns and rows of the grid.
+    pub fn size(mut self, size: (usize, usize)) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets whether or not the generated rings are smoothed using linear interpolation.
+    pub fn smooth(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
+    /// Sets whether the area of the polygons of each Feature is stored in its `area`
+    /// property (see [`ContourBuilder::area`]).
+    ///
+    /// [`ContourBuilder::area`]: struct.ContourBuilder.html#method.area
+    pub fn area(mut self, area: bool) -> Self {
+        self.area = area;
+        self
+    }
+
+    /// Sets the thresholds: a list of thresholds (`Vec<f64>` or `&[f64]`),
+    /// an approximate count (`usize`) or a [`ThresholdSpec::Fn`].
+    ///
+    /// [`ThresholdSpec::Fn`]: enum.ThresholdSpec.html#variant.Fn
+    pub fn thresholds(mut self, thresholds: impl Into<ThresholdSpec>) -> Self {
+        self.thresholds = thresholds.into();
+        self
+    }
+
+    /// Computes the contours of the given `values`, returning one Feature
+    /// per threshold (see [`ContourBuilder::contours`]).
+    ///
+    /// [`ContourBuilder::contours`]: struct.ContourBuilder.html#method.contours
+    pub fn compute(&self, values: &[f64]) -> Result<Vec<Feature>> {
+        let builder = ContourBuilder::new(self.size.0 as u32, self.size.1 as u32, self.smooth).area(self.area);
+        builder.contours(values, &self.thresholds.thresholds(values))
+    }
+}
+
+/// Computes about `count` thresholds such that the bands between consecutive
+/// thresholds (and above the last one) cover the same number of cells.
+///
+/// The finite `values` (skipping nodata `NaN` values, and the `background` value when
+/// given, such as a `0` covering most of the grid) are sorted and the thresholds are
+/// the values found at every `1 / count` of them, starting with the lowest one.
+/// Unlike quantiles, which may be interpolated between values, the thresholds are
+/// values of the grid; thresholds equal to the previous one, which would give empty
+/// bands, are skipped, so that fewer thresholds are returned on grids with many ties.
+///
+/// ```
+/// # use contour::thresholds_equal_area;
+/// let values = [0., 0., 0., 0., 0., 0., 1., 2., 3., 4.];
+/// assert_eq!(thresholds_equal_area(&values, 2, None), vec![0.]);
+/// assert_eq!(thresholds_equal_area(&values, 2, Some(0.)), vec![1., 3.]);
+/// ```
+pub fn thresholds_equal_area(values: &[f64], count: usize, background: Option<f64>) -> Vec<f64> {
+    let mut sorted = values
+        .iter()
+        .cloned()
+        .filter(|v| v.is_finite() && Some(*v) != background)
+        .collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mut thresholds: Vec<f64> = Vec::with_capacity(count);
+    if sorted.is_empty() {
+        return thresholds;
+    }
+    for i in 0..count {
+        let threshold = sorted[i * sorted.len() / count];
+        if thresholds.last() != Some(&threshold) {
+            thresholds.push(threshold);
+        }
+    }
+    thresholds
+}
+
+/// Computes thresholds splitting the finite `values` into `classes` classes using
+/// Jenks natural breaks (the Fisher–Jenks algorithm, minimizing the sum of the squared
+/// deviations from the class means).
+///
+/// Returns the lowest value of each class but the first one, in ascending order
+/// (`classes - 1` thresholds), so that the contour of each threshold covers its class
+/// and the upper ones. Equal values are always in the same class, so that fewer
+/// thresholds are returned when there are fewer distinct values than classes.
+///
+/// The computation takes `O(classes × n²)` time, `n` being the number of distinct values:
+/// see [`thresholds_jenks_sampled`] for large grids.
+///
+/// ```
+/// # use contour::thresholds_jenks;
+/// let values = [1., 2., 3., 10., 11., 12., 20., 21., 22.];
+/// assert_eq!(thresholds_jenks(&values, 3), vec![10., 20.]);
+/// ```
+///
+/// [`thresholds_jenks_sampled`]: fn.thresholds_jenks_sampled.html
+pub fn thresholds_jenks(values: &[f64], classes: usize) -> Vec<f64> {
+    thresholds_jenks_sampled(values, classes, usize::MAX)
+}
+
+/// Computes thresholds like [`thresholds_jenks`] from at most `samples` of the finite
+/// `values`, evenly spread over their sorted sequence (the minimum and the maximum being
+/// always used), to bound the computation time on large grids.
+///
+/// [`thresholds_jenks`]: fn.thresholds_jenks.html
+pub fn thresholds_jenks_sampled(values: &[f64], classes: usize, samples: usize) -> Vec<f64> {
+    let mut sorted = values.iter().cloned().filter(|v| v.is_finite()).collect::<Vec<_>>();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    if sorted.len() > samples && samples >= 2 {
+        let n = sorted.len() - 1;
+        sorted = (0..samples).map(|i| sorted[i * n / (samples - 1)]).collect();
+    }
+
+    // Distinct values and their number of occurrences.
+    let mut distinct: Vec<(f64, f64)> = Vec::new();
+    for v in sorted {
+        match distinct.last_mut() {
+            Some(last) if last.0 == v => last.1 += 1.,
+            _ => distinct.push((v, 1.)),
+        }
+    }
+    let n = distinct.len();
+    let classes = classes.min(n);
+    if classes < 2 {
+        return Vec::new();
+    }
+
+    // Prefix sums of the weights, the values and their squares, giving the sum
+    // of the squared deviations of the values `i..j` in constant time.
+    let mut sums = vec![(0., 0., 0.); n + 1];
+    for (i, (v, w)) in distinct.iter().enumerate() {
+        let (sw, sv, svv) = sums[i];
+        sums[i + 1] = (sw + w, sv + w * v, svv + w * v * v);
+    }
+    let deviation = |i: usize, j: usize| {
+        let (sw, sv, svv) = (sums[j].0 - sums[i].0, sums[j].1 - sums[i].1, sums[j].2 - sums[i].2);
+        svv - sv * sv / sw
+    };
+
+    // `cost[c][j]` is the lowest deviation of the values `0..j` split in `c + 1` classes,
+    // the last of them starting at `start[c][j]`.
+    let mut cost = vec![vec![f64::INFINITY; n + 1]; classes];
+    let mut start = vec![vec![0; n + 1]; classes];
+    for (j, first) in cost[0].iter_mut().enumerate().skip(1) {
+        *first = deviation(0, j);
+    }
+    for c in 1..classes {
+        for j in c + 1..=n {
+            for i in c..j {
+                let candidate = cost[c - 1][i] + deviation(i, j);
+                if candidate < cost[c][j] {
+                    cost[c][j] = candidate;
+                    start[c][j] = i;
+                }
+            }
+        }
+    }
+
+    let mut thresholds = Vec::with_capacity(classes - 1);
+    let mut j = n;
+    for c in (1..classes).rev() {
+        j = start[c][j];
+        thresholds.push(distinct[j].0);
+    }
+    thresholds.reverse();
+    thresholds
+}
+
+// Port of d3-array's `ticks`, `tickIncrement` and `tickStep`.
+fn tick_spec(start: f64, stop: f64, count: f64) -> (f64, f64, f64) {
+    let (e10, e5, e2) = (50f64.sqrt(), 10f64.sqrt(), 2f64.sqrt());
+    let step = (stop - start) / count.max(0.);
+    let power = step.log10().floor();
+    let error = step / 10f64.powf(power);
+    let factor = if error >= e10 {
+        10.
+    } else if error >= e5 {
+        5.
+    } else if error >= e2 {
+        2.
+    } else {
+        1.
+    };
+    let (mut i1, mut i2, inc);
+    if power < 0. {
+        let i = 10f64.powf(-power) / factor;
+        i1 = (start * i).round();
+        i2 = (stop * i).round();
+        if i1 / i < start {
+            i1 += 1.;
+        }
+        if i2 / i > stop {
+            i2 -= 1.;
+        }
+        inc = -i;
+    } else {
+        let i = 10f64.powf(power) * factor;
+        i1 = (start / i).round();
+        i2 = (stop / i).round();
+        if i1 * i < start {
+            i1 += 1.;
+        }
+        if i2 * i > stop {
+            i2 -= 1.;
+        }
+        inc = i;
+    }
+    if i2 < i1 && (0.5..2.).contains(&count) {
+        return tick_spec(start, stop, count * 2.);
+    }
+    (i1, i2, inc)
+}
+
+/// Returns about `count` "nice" values (multiples of 1, 2 or 5 times a power of ten)
+/// spanning `start` to `stop` (both included), like d3-array's `ticks` does.
+///
+/// [`ThresholdSpec::Count`] uses these values, without the last one when it is the
+/// maximum of the grid (whose contour would be empty), as d3-contour does.
+///
+/// ```
+/// assert_eq!(contour::ticks(0., 100., 5.), vec![0., 20., 40., 60., 80., 100.]);
+/// ```
+///
+/// [`ThresholdSpec::Count`]: enum.ThresholdSpec.html#variant.Count
+pub fn ticks(start: f64, stop: f64, count: f64) -> Vec<f64> {
+    if count.is_nan() || count <= 0. {
+        return Vec::new();
+    }
+    if start == stop {
+        return vec![start];
+    }
+    let reverse = stop < start;
+    let (i1, i2, inc) = if reverse { tick_spec(stop, start, count) } else { tick_spec(start, stop, count) };
+    if i1.is_nan() || i2.is_nan() || i2 < i1 {
+        return Vec::new();
+    }
+    let n = (i2 - i1 + 1.) as usize;
+    let tick = |i: usize| {
+        let i = if reverse { i2 - i as f64 } else { i1 + i as f64 };
+        if inc < 0. { i / -inc } else { i * inc }
+    };
+    (0..n).map(tick).collect()
+}
+
+fn tick_step(start: f64, stop: f64, count: f64) -> f64 {
+    let reverse = stop < start;
+    let inc = if reverse { tick_spec(stop, start, count).2 } else { tick_spec(start, stop, count).2 };
+    let step = if inc < 0. { 1. / -inc } else { inc };
+    if reverse { -step } else { step }
+}
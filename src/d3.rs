@@ -0,0 +1,44 @@
+//! Output in the format of [d3-contour](https://github.com/d3/d3-contour/),
+//! for code rendering the result of `d3.contours()`.
+//!
+//! d3-contour returns an array of GeoJSON MultiPolygon geometry objects
+//! (not Features), the threshold of each one being stored in a `value` member:
+//! `[{"type": "MultiPolygon", "value": 0.5, "coordinates": [...]}, ...]`.
+
+use geojson::{Feature, Value};
+use serde_json::{Value as JsonValue, json};
+
+/// Converts the given `features` (as returned by [`ContourBuilder::contours`])
+/// to an array of d3-contour MultiPolygon geometry objects, using the `value`
+/// property of each Feature as `value` member.
+///
+/// Polygon geometries are written as MultiPolygons of one polygon, and Features
+/// without geometry as MultiPolygons without any polygon.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn to_d3(features: &[Feature]) -> JsonValue {
+    features
+        .iter()
+        .map(|feature| {
+            let value = feature
+                .properties
+                .as_ref()
+                .and_then(|p| p.get("value"))
+                .cloned()
+                .unwrap_or(JsonValue::Null);
+            let coordinates = match feature.geometry.as_ref().map(|g| &g.value) {
+                Some(Value::Polygon(polygon)) => vec![polygon.clone()],
+                Some(Value::MultiPolygon(polygons)) => polygons.clone(),
+                _ => Vec::new(),
+            };
+            json!({ "type": "MultiPolygon", "value": value, "coordinates": coordinates })
+        })
+        .collect()
+}
+
+/// Converts the given `features` like [`to_d3`] and serializes the result as a JSON string.
+///
+/// [`to_d3`]: fn.to_d3.html
+pub fn to_d3_string(features: &[Feature]) -> String {
+    to_d3(features).to_string()
+}
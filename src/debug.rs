@@ -0,0 +1,89 @@
+//! Helpers to visualize grids and contours when debugging.
+//!
+//! Grid values are drawn as cells: the value at column `x` and row `y`
+//! covers the square from `(x, y)` to `(x + 1, y + 1)` in the coordinate space
+//! of the rings returned by [`contour_rings`] (and of the unsmoothed
+//! [`ContourBuilder`] output).
+//!
+//! [`contour_rings`]: ../fn.contour_rings.html
+//! [`ContourBuilder`]: ../struct.ContourBuilder.html
+
+use crate::contour::Pt;
+use crate::error::{ErrorKind, Result, new_error};
+use crate::raster::rasterize;
+use std::io::{self, Write};
+
+/// Renders the grid as an ASCII art block, one line per row,
+/// with cells greater than or equal to `threshold` drawn as `#` and the others as `.`.
+///
+/// # Arguments
+///
+/// * `values` - The slice of values to be used.
+/// * `dx` - The number of columns in the grid.
+/// * `dy` - The number of rows in the grid.
+/// * `threshold` - The threshold value.
+pub fn ascii_grid(values: &[f64], dx: u32, dy: u32, threshold: f64) -> Result<String> {
+    check_dimensions(values, dx, dy)?;
+    let mut out = String::with_capacity((dx as usize + 1) * dy as usize);
+    for row in values.chunks(dx.max(1) as usize) {
+        out.extend(row.iter().map(|v| if *v >= threshold { '#' } else { '.' }));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Renders the grid as an ASCII art block like [`ascii_grid`], overlaying
+/// the vertices of the given `rings` as `+`.
+///
+/// As ring vertices lie on the edges and corners of the cells, the drawing uses
+/// two characters per cell in each direction: cells are drawn at odd positions,
+/// and the positions in between are blank unless a vertex lies there.
+/// Vertices are snapped to the closest half-cell position.
+///
+/// [`ascii_grid`]: fn.ascii_grid.html
+pub fn ascii_grid_with_rings<R: AsRef<[Pt]>>(values: &[f64], dx: u32, dy: u32, threshold: f64, rings: &[R]) -> Result<String> {
+    check_dimensions(values, dx, dy)?;
+    let (width, height) = (2 * dx as usize + 1, 2 * dy as usize + 1);
+    let mut canvas = vec![' '; width * height];
+    for (i, v) in values.iter().enumerate() {
+        let (x, y) = (i % dx as usize, i / dx as usize);
+        canvas[(2 * y + 1) * width + 2 * x + 1] = if *v >= threshold { '#' } else { '.' };
+    }
+    for point in rings.iter().flat_map(|ring| ring.as_ref()) {
+        let (x, y) = ((point[0] * 2.).round(), (point[1] * 2.).round());
+        if x >= 0. && y >= 0. && (x as usize) < width && (y as usize) < height {
+            canvas[y as usize * width + x as usize] = '+';
+        }
+    }
+    let mut out = String::with_capacity((width + 1) * height);
+    for row in canvas.chunks(width) {
+        out.extend(row.iter());
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Rasterizes the given `rings` onto a `dx` * `dy` grid and writes it
+/// as a binary PGM (portable graymap) image.
+///
+/// A cell is drawn white when its center is inside the rings (using the even-odd rule,
+/// so holes are drawn black as long as they are part of `rings`) and black otherwise.
+///
+/// # Arguments
+///
+/// * `writer` - Where to write the image.
+/// * `rings` - The rings to rasterize (typically all the rings of one threshold).
+/// * `dx` - The number of columns in the grid.
+/// * `dy` - The number of rows in the grid.
+pub fn write_pgm<W: Write, R: AsRef<[Pt]>>(mut writer: W, rings: &[R], dx: u32, dy: u32) -> io::Result<()> {
+    write!(writer, "P5\n{} {}\n255\n", dx, dy)?;
+    let pixels: Vec<u8> = rasterize(rings, dx, dy).into_iter().map(|inside| if inside { 255 } else { 0 }).collect();
+    writer.write_all(&pixels)
+}
+
+fn check_dimensions(values: &[f64], dx: u32, dy: u32) -> Result<()> {
+    if values.len() != dx as usize * dy as usize {
+        return Err(new_error(ErrorKind::BadDimension));
+    }
+    Ok(())
+}
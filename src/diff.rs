@@ -0,0 +1,42 @@
+//! Differences between two outputs of the contours of a grid, e.g. the extent of a flood
+//! on two days: for each threshold, the regions gained and lost by its polygons.
+//!
+//! The polygons of both outputs are rasterized onto the grid (see the [`raster`] module)
+//! and the cells covered by one output but not the other are contoured: the regions follow
+//! the cells of the grid, the differences smaller than a cell being ignored.
+//!
+//! [`raster`]: ../raster/index.html
+
+use crate::contour::ContourBuilder;
+use crate::error::Result;
+use geojson::Feature;
+
+/// The regions where the polygons of a threshold appeared and disappeared between two
+/// outputs (see [`diff_contours`]), as Features of MultiPolygon whose `value` property
+/// is the threshold.
+///
+/// [`diff_contours`]: fn.diff_contours.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContourDiff {
+    /// The threshold, read from the `value` property of the Feature of the first output
+    /// (NaN if it has none).
+    pub threshold: f64,
+    /// The region covered by the second output but not by the first one.
+    pub gained: Feature,
+    /// The region covered by the first output but not by the second one.
+    pub lost: Feature,
+}
+
+/// Computes the differences between the Features `before` and `after` of the same
+/// thresholds, in the same order, computed in grid coordinates (without origin or step)
+/// for a grid of `dx` columns and `dy` rows. See [`ContourBuilder::contours_diff`] for
+/// Features computed with other options.
+///
+/// Returns an [`ErrorKind::MismatchedThresholds`] error if there isn't the same number
+/// of Features in both outputs.
+///
+/// [`ContourBuilder::contours_diff`]: ../struct.ContourBuilder.html#method.contours_diff
+/// [`ErrorKind::MismatchedThresholds`]: ../enum.ErrorKind.html#variant.MismatchedThresholds
+pub fn diff_contours(before: &[Feature], after: &[Feature], dx: u32, dy: u32) -> Result<Vec<ContourDiff>> {
+    ContourBuilder::new(dx, dy, false).contours_diff(before, after)
+}
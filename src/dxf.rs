@@ -0,0 +1,110 @@
+//! DXF output (for CAD software).
+//!
+//! Each ring is written as a closed `LWPOLYLINE` entity placed on a layer named
+//! after its threshold (`CONTOUR_105_5` for the threshold `105.5`) and whose elevation
+//! is the threshold, so that the contours are imported as 3-D contour lines.
+
+use geojson::{Feature, Value};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Options of the DXF output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DxfOptions {
+    /// Number of decimals written for the coordinates and elevations (default: `6`).
+    pub precision: usize,
+    /// Factor applied to the coordinates (but not to the elevations), for example
+    /// to convert them to the drawing units (default: `1`).
+    pub scale: f64,
+    /// Value of the `$INSUNITS` header variable (default: `0`, unitless; `6` is meters).
+    pub units: u8,
+}
+
+impl Default for DxfOptions {
+    fn default() -> Self {
+        DxfOptions {
+            precision: 6,
+            scale: 1.,
+            units: 0,
+        }
+    }
+}
+
+/// Returns the name of the layer of the contours of the given `threshold`.
+pub fn layer_name(threshold: f64) -> String {
+    format!("CONTOUR_{}", threshold).replace('.', "_")
+}
+
+/// Converts the given `features` (as returned by [`ContourBuilder::contours`])
+/// to a DXF document, using the `value` property of each Feature as elevation.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn to_dxf(features: &[Feature], options: &DxfOptions) -> String {
+    let rings = features
+        .iter()
+        .flat_map(|feature| {
+            let value = feature
+                .properties
+                .as_ref()
+                .and_then(|p| p.get("value"))
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.);
+            let rings = match feature.geometry.as_ref().map(|g| &g.value) {
+                Some(Value::Polygon(polygon)) => polygon.iter().collect(),
+                Some(Value::MultiPolygon(polygons)) => polygons.iter().flatten().collect(),
+                _ => Vec::new(),
+            };
+            rings.into_iter().map(move |ring| (value, ring))
+        })
+        .collect::<Vec<_>>();
+    let layers = rings.iter().map(|(value, _)| layer_name(*value)).collect::<BTreeSet<_>>();
+
+    let mut out = String::new();
+    let mut pair = |code: u16, value: &str| writeln!(out, "{}\n{}", code, value).unwrap();
+    pair(0, "SECTION");
+    pair(2, "HEADER");
+    pair(9, "$ACADVER");
+    pair(1, "AC1015");
+    pair(9, "$INSUNITS");
+    pair(70, &options.units.to_string());
+    pair(0, "ENDSEC");
+
+    pair(0, "SECTION");
+    pair(2, "TABLES");
+    pair(0, "TABLE");
+    pair(2, "LAYER");
+    pair(70, &layers.len().to_string());
+    for layer in &layers {
+        pair(0, "LAYER");
+        pair(2, layer);
+        pair(70, "0");
+        pair(62, "7");
+        pair(6, "CONTINUOUS");
+    }
+    pair(0, "ENDTAB");
+    pair(0, "ENDSEC");
+
+    pair(0, "SECTION");
+    pair(2, "ENTITIES");
+    let number = |v: f64| format!("{:.*}", options.precision, v);
+    for (value, ring) in rings {
+        // The rings of the polygons are closed, with or without their closing point
+        // (see `ContourBuilder::ring_closure`), which is implied by the closed flag.
+        let closed = ring.len() > 1;
+        let vertices = if closed && ring.first() == ring.last() { &ring[..ring.len() - 1] } else { &ring[..] };
+        pair(0, "LWPOLYLINE");
+        pair(100, "AcDbEntity");
+        pair(8, &layer_name(value));
+        pair(100, "AcDbPolyline");
+        pair(90, &vertices.len().to_string());
+        pair(70, if closed { "1" } else { "0" });
+        pair(38, &number(value));
+        for point in vertices {
+            pair(10, &number(point[0] * options.scale));
+            pair(20, &number(point[1] * options.scale));
+        }
+    }
+    pair(0, "ENDSEC");
+    pair(0, "EOF");
+    out
+}
@@ -1,3 +1,6 @@
+use crate::histogram::Extent;
+use crate::stats::ValidationReport;
+use crate::validate::Intersection;
 use std::error::Error as StdError;
 use std::fmt;
 use std::result;
@@ -28,12 +31,39 @@ impl Error {
 
 /// The specific type of an error.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
     BadDimension,
     JsonError(serde_json::error::Error),
     Unexpected,
-    #[doc(hidden)]
-    __Nonexhaustive,
+    Io(std::io::Error),
+    InvalidNumber { row: usize, column: usize, value: String },
+    RaggedRow { row: usize, len: usize, expected: usize },
+    InvalidThreshold(f64),
+    Reprojection(Box<dyn StdError + Send + Sync>),
+    OutputLimitExceeded { rings: usize, vertices: usize },
+    InvalidGeometry(Vec<Intersection>),
+    InvalidWindow { x: u32, y: u32, width: u32, height: u32 },
+    InvalidStride { row_stride: usize, dx: u32 },
+    InvalidInput(ValidationReport),
+    InvalidExtent(Extent),
+    ThresholdOutOfPeriod { threshold: f64, period: f64 },
+    MismatchedThresholds { before: usize, after: usize },
+    RawFileLength { len: u64, expected: u64 },
+    NotNested { lower: f64, higher: f64 },
+    PropertiesNotObject { index: usize, value: serde_json::Value },
+    Render(Box<dyn StdError + Send + Sync>),
+    PolylineOutOfRange { x: f64, y: f64, precision: u32 },
+    #[cfg(feature = "clip")]
+    DegenerateClipRegion { ring: usize },
+    #[cfg(feature = "gpkg")]
+    Sqlite(rusqlite::Error),
+    #[cfg(feature = "netcdf")]
+    Netcdf(::netcdf::Error),
+    #[cfg(feature = "netcdf")]
+    NetcdfDimensions { variable: String, dimensions: usize },
+    #[cfg(feature = "arrow")]
+    Arrow(arrow_schema::ArrowError),
 }
 
 impl From<serde_json::error::Error> for Error {
@@ -42,13 +72,67 @@ impl From<serde_json::error::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Error {
+        new_error(ErrorKind::Io(err))
+    }
+}
+
+#[cfg(feature = "gpkg")]
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Error {
+        new_error(ErrorKind::Sqlite(err))
+    }
+}
+
+#[cfg(feature = "netcdf")]
+impl From<::netcdf::Error> for Error {
+    fn from(err: ::netcdf::Error) -> Error {
+        new_error(ErrorKind::Netcdf(err))
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow_schema::ArrowError> for Error {
+    fn from(err: arrow_schema::ArrowError) -> Error {
+        new_error(ErrorKind::Arrow(err))
+    }
+}
+
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self.0 {
             ErrorKind::JsonError(ref err) => Some(err),
             ErrorKind::BadDimension => None,
             ErrorKind::Unexpected => None,
-            _ => unreachable!(),
+            ErrorKind::Io(ref err) => Some(err),
+            ErrorKind::InvalidNumber { .. } => None,
+            ErrorKind::RaggedRow { .. } => None,
+            ErrorKind::InvalidThreshold(_) => None,
+            ErrorKind::Reprojection(ref err) => Some(&**err),
+            ErrorKind::OutputLimitExceeded { .. } => None,
+            ErrorKind::InvalidGeometry(_) => None,
+            ErrorKind::InvalidWindow { .. } => None,
+            ErrorKind::InvalidStride { .. } => None,
+            ErrorKind::InvalidInput(_) => None,
+            ErrorKind::InvalidExtent(_) => None,
+            ErrorKind::ThresholdOutOfPeriod { .. } => None,
+            ErrorKind::MismatchedThresholds { .. } => None,
+            ErrorKind::RawFileLength { .. } => None,
+            ErrorKind::NotNested { .. } => None,
+            ErrorKind::PropertiesNotObject { .. } => None,
+            ErrorKind::Render(ref err) => Some(&**err),
+            ErrorKind::PolylineOutOfRange { .. } => None,
+            #[cfg(feature = "clip")]
+            ErrorKind::DegenerateClipRegion { .. } => None,
+            #[cfg(feature = "gpkg")]
+            ErrorKind::Sqlite(ref err) => Some(err),
+            #[cfg(feature = "netcdf")]
+            ErrorKind::Netcdf(ref err) => Some(err),
+            #[cfg(feature = "netcdf")]
+            ErrorKind::NetcdfDimensions { .. } => None,
+            #[cfg(feature = "arrow")]
+            ErrorKind::Arrow(ref err) => Some(err),
         }
     }
 }
@@ -59,7 +143,49 @@ impl fmt::Display for Error {
             ErrorKind::JsonError(ref err) => err.fmt(f),
             ErrorKind::BadDimension => write!(f, "The length of provided values doesn't match the (dx, dy) dimensions of the grid"),
             ErrorKind::Unexpected => write!(f, "Unexpected error while computing contours"),
-            _ => unreachable!(),
+            ErrorKind::Io(ref err) => err.fmt(f),
+            ErrorKind::InvalidNumber { row, column, ref value } => write!(f, "Invalid number {:?} at row {}, column {}", value, row, column),
+            ErrorKind::RaggedRow { row, len, expected } => write!(f, "Row {} has {} columns, expected {}", row, len, expected),
+            ErrorKind::InvalidThreshold(value) => write!(f, "Invalid threshold {} (thresholds must be finite)", value),
+            ErrorKind::Reprojection(ref err) => write!(f, "Reprojection failed: {}", err),
+            ErrorKind::OutputLimitExceeded { rings, vertices } => {
+                write!(f, "Output limit exceeded ({} rings for the threshold, {} positions in total)", rings, vertices)
+            }
+            ErrorKind::InvalidGeometry(ref intersections) => match intersections.first() {
+                Some(first) => write!(f, "Invalid geometry at threshold {} ({} pairs of intersecting segments)", first.threshold, intersections.len()),
+                None => write!(f, "Invalid geometry"),
+            },
+            ErrorKind::InvalidWindow { x, y, width, height } => {
+                write!(f, "The window of {}x{} values at column {}, row {} doesn't fit in the grid", width, height, x, y)
+            }
+            ErrorKind::InvalidStride { row_stride, dx } => write!(f, "The row stride {} is smaller than the {} columns of the grid", row_stride, dx),
+            ErrorKind::InvalidInput(ref report) => write!(f, "Invalid input values: {}", report),
+            ErrorKind::InvalidExtent(Extent { x_min, y_min, x_max, y_max }) => {
+                write!(f, "Invalid extent [{}, {}, {}, {}] (the bounds must be finite, the max ones greater than the min ones)", x_min, y_min, x_max, y_max)
+            }
+            ErrorKind::ThresholdOutOfPeriod { threshold, period } => write!(f, "The threshold {} of circular values isn't in [0, {})", threshold, period),
+            ErrorKind::MismatchedThresholds { before, after } => write!(f, "The outputs have {} and {} Features, expected the same thresholds", before, after),
+            ErrorKind::RawFileLength { len, expected } => write!(f, "The raw file has {} bytes, expected {} for the values of the grid", len, expected),
+            ErrorKind::NotNested { lower, higher } => write!(f, "The contour of {} isn't within the contour of {}", higher, lower),
+            ErrorKind::PropertiesNotObject { index, ref value } => {
+                write!(f, "The properties of the threshold at index {} aren't serialized to a JSON object: {}", index, value)
+            }
+            ErrorKind::Render(ref err) => write!(f, "Rendering failed: {}", err),
+            ErrorKind::PolylineOutOfRange { x, y, precision } => {
+                write!(f, "The position [{}, {}] can't be encoded as a polyline with {} decimals", x, y, precision)
+            }
+            #[cfg(feature = "clip")]
+            ErrorKind::DegenerateClipRegion { ring } => write!(f, "The ring {} of the clipping region has less than 3 distinct positions", ring),
+            #[cfg(feature = "gpkg")]
+            ErrorKind::Sqlite(ref err) => err.fmt(f),
+            #[cfg(feature = "netcdf")]
+            ErrorKind::Netcdf(ref err) => err.fmt(f),
+            #[cfg(feature = "netcdf")]
+            ErrorKind::NetcdfDimensions { ref variable, dimensions } => {
+                write!(f, "The NetCDF variable {} has {} dimensions, expected 2 or 3", variable, dimensions)
+            }
+            #[cfg(feature = "arrow")]
+            ErrorKind::Arrow(ref err) => err.fmt(f),
         }
     }
 }
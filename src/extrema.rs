@@ -0,0 +1,135 @@
+//! Local extrema (peaks and pits) of the grid, usually marked on contour maps.
+
+/// Options of [`ContourBuilder::extrema`].
+///
+/// [`ContourBuilder::extrema`]: ../struct.ContourBuilder.html#method.extrema
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExtremaOptions {
+    /// Half-size, in cells, of the neighborhood used to approximate the prominence
+    /// of the extrema (default: `1`, the 8 neighbors).
+    pub radius: usize,
+    /// Minimum prominence of the extrema (default: `0`, keeping all of them).
+    ///
+    /// The prominence of a maximum is approximated by the difference between its value
+    /// and the highest value on the border of its neighborhood (the cells at `radius`
+    /// cells from it, or from the cells of its plateau), standing for the saddle
+    /// to go through to reach a higher peak. Likewise for a minimum, with the lowest value.
+    /// Neighborhoods whose border lies entirely outside of the grid give an infinite prominence.
+    pub min_prominence: f64,
+}
+
+impl Default for ExtremaOptions {
+    fn default() -> Self {
+        ExtremaOptions {
+            radius: 1,
+            min_prominence: 0.,
+        }
+    }
+}
+
+/// The kind of an extremum, stored in the `kind` property of its Feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExtremumKind {
+    /// A local maximum (`"max"`).
+    Max,
+    /// A local minimum (`"min"`).
+    Min,
+}
+
+impl ExtremumKind {
+    /// The value of the `kind` property.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExtremumKind::Max => "max",
+            ExtremumKind::Min => "min",
+        }
+    }
+}
+
+pub(crate) struct Extremum {
+    pub x: usize,
+    pub y: usize,
+    pub value: f64,
+    pub kind: ExtremumKind,
+}
+
+// Finds the local extrema of the grid: the cells, or plateaus of 8-connected cells having
+// the same value, whose neighbors are all lower (maxima) or all higher (minima).
+// Cells outside of the grid and NaN values are ignored. A plateau is reported once,
+// at its first cell in row-major order.
+pub(crate) fn find_extrema<V: Fn(usize) -> f64>(values: &V, dx: usize, dy: usize, options: &ExtremaOptions) -> Vec<Extremum> {
+    let neighbors = |x: usize, y: usize| {
+        (y.saturating_sub(1)..(y + 2).min(dy))
+            .flat_map(move |ny| (x.saturating_sub(1)..(x + 2).min(dx)).map(move |nx| (nx, ny)))
+            .filter(move |&n| n != (x, y))
+    };
+    let mut visited = vec![false; dx * dy];
+    let mut extrema = Vec::new();
+    for start in 0..dx * dy {
+        let value = values(start);
+        if visited[start] || value.is_nan() {
+            continue;
+        }
+        // Flood fill of the plateau.
+        visited[start] = true;
+        let mut plateau = vec![start];
+        let (mut higher, mut lower) = (false, false);
+        let mut i = 0;
+        while i < plateau.len() {
+            let (x, y) = (plateau[i] % dx, plateau[i] / dx);
+            for (nx, ny) in neighbors(x, y) {
+                let n = ny * dx + nx;
+                let v = values(n);
+                if v == value {
+                    if !visited[n] {
+                        visited[n] = true;
+                        plateau.push(n);
+                    }
+                } else if v > value {
+                    higher = true;
+                } else if v < value {
+                    lower = true;
+                }
+            }
+            i += 1;
+        }
+        let kind = match (higher, lower) {
+            (false, true) => ExtremumKind::Max,
+            (true, false) => ExtremumKind::Min,
+            _ => continue,
+        };
+        if options.min_prominence > 0. && prominence(values, dx, dy, &plateau, value, kind, options.radius) < options.min_prominence {
+            continue;
+        }
+        extrema.push(Extremum {
+            x: start % dx,
+            y: start / dx,
+            value,
+            kind,
+        });
+    }
+    extrema
+}
+
+fn prominence<V: Fn(usize) -> f64>(values: &V, dx: usize, dy: usize, plateau: &[usize], value: f64, kind: ExtremumKind, radius: usize) -> f64 {
+    let (mut x0, mut y0, mut x1, mut y1) = (usize::MAX, usize::MAX, 0, 0);
+    for i in plateau {
+        x0 = x0.min(i % dx);
+        y0 = y0.min(i / dx);
+        x1 = x1.max(i % dx);
+        y1 = y1.max(i / dx);
+    }
+    // The border of the neighborhood, in signed coordinates as it may lie outside of the grid.
+    let (x0, y0) = (x0 as isize - radius as isize, y0 as isize - radius as isize);
+    let (x1, y1) = (x1 as isize + radius as isize, y1 as isize + radius as isize);
+    let inside = |x: isize, y: isize| x >= 0 && y >= 0 && (x as usize) < dx && (y as usize) < dy;
+    let border = (y0..=y1)
+        .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+        .filter(|&(x, y)| (x == x0 || x == x1 || y == y0 || y == y1) && inside(x, y))
+        .map(|(x, y)| values(y as usize * dx + x as usize))
+        .filter(|v| !v.is_nan());
+    match kind {
+        ExtremumKind::Max => value - border.fold(f64::NEG_INFINITY, f64::max),
+        ExtremumKind::Min => border.fold(f64::INFINITY, f64::min) - value,
+    }
+}
@@ -0,0 +1,288 @@
+//! FlatGeobuf writer (only available with the `flatgeobuf` feature).
+//!
+//! The Features are written as MultiPolygon geometries (with z coordinates when their
+//! positions have a third one, see [`ContourBuilder::z`]), with their `value` property in
+//! a `value` Double column. The file starts with the packed Hilbert R-tree of the bounding
+//! boxes of the Features (unless [`FgbOptions::index_node_size`] is `0`), the Features being
+//! written in the order of the Hilbert curve, for the clients to range-request the ones
+//! intersecting a bounding box.
+//!
+//! The bounding box of a Feature is its `bbox` member when it has one (see
+//! [`ContourBuilder::bbox`]), and the one of its positions otherwise. The index preceding
+//! the Features, each Feature is encoded twice, once to know the offsets of the index and
+//! once to be written, rather than buffering the whole output.
+//!
+//! [`ContourBuilder::z`]: ../struct.ContourBuilder.html#method.z
+//! [`ContourBuilder::bbox`]: ../struct.ContourBuilder.html#method.bbox
+//! [`FgbOptions::index_node_size`]: struct.FgbOptions.html#structfield.index_node_size
+
+use crate::error::Result;
+use flatbuffers::FlatBufferBuilder;
+use geojson::{Feature, Value};
+use std::io::Write;
+
+const MAGIC: [u8; 8] = [0x66, 0x67, 0x62, 0x03, 0x66, 0x67, 0x62, 0x00];
+const GEOMETRY_POLYGON: u8 = 3;
+const GEOMETRY_MULTIPOLYGON: u8 = 6;
+const COLUMN_DOUBLE: u8 = 10;
+const HILBERT_MAX: f64 = ((1 << 16) - 1) as f64;
+
+/// Options of the FlatGeobuf output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FgbOptions {
+    /// The name of the dataset (default: `"contours"`).
+    pub name: String,
+    /// The EPSG code of the coordinate reference system, if any (default: `None`). It
+    /// should match the [`ContourBuilder::reproject_with`] transformation, if any, like `4326`
+    /// for longitudes and latitudes.
+    ///
+    /// [`ContourBuilder::reproject_with`]: ../struct.ContourBuilder.html#method.reproject_with
+    pub epsg: Option<i32>,
+    /// The number of children of the nodes of the index, or `0` for no index (default: `16`).
+    pub index_node_size: u16,
+}
+
+impl Default for FgbOptions {
+    fn default() -> Self {
+        FgbOptions {
+            name: String::from("contours"),
+            epsg: None,
+            index_node_size: 16,
+        }
+    }
+}
+
+/// Writes the given `features` (as returned by [`ContourBuilder::contours`]) to `writer`
+/// as FlatGeobuf.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn write_fgb<W: Write>(mut writer: W, features: &[Feature], options: &FgbOptions) -> Result<()> {
+    let z = features.iter().flat_map(feature_polygons).flatten().flatten().any(|point| point.len() > 2);
+    let mut builder = FlatBufferBuilder::new();
+    // The bounding box and the encoded size of each Feature.
+    let mut items = features
+        .iter()
+        .enumerate()
+        .map(|(i, feature)| Item {
+            bbox: feature_bbox(feature),
+            size: encode_feature(&mut builder, feature, z).len() as u64,
+            feature: i,
+        })
+        .collect::<Vec<_>>();
+    let envelope = items.iter().fold(EMPTY, |envelope, item| union(envelope, item.bbox));
+    let index_node_size = if features.is_empty() { 0 } else { options.index_node_size };
+    if index_node_size > 0 {
+        items.sort_by_key(|item| std::cmp::Reverse(hilbert_value(item.bbox, envelope)));
+    }
+
+    writer.write_all(&MAGIC)?;
+    writer.write_all(encode_header(&mut builder, options, envelope, z, features.len(), index_node_size))?;
+    if index_node_size > 0 {
+        for node in packed_rtree(&items, index_node_size as usize) {
+            for v in node.bbox.iter() {
+                writer.write_all(&v.to_le_bytes())?;
+            }
+            writer.write_all(&node.offset.to_le_bytes())?;
+        }
+    }
+    for item in &items {
+        writer.write_all(encode_feature(&mut builder, &features[item.feature], z))?;
+    }
+    Ok(())
+}
+
+// A Feature to write, with its bounding box and the size of its encoding.
+struct Item {
+    bbox: [f64; 4],
+    size: u64,
+    feature: usize,
+}
+
+// A node of the index: the bounding box of its children (or of its Feature), and the
+// index of its first child (or the offset of its Feature from the first one).
+#[derive(Clone, Copy)]
+struct Node {
+    bbox: [f64; 4],
+    offset: u64,
+}
+
+const EMPTY: [f64; 4] = [f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY];
+
+fn union(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])]
+}
+
+// The nodes of the packed R-tree of the items (in their order), from the root to the
+// leaves, each level being stored after the one of its parents.
+fn packed_rtree(items: &[Item], node_size: usize) -> Vec<Node> {
+    // The number of nodes of each level, from the leaves to the root.
+    let mut levels = vec![items.len()];
+    while *levels.last().unwrap() > 1 || levels.len() == 1 {
+        levels.push(levels.last().unwrap().div_ceil(node_size));
+    }
+    let mut nodes = vec![Node { bbox: EMPTY, offset: 0 }; levels.iter().sum()];
+    let mut start = nodes.len() - items.len();
+    let mut offset = 0;
+    for (node, item) in nodes[start..].iter_mut().zip(items) {
+        *node = Node { bbox: item.bbox, offset };
+        offset += item.size;
+    }
+    for level in levels.windows(2) {
+        let parents = start - level[1];
+        for (i, parent) in (parents..start).enumerate() {
+            let first = start + i * node_size;
+            let children = first..(first + node_size).min(start + level[0]);
+            let bbox = nodes[children].iter().fold(EMPTY, |bbox, node| union(bbox, node.bbox));
+            nodes[parent] = Node { bbox, offset: first as u64 };
+        }
+        start = parents;
+    }
+    nodes
+}
+
+// The position of the center of the bounding box on the Hilbert curve filling the
+// envelope of the Features.
+fn hilbert_value(bbox: [f64; 4], envelope: [f64; 4]) -> u64 {
+    if bbox[0] > bbox[2] {
+        return 0;
+    }
+    let scale = |center: f64, min: f64, max: f64| {
+        if max > min { (HILBERT_MAX * (center - min) / (max - min)).floor() as u64 } else { 0 }
+    };
+    let mut x = scale((bbox[0] + bbox[2]) / 2., envelope[0], envelope[2]);
+    let mut y = scale((bbox[1] + bbox[3]) / 2., envelope[1], envelope[3]);
+    let n = 1 << 16;
+    let mut d = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let (rx, ry) = ((x & s > 0) as u64, (y & s > 0) as u64);
+        d += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    d
+}
+
+// Encodes the header (size-prefixed) in the builder, returning the encoded bytes.
+fn encode_header<'a>(
+    builder: &'a mut FlatBufferBuilder<'static>,
+    options: &FgbOptions,
+    envelope: [f64; 4],
+    z: bool,
+    features_count: usize,
+    index_node_size: u16,
+) -> &'a [u8] {
+    builder.reset();
+    let name = builder.create_string(&options.name);
+    let envelope = if envelope[0] <= envelope[2] { Some(builder.create_vector(&envelope)) } else { None };
+    let column_name = builder.create_string("value");
+    let column = builder.start_table();
+    builder.push_slot_always(4, column_name);
+    builder.push_slot(6, COLUMN_DOUBLE, 0);
+    let column = builder.end_table(column);
+    let columns = builder.create_vector(&[column]);
+    let crs = options.epsg.map(|code| {
+        let org = builder.create_string("EPSG");
+        let crs = builder.start_table();
+        builder.push_slot_always(4, org);
+        builder.push_slot(6, code, 0);
+        builder.end_table(crs)
+    });
+    let header = builder.start_table();
+    builder.push_slot_always(4, name);
+    if let Some(envelope) = envelope {
+        builder.push_slot_always(6, envelope);
+    }
+    builder.push_slot(8, GEOMETRY_MULTIPOLYGON, 0);
+    builder.push_slot(10, z, false);
+    builder.push_slot_always(18, columns);
+    builder.push_slot(20, features_count as u64, 0);
+    builder.push_slot(22, index_node_size, 16);
+    if let Some(crs) = crs {
+        builder.push_slot_always(24, crs);
+    }
+    let header = builder.end_table(header);
+    builder.finish_size_prefixed(header, None);
+    builder.finished_data()
+}
+
+// Encodes the Feature (size-prefixed) in the builder, returning the encoded bytes.
+fn encode_feature<'a>(builder: &'a mut FlatBufferBuilder<'static>, feature: &Feature, z: bool) -> &'a [u8] {
+    builder.reset();
+    let parts = feature_polygons(feature)
+        .iter()
+        .map(|polygon| {
+            let ends = polygon
+                .iter()
+                .scan(0, |end, ring| {
+                    *end += ring.len() as u32;
+                    Some(*end)
+                })
+                .collect::<Vec<_>>();
+            let points = polygon.iter().flatten();
+            let xy = points.clone().flat_map(|point| point[..2].iter().copied()).collect::<Vec<_>>();
+            let ends = builder.create_vector(&ends);
+            let xy = builder.create_vector(&xy);
+            let zs = if z {
+                let zs = points.map(|point| point.get(2).copied().unwrap_or(0.)).collect::<Vec<_>>();
+                Some(builder.create_vector(&zs))
+            } else {
+                None
+            };
+            let part = builder.start_table();
+            builder.push_slot_always(4, ends);
+            builder.push_slot_always(6, xy);
+            if let Some(zs) = zs {
+                builder.push_slot_always(8, zs);
+            }
+            builder.push_slot(16, GEOMETRY_POLYGON, 0);
+            builder.end_table(part)
+        })
+        .collect::<Vec<_>>();
+    let parts = builder.create_vector(&parts);
+    let geometry = builder.start_table();
+    builder.push_slot(16, GEOMETRY_MULTIPOLYGON, 0);
+    builder.push_slot_always(18, parts);
+    let geometry = builder.end_table(geometry);
+    // The value, in the column 0.
+    let value = feature.properties.as_ref().and_then(|properties| properties.get("value")?.as_f64());
+    let properties = value.map(|value| {
+        let mut bytes = 0u16.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&value.to_le_bytes());
+        builder.create_vector(&bytes)
+    });
+    let root = builder.start_table();
+    builder.push_slot_always(4, geometry);
+    if let Some(properties) = properties {
+        builder.push_slot_always(6, properties);
+    }
+    let root = builder.end_table(root);
+    builder.finish_size_prefixed(root, None);
+    builder.finished_data()
+}
+
+fn feature_polygons(feature: &Feature) -> Vec<Vec<Vec<Vec<f64>>>> {
+    match feature.geometry.as_ref().map(|geometry| &geometry.value) {
+        Some(Value::Polygon(polygon)) => vec![polygon.clone()],
+        Some(Value::MultiPolygon(polygons)) => polygons.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn feature_bbox(feature: &Feature) -> [f64; 4] {
+    match feature.bbox {
+        Some(ref bbox) if bbox.len() == 4 => [bbox[0], bbox[1], bbox[2], bbox[3]],
+        Some(ref bbox) if bbox.len() == 6 => [bbox[0], bbox[1], bbox[3], bbox[4]],
+        _ => feature_polygons(feature)
+            .iter()
+            .flatten()
+            .flatten()
+            .fold(EMPTY, |bbox, point| union(bbox, [point[0], point[1], point[0], point[1]])),
+    }
+}
@@ -0,0 +1,123 @@
+//! Columnar output following the [GeoArrow](https://geoarrow.org) memory layout.
+//!
+//! The buffers of a [`MultiPolygonArray`] map one-to-one to the arrays of a
+//! geoarrow `MultiPolygon` column (`List<List<List<Struct<x, y>>>>` or, with
+//! interleaved coordinates, `List<List<List<FixedSizeList<2>>>>`), so they can be
+//! handed to arrow without copying (e.g. with `OffsetBuffer::new` and
+//! `ScalarBuffer::from`), as [`MultiPolygonArray::into_record_batch`] does with the
+//! `arrow` feature.
+//!
+//! [`MultiPolygonArray`]: struct.MultiPolygonArray.html
+//! [`MultiPolygonArray::into_record_batch`]: struct.MultiPolygonArray.html#method.into_record_batch
+
+#[cfg(feature = "arrow")]
+use crate::error::Result;
+#[cfg(feature = "arrow")]
+use arrow_array::{ArrayRef, FixedSizeListArray, Float64Array, ListArray, RecordBatch};
+#[cfg(feature = "arrow")]
+use arrow_buffer::OffsetBuffer;
+#[cfg(feature = "arrow")]
+use arrow_schema::{DataType, Field, Schema};
+#[cfg(feature = "arrow")]
+use std::collections::HashMap;
+#[cfg(feature = "arrow")]
+use std::sync::Arc;
+
+/// Contours stored as a geoarrow MultiPolygon column plus a `value` column,
+/// one row per threshold.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiPolygonArray {
+    /// The threshold value of each row.
+    pub values: Vec<f64>,
+    /// Offsets into `polygon_offsets` of the polygons of each row
+    /// (`values.len() + 1` elements).
+    pub geom_offsets: Vec<i32>,
+    /// Offsets into `ring_offsets` of the rings of each polygon.
+    pub polygon_offsets: Vec<i32>,
+    /// Offsets into the coordinates of the vertices of each ring.
+    pub ring_offsets: Vec<i32>,
+    /// The interleaved `x, y` coordinates of the vertices (`2 * ring_offsets.last()` elements).
+    pub coords: Vec<f64>,
+}
+
+impl MultiPolygonArray {
+    pub(crate) fn new() -> Self {
+        MultiPolygonArray {
+            values: Vec::new(),
+            geom_offsets: vec![0],
+            polygon_offsets: vec![0],
+            ring_offsets: vec![0],
+            coords: Vec::new(),
+        }
+    }
+
+    /// The number of rows (thresholds) of the array.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the array has no row.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the `x` coordinates of the vertices (for the separated `Struct<x, y>` layout).
+    pub fn xs(&self) -> Vec<f64> {
+        self.coords.iter().step_by(2).cloned().collect()
+    }
+
+    /// Returns the `y` coordinates of the vertices (for the separated `Struct<x, y>` layout).
+    pub fn ys(&self) -> Vec<f64> {
+        self.coords.iter().skip(1).step_by(2).cloned().collect()
+    }
+
+    // Appends a row made of the given polygons (each one being a list of rings).
+    pub(crate) fn push<P, R>(&mut self, value: f64, polygons: P)
+    where
+        P: IntoIterator<Item = R>,
+        R: IntoIterator<Item = Vec<Vec<f64>>>,
+    {
+        for rings in polygons {
+            for ring in rings {
+                for point in ring {
+                    self.coords.push(point[0]);
+                    self.coords.push(point[1]);
+                }
+                self.ring_offsets.push((self.coords.len() / 2) as i32);
+            }
+            self.polygon_offsets.push((self.ring_offsets.len() - 1) as i32);
+        }
+        self.geom_offsets.push((self.polygon_offsets.len() - 1) as i32);
+        self.values.push(value);
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl MultiPolygonArray {
+    /// Converts the array to an arrow `RecordBatch` (without copying the buffers) of two
+    /// columns, one row per threshold:
+    ///
+    /// - `geometry`, the GeoArrow MultiPolygon column with interleaved coordinates
+    ///   (`List<List<List<FixedSizeList<Float64, 2>>>>`, with the `geoarrow.multipolygon`
+    ///   extension name in the metadata of its field),
+    /// - `value`, the threshold of each row (`Float64`).
+    ///
+    /// Only available with the `arrow` feature.
+    pub fn into_record_batch(self) -> Result<RecordBatch> {
+        let list = |name: &str, offsets: Vec<i32>, values: ArrayRef| -> Result<ArrayRef> {
+            let field = Arc::new(Field::new(name, values.data_type().clone(), false));
+            Ok(Arc::new(ListArray::try_new(field, OffsetBuffer::new(offsets.into()), values, None)?))
+        };
+        let xy = Arc::new(Field::new("xy", DataType::Float64, false));
+        let coords = Arc::new(FixedSizeListArray::try_new(xy, 2, Arc::new(Float64Array::from(self.coords)), None)?);
+        let rings = list("vertices", self.ring_offsets, coords)?;
+        let polygons = list("rings", self.polygon_offsets, rings)?;
+        let geometry = list("polygons", self.geom_offsets, polygons)?;
+        let metadata = HashMap::from([(String::from("ARROW:extension:name"), String::from("geoarrow.multipolygon"))]);
+        let schema = Schema::new(vec![
+            Field::new("geometry", geometry.data_type().clone(), false).with_metadata(metadata),
+            Field::new("value", DataType::Float64, false),
+        ]);
+        Ok(RecordBatch::try_new(Arc::new(schema), vec![geometry, Arc::new(Float64Array::from(self.values))])?)
+    }
+}
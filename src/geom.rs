@@ -0,0 +1,399 @@
+//! Geometric primitives on rings, such as the ones returned by [`contour_rings`]
+//! or found in the polygons of the [`ContourBuilder`] output.
+//!
+//! A ring is a slice of positions (`[x, y]`, any further coordinate being ignored),
+//! normally closed (its last position being the same as its first one), the functions
+//! treating it as closed anyway. Orientations are given with the y axis pointing up,
+//! as in GeoJSON: in the grid coordinates of [`contour_rings`] the y axis points down,
+//! so its clockwise exterior rings look counterclockwise when drawn.
+//!
+//! None of these functions panics on degenerate rings (empty, or with less than three
+//! distinct positions).
+//!
+//! [`contour_rings`]: ../fn.contour_rings.html
+//! [`ContourBuilder`]: ../struct.ContourBuilder.html
+
+use crate::contour::Pt;
+use robust::Coord;
+
+/// Computes twice the signed area of the ring: positive when the ring is clockwise
+/// (like the exterior rings of the contours), negative when it's counterclockwise
+/// (like their holes), and zero for a degenerate ring.
+///
+/// ```
+/// # use contour::geom::area;
+/// let square = vec![vec![0., 0.], vec![0., 1.], vec![1., 1.], vec![1., 0.], vec![0., 0.]];
+/// assert_eq!(area(&square), 2.);
+/// let reversed = square.iter().rev().cloned().collect::<Vec<_>>();
+/// assert_eq!(area(&reversed), -2.);
+/// assert_eq!(area(&[]), 0.);
+/// ```
+pub fn area(ring: &[Pt]) -> f64 {
+    let n = ring.len();
+    if n == 0 {
+        return 0.;
+    }
+    let mut area = ring[n - 1][1] * ring[0][0] - ring[n - 1][0] * ring[0][1];
+    for i in 1..n {
+        area += ring[i - 1][1] * ring[i][0] - ring[i - 1][0] * ring[i][1];
+    }
+    area
+}
+
+/// Tells where the ring `hole` is relative to `ring`, given the first of its positions
+/// not lying on the boundary of `ring` (see [`ring_contains`]): `1` inside, `-1` outside.
+/// Returns `0` when all of them lie on the boundary (or `hole` is empty).
+///
+/// ```
+/// # use contour::geom::contains;
+/// let square = vec![vec![0., 0.], vec![0., 4.], vec![4., 4.], vec![4., 0.], vec![0., 0.]];
+/// let hole = vec![vec![1., 1.], vec![2., 1.], vec![1., 2.], vec![1., 1.]];
+/// assert_eq!(contains(&square, &hole), 1);
+/// assert_eq!(contains(&hole, &square), -1);
+/// assert_eq!(contains(&square, &square), 0);
+/// ```
+///
+/// [`ring_contains`]: fn.ring_contains.html
+pub fn contains(ring: &[Pt], hole: &[Pt]) -> i32 {
+    for point in hole {
+        let c = ring_contains(ring, point);
+        if c != 0 {
+            return c;
+        }
+    }
+    0
+}
+
+/// Tells where `point` is relative to `ring`: `1` strictly inside, `-1` strictly outside
+/// and `0` on its boundary. Every point is outside an empty ring.
+///
+/// The decisions are exact (see [`orient2d`]): they don't depend on rounding errors,
+/// even for points nearly on the boundary.
+///
+/// ```
+/// # use contour::geom::ring_contains;
+/// let square = vec![vec![0., 0.], vec![0., 4.], vec![4., 4.], vec![4., 0.], vec![0., 0.]];
+/// assert_eq!(ring_contains(&square, &[1., 1.]), 1);
+/// assert_eq!(ring_contains(&square, &[4., 2.]), 0);
+/// assert_eq!(ring_contains(&square, &[5., 1.]), -1);
+/// ```
+pub fn ring_contains(ring: &[Pt], point: &[f64]) -> i32 {
+    let y = point[1];
+    let n = ring.len();
+    let mut contains = -1;
+    if n == 0 {
+        return contains;
+    }
+    let mut j = n - 1;
+    for i in 0..n {
+        let pi = &ring[i];
+        let yi = pi[1];
+        let pj = &ring[j];
+        let yj = pj[1];
+        if segment_contains(pi, pj, point) {
+            return 0;
+        }
+        // Whether the ray going from the point towards increasing x crosses the segment.
+        if (yi > y) != (yj > y) {
+            let orientation = orient2d(pi, pj, point);
+            if orientation != 0. && (orientation > 0.) == (yj > yi) {
+                contains = -contains;
+            }
+        }
+        j = i;
+    }
+    contains
+}
+
+/// Tells whether `point` is inside `ring` or on its boundary (see [`ring_contains`]).
+///
+/// ```
+/// # use contour::geom::point_in_ring;
+/// let square = vec![vec![0., 0.], vec![0., 4.], vec![4., 4.], vec![4., 0.], vec![0., 0.]];
+/// assert!(point_in_ring(&square, &[4., 2.]));
+/// assert!(!point_in_ring(&square, &[5., 1.]));
+/// ```
+///
+/// [`ring_contains`]: fn.ring_contains.html
+pub fn point_in_ring(ring: &[Pt], point: &[f64]) -> bool {
+    ring_contains(ring, point) != -1
+}
+
+/// Computes the bounding box (`[min x, min y, max x, max y]`) of the ring,
+/// or `None` if it's empty.
+///
+/// ```
+/// # use contour::geom::ring_bbox;
+/// let triangle = vec![vec![1., 0.], vec![3., 2.], vec![0., 1.], vec![1., 0.]];
+/// assert_eq!(ring_bbox(&triangle), Some(vec![0., 0., 3., 2.]));
+/// assert_eq!(ring_bbox(&[]), None);
+/// ```
+pub fn ring_bbox(ring: &[Pt]) -> Option<Vec<f64>> {
+    ring.iter().fold(None, |bbox, p| match bbox {
+        None => Some(vec![p[0], p[1], p[0], p[1]]),
+        Some(b) => Some(vec![b[0].min(p[0]), b[1].min(p[1]), b[2].max(p[0]), b[3].max(p[1])]),
+    })
+}
+
+/// Removes the positions of the closed ring lying exactly on the segment between their
+/// neighbours (see [`orient2d`]), such as the runs of positions along the lines of the grid
+/// in the rings of the contours. The ring stays closed, and is left unchanged if it isn't
+/// closed or has less than 3 positions which aren't collinear.
+///
+/// ```
+/// # use contour::geom::merge_collinear;
+/// let mut ring = vec![vec![0., 0.], vec![0., 1.], vec![0., 2.], vec![2., 2.], vec![1., 1.], vec![0., 0.]];
+/// merge_collinear(&mut ring);
+/// assert_eq!(ring, vec![vec![0., 0.], vec![0., 2.], vec![2., 2.], vec![0., 0.]]);
+/// ```
+///
+/// [`orient2d`]: fn.orient2d.html
+pub fn merge_collinear(ring: &mut Vec<Pt>) {
+    if ring.len() < 2 || ring.first() != ring.last() {
+        return;
+    }
+    let n = ring.len() - 1;
+    // The indexes of the positions kept, each one checked against the last two kept.
+    let mut kept: Vec<usize> = Vec::with_capacity(n);
+    for i in 0..n {
+        while kept.len() >= 2 && segment_contains(&ring[kept[kept.len() - 2]], &ring[i], &ring[kept[kept.len() - 1]]) {
+            kept.pop();
+        }
+        kept.push(i);
+    }
+    // The positions around the start of the ring.
+    while kept.len() > 3 {
+        let (first, last) = (kept[0], kept[kept.len() - 1]);
+        if segment_contains(&ring[kept[kept.len() - 2]], &ring[first], &ring[last]) {
+            kept.pop();
+        } else if segment_contains(&ring[last], &ring[kept[1]], &ring[first]) {
+            kept.remove(0);
+        } else {
+            break;
+        }
+    }
+    if kept.len() < 3 || kept.len() == n {
+        return;
+    }
+    let mut positions = std::mem::take(ring);
+    ring.extend(kept.iter().map(|i| std::mem::take(&mut positions[*i])));
+    ring.push(ring[0].clone());
+}
+
+/// Subdivides the segments of the ring longer than `max_segment_length` into pieces
+/// of equal length, not longer than it, inserting the intermediate positions (all their
+/// coordinates being interpolated). Segments whose length isn't finite are left unchanged.
+///
+/// ```
+/// # use contour::geom::densify;
+/// let mut ring = vec![vec![0., 0.], vec![3., 0.], vec![0., 1.], vec![0., 0.]];
+/// densify(&mut ring, 1.5);
+/// assert_eq!(ring[..4], [vec![0., 0.], vec![1.5, 0.], vec![3., 0.], vec![2., 1. / 3.]]);
+/// assert_eq!(ring.len(), 7);
+/// ```
+pub fn densify(ring: &mut Vec<Pt>, max_segment_length: f64) {
+    if ring.len() < 2 || max_segment_length.is_nan() || max_segment_length <= 0. {
+        return;
+    }
+    let mut densified = Vec::with_capacity(ring.len());
+    for (i, b) in ring.iter().enumerate().skip(1) {
+        let a = &ring[i - 1];
+        densified.push(a.clone());
+        let length = (b[0] - a[0]).hypot(b[1] - a[1]);
+        if length > max_segment_length && length.is_finite() {
+            let pieces = (length / max_segment_length).ceil() as usize;
+            for k in 1..pieces {
+                let t = k as f64 / pieces as f64;
+                densified.push(a.iter().zip(b).map(|(p, q)| p + (q - p) * t).collect());
+            }
+        }
+    }
+    densified.push(ring[ring.len() - 1].clone());
+    *ring = densified;
+}
+
+/// The mean radius of the Earth, in meters (for [`Metric::Spherical`]).
+///
+/// [`Metric::Spherical`]: enum.Metric.html#variant.Spherical
+pub const MEAN_EARTH_RADIUS: f64 = 6_371_008.8;
+
+// The semi-major axis and the flattening of the WGS84 ellipsoid.
+const WGS84_A: f64 = 6_378_137.;
+const WGS84_F: f64 = 1. / 298.257_223_563;
+
+/// How areas and lengths are computed from positions (see [`ContourBuilder::metric`]).
+///
+/// The geodesic metrics take longitudes and latitudes, in degrees (the output coordinates
+/// of contours reprojected to them with [`reproject_with`], or of a grid georeferenced in degrees).
+///
+/// ```
+/// # use contour::geom::{Metric, MEAN_EARTH_RADIUS};
+/// let square = vec![vec![0., 0.], vec![0., 1.], vec![1., 1.], vec![1., 0.], vec![0., 0.]];
+/// assert_eq!(Metric::Planar.area(&square), 1.);
+/// let area = Metric::Spherical { radius: MEAN_EARTH_RADIUS }.area(&square);
+/// assert!((area / 1e6 - 12_364.).abs() < 1.);
+/// ```
+///
+/// [`ContourBuilder::metric`]: ../struct.ContourBuilder.html#method.metric
+/// [`reproject_with`]: ../struct.ContourBuilder.html#method.reproject_with
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Metric {
+    /// In the units of the coordinates (default).
+    #[default]
+    Planar,
+    /// On a sphere of the given radius, the segments being great-circle arcs
+    /// (in the units of the radius).
+    Spherical { radius: f64 },
+    /// On the WGS84 ellipsoid, in meters: the lengths are the ones of the geodesics
+    /// (Vincenty's formulae) and the areas are computed on the sphere of the same area
+    /// (with the authalic latitudes), the segments being great-circle arcs on it.
+    Wgs84,
+}
+
+impl Metric {
+    /// Computes the (positive) area of the ring.
+    pub fn area(&self, ring: &[Pt]) -> f64 {
+        match *self {
+            Metric::Planar => area(ring).abs() / 2.,
+            Metric::Spherical { radius } => spherical_excess(ring, |phi| phi) * radius * radius,
+            Metric::Wgs84 => {
+                let qp = authalic_q(1.);
+                let radius = WGS84_A * (qp / 2.).sqrt();
+                spherical_excess(ring, |phi| (authalic_q(phi.sin()) / qp).clamp(-1., 1.).asin()) * radius * radius
+            }
+        }
+    }
+
+    /// Computes the length of the boundary of the ring (closed or not).
+    pub fn perimeter(&self, ring: &[Pt]) -> f64 {
+        match ring.len() {
+            0 => 0.,
+            n => (0..n).map(|i| self.distance(&ring[i], &ring[(i + 1) % n])).sum(),
+        }
+    }
+
+    /// Computes the distance between the positions `a` and `b`.
+    pub fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+        match *self {
+            Metric::Planar => (b[0] - a[0]).hypot(b[1] - a[1]),
+            Metric::Spherical { radius } => haversine(a, b) * radius,
+            Metric::Wgs84 => vincenty(a, b).unwrap_or_else(|| haversine(a, b) * MEAN_EARTH_RADIUS),
+        }
+    }
+}
+
+// The area of the ring of longitudes and latitudes (in degrees) on the unit sphere,
+// `latitude` converting the latitudes (in radians) first: the sum of the signed areas
+// of the triangles made by the segments and the north pole (as done by d3-geo).
+fn spherical_excess<L: Fn(f64) -> f64>(ring: &[Pt], latitude: L) -> f64 {
+    let n = ring.len();
+    if n < 3 {
+        return 0.;
+    }
+    let point = |p: &Pt| {
+        let phi = latitude(p[1].to_radians()) / 2. + std::f64::consts::FRAC_PI_4;
+        (p[0].to_radians(), phi.sin(), phi.cos())
+    };
+    let (mut lambda0, mut sin0, mut cos0) = point(&ring[n - 1]);
+    let mut sum = 0.;
+    for p in ring {
+        let (lambda, sin, cos) = point(p);
+        let d = lambda - lambda0;
+        let (sd, ad) = if d >= 0. { (1., d) } else { (-1., -d) };
+        let k = sin0 * sin;
+        sum += (k * sd * ad.sin()).atan2(cos0 * cos + k * ad.cos());
+        lambda0 = lambda;
+        sin0 = sin;
+        cos0 = cos;
+    }
+    // The area of the smallest of the two regions bounded by the ring.
+    let area = (2. * sum).abs();
+    if area > 2. * std::f64::consts::PI {
+        4. * std::f64::consts::PI - area
+    } else {
+        area
+    }
+}
+
+// The function `q` of the authalic latitude on the WGS84 ellipsoid, given the sine
+// of the latitude.
+fn authalic_q(sin: f64) -> f64 {
+    let e2 = WGS84_F * (2. - WGS84_F);
+    let e = e2.sqrt();
+    (1. - e2) * (sin / (1. - e2 * sin * sin) - ((1. - e * sin) / (1. + e * sin)).ln() / (2. * e))
+}
+
+// The central angle between two positions (longitudes and latitudes, in degrees).
+fn haversine(a: &[f64], b: &[f64]) -> f64 {
+    let (phi1, phi2) = (a[1].to_radians(), b[1].to_radians());
+    let (dphi, dlambda) = (phi2 - phi1, (b[0] - a[0]).to_radians());
+    let h = (dphi / 2.).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.).sin().powi(2);
+    2. * h.sqrt().min(1.).asin()
+}
+
+// The length of the geodesic between two positions on the WGS84 ellipsoid (Vincenty's
+// inverse formula), `None` when it doesn't converge (for nearly antipodal positions).
+fn vincenty(a: &[f64], b: &[f64]) -> Option<f64> {
+    let semi_minor = (1. - WGS84_F) * WGS84_A;
+    let l = (b[0] - a[0]).to_radians();
+    let u1 = ((1. - WGS84_F) * a[1].to_radians().tan()).atan();
+    let u2 = ((1. - WGS84_F) * b[1].to_radians().tan()).atan();
+    let (sin_u1, cos_u1, sin_u2, cos_u2) = (u1.sin(), u1.cos(), u2.sin(), u2.cos());
+    let mut lambda = l;
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = (cos_u2 * sin_lambda).hypot(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+        if sin_sigma == 0. {
+            return Some(0.);
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos2_alpha = 1. - sin_alpha * sin_alpha;
+        // On the equator, `cos2_alpha` is zero.
+        let cos_2sigma_m = if cos2_alpha == 0. { 0. } else { cos_sigma - 2. * sin_u1 * sin_u2 / cos2_alpha };
+        let c = WGS84_F / 16. * cos2_alpha * (4. + WGS84_F * (4. - 3. * cos2_alpha));
+        let previous = lambda;
+        lambda = l + (1. - c) * WGS84_F * sin_alpha * (sigma + c * sin_sigma * (cos_2sigma_m + c * cos_sigma * (-1. + 2. * cos_2sigma_m * cos_2sigma_m)));
+        if (lambda - previous).abs() < 1e-12 {
+            let u2 = cos2_alpha * (WGS84_A * WGS84_A - semi_minor * semi_minor) / (semi_minor * semi_minor);
+            let big_a = 1. + u2 / 16384. * (4096. + u2 * (-768. + u2 * (320. - 175. * u2)));
+            let big_b = u2 / 1024. * (256. + u2 * (-128. + u2 * (74. - 47. * u2)));
+            let delta_sigma = big_b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + big_b / 4.
+                        * (cos_sigma * (-1. + 2. * cos_2sigma_m * cos_2sigma_m)
+                            - big_b / 6. * cos_2sigma_m * (-3. + 4. * sin_sigma * sin_sigma) * (-3. + 4. * cos_2sigma_m * cos_2sigma_m)));
+            return Some(semi_minor * big_a * (sigma - delta_sigma));
+        }
+    }
+    None
+}
+
+/// Computes the orientation of the points `a`, `b` and `c`: positive when they are
+/// counterclockwise (with the y axis pointing up), negative when they are clockwise
+/// and zero when they are collinear. Only the sign of the result is meaningful.
+///
+/// The sign is exact (with Shewchuk's adaptive predicates, from the `robust` crate): it
+/// doesn't depend on the platform nor on rounding errors.
+///
+/// ```
+/// # use contour::geom::orient2d;
+/// assert!(orient2d(&[0., 0.], &[1., 0.], &[0., 1.]) > 0.);
+/// assert!(orient2d(&[0., 0.], &[0., 1.], &[1., 0.]) < 0.);
+/// assert_eq!(orient2d(&[0.1, 0.1], &[0.2, 0.2], &[0.3, 0.3]), 0.);
+/// ```
+pub fn orient2d(a: &[f64], b: &[f64], c: &[f64]) -> f64 {
+    let coord = |p: &[f64]| Coord { x: p[0], y: p[1] };
+    robust::orient2d(coord(a), coord(b), coord(c))
+}
+
+// Whether `c` lies on the segment from `a` to `b`.
+pub(crate) fn segment_contains(a: &[f64], b: &[f64], c: &[f64]) -> bool {
+    orient2d(a, b, c) == 0. && within(a[0], c[0], b[0]) && within(a[1], c[1], b[1])
+}
+
+fn within(p: f64, q: f64, r: f64) -> bool {
+    p <= q && q <= r || r <= q && q <= p
+}
@@ -0,0 +1,203 @@
+//! GeoPackage writer (only available with the `gpkg` feature).
+//!
+//! The Features are written to a feature table of MultiPolygon geometries (MultiPolygon Z
+//! geometries when their positions have a third coordinate, see [`ContourBuilder::z`]),
+//! with their `value` property in a `value` REAL column, and optionally their rings as the
+//! MultiLineString geometries of a second table of isolines. The geometries are stored as
+//! GeoPackage binary blobs: the standard header (with the SRS id and the envelope of the
+//! geometry) followed by the little-endian ISO WKB of the geometry (see the [`wkb`] module).
+//!
+//! [`ContourBuilder::z`]: ../struct.ContourBuilder.html#method.z
+//! [`wkb`]: ../wkb/index.html
+
+use crate::contour::LinearRing;
+use crate::error::Result;
+use crate::wkb::{Dialect, has_z, write_multilinestring, write_multipolygon};
+use geojson::{Feature, Value};
+use rusqlite::{Connection, params};
+use std::path::Path;
+
+/// Options of the GeoPackage output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpkgOptions {
+    /// The name of the table of the contours (default: `"contours"`).
+    pub table: String,
+    /// The name of the table of the isolines, if any (default: `None`).
+    pub isolines: Option<String>,
+    /// The id of the spatial reference system of the coordinates, an EPSG code (default: `-1`,
+    /// the undefined Cartesian system of the grid coordinates). It should match the
+    /// [`ContourBuilder::reproject_with`] transformation, if any, like `4326` for longitudes
+    /// and latitudes.
+    ///
+    /// [`ContourBuilder::reproject_with`]: ../struct.ContourBuilder.html#method.reproject_with
+    pub srid: i32,
+}
+
+impl Default for GpkgOptions {
+    fn default() -> Self {
+        GpkgOptions {
+            table: String::from("contours"),
+            isolines: None,
+            srid: -1,
+        }
+    }
+}
+
+/// Writes the given `features` (as returned by [`ContourBuilder::contours`]) to a new
+/// GeoPackage at `path`, replacing the file if it exists.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn write_gpkg<P: AsRef<Path>>(path: P, features: &[Feature], options: &GpkgOptions) -> Result<()> {
+    let path = path.as_ref();
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let mut connection = Connection::open(path)?;
+    let transaction = connection.transaction()?;
+    create_metadata(&transaction, options.srid)?;
+    let polygons = features.iter().map(feature_polygons).collect::<Vec<_>>();
+    let values = features.iter().map(feature_value).collect::<Vec<_>>();
+    let blobs = polygons.iter().map(|polygons| {
+        let rings = polygons.iter().flatten().collect::<Vec<_>>();
+        geometry(options.srid, &rings, |wkb, z| write_multipolygon(wkb, polygons, z, Dialect::Iso))
+    });
+    write_table(&transaction, &options.table, "MULTIPOLYGON", options.srid, &polygons, blobs.collect(), &values)?;
+    if let Some(ref table) = options.isolines {
+        let blobs = polygons.iter().map(|polygons| {
+            let rings = polygons.iter().flatten().collect::<Vec<_>>();
+            geometry(options.srid, &rings, |wkb, z| write_multilinestring(wkb, &rings, z, Dialect::Iso))
+        });
+        write_table(&transaction, table, "MULTILINESTRING", options.srid, &polygons, blobs.collect(), &values)?;
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+// Creates the metadata tables of the GeoPackage, with the spatial reference systems
+// it requires and the one of `srid`.
+fn create_metadata(connection: &Connection, srid: i32) -> Result<()> {
+    connection.execute_batch(
+        "PRAGMA application_id = 1196444487;
+        PRAGMA user_version = 10300;
+        CREATE TABLE gpkg_spatial_ref_sys (
+            srs_name TEXT NOT NULL,
+            srs_id INTEGER NOT NULL PRIMARY KEY,
+            organization TEXT NOT NULL,
+            organization_coordsys_id INTEGER NOT NULL,
+            definition TEXT NOT NULL,
+            description TEXT
+        );
+        CREATE TABLE gpkg_contents (
+            table_name TEXT NOT NULL PRIMARY KEY,
+            data_type TEXT NOT NULL,
+            identifier TEXT UNIQUE,
+            description TEXT DEFAULT '',
+            last_change DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+            min_x DOUBLE,
+            min_y DOUBLE,
+            max_x DOUBLE,
+            max_y DOUBLE,
+            srs_id INTEGER,
+            CONSTRAINT fk_gc_r_srs_id FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys(srs_id)
+        );
+        CREATE TABLE gpkg_geometry_columns (
+            table_name TEXT NOT NULL,
+            column_name TEXT NOT NULL,
+            geometry_type_name TEXT NOT NULL,
+            srs_id INTEGER NOT NULL,
+            z TINYINT NOT NULL,
+            m TINYINT NOT NULL,
+            CONSTRAINT pk_geom_cols PRIMARY KEY (table_name, column_name),
+            CONSTRAINT fk_gc_tn FOREIGN KEY (table_name) REFERENCES gpkg_contents(table_name),
+            CONSTRAINT fk_gc_srs FOREIGN KEY (srs_id) REFERENCES gpkg_spatial_ref_sys (srs_id)
+        );
+        INSERT INTO gpkg_spatial_ref_sys VALUES
+            ('Undefined cartesian SRS', -1, 'NONE', -1, 'undefined', 'undefined cartesian coordinate reference system'),
+            ('Undefined geographic SRS', 0, 'NONE', 0, 'undefined', 'undefined geographic coordinate reference system'),
+            ('WGS 84 geodetic', 4326, 'EPSG', 4326,
+             'GEOGCS[\"WGS 84\",DATUM[\"WGS_1984\",SPHEROID[\"WGS 84\",6378137,298.257223563]],PRIMEM[\"Greenwich\",0],UNIT[\"degree\",0.0174532925199433]]',
+             'longitude/latitude coordinates in decimal degrees on the WGS 84 spheroid');",
+    )?;
+    // The definitions of the other systems are left to the applications knowing the EPSG codes.
+    connection.execute(
+        "INSERT OR IGNORE INTO gpkg_spatial_ref_sys VALUES (?1, ?2, 'EPSG', ?2, 'undefined', NULL)",
+        params![format!("EPSG:{}", srid), srid],
+    )?;
+    Ok(())
+}
+
+// Creates the feature table `table` of the given geometries and values, and registers it.
+fn write_table(
+    connection: &Connection,
+    table: &str,
+    geometry_type: &str,
+    srid: i32,
+    polygons: &[Vec<Vec<LinearRing>>],
+    blobs: Vec<Vec<u8>>,
+    values: &[Option<f64>],
+) -> Result<()> {
+    let name = table.replace('"', "\"\"");
+    connection.execute_batch(&format!(
+        "CREATE TABLE \"{}\" (fid INTEGER PRIMARY KEY AUTOINCREMENT, geom {}, value REAL)",
+        name, geometry_type
+    ))?;
+    let mut insert = connection.prepare(&format!("INSERT INTO \"{}\" (geom, value) VALUES (?1, ?2)", name))?;
+    for (blob, value) in blobs.iter().zip(values) {
+        insert.execute(params![blob, value])?;
+    }
+    let z = has_z(polygons.iter().flatten().flatten());
+    let points = polygons.iter().flatten().flatten().flatten().collect::<Vec<_>>();
+    let (min_x, min_y, max_x, max_y) = bounds(points.into_iter()).map_or((None, None, None, None), |b| (Some(b[0]), Some(b[1]), Some(b[2]), Some(b[3])));
+    connection.execute(
+        "INSERT INTO gpkg_contents (table_name, data_type, identifier, min_x, min_y, max_x, max_y, srs_id)
+        VALUES (?1, 'features', ?1, ?2, ?3, ?4, ?5, ?6)",
+        params![table, min_x, min_y, max_x, max_y, srid],
+    )?;
+    connection.execute(
+        "INSERT INTO gpkg_geometry_columns VALUES (?1, 'geom', ?2, ?3, ?4, 0)",
+        params![table, geometry_type, srid, z as i32],
+    )?;
+    Ok(())
+}
+
+// The GeoPackage binary geometry of the given rings: its header, followed by the WKB
+// written by `wkb` (with z coordinates or not).
+fn geometry<F: FnOnce(&mut Vec<u8>, bool)>(srid: i32, rings: &[&LinearRing], wkb: F) -> Vec<u8> {
+    let mut blob = vec![b'G', b'P', 0];
+    let z = has_z(rings.iter().copied());
+    // Little-endian, with an envelope of [min x, max x, min y, max y], or empty.
+    match bounds(rings.iter().flat_map(|ring| ring.iter())) {
+        Some([min_x, min_y, max_x, max_y]) => {
+            blob.push(0b0000_0011);
+            blob.extend_from_slice(&srid.to_le_bytes());
+            for v in [min_x, max_x, min_y, max_y].iter() {
+                blob.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        None => {
+            blob.push(0b0001_0001);
+            blob.extend_from_slice(&srid.to_le_bytes());
+        }
+    }
+    wkb(&mut blob, z);
+    blob
+}
+
+fn bounds<'a, I: Iterator<Item = &'a Vec<f64>>>(points: I) -> Option<[f64; 4]> {
+    points.fold(None, |bounds, point| {
+        let [min_x, min_y, max_x, max_y] = bounds.unwrap_or([point[0], point[1], point[0], point[1]]);
+        Some([min_x.min(point[0]), min_y.min(point[1]), max_x.max(point[0]), max_y.max(point[1])])
+    })
+}
+
+fn feature_polygons(feature: &Feature) -> Vec<Vec<LinearRing>> {
+    match feature.geometry.as_ref().map(|geometry| &geometry.value) {
+        Some(Value::Polygon(polygon)) => vec![polygon.clone()],
+        Some(Value::MultiPolygon(polygons)) => polygons.clone(),
+        _ => Vec::new(),
+    }
+}
+
+fn feature_value(feature: &Feature) -> Option<f64> {
+    feature.properties.as_ref()?.get("value")?.as_f64()
+}
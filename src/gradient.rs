@@ -0,0 +1,86 @@
+//! Gradient of the grid: slope and aspect grids, of the same dimensions as the grid
+//! of values, to be contoured with [`ContourBuilder::contours`] like any other grid
+//! (e.g. to map the steep areas of a terrain).
+//!
+//! The derivatives are estimated with central differences, and one-sided differences
+//! on the border of the grid: the gradient at a value is NaN when this value or one of
+//! the neighbours used by the differences (its 4 neighbours, inside the grid) is NaN.
+//!
+//! [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+
+use crate::contour::check_dimensions;
+use crate::error::Result;
+
+/// Computes the magnitude of the gradient of the grid of `dx` columns and `dy` rows
+/// (the rise over run of the steepest slope), given the width and height of its cells.
+/// Returns an [`ErrorKind::BadDimension`] error if the length of `values` doesn't match
+/// the dimensions of the grid.
+///
+/// ```
+/// # use contour::ContourBuilder;
+/// # use contour::gradient::gradient_magnitude;
+/// let values = (0..100).map(|i| ((i % 10) as f64).powi(2)).collect::<Vec<_>>();
+/// let slopes = gradient_magnitude(&values, 10, 10, 1., 1.)?;
+/// assert_eq!(slopes[15], 10.);
+/// let steep = ContourBuilder::new(10, 10, true).contours(&slopes, &[10.])?;
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`ErrorKind::BadDimension`]: ../enum.ErrorKind.html#variant.BadDimension
+pub fn gradient_magnitude(values: &[f64], dx: u32, dy: u32, cell_size_x: f64, cell_size_y: f64) -> Result<Vec<f64>> {
+    gradient(values, dx, dy, cell_size_x, cell_size_y, f64::hypot)
+}
+
+/// Computes the aspect of the grid of `dx` columns and `dy` rows (the direction the
+/// slopes face, downhill), given the width and height of its cells, like
+/// [`gradient_magnitude`] does: in degrees from `0` to `360`, clockwise from the north,
+/// the first row of the grid being the northernmost one. The aspect of flat areas is NaN.
+///
+/// ```
+/// # use contour::gradient::aspect;
+/// // The values increase eastwards, the slopes face the west.
+/// assert_eq!(aspect(&[0., 1., 2., 0., 1., 2.], 3, 2, 1., 1.)?, vec![270.; 6]);
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`gradient_magnitude`]: fn.gradient_magnitude.html
+pub fn aspect(values: &[f64], dx: u32, dy: u32, cell_size_x: f64, cell_size_y: f64) -> Result<Vec<f64>> {
+    gradient(values, dx, dy, cell_size_x, cell_size_y, |east, south| {
+        if east == 0. && south == 0. {
+            f64::NAN
+        } else {
+            // The downhill direction is (-east, -north), i.e. (-east, south).
+            (-east).atan2(south).to_degrees().rem_euclid(360.)
+        }
+    })
+}
+
+// Maps the derivatives of the values along the x axis (eastwards) and along the y axis
+// (southwards, following the rows) with `f`.
+fn gradient<F: Fn(f64, f64) -> f64>(values: &[f64], dx: u32, dy: u32, cell_size_x: f64, cell_size_y: f64, f: F) -> Result<Vec<f64>> {
+    check_dimensions(dx, dy, values.len())?;
+    let (dx, dy) = (dx as usize, dy as usize);
+    let mut result = Vec::with_capacity(values.len());
+    for y in 0..dy {
+        for x in 0..dx {
+            if values[y * dx + x].is_nan() {
+                result.push(f64::NAN);
+                continue;
+            }
+            let along_x = derivative(|i| values[y * dx + i], x, dx, cell_size_x);
+            let along_y = derivative(|i| values[i * dx + x], y, dy, cell_size_y);
+            result.push(f(along_x, along_y));
+        }
+    }
+    Ok(result)
+}
+
+// The derivative at `i` of the `n` values given by `value`, `size` apart: with central
+// differences, or one-sided ones at the ends (`0` when there is a single value).
+fn derivative<V: Fn(usize) -> f64>(value: V, i: usize, n: usize, size: f64) -> f64 {
+    if n < 2 {
+        return 0.;
+    }
+    let (low, high) = (i.saturating_sub(1), (i + 1).min(n - 1));
+    (value(high) - value(low)) / ((high - low) as f64 * size)
+}
@@ -0,0 +1,219 @@
+//! Contours of values on hexagonal grids, without resampling them to a square grid.
+//!
+//! The centers of the hexagons form a triangular lattice: each triangle joins the centers
+//! of three hexagons sharing a corner. The triangles are marched like the cells of the
+//! marching squares (each triangle having at most one segment, between two of its edges),
+//! and the segments are stitched into rings by their endpoints.
+//!
+//! The hexagon of axial coordinates `(q, r)` and `size` (the distance from its center
+//! to its corners) is centered on `(size * √3 * (q + r / 2), size * 3 / 2 * r)` with the
+//! [`HexOrientation::PointyTop`] orientation, on `(size * 3 / 2 * q, size * √3 * (r + q / 2))`
+//! with the [`HexOrientation::FlatTop`] one, the y axis going along the rows of the grid like
+//! with the square grids. The hexagons outside of the grid are below every threshold.
+//!
+//! [`HexOrientation::PointyTop`]: enum.HexOrientation.html#variant.PointyTop
+//! [`HexOrientation::FlatTop`]: enum.HexOrientation.html#variant.FlatTop
+
+use crate::contour::{LinearRing, Pt, check_dimensions, group_holes};
+use crate::error::Result;
+use crate::ring::Ring;
+use geojson::{Feature, Geometry, Value};
+use serde_json::map::Map;
+use std::collections::HashMap;
+
+/// The orientation of the hexagons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexOrientation {
+    /// Hexagons with a corner at the top, their rows being horizontal.
+    PointyTop,
+    /// Hexagons with a side at the top, their columns being vertical.
+    FlatTop,
+}
+
+/// How the values of the grid are laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexCoordinates {
+    /// Offset coordinates: the rows (or columns, with [`HexOrientation::FlatTop`]) of the grid
+    /// are zig-zagging, the odd ones being shifted by half a hexagon ("odd-r" and "odd-q" layouts).
+    ///
+    /// [`HexOrientation::FlatTop`]: enum.HexOrientation.html#variant.FlatTop
+    Offset,
+    /// Axial coordinates: the value at column `q` and row `r` is the one of the hexagon `(q, r)`,
+    /// the grid covering a rhombus of hexagons.
+    Axial,
+}
+
+/// A grid of `dx` columns and `dy` rows of hexagons.
+///
+/// ```
+/// # use contour::hex::{HexCoordinates, HexGrid, HexOrientation};
+/// let grid = HexGrid::new(3, 3, HexOrientation::PointyTop, HexCoordinates::Offset, 1.);
+/// let rings = grid.contour_rings(&[
+///     0., 0., 0.,
+///     0., 1., 0.,
+///     0., 0., 0.,
+/// ], 0.5, true)?;
+/// assert_eq!(rings.len(), 1);
+/// // One position on each side of the hexagon, and the closing one.
+/// assert_eq!(rings[0].len(), 7);
+/// # Ok::<(), contour::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HexGrid {
+    dx: u32,
+    dy: u32,
+    orientation: HexOrientation,
+    coordinates: HexCoordinates,
+    size: f64,
+}
+
+impl HexGrid {
+    /// Constructs a new grid of `dx` columns and `dy` rows of hexagons of the given `size`
+    /// (the distance from their center to their corners).
+    pub fn new(dx: u32, dy: u32, orientation: HexOrientation, coordinates: HexCoordinates, size: f64) -> Self {
+        HexGrid {
+            dx,
+            dy,
+            orientation,
+            coordinates,
+            size,
+        }
+    }
+
+    /// Returns the center of the hexagon at the given column and row of the grid.
+    pub fn center(&self, column: u32, row: u32) -> [f64; 2] {
+        let (q, r) = self.axial(column as i64, row as i64);
+        self.position(q as f64, r as f64)
+    }
+
+    /// Computes the rings of the contour of the given threshold, the exterior rings
+    /// being clockwise and the holes counter-clockwise (the y axis pointing up).
+    ///
+    /// With `smooth`, the positions are linearly interpolated between the centers
+    /// of the hexagons, otherwise they are halfway between them.
+    pub fn contour_rings(&self, values: &[f64], threshold: f64, smooth: bool) -> Result<Vec<Ring>> {
+        check_dimensions(self.dx, self.dy, values.len())?;
+        Ok(self.rings(values, threshold, smooth).into_iter().map(Ring::from).collect())
+    }
+
+    /// Computes a contour (a MultiPolygon Feature with its threshold as `value` property)
+    /// for each of the given thresholds.
+    pub fn contours(&self, values: &[f64], thresholds: &[f64], smooth: bool) -> Result<Vec<Feature>> {
+        check_dimensions(self.dx, self.dy, values.len())?;
+        Ok(thresholds
+            .iter()
+            .map(|&threshold| {
+                let polygons = group_holes(self.rings(values, threshold, smooth));
+                let mut properties = Map::with_capacity(1);
+                properties.insert(String::from("value"), serde_json::Value::from(threshold));
+                Feature {
+                    bbox: None,
+                    geometry: Some(Geometry::new(Value::MultiPolygon(polygons))),
+                    properties: Some(properties),
+                    id: None,
+                    foreign_members: None,
+                }
+            })
+            .collect())
+    }
+
+    // The axial coordinates of the hexagon at the given column and row.
+    fn axial(&self, column: i64, row: i64) -> (i64, i64) {
+        match (self.coordinates, self.orientation) {
+            (HexCoordinates::Axial, _) => (column, row),
+            (HexCoordinates::Offset, HexOrientation::PointyTop) => (column - (row - (row & 1)) / 2, row),
+            (HexCoordinates::Offset, HexOrientation::FlatTop) => (column, row - (column - (column & 1)) / 2),
+        }
+    }
+
+    // The Cartesian position of the given (possibly fractional) axial coordinates.
+    fn position(&self, q: f64, r: f64) -> [f64; 2] {
+        let sqrt3 = 3f64.sqrt();
+        match self.orientation {
+            HexOrientation::PointyTop => [self.size * sqrt3 * (q + r / 2.), self.size * 1.5 * r],
+            HexOrientation::FlatTop => [self.size * 1.5 * q, self.size * sqrt3 * (r + q / 2.)],
+        }
+    }
+
+    fn rings(&self, values: &[f64], threshold: f64, smooth: bool) -> Vec<LinearRing> {
+        let (dx, dy) = (self.dx as i64, self.dy as i64);
+        if dx == 0 || dy == 0 {
+            return Vec::new();
+        }
+        // The values in a rhombus of axial coordinates covering the grid, with a margin
+        // of one hexagon, the hexagons outside of the grid being NaN.
+        let corners = [self.axial(0, 0), self.axial(dx - 1, 0), self.axial(0, dy - 1), self.axial(dx - 1, dy - 1)];
+        let (q_min, q_max) = (corners.iter().map(|c| c.0).min().unwrap() - 1, corners.iter().map(|c| c.0).max().unwrap() + 1);
+        let (r_min, r_max) = (corners.iter().map(|c| c.1).min().unwrap() - 1, corners.iter().map(|c| c.1).max().unwrap() + 1);
+        let width = (q_max - q_min + 1) as usize;
+        let mut lattice = vec![f64::NAN; width * (r_max - r_min + 1) as usize];
+        for row in 0..dy {
+            for column in 0..dx {
+                let (q, r) = self.axial(column, row);
+                lattice[(r - r_min) as usize * width + (q - q_min) as usize] = values[(row * dx + column) as usize];
+            }
+        }
+        let index = |q: i64, r: i64| (r - r_min) as usize * width + (q - q_min) as usize;
+        let axial = |i: usize| ((i % width) as i64 + q_min, (i / width) as i64 + r_min);
+        let above = |i: usize| lattice[i] >= threshold;
+
+        // The segments, from the edge where the triangle goes from below to above the threshold
+        // (counter-clockwise) to the one where it goes back below, an edge being designated by
+        // the (sorted) indices of its ends.
+        let mut segments: Vec<((usize, usize), (usize, usize))> = Vec::new();
+        let mut points: HashMap<(usize, usize), Pt> = HashMap::new();
+        for r in r_min..r_max {
+            for q in q_min..q_max {
+                // The two triangles between the hexagon and its neighbors, counter-clockwise.
+                let triangles = [[(q, r), (q + 1, r), (q, r + 1)], [(q + 1, r), (q + 1, r + 1), (q, r + 1)]];
+                for triangle in triangles.iter() {
+                    let vertices = [index(triangle[0].0, triangle[0].1), index(triangle[1].0, triangle[1].1), index(triangle[2].0, triangle[2].1)];
+                    let (mut start, mut end) = (None, None);
+                    for k in 0..3 {
+                        let (a, b) = (vertices[k], vertices[(k + 1) % 3]);
+                        let key = (a.min(b), a.max(b));
+                        match (above(a), above(b)) {
+                            (false, true) => start = Some(key),
+                            (true, false) => end = Some(key),
+                            _ => continue,
+                        }
+                        points.entry(key).or_insert_with(|| {
+                            let (below, upper) = if above(a) { (b, a) } else { (a, b) };
+                            let (v0, v1) = (lattice[below], lattice[upper]);
+                            let t = if smooth && v0.is_finite() && v1.is_finite() { (threshold - v0) / (v1 - v0) } else { 0.5 };
+                            let (p0, p1) = (axial(below), axial(upper));
+                            let p = self.position(p0.0 as f64 + t * (p1.0 - p0.0) as f64, p0.1 as f64 + t * (p1.1 - p0.1) as f64);
+                            vec![p[0], p[1]]
+                        });
+                    }
+                    if let (Some(start), Some(end)) = (start, end) {
+                        segments.push((start, end));
+                    }
+                }
+            }
+        }
+
+        // Each edge crossing the threshold starts exactly one segment and ends another one.
+        let next: HashMap<(usize, usize), usize> = segments.iter().enumerate().map(|(i, segment)| (segment.0, i)).collect();
+        let mut stitched = vec![false; segments.len()];
+        let mut rings = Vec::new();
+        for first in 0..segments.len() {
+            if stitched[first] {
+                continue;
+            }
+            let mut ring = Vec::new();
+            let mut i = first;
+            while !stitched[i] {
+                stitched[i] = true;
+                ring.push(points[&segments[i].0].clone());
+                match next.get(&segments[i].1) {
+                    Some(&j) => i = j,
+                    None => break,
+                }
+            }
+            ring.push(ring[0].clone());
+            rings.push(ring);
+        }
+        rings
+    }
+}
@@ -0,0 +1,85 @@
+//! Binning of scattered points into a grid of counts (a 2-D histogram), to be contoured
+//! like any other grid (see [`ContourBuilder::contours_binned`], giving the contours
+//! in the coordinates of the points).
+//!
+//! The grid covers the [`Extent`] of the points with `dx` columns and `dy` rows of equal
+//! cells, the first row being along `y_min`: the point `(x, y)` falls in the column
+//! `floor((x - x_min) / (x_max - x_min) * dx)` and likewise for its row. Each cell is
+//! half-open, but for the last column and the last row, which also hold the points exactly
+//! on `x_max` and `y_max`.
+//!
+//! [`ContourBuilder::contours_binned`]: ../struct.ContourBuilder.html#method.contours_binned
+//! [`Extent`]: struct.Extent.html
+
+use crate::contour::check_dimensions;
+use crate::error::{ErrorKind, Result, new_error};
+
+/// The rectangle covered by the grid of the binned points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Extent {
+    pub x_min: f64,
+    pub y_min: f64,
+    pub x_max: f64,
+    pub y_max: f64,
+}
+
+impl Extent {
+    /// Returns whether the bounds are finite, the max ones being greater than the min ones.
+    pub fn is_valid(&self) -> bool {
+        [self.x_min, self.y_min, self.x_max, self.y_max].iter().all(|v| v.is_finite()) && self.x_min < self.x_max && self.y_min < self.y_max
+    }
+}
+
+/// Bins the `points` into a grid of `dx` columns and `dy` rows covering the `extent`,
+/// returning the number of points in each cell, or the sum of their `weights` (the weight
+/// of each point being at the same index as the point). The points outside of the extent
+/// and the NaN ones are ignored.
+///
+/// Returns an [`ErrorKind::InvalidExtent`] error if the extent isn't valid (see
+/// [`Extent::is_valid`]), or an [`ErrorKind::BadDimension`] error if there isn't one
+/// weight per point.
+///
+/// ```
+/// # use contour::histogram::{Extent, bin_points};
+/// let extent = Extent { x_min: 0., y_min: 0., x_max: 2., y_max: 1. };
+/// let points = vec![(0.5, 0.5), (1.5, 0.2), (2., 1.), (3., 0.)];
+/// assert_eq!(bin_points(points.iter().copied(), extent, 2, 1, None)?, [1., 2.]);
+/// assert_eq!(bin_points(points.into_iter(), extent, 2, 1, Some(&[1., 2., 3., 4.]))?, [1., 5.]);
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`ErrorKind::InvalidExtent`]: ../enum.ErrorKind.html#variant.InvalidExtent
+/// [`ErrorKind::BadDimension`]: ../enum.ErrorKind.html#variant.BadDimension
+/// [`Extent::is_valid`]: struct.Extent.html#method.is_valid
+pub fn bin_points(points: impl Iterator<Item = (f64, f64)>, extent: Extent, dx: u32, dy: u32, weights: Option<&[f64]>) -> Result<Vec<f64>> {
+    if !extent.is_valid() {
+        return Err(new_error(ErrorKind::InvalidExtent(extent)));
+    }
+    check_dimensions(dx, dy, dx as usize * dy as usize)?;
+    let mut counts = vec![0.; dx as usize * dy as usize];
+    let mut len = 0;
+    for (i, (x, y)) in points.enumerate() {
+        len = i + 1;
+        let weight = match weights {
+            Some(weights) => *weights.get(i).ok_or(new_error(ErrorKind::BadDimension))?,
+            None => 1.,
+        };
+        if let (Some(column), Some(row)) = (bin(x, extent.x_min, extent.x_max, dx), bin(y, extent.y_min, extent.y_max, dy)) {
+            counts[row * dx as usize + column] += weight;
+        }
+    }
+    match weights {
+        Some(weights) if weights.len() != len => Err(new_error(ErrorKind::BadDimension)),
+        _ => Ok(counts),
+    }
+}
+
+// The index of the bin of `v` among the `n` bins of `[min, max]`, if any.
+fn bin(v: f64, min: f64, max: f64, n: u32) -> Option<usize> {
+    if !(min..=max).contains(&v) || n == 0 {
+        return None;
+    }
+    // The values on `max` (or rounded up to `n`) go to the last bin.
+    let i = ((v - min) / (max - min) * f64::from(n)).floor() as usize;
+    Some(i.min(n as usize - 1))
+}
@@ -0,0 +1,304 @@
+//! Spatial index over the polygons of contours, for bounding box
+//! and point queries, and point-in-contour test.
+//!
+//! The index is a static R-tree packed with the
+//! [Sort-Tile-Recursive](https://apps.dtic.mil/sti/pdfs/ADA324493.pdf) algorithm:
+//! it is built once from the contours and can't be updated. With the `rstar` feature,
+//! the index is a bulk loaded `rstar::RTree` of the [`IndexEntry`]s instead (which
+//! implement `RTreeObject`, their envelope being their bounding box).
+//!
+//! [`IndexEntry`]: struct.IndexEntry.html
+
+use crate::geom::ring_contains;
+use crate::contour::LinearRing;
+use geojson::{Feature, Value};
+#[cfg(feature = "rstar")]
+use rstar::{AABB, RTree, RTreeObject};
+#[cfg(not(feature = "rstar"))]
+use std::ops::Range;
+
+#[cfg(not(feature = "rstar"))]
+const NODE_CAPACITY: usize = 16;
+
+/// A polygon of the index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexEntry {
+    /// The index of the Feature (i.e. of the threshold) the polygon belongs to.
+    pub feature: usize,
+    /// The index of the polygon within its Feature.
+    pub polygon: usize,
+    /// The bounding box of the polygon (`[min x, min y, max x, max y]`).
+    pub bbox: [f64; 4],
+}
+
+/// The envelope of an entry is its bounding box. Only available with the `rstar` feature.
+#[cfg(feature = "rstar")]
+impl RTreeObject for IndexEntry {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_corners([self.bbox[0], self.bbox[1]], [self.bbox[2], self.bbox[3]])
+    }
+}
+
+/// The band of values a point falls into (see [`ContourIndex::band`]).
+///
+/// [`ContourIndex::band`]: struct.ContourIndex.html#method.band
+#[derive(Clone, Debug, PartialEq)]
+pub struct Band {
+    /// The index of the Feature of the lower threshold of the band.
+    pub feature: usize,
+    /// The lower threshold of the band.
+    pub lower: f64,
+    /// The next higher threshold, if any.
+    pub upper: Option<f64>,
+}
+
+#[cfg(not(feature = "rstar"))]
+#[derive(Clone, Debug)]
+struct Node {
+    bbox: [f64; 4],
+    // The indices of the children in the level below (or in `entries` for the leaves).
+    children: Range<usize>,
+}
+
+/// A spatial index of the polygons of a `Vec` of Features
+/// (as returned by [`ContourBuilder::contours`]).
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+#[derive(Clone, Debug)]
+pub struct ContourIndex {
+    // The entries (ordered by Feature then by polygon with the `rstar` feature), and their polygons.
+    entries: Vec<IndexEntry>,
+    polygons: Vec<Vec<LinearRing>>,
+    // The thresholds (the `value` property of the Features) in ascending order,
+    // with the index of their Feature.
+    thresholds: Vec<(f64, usize)>,
+    // The levels of the tree, from the leaves to the root.
+    #[cfg(not(feature = "rstar"))]
+    levels: Vec<Vec<Node>>,
+    #[cfg(feature = "rstar")]
+    tree: RTree<IndexEntry>,
+}
+
+impl ContourIndex {
+    /// Builds the index of the polygons of the given `features`
+    /// (Features whose geometry isn't a Polygon or a MultiPolygon are ignored).
+    pub fn new(features: &[Feature]) -> Self {
+        let mut items = Vec::new();
+        for (f, feature) in features.iter().enumerate() {
+            let polygons = match feature.geometry.as_ref().map(|g| &g.value) {
+                Some(Value::Polygon(polygon)) => vec![polygon],
+                Some(Value::MultiPolygon(polygons)) => polygons.iter().collect(),
+                _ => Vec::new(),
+            };
+            for (p, polygon) in polygons.into_iter().enumerate() {
+                if let Some(bbox) = polygon.first().and_then(|shell| bbox(shell)) {
+                    let entry = IndexEntry { feature: f, polygon: p, bbox };
+                    items.push((entry, polygon.clone()));
+                }
+            }
+        }
+
+        let mut thresholds: Vec<(f64, usize)> = features
+            .iter()
+            .enumerate()
+            .filter_map(|(f, feature)| {
+                let value = feature.properties.as_ref()?.get("value")?.as_f64()?;
+                Some((value, f))
+            })
+            .collect();
+        thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // The items are ordered by Feature then by polygon.
+        #[cfg(feature = "rstar")]
+        let (entries, polygons): (Vec<_>, _) = items.into_iter().unzip();
+        #[cfg(feature = "rstar")]
+        let tree = RTree::bulk_load(entries.clone());
+        #[cfg(not(feature = "rstar"))]
+        let (entries, polygons, levels) = packed_tree(items);
+        ContourIndex {
+            entries,
+            polygons,
+            thresholds,
+            #[cfg(not(feature = "rstar"))]
+            levels,
+            #[cfg(feature = "rstar")]
+            tree,
+        }
+    }
+
+    /// The number of polygons of the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the index has no polygon.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the polygons whose bounding box intersects the given `bbox`
+    /// (`[min x, min y, max x, max y]`), ordered by Feature then by polygon.
+    pub fn locate_in_envelope(&self, bbox: [f64; 4]) -> Vec<&IndexEntry> {
+        self.search(bbox).into_iter().map(|(entry, _)| entry).collect()
+    }
+
+    /// Returns the polygons containing the point (`x`, `y`), ordered by Feature
+    /// then by polygon. Points on the boundary of a polygon (on its exterior ring
+    /// or on the ring of one of its holes) are contained by it.
+    pub fn containing_point(&self, x: f64, y: f64) -> Vec<&IndexEntry> {
+        let point = [x, y];
+        self.search([x, y, x, y])
+            .into_iter()
+            .filter(|(_, polygon)| polygon_contains(polygon, &point))
+            .map(|(entry, _)| entry)
+            .collect()
+    }
+
+    /// Returns the band of values the point (`x`, `y`) falls into: the highest threshold
+    /// (the `value` property of the Features) whose contour contains the point
+    /// (see [`containing_point`]) and the next higher threshold.
+    /// Returns `None` when no contour contains the point.
+    ///
+    /// [`containing_point`]: #method.containing_point
+    pub fn band(&self, x: f64, y: f64) -> Option<Band> {
+        let containing = self.containing_point(x, y);
+        let position = self
+            .thresholds
+            .iter()
+            .rposition(|(_, f)| containing.iter().any(|entry| entry.feature == *f))?;
+        let (lower, feature) = self.thresholds[position];
+        let upper = self.thresholds.get(position + 1).map(|(value, _)| *value);
+        Some(Band { feature, lower, upper })
+    }
+
+    // The entries whose bounding box intersects `bbox`, with their polygon,
+    // ordered by Feature then by polygon.
+    #[cfg(feature = "rstar")]
+    fn search(&self, bbox: [f64; 4]) -> Vec<(&IndexEntry, &[LinearRing])> {
+        let envelope = AABB::from_corners([bbox[0], bbox[1]], [bbox[2], bbox[3]]);
+        let mut found = self
+            .tree
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|entry| self.entries.binary_search_by_key(&(entry.feature, entry.polygon), |e| (e.feature, e.polygon)).unwrap())
+            .collect::<Vec<_>>();
+        found.sort_unstable();
+        found.into_iter().map(|i| (&self.entries[i], &self.polygons[i][..])).collect()
+    }
+
+    #[cfg(not(feature = "rstar"))]
+    fn search(&self, bbox: [f64; 4]) -> Vec<(&IndexEntry, &[LinearRing])> {
+        let mut found = Vec::new();
+        if let Some(root) = self.levels.last() {
+            self.search_level(self.levels.len() - 1, 0..root.len(), &bbox, &mut found);
+        }
+        found.sort_by_key(|i| (self.entries[*i].feature, self.entries[*i].polygon));
+        found.into_iter().map(|i| (&self.entries[i], &self.polygons[i][..])).collect()
+    }
+
+    #[cfg(not(feature = "rstar"))]
+    fn search_level(&self, level: usize, nodes: Range<usize>, bbox: &[f64; 4], found: &mut Vec<usize>) {
+        for node in &self.levels[level][nodes] {
+            if !intersects(&node.bbox, bbox) {
+                continue;
+            }
+            if level == 0 {
+                found.extend(node.children.clone().filter(|i| intersects(&self.entries[*i].bbox, bbox)));
+            } else {
+                self.search_level(level - 1, node.children.clone(), bbox, found);
+            }
+        }
+    }
+}
+
+/// Returns `true` if the point (`x`, `y`) is inside the geometry of the given `feature`
+/// (as returned by [`ContourBuilder::contours`]): inside the exterior ring of one of its
+/// polygons but not inside one of the holes of this polygon.
+///
+/// Points on the boundary of a polygon (on its exterior ring or on the ring of one of
+/// its holes) are inside it, as they are on the isoline of the threshold.
+/// Features whose geometry isn't a Polygon or a MultiPolygon don't contain any point.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn contains_point(feature: &Feature, x: f64, y: f64) -> bool {
+    let point = [x, y];
+    match feature.geometry.as_ref().map(|g| &g.value) {
+        Some(Value::Polygon(polygon)) => polygon_contains(polygon, &point),
+        Some(Value::MultiPolygon(polygons)) => polygons.iter().any(|polygon| polygon_contains(polygon, &point)),
+        _ => false,
+    }
+}
+
+fn polygon_contains(polygon: &[LinearRing], point: &[f64]) -> bool {
+    match polygon.split_first() {
+        Some((shell, holes)) if !shell.is_empty() => {
+            ring_contains(shell, point) != -1 && holes.iter().all(|hole| hole.is_empty() || ring_contains(hole, point) != 1)
+        }
+        _ => false,
+    }
+}
+
+// Packs the entries (with their polygon) in the levels of a tree, from the leaves to the root,
+// returning them in the order of the leaves.
+#[cfg(not(feature = "rstar"))]
+fn packed_tree(items: Vec<(IndexEntry, Vec<LinearRing>)>) -> (Vec<IndexEntry>, Vec<Vec<LinearRing>>, Vec<Vec<Node>>) {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    let leaves = pack(&mut order, |i| items[i].0.bbox);
+    let mut slots: Vec<_> = items.into_iter().map(Some).collect();
+    let (entries, polygons) = order.into_iter().map(|i| slots[i].take().unwrap()).unzip();
+
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let below = levels.last().unwrap();
+        let mut order: Vec<usize> = (0..below.len()).collect();
+        let level = pack(&mut order, |i| below[i].bbox);
+        // The children of the new nodes are in `order`, which isn't the order of the level below:
+        // reorder the level below accordingly.
+        let reordered = order.iter().map(|i| below[*i].clone()).collect();
+        *levels.last_mut().unwrap() = reordered;
+        levels.push(level);
+    }
+    (entries, polygons, levels)
+}
+
+#[cfg(not(feature = "rstar"))]
+// Sorts `order` (the indices of the items to pack) following the Sort-Tile-Recursive algorithm
+// and returns the nodes grouping consecutive items of `order`.
+fn pack<B: Fn(usize) -> [f64; 4]>(order: &mut [usize], bbox: B) -> Vec<Node> {
+    let center = |i: usize, axis: usize| {
+        let b = bbox(i);
+        (b[axis] + b[axis + 2]) / 2.
+    };
+    let n_nodes = order.len().div_ceil(NODE_CAPACITY);
+    let n_slices = (n_nodes as f64).sqrt().ceil().max(1.) as usize;
+    let slice_size = n_slices * NODE_CAPACITY;
+    order.sort_by(|a, b| center(*a, 0).partial_cmp(&center(*b, 0)).unwrap());
+    for slice in order.chunks_mut(slice_size) {
+        slice.sort_by(|a, b| center(*a, 1).partial_cmp(&center(*b, 1)).unwrap());
+    }
+
+    let mut nodes = Vec::with_capacity(n_nodes);
+    let mut start = 0;
+    for slice in order.chunks(slice_size) {
+        for group in slice.chunks(NODE_CAPACITY) {
+            let node_bbox = group.iter().map(|i| bbox(*i)).fold([f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY], union);
+            nodes.push(Node { bbox: node_bbox, children: start..start + group.len() });
+            start += group.len();
+        }
+    }
+    nodes
+}
+
+fn bbox(ring: &[Vec<f64>]) -> Option<[f64; 4]> {
+    ring.iter().map(|p| [p[0], p[1], p[0], p[1]]).reduce(union)
+}
+
+fn union(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])]
+}
+
+#[cfg(not(feature = "rstar"))]
+fn intersects(a: &[f64; 4], b: &[f64; 4]) -> bool {
+    a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3]
+}
@@ -0,0 +1,107 @@
+//! Instrumentation of the computation of the contours with `tracing` spans, telling
+//! which threshold and which phase takes time (only available with the `tracing` feature).
+//!
+//! The spans of one computation are nested: a `contours` span (at the `DEBUG` level, with
+//! the `dx` and `dy` dimensions of the grid) contains one `threshold` span (at the `DEBUG`
+//! level) per threshold, which contains the spans of the phases of its computation (at the
+//! `TRACE` level, named after their [`SpanKind`]). The spans within a `threshold` span have
+//! a `threshold` field, and the `threshold`, `stitch` and `group_holes` spans record the
+//! number of rings (or polygons, for the latter) computed in their `rings` field. Any
+//! `tracing` subscriber can collect them, e.g. to time the phases.
+//!
+//! Without the feature, the instrumentation compiles to nothing.
+//!
+//! [`SpanKind`]: enum.SpanKind.html
+
+/// The part of the computation covered by a span.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpanKind {
+    /// The computation of the contours of all the thresholds (the `contours` span).
+    Contours,
+    /// The computation of the contours of one threshold (the `threshold` span).
+    Threshold,
+    /// The classification of the cells of the grid (marching squares cases, the `classify` span).
+    Classify,
+    /// The stitching of the segments of the cells into rings (the `stitch` span).
+    Stitch,
+    /// The smoothing of the rings (the `smooth` span).
+    Smooth,
+    /// The grouping of the rings into polygons with their holes (the `group_holes` span).
+    GroupHoles,
+    /// The conversion to output coordinates (origin, step, reprojection, clipping...,
+    /// the `georeference` span).
+    Georeference,
+    /// The creation of the Feature (properties and geometry, the `feature` span).
+    Feature,
+}
+
+impl SpanKind {
+    /// The name of the spans of this kind.
+    pub fn name(self) -> &'static str {
+        match self {
+            SpanKind::Contours => "contours",
+            SpanKind::Threshold => "threshold",
+            SpanKind::Classify => "classify",
+            SpanKind::Stitch => "stitch",
+            SpanKind::Smooth => "smooth",
+            SpanKind::GroupHoles => "group_holes",
+            SpanKind::Georeference => "georeference",
+            SpanKind::Feature => "feature",
+        }
+    }
+}
+
+// Enters the `contours` span of the computation on a grid of `dx` by `dy` values,
+// exited when the returned guard is dropped.
+#[cfg(feature = "tracing")]
+pub(crate) fn enter_contours(dx: u32, dy: u32) -> Guard {
+    Guard(tracing::debug_span!("contours", dx, dy).entered())
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn enter_contours(_dx: u32, _dy: u32) -> Guard {
+    Guard
+}
+
+// Enters a span within the `contours` one, exited when the returned guard is dropped.
+#[cfg(feature = "tracing")]
+pub(crate) fn enter(kind: SpanKind, threshold: Option<f64>) -> Guard {
+    use tracing::field::Empty;
+    let span = match kind {
+        SpanKind::Contours => tracing::debug_span!("contours", dx = Empty, dy = Empty),
+        SpanKind::Threshold => tracing::debug_span!("threshold", threshold, rings = Empty),
+        SpanKind::Classify => tracing::trace_span!("classify", threshold),
+        SpanKind::Stitch => tracing::trace_span!("stitch", threshold, rings = Empty),
+        SpanKind::Smooth => tracing::trace_span!("smooth", threshold),
+        SpanKind::GroupHoles => tracing::trace_span!("group_holes", threshold, rings = Empty),
+        SpanKind::Georeference => tracing::trace_span!("georeference", threshold),
+        SpanKind::Feature => tracing::trace_span!("feature", threshold),
+    };
+    Guard(span.entered())
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn enter(_kind: SpanKind, _threshold: Option<f64>) -> Guard {
+    Guard
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) struct Guard(tracing::span::EnteredSpan);
+
+#[cfg(feature = "tracing")]
+impl Guard {
+    pub fn rings(&mut self, rings: usize) {
+        self.0.record("rings", rings);
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Guard;
+
+#[cfg(not(feature = "tracing"))]
+impl Guard {
+    #[inline(always)]
+    pub fn rings(&mut self, _rings: usize) {}
+}
@@ -0,0 +1,112 @@
+//! Loaders turning common grid formats into values ready for [`ContourBuilder`].
+//!
+//! [`ContourBuilder`]: ../struct.ContourBuilder.html
+
+use crate::error::{ErrorKind, Result, new_error};
+use std::io::BufRead;
+
+/// Options of [`read_csv_grid_with`].
+///
+/// [`read_csv_grid_with`]: fn.read_csv_grid_with.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvOptions {
+    /// The character separating the cells of a row (default: `,`).
+    pub delimiter: char,
+    /// The cell contents (after trimming whitespace) read as nodata,
+    /// and mapped to `NaN` (default: the empty cell and `NA`).
+    pub nodata: Vec<String>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            nodata: vec![String::new(), String::from("NA")],
+        }
+    }
+}
+
+/// Reads a grid stored as a CSV matrix (one row of the grid per line) using the
+/// default [`CsvOptions`].
+/// Returns the values (in row-major order), the number of columns and the number of rows.
+///
+/// Blank lines are skipped and nodata cells (empty or `NA`) are read as `NaN`,
+/// which is never considered to be above a threshold.
+/// Row and column numbers reported in errors start at 1.
+///
+/// [`CsvOptions`]: struct.CsvOptions.html
+pub fn read_csv_grid<R: BufRead>(reader: R) -> Result<(Vec<f64>, usize, usize)> {
+    read_csv_grid_with(reader, &CsvOptions::default())
+}
+
+/// Reads a grid stored as a CSV matrix like [`read_csv_grid`], using the given `options`.
+///
+/// [`read_csv_grid`]: fn.read_csv_grid.html
+pub fn read_csv_grid_with<R: BufRead>(reader: R, options: &CsvOptions) -> Result<(Vec<f64>, usize, usize)> {
+    let mut values = Vec::new();
+    let mut width = None;
+    let mut height = 0;
+    for (row, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut len = 0;
+        for (column, cell) in line.split(options.delimiter).enumerate() {
+            let cell = cell.trim();
+            if options.nodata.iter().any(|nodata| nodata == cell) {
+                values.push(f64::NAN);
+            } else {
+                values.push(cell.parse().map_err(|_| {
+                    new_error(ErrorKind::InvalidNumber {
+                        row: row + 1,
+                        column: column + 1,
+                        value: cell.to_string(),
+                    })
+                })?);
+            }
+            len += 1;
+        }
+        match width {
+            None => width = Some(len),
+            Some(expected) if expected != len => {
+                return Err(new_error(ErrorKind::RaggedRow { row: row + 1, len, expected }));
+            }
+            _ => {}
+        }
+        height += 1;
+    }
+    Ok((values, width.unwrap_or(0), height))
+}
+
+/// Replaces every occurrence of the `nodata` marker (such as the `_FillValue` or
+/// `missing_value` attribute of a NetCDF variable) by `NaN`,
+/// which is never considered to be above a threshold.
+pub fn mask_nodata(values: &mut [f64], nodata: f64) {
+    values
+        .iter_mut()
+        .filter(|v| **v == nodata)
+        .for_each(|v| *v = f64::NAN);
+}
+
+/// Computes the origin and step to give to [`ContourBuilder`] from a 1-D
+/// coordinate variable holding the coordinate of the center of each cell
+/// along one axis (such as the `lon` or `lat` variable of a NetCDF file).
+///
+/// Returns `None` when there are less than two coordinates or when they aren't
+/// evenly spaced (with a relative tolerance of `1e-6` of the step).
+///
+/// [`ContourBuilder`]: ../struct.ContourBuilder.html
+pub fn origin_and_step(coordinates: &[f64]) -> Option<(f64, f64)> {
+    if coordinates.len() < 2 {
+        return None;
+    }
+    let step = (coordinates[coordinates.len() - 1] - coordinates[0]) / (coordinates.len() - 1) as f64;
+    let evenly_spaced = coordinates
+        .windows(2)
+        .all(|w| ((w[1] - w[0]) - step).abs() <= step.abs() * 1e-6);
+    if !evenly_spaced || step == 0. || !step.is_finite() {
+        return None;
+    }
+    Some((coordinates[0] - step / 2., step))
+}
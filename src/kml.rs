@@ -0,0 +1,161 @@
+//! KML output (for Google Earth and other KML viewers).
+//!
+//! KML coordinates are `longitude,latitude[,altitude]`, so the contours should be
+//! computed with the origin and step of the grid set (see [`ContourBuilder::x_origin`]
+//! and the following methods) for them to be positioned correctly.
+//!
+//! [`ContourBuilder::x_origin`]: ../struct.ContourBuilder.html#method.x_origin
+
+use geojson::{Feature, Value};
+use std::fmt::Write;
+
+/// Options of the KML output.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KmlOptions {
+    /// Whether [`ContourBuilder::contours_kml`] emits one Placemark per polygon
+    /// instead of one per threshold.
+    ///
+    /// [`ContourBuilder::contours_kml`]: ../struct.ContourBuilder.html#method.contours_kml
+    pub per_polygon: bool,
+    /// Style applied to the Placemarks (no style when `None`).
+    pub style: Option<KmlStyle>,
+}
+
+/// Style of the Placemarks of the KML output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KmlStyle {
+    /// Color of the outlines, as `[red, green, blue, alpha]`.
+    pub line_color: [u8; 4],
+    /// Width of the outlines, in pixels.
+    pub line_width: f64,
+    /// Colormap used for the fill of the polygons, as `[red, green, blue, alpha]` colors:
+    /// the lowest threshold gets the first color, the highest threshold the last one,
+    /// and the colors of the other thresholds are linearly interpolated.
+    /// Polygons aren't filled when empty.
+    pub fill_colors: Vec<[u8; 4]>,
+}
+
+impl Default for KmlStyle {
+    fn default() -> Self {
+        KmlStyle {
+            line_color: [0, 0, 0, 255],
+            line_width: 1.,
+            fill_colors: Vec::new(),
+        }
+    }
+}
+
+/// Converts the given `features` (as returned by [`ContourBuilder::contours`] or
+/// [`ContourBuilder::contours_exploded`]) to a KML document, with one Placemark
+/// per Feature named after its `value` property.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+/// [`ContourBuilder::contours_exploded`]: ../struct.ContourBuilder.html#method.contours_exploded
+pub fn to_kml(features: &[Feature], options: &KmlOptions) -> String {
+    let values = features.iter().map(feature_value).collect::<Vec<_>>();
+    let (min, max) = values
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(*v), max.max(*v)));
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n");
+    for (i, (feature, value)) in features.iter().zip(&values).enumerate() {
+        out.push_str("<Placemark>\n");
+        if let Some(value) = value {
+            writeln!(out, "<name>{}</name>", escape(&value.to_string())).unwrap();
+        }
+        if let Some(ref style) = options.style {
+            write_style(&mut out, style, i, value.map(|v| position(v, min, max)));
+        }
+        let polygons = match feature.geometry.as_ref().map(|g| &g.value) {
+            Some(Value::Polygon(polygon)) => vec![polygon],
+            Some(Value::MultiPolygon(polygons)) => polygons.iter().collect(),
+            _ => Vec::new(),
+        };
+        out.push_str("<MultiGeometry>\n");
+        for polygon in polygons {
+            out.push_str("<Polygon>\n");
+            for (j, ring) in polygon.iter().enumerate() {
+                let boundary = if j == 0 { "outerBoundaryIs" } else { "innerBoundaryIs" };
+                write!(out, "<{}><LinearRing><coordinates>", boundary).unwrap();
+                // The rings of KML are closed, even if the ones of the Feature aren't
+                // (see `ContourBuilder::ring_closure`).
+                let closing = ring.first().filter(|first| ring.len() > 1 && ring.last() != Some(*first));
+                for (k, point) in ring.iter().chain(closing).enumerate() {
+                    if k > 0 {
+                        out.push(' ');
+                    }
+                    let coords = point.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+                    out.push_str(&coords.join(","));
+                }
+                writeln!(out, "</coordinates></LinearRing></{}>", boundary).unwrap();
+            }
+            out.push_str("</Polygon>\n");
+        }
+        out.push_str("</MultiGeometry>\n</Placemark>\n");
+    }
+    out.push_str("</Document>\n</kml>\n");
+    out
+}
+
+fn feature_value(feature: &Feature) -> Option<f64> {
+    feature.properties.as_ref()?.get("value")?.as_f64()
+}
+
+fn position(value: f64, min: f64, max: f64) -> f64 {
+    if max > min {
+        (value - min) / (max - min)
+    } else {
+        0.
+    }
+}
+
+fn write_style(out: &mut String, style: &KmlStyle, i: usize, position: Option<f64>) {
+    writeln!(out, "<Style id=\"contour-{}\">", i).unwrap();
+    writeln!(
+        out,
+        "<LineStyle><color>{}</color><width>{}</width></LineStyle>",
+        kml_color(style.line_color),
+        style.line_width
+    )
+    .unwrap();
+    match (style.fill_colors.is_empty(), position) {
+        (false, Some(position)) => writeln!(
+            out,
+            "<PolyStyle><color>{}</color></PolyStyle>",
+            kml_color(interpolate(&style.fill_colors, position))
+        )
+        .unwrap(),
+        _ => out.push_str("<PolyStyle><fill>0</fill></PolyStyle>\n"),
+    }
+    out.push_str("</Style>\n");
+}
+
+// Picks the color at `t` (between 0 and 1) in the given colormap.
+fn interpolate(colors: &[[u8; 4]], t: f64) -> [u8; 4] {
+    if colors.len() == 1 {
+        return colors[0];
+    }
+    let t = t.clamp(0., 1.) * (colors.len() - 1) as f64;
+    let i = (t.floor() as usize).min(colors.len() - 2);
+    let f = t - i as f64;
+    let mut color = [0; 4];
+    for (c, (a, b)) in color.iter_mut().zip(colors[i].iter().zip(colors[i + 1].iter())) {
+        *c = (*a as f64 + (*b as f64 - *a as f64) * f).round() as u8;
+    }
+    color
+}
+
+// KML colors are written as `aabbggrr`.
+fn kml_color(color: [u8; 4]) -> String {
+    format!("{:02x}{:02x}{:02x}{:02x}", color[3], color[2], color[1], color[0])
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,103 @@
+//! Placement of the labels of the isolines: candidate positions spaced regularly
+//! along the rings, with the angle to rotate the text by.
+
+use crate::contour::Pt;
+use geojson::{Feature, Value};
+
+/// A candidate position for a label, as computed by [`label_positions`].
+///
+/// [`label_positions`]: fn.label_positions.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LabelPos {
+    /// The x coordinate of the position, on the ring.
+    pub x: f64,
+    /// The y coordinate of the position.
+    pub y: f64,
+    /// The angle of the segment of the ring at the position, in degrees counterclockwise
+    /// from the x axis (with the y axis pointing up; clockwise with the y axis pointing
+    /// down), normalized from `-90` (excluded) to `90` so that the text is never upside down.
+    pub angle: f64,
+    /// The threshold of the ring.
+    pub threshold: f64,
+}
+
+/// Computes candidate positions for the labels of the rings of the given `features`
+/// (as returned by [`ContourBuilder::contours`], the threshold being read from their
+/// `value` property, NaN without it), walking each ring from its first position:
+/// the first label is placed half the `spacing` along it, and the next ones every
+/// `spacing` (in output units). The rings shorter than `min_length` are skipped.
+///
+/// Nothing is placed when `spacing` isn't positive.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn label_positions(features: &[Feature], spacing: f64, min_length: f64) -> Vec<LabelPos> {
+    let mut positions = Vec::new();
+    for feature in features {
+        let threshold = feature
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get("value")?.as_f64())
+            .unwrap_or(f64::NAN);
+        let rings = match feature.geometry.as_ref().map(|g| &g.value) {
+            Some(Value::Polygon(polygon)) => polygon.iter().collect(),
+            Some(Value::MultiPolygon(polygons)) => polygons.iter().flatten().collect(),
+            _ => Vec::new(),
+        };
+        positions.extend(ring_label_positions(&rings, threshold, spacing, min_length));
+    }
+    positions
+}
+
+/// Computes candidate positions for the labels of the given `rings` (such as the ones
+/// returned by [`contour_rings`]) of the given `threshold`, like [`label_positions`] does.
+///
+/// ```
+/// # use contour::label::ring_label_positions;
+/// let square = vec![vec![0., 0.], vec![10., 0.], vec![10., 10.], vec![0., 10.], vec![0., 0.]];
+/// let positions = ring_label_positions(&[square], 1., 10., 0.);
+/// assert_eq!(positions.iter().map(|p| (p.x, p.y, p.angle)).collect::<Vec<_>>(), [(5., 0., 0.), (10., 5., 90.), (5., 10., 0.), (0., 5., 90.)]);
+/// ```
+///
+/// [`contour_rings`]: ../fn.contour_rings.html
+/// [`label_positions`]: fn.label_positions.html
+pub fn ring_label_positions<R: AsRef<[Pt]>>(rings: &[R], threshold: f64, spacing: f64, min_length: f64) -> Vec<LabelPos> {
+    let mut positions = Vec::new();
+    if spacing.is_nan() || spacing <= 0. {
+        return positions;
+    }
+    for ring in rings {
+        let ring = ring.as_ref();
+        let length = ring.windows(2).map(|w| (w[1][0] - w[0][0]).hypot(w[1][1] - w[0][1])).sum::<f64>();
+        if length < min_length || !length.is_finite() {
+            continue;
+        }
+        // The distance along the ring of the next label, and of the current segment.
+        let (mut next, mut start) = (spacing / 2., 0.);
+        for w in ring.windows(2) {
+            let (dx, dy) = (w[1][0] - w[0][0], w[1][1] - w[0][1]);
+            let len = dx.hypot(dy);
+            if len == 0. {
+                continue;
+            }
+            let angle = upright(dy.atan2(dx).to_degrees());
+            while next < start + len {
+                let t = (next - start) / len;
+                positions.push(LabelPos { x: w[0][0] + t * dx, y: w[0][1] + t * dy, angle, threshold });
+                next += spacing;
+            }
+            start += len;
+        }
+    }
+    positions
+}
+
+// Normalizes the angle (from -180 to 180 degrees) from -90 (excluded) to 90.
+fn upright(angle: f64) -> f64 {
+    if angle > 90. {
+        angle - 180.
+    } else if angle <= -90. {
+        angle + 180.
+    } else {
+        angle
+    }
+}
@@ -47,16 +47,78 @@
 //! [`contour_rings`]: fn.contour_rings.html
 //! [`ContourBuilder`]: struct.ContourBuilder.html
 
-mod area;
+#![cfg_attr(not(any(feature = "fast-unchecked", feature = "mmap")), forbid(unsafe_code))]
+#![cfg_attr(all(feature = "mmap", not(feature = "fast-unchecked")), deny(unsafe_code))]
+
+#[cfg(feature = "buffer")]
+pub mod buffer;
+pub mod cache;
+mod clip;
 mod contour;
+mod contours;
+pub mod classes;
+pub mod d3;
+pub mod debug;
+pub mod diff;
+pub mod dxf;
 mod error;
+pub mod extrema;
+#[cfg(feature = "flatgeobuf")]
+pub mod flatgeobuf;
+pub mod geoarrow;
+pub mod geom;
+#[cfg(feature = "gpkg")]
+pub mod gpkg;
+pub mod gradient;
+pub mod hex;
+pub mod histogram;
+pub mod index;
+pub mod instrument;
+pub mod io;
+pub mod kml;
+pub mod label;
+pub mod mesh;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "netcdf")]
+pub mod netcdf;
+pub mod pad;
+pub mod path;
+pub mod plot;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod polyline;
+pub mod raster;
+pub mod resample;
+mod ring;
+pub mod section;
+pub mod shapefile;
+pub mod simplify;
+pub mod smooth;
+pub mod source;
+mod stats;
+#[cfg(feature = "stream")]
+pub mod stream;
+pub mod style;
+pub mod svg;
+pub mod testing;
+#[cfg(feature = "tin")]
+pub mod tin;
+mod trace;
+mod validate;
+pub mod wkb;
+mod zonal;
 
-pub use crate::contour::{ContourBuilder, contour_rings};
+pub use crate::contour::{Algorithm, ContourBuilder, ContourOptions, EmptyGeometry, EpsilonRounding, GeometryMode, IsoRingBuilder, ReprojectionFailure, RingClosure, RingDirections, Validation, case_indices, contour_rings, contour_rings_smooth, contour_rings_with_directions};
+pub use crate::contours::{Contours, ThresholdFn, ThresholdSpec, contours, thresholds_equal_area, thresholds_jenks, thresholds_jenks_sampled, ticks};
+pub use crate::error::{Error, ErrorKind, Result};
+pub use crate::ring::{Ring, RingDirection, Side, Winding};
+pub use crate::stats::{GridStats, MAX_REPORTED_INDICES, ThresholdDiagnostics, ValidationReport, area_above_thresholds};
+pub use crate::validate::{Intersection, SegmentId};
 
 #[cfg(test)]
 mod tests {
-    use crate::ContourBuilder;
-    use geojson;
+    use crate::{Algorithm, ContourBuilder, ContourOptions, EmptyGeometry, EpsilonRounding, ErrorKind, GeometryMode, Intersection, IsoRingBuilder, MAX_REPORTED_INDICES, ReprojectionFailure, Ring, RingClosure, RingDirections, SegmentId, Side, ThresholdDiagnostics, ThresholdSpec, Validation, Winding, area_above_thresholds, case_indices, classes, contour_rings, contour_rings_smooth, contour_rings_with_directions, contours, d3, debug, diff, dxf, extrema, geom, gradient, hex, index, io, kml, label, mesh, path, plot, polyline, raster, resample, section, shapefile, simplify, style, svg, testing, validate, wkb};
 
     #[test]
     fn test_empty_polygons() {
@@ -200,7 +262,7 @@ mod tests {
     #[test]
     fn test_multipolygon_with_hole() {
         let c = ContourBuilder::new(10, 10, true);
-        let res = c.contours(&[
+        let values = [
             0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
             0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
             0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
@@ -211,9 +273,12 @@ mod tests {
             0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
             0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
             0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
-        ], &[0.5]).unwrap();
+        ];
+        let res = c.contours(&values, &[0.5]).unwrap();
         match res[0].clone().geometry.unwrap().value {
             geojson::Value::MultiPolygon(p) => {
+                let rings = p.iter().flatten().cloned().collect::<Vec<_>>();
+                let drawing = debug::ascii_grid_with_rings(&values, 10, 10, 0.5, &rings).unwrap();
                 assert_eq!(
                     p,
                     vec![
@@ -242,7 +307,7 @@ mod tests {
                                 vec![7., 4.5], vec![6.5, 5.],
                             ],
                         ],
-                ]);
+                ], "\n{}", drawing);
             }
             _ => panic!(""),
         };
@@ -328,4 +393,4541 @@ mod tests {
             _ => panic!(""),
         };
     }
+
+    #[test]
+    fn test_geometry_mode_auto_single_polygon() {
+        let c = ContourBuilder::new(10, 10, true).geometry_mode(GeometryMode::Auto);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5]).unwrap();
+        match res[0].clone().geometry.unwrap().value {
+            geojson::Value::Polygon(p) => {
+                // The shell keeps its hole.
+                assert_eq!(p.len(), 2);
+                assert_eq!(p[0].len(), 17);
+                assert_eq!(p[1].len(), 9);
+            }
+            _ => panic!(""),
+        };
+        let json = serde_json::to_value(&res[0]).unwrap();
+        assert_eq!(json["geometry"]["type"], "Polygon");
+        assert_eq!(json["geometry"]["coordinates"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_geometry_mode_auto_multiple_polygons() {
+        let c = ContourBuilder::new(10, 10, true).geometry_mode(GeometryMode::Auto);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5]).unwrap();
+        match res[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => assert_eq!(p.len(), 2),
+            _ => panic!(""),
+        };
+        let json = serde_json::to_value(&res[0]).unwrap();
+        assert_eq!(json["geometry"]["type"], "MultiPolygon");
+    }
+
+    #[test]
+    fn test_empty_geometry() {
+        let values = [0.; 100];
+        let res = ContourBuilder::new(10, 10, true)
+            .geometry_mode(GeometryMode::Auto)
+            .contours(&values, &[0.5])
+            .unwrap();
+        let json = serde_json::to_value(&res[0]).unwrap();
+        assert_eq!(json["geometry"]["type"], "MultiPolygon");
+        assert!(json["geometry"]["coordinates"].as_array().unwrap().is_empty());
+
+        let res = ContourBuilder::new(10, 10, true)
+            .empty_geometry(EmptyGeometry::Null)
+            .contours(&values, &[0.5])
+            .unwrap();
+        assert!(res[0].geometry.is_none());
+        let json = serde_json::to_value(&res[0]).unwrap();
+        assert!(json["geometry"].is_null());
+        assert_eq!(json["properties"]["value"], 0.5);
+    }
+
+    #[test]
+    fn test_contours_exploded() {
+        let c = ContourBuilder::new(10, 10, true);
+        let res = c.contours_exploded(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0., 1., 1., 1., 0., 0.,
+            0., 1., 0., 1., 0., 1., 0., 1., 0., 0.,
+            0., 1., 1., 1., 0., 1., 1., 1., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5]).unwrap();
+        assert_eq!(res.len(), 2);
+        for (i, feature) in res.iter().enumerate() {
+            let properties = feature.properties.as_ref().unwrap();
+            assert_eq!(properties["value"], 0.5);
+            assert_eq!(properties["part"], i);
+            match feature.geometry.clone().unwrap().value {
+                geojson::Value::MultiPolygon(p) => {
+                    // One polygon, with its hole still attached.
+                    assert_eq!(p.len(), 1);
+                    assert_eq!(p[0].len(), 2);
+                }
+                _ => panic!(""),
+            };
+        }
+    }
+
+    #[test]
+    fn test_z_coordinate() {
+        let c = ContourBuilder::new(10, 10, true).z(true);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5, 1.5]).unwrap();
+        let flat = ContourBuilder::new(10, 10, true).contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5, 1.5]).unwrap();
+        for ((feature, flat_feature), threshold) in res.iter().zip(flat.iter()).zip(&[0.5, 1.5]) {
+            match (feature.clone().geometry.unwrap().value, flat_feature.clone().geometry.unwrap().value) {
+                (geojson::Value::MultiPolygon(p), geojson::Value::MultiPolygon(flat_p)) => {
+                    let points = p.iter().flatten().flatten().collect::<Vec<_>>();
+                    let flat_points = flat_p.iter().flatten().flatten().collect::<Vec<_>>();
+                    assert!(!points.is_empty());
+                    assert_eq!(points.len(), flat_points.len());
+                    for (pt, flat_pt) in points.iter().zip(flat_points) {
+                        assert_eq!(pt.len(), 3);
+                        assert_eq!(pt[2], *threshold);
+                        // Smoothing only touches x/y.
+                        assert_eq!(pt[..2], flat_pt[..]);
+                    }
+                }
+                _ => panic!(""),
+            };
+        }
+    }
+
+    #[test]
+    fn test_crs() {
+        let c = ContourBuilder::new(10, 10, true).crs("EPSG:3857");
+        let mut values = [0.; 100];
+        values[44] = 1.;
+        let res = c.contours(&values, &[0.5]).unwrap();
+        let serialized = serde_json::to_string(&res[0]).unwrap();
+        assert_eq!(serialized.matches("\"crs\"").count(), 1);
+        assert!(serialized.contains(r#""crs":{"properties":{"name":"EPSG:3857"},"type":"name"}"#));
+        match serialized.parse::<geojson::GeoJson>().unwrap() {
+            geojson::GeoJson::Feature(feature) => assert_eq!(feature, res[0]),
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    fn test_case_indices() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let cases = case_indices(&values, 0.5, 10, 10).unwrap();
+        assert_eq!(cases.len(), 11 * 11);
+        // Cell (x, y) is stored at index (y + 1) * 11 + (x + 1).
+        let case = |x: usize, y: usize| cases[(y + 1) * 11 + x + 1];
+        // Corners of the square.
+        assert_eq!(case(2, 2), 2);
+        assert_eq!(case(5, 2), 1);
+        assert_eq!(case(2, 7), 4);
+        assert_eq!(case(5, 7), 8);
+        // Edges and inside of the square.
+        assert_eq!(case(3, 2), 3);
+        assert_eq!(case(2, 3), 6);
+        assert_eq!(case(3, 3), 15);
+        // Synthetic border cells.
+        assert_eq!(cases[0], 0);
+        assert!(cases.iter().filter(|c| **c != 0 && **c != 15).count() == 16);
+        assert!(case_indices(&values, 0.5, 10, 11).is_err());
+    }
+
+    #[test]
+    fn test_debug_rendering() {
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 1., 0.,
+            0., 1., 0., 0.,
+            0., 0., 0., 0.,
+        ];
+        assert_eq!(debug::ascii_grid(&values, 4, 4, 0.5).unwrap(), "....\n.##.\n.#..\n....\n");
+        let rings = contour_rings(&values, 0.5, 4, 4).unwrap();
+        assert_eq!(
+            debug::ascii_grid_with_rings(&values, 4, 4, 0.5, &rings).unwrap(),
+            concat!(
+                "         \n",
+                " . . . . \n",
+                "   + +   \n",
+                " .+# #+. \n",
+                "     +   \n",
+                " .+#+. . \n",
+                "   +     \n",
+                " . . . . \n",
+                "         \n",
+            )
+        );
+        let mut pgm = Vec::new();
+        debug::write_pgm(&mut pgm, &rings, 4, 4).unwrap();
+        assert_eq!(&pgm[..11], b"P5\n4 4\n255\n");
+        assert_eq!(
+            &pgm[11..],
+            &[0, 0, 0, 0, 0, 255, 255, 0, 0, 255, 0, 0, 0, 0, 0, 0][..]
+        );
+        assert!(debug::ascii_grid(&values, 3, 4, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_read_csv_grid() {
+        let csv = "\
+0, 0, 0, 0, 0
+0, 1, 1, 1, 0
+0, 1, NA, 1, 0
+0, 1, 1, ,0
+
+0, 0, 0, 0, 0
+";
+        let (values, dx, dy) = io::read_csv_grid(csv.as_bytes()).unwrap();
+        assert_eq!((dx, dy), (5, 5));
+        assert_eq!(values[6], 1.);
+        assert!(values[12].is_nan());
+        assert!(values[18].is_nan());
+        // Missing values are below any threshold, making a hole and a notch in the square.
+        let res = ContourBuilder::new(dx as u32, dy as u32, false).contours(&values, &[0.5]).unwrap();
+        match res[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => {
+                assert_eq!(p.len(), 1);
+                assert_eq!(p[0].len(), 1);
+                assert!(p[0][0].contains(&vec![3., 3.5]));
+            }
+            _ => panic!(""),
+        };
+
+        let options = io::CsvOptions { delimiter: ';', nodata: vec![String::from("-9999")] };
+        let (values, dx, dy) = io::read_csv_grid_with("1;-9999\n2;3\n".as_bytes(), &options).unwrap();
+        assert_eq!((dx, dy), (2, 2));
+        assert!(values[1].is_nan());
+
+        let err = io::read_csv_grid("0,1\n0,x\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid number \"x\" at row 2, column 2");
+        let err = io::read_csv_grid("0,1\n0\n".as_bytes()).unwrap_err();
+        assert_eq!(err.to_string(), "Row 2 has 1 columns, expected 2");
+    }
+
+    #[test]
+    fn test_gridded_data_helpers() {
+        let mut values = vec![-999., 1., 2., -999.];
+        io::mask_nodata(&mut values, -999.);
+        assert!(values[0].is_nan() && values[3].is_nan());
+        assert_eq!(values[1..3], [1., 2.]);
+
+        assert_eq!(io::origin_and_step(&[-179.5, -178.5, -177.5]), Some((-180., 1.)));
+        assert_eq!(io::origin_and_step(&[89.75, 89.25, 88.75]), Some((90., -0.5)));
+        assert_eq!(io::origin_and_step(&[0., 1., 3.]), None);
+        assert_eq!(io::origin_and_step(&[0.]), None);
+    }
+
+    #[test]
+    fn test_contours_column_major() {
+        let (dx, dy) = (7, 5);
+        let values = (0..dx * dy)
+            .map(|i| ((i % dx) as f64 - 3.).powi(2) + ((i / dx) as f64 - 2.).powi(2) * 2.)
+            .collect::<Vec<f64>>();
+        let column_major = (0..dx * dy)
+            .map(|i| values[(i % dy) * dx + i / dy])
+            .collect::<Vec<f64>>();
+        let c = ContourBuilder::new(dx as u32, dy as u32, true);
+        assert_eq!(
+            c.contours(&values, &[1.5, 4.5]).unwrap(),
+            c.contours_column_major(&column_major, &[1.5, 4.5]).unwrap()
+        );
+        assert!(c.contours_column_major(&column_major[1..], &[1.5]).is_err());
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn test_contours_matrix() {
+        let (dx, dy) = (7, 5);
+        let values = (0..dx * dy)
+            .map(|i| ((i % dx) as f64 - 3.).powi(2) + ((i / dx) as f64 - 2.).powi(2) * 2.)
+            .collect::<Vec<f64>>();
+        let matrix = nalgebra::DMatrix::from_fn(dy, dx, |y, x| values[y * dx + x]);
+        let c = ContourBuilder::new(dx as u32, dy as u32, true);
+        assert_eq!(c.contours_matrix(&matrix, &[1.5, 4.5]).unwrap(), c.contours(&values, &[1.5, 4.5]).unwrap());
+        // The dimensions must match, not only the number of values.
+        let transposed = ContourBuilder::new(dy as u32, dx as u32, true);
+        assert!(transposed.contours_matrix(&matrix, &[1.5]).is_err());
+        assert!(transposed.contours_matrix(&matrix.transpose(), &[1.5]).is_ok());
+
+        // The values are checked in strict mode.
+        let mut invalid = matrix.clone();
+        invalid[(1, 2)] = f64::NAN;
+        match c.clone().strict_input(true).contours_matrix(&invalid, &[1.5]).unwrap_err().into_kind() {
+            ErrorKind::InvalidInput(report) => assert_eq!(report.nan_indices, [dx + 2]),
+            _ => panic!("unexpected error kind"),
+        }
+    }
+
+    #[test]
+    fn test_contours_geoarrow() {
+        let c = ContourBuilder::new(10, 10, true);
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let array = c.contours_geoarrow(&values, &[0.5, 2.]).unwrap();
+        assert_eq!(array.len(), 2);
+        assert_eq!(array.values, vec![0.5, 2.]);
+        // One polygon for the first threshold, none for the second.
+        assert_eq!(array.geom_offsets, vec![0, 1, 1]);
+        // The polygon has two rings (the shell and the hole).
+        assert_eq!(array.polygon_offsets, vec![0, 2]);
+        assert_eq!(array.ring_offsets, vec![0, 17, 26]);
+        assert_eq!(array.coords.len(), 52);
+        assert_eq!(array.coords[..4], [6., 7.5, 6., 6.5]);
+        assert_eq!(array.coords[34..36], [4.5, 7.]);
+        assert_eq!(array.xs()[17], 4.5);
+        assert_eq!(array.ys()[17], 7.);
+
+        let features = c.contours(&values, &[0.5]).unwrap();
+        match features[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => {
+                let coords = p.iter().flatten().flatten().flatten().cloned().collect::<Vec<f64>>();
+                assert_eq!(coords, array.coords);
+            }
+            _ => panic!(""),
+        };
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_contours_record_batch() {
+        use arrow_array::Array;
+        use arrow_array::cast::AsArray;
+        use arrow_array::types::Float64Type;
+        use arrow_ipc::reader::StreamReader;
+        use arrow_ipc::writer::StreamWriter;
+
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0., 0.,
+            0., 1., 0., 1., 0., 2.,
+            0., 1., 1., 1., 0., 0.,
+            0., 0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(6, 5, false);
+        let thresholds = [0.5, 1.5, 3.];
+        let batch = c.contours_record_batch(&values, &thresholds).unwrap();
+        let field = batch.schema().field(0).clone();
+        assert_eq!(field.name(), "geometry");
+        assert_eq!(field.metadata()["ARROW:extension:name"], "geoarrow.multipolygon");
+
+        // Written and read back as an arrow IPC stream.
+        let mut bytes = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut bytes, &batch.schema()).unwrap();
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+        let batches = StreamReader::try_new(&bytes[..], None).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches, [batch]);
+
+        // The rows match the polygons of the GeoJSON output.
+        let read = &batches[0];
+        assert_eq!(read.num_rows(), 3);
+        assert_eq!(read.column(1).as_primitive::<Float64Type>().values(), &thresholds);
+        let features = c.contours(&values, &thresholds).unwrap();
+        let geometry = read.column(0).as_list::<i32>();
+        for (row, feature) in features.iter().enumerate() {
+            let polygons = match feature.geometry.as_ref().unwrap().value {
+                geojson::Value::MultiPolygon(ref p) => p.clone(),
+                _ => panic!(""),
+            };
+            let read = geometry.value(row);
+            let read = read.as_list::<i32>();
+            assert_eq!(read.len(), polygons.len());
+            for (p, polygon) in polygons.iter().enumerate() {
+                let rings = read.value(p);
+                let rings = rings.as_list::<i32>();
+                assert_eq!(rings.len(), polygon.len());
+                for (r, ring) in polygon.iter().enumerate() {
+                    let vertices = rings.value(r);
+                    let coords = vertices.as_fixed_size_list().values().as_primitive::<Float64Type>().values().to_vec();
+                    assert_eq!(coords, ring.iter().flatten().cloned().collect::<Vec<_>>());
+                }
+            }
+        }
+        assert_eq!(geometry.value(2).len(), 0);
+    }
+
+    #[test]
+    fn test_write_shapefile() {
+        let c = ContourBuilder::new(10, 10, false);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5, 2.]).unwrap();
+        let (mut shp, mut shx, mut dbf) = (Vec::new(), Vec::new(), Vec::new());
+        shapefile::write_shapefile_to(&mut shp, &mut shx, &mut dbf, &res).unwrap();
+
+        let be_i32 = |b: &[u8], at: usize| i32::from_be_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]]);
+        let le_i32 = |b: &[u8], at: usize| i32::from_le_bytes([b[at], b[at + 1], b[at + 2], b[at + 3]]);
+        let le_f64 = |b: &[u8], at: usize| {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&b[at..at + 8]);
+            f64::from_le_bytes(bytes)
+        };
+        assert_eq!(be_i32(&shp, 0), 9994);
+        assert_eq!(be_i32(&shp, 24) as usize * 2, shp.len());
+        assert_eq!(le_i32(&shp, 32), 5);
+        assert_eq!([le_f64(&shp, 36), le_f64(&shp, 44), le_f64(&shp, 52), le_f64(&shp, 60)], [3., 3., 6., 8.]);
+        assert_eq!(be_i32(&shx, 24) as usize * 2, shx.len());
+        assert_eq!(shx.len(), 100 + 2 * 8);
+
+        // First record: a polygon with the shell and the hole.
+        assert_eq!(be_i32(&shp, 100), 1);
+        assert_eq!(le_i32(&shp, 108), 5);
+        let (num_parts, num_points) = (le_i32(&shp, 144) as usize, le_i32(&shp, 148) as usize);
+        assert_eq!((num_parts, num_points), (2, 26));
+        assert_eq!((le_i32(&shp, 152), le_i32(&shp, 156)), (0, 17));
+        let points = (0..num_points)
+            .map(|i| (le_f64(&shp, 160 + 16 * i), le_f64(&shp, 168 + 16 * i)))
+            .collect::<Vec<_>>();
+        let signed_area = |ring: &[(f64, f64)]| {
+            ring.windows(2).map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1).sum::<f64>() / 2.
+        };
+        // Clockwise shell and counterclockwise hole.
+        assert_eq!(signed_area(&points[..17]), -14.5);
+        assert_eq!(signed_area(&points[17..]), 2.5);
+        // Second record: a null shape.
+        let second = 100 + 8 + be_i32(&shp, 104) as usize * 2;
+        assert_eq!(be_i32(&shx, 108) as usize * 2, second);
+        assert_eq!(be_i32(&shp, second), 2);
+        assert_eq!(le_i32(&shp, second + 8), 0);
+
+        assert_eq!(u32::from_le_bytes([dbf[4], dbf[5], dbf[6], dbf[7]]), 2);
+        assert_eq!(&dbf[32..37], b"VALUE");
+        let records = &dbf[65..];
+        assert_eq!(std::str::from_utf8(&records[1..25]).unwrap().trim(), "0.500000000000000");
+        assert_eq!(std::str::from_utf8(&records[26..50]).unwrap().trim(), "2.000000000000000");
+        assert_eq!(records[50], 0x1A);
+    }
+
+    #[test]
+    fn test_bbox() {
+        let c = ContourBuilder::new(10, 10, false).bbox(true).x_origin(100.).y_step(-1.);
+        let res = c.contours_exploded(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5]).unwrap();
+        assert_eq!(res[0].bbox, Some(vec![103., -8., 105., -3.]));
+        assert_eq!(res[1].bbox, Some(vec![106., -8., 107., -3.]));
+        let json = serde_json::to_value(&res[0]).unwrap();
+        assert_eq!(json["bbox"], serde_json::json!([103., -8., 105., -3.]));
+
+        let res = c.contours(&[0.; 100], &[0.5]).unwrap();
+        assert_eq!(res[0].bbox, None);
+    }
+
+    #[test]
+    fn test_contours_kml() {
+        let c = ContourBuilder::new(10, 10, false).x_origin(5.).y_origin(45.).x_step(0.1).y_step(-0.1);
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let options = kml::KmlOptions {
+            per_polygon: false,
+            style: Some(kml::KmlStyle {
+                line_color: [255, 0, 0, 255],
+                line_width: 2.,
+                fill_colors: vec![[0, 0, 255, 128], [255, 255, 0, 128]],
+            }),
+        };
+        let doc = c.contours_kml(&values, &[0.5, 0.75], &options).unwrap();
+        assert!(doc.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<kml xmlns=\"http://www.opengis.net/kml/2.2\">"));
+        assert!(doc.trim_end().ends_with("</Document>\n</kml>"));
+        for tag in &["kml", "Document", "Placemark", "Style", "MultiGeometry", "Polygon", "LinearRing", "coordinates"] {
+            assert_eq!(
+                doc.matches(&format!("<{}>", tag)).count() + doc.matches(&format!("<{} ", tag)).count(),
+                doc.matches(&format!("</{}>", tag)).count(),
+                "unbalanced <{}>",
+                tag
+            );
+        }
+        assert_eq!(doc.matches("<Placemark>").count(), 2);
+        assert_eq!(doc.matches("<Polygon>").count(), 2);
+        assert_eq!(doc.matches("<outerBoundaryIs>").count(), 2);
+        assert_eq!(doc.matches("<innerBoundaryIs>").count(), 2);
+        assert!(doc.contains("<name>0.5</name>"));
+        assert!(doc.contains("<LineStyle><color>ff0000ff</color><width>2</width></LineStyle>"));
+        // Endpoints of the colormap for the lowest and highest thresholds.
+        assert!(doc.contains("<PolyStyle><color>80ff0000</color></PolyStyle>"));
+        assert!(doc.contains("<PolyStyle><color>8000ffff</color></PolyStyle>"));
+        // lon,lat coordinates.
+        assert!(doc.contains("<coordinates>5.6,44.25 "));
+
+        let options = kml::KmlOptions { per_polygon: true, style: None };
+        let doc = c.contours_kml(&values, &[0.5], &options).unwrap();
+        assert_eq!(doc.matches("<Placemark>").count(), 1);
+        assert!(!doc.contains("<Style"));
+    }
+
+    #[test]
+    fn test_to_dxf() {
+        let c = ContourBuilder::new(10, 10, false);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5, 1.5]).unwrap();
+        let options = dxf::DxfOptions { precision: 2, scale: 10., units: 6 };
+        let doc = dxf::to_dxf(&res, &options);
+        let lines = doc.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len() % 2, 0);
+        let pairs = lines.chunks(2).map(|p| (p[0].parse::<u16>().unwrap(), p[1])).collect::<Vec<_>>();
+        assert_eq!(pairs.last(), Some(&(0, "EOF")));
+        assert!(pairs.contains(&(9, "$INSUNITS")));
+        let layers = pairs.windows(2).filter(|w| w[0] == (0, "LAYER")).map(|w| w[1].1).collect::<Vec<_>>();
+        assert_eq!(layers, vec!["CONTOUR_0_5", "CONTOUR_1_5"]);
+
+        // Group the entities with their codes.
+        let start = pairs.iter().position(|p| *p == (2, "ENTITIES")).unwrap();
+        let mut polylines: Vec<Vec<(u16, &str)>> = Vec::new();
+        for pair in &pairs[start + 1..] {
+            if pair.0 == 0 {
+                polylines.push(Vec::new());
+            }
+            polylines.last_mut().unwrap().push(*pair);
+        }
+        polylines.retain(|entity| entity[0] == (0, "LWPOLYLINE"));
+        assert_eq!(polylines.len(), 2);
+        let get = |entity: &Vec<(u16, &str)>, code| entity.iter().find(|p| p.0 == code).unwrap().1.to_string();
+        assert_eq!(get(&polylines[0], 8), "CONTOUR_0_5");
+        assert_eq!(get(&polylines[0], 70), "1");
+        assert_eq!(get(&polylines[0], 38), "0.50");
+        assert_eq!(get(&polylines[0], 90), "20");
+        assert_eq!(polylines[0].iter().filter(|p| p.0 == 10).count(), 20);
+        assert_eq!(get(&polylines[0], 10), "70.00");
+        assert_eq!(get(&polylines[0], 20), "85.00");
+        assert_eq!(get(&polylines[1], 8), "CONTOUR_1_5");
+        assert_eq!(get(&polylines[1], 38), "1.50");
+        assert_eq!(get(&polylines[1], 90), "8");
+    }
+
+    #[test]
+    fn test_encoded_polylines() {
+        // Reference example of the format documentation.
+        let points = vec![vec![-120.2, 38.5], vec![-120.95, 40.7], vec![-126.453, 43.252]];
+        assert_eq!(polyline::encode(&points, 5).unwrap(), "_p~iF~ps|U_ulLnnqC_mqNvxq`@");
+        assert_eq!(polyline::decode("_p~iF~ps|U_ulLnnqC_mqNvxq`@", 5).unwrap(), points);
+        assert!(polyline::decode("_p~iF~ps|U_", 5).is_none());
+        // The largest coordinates, and the differences between them, round trip.
+        let far = vec![vec![-4e12, 4e12], vec![4e12, -4e12]];
+        assert_eq!(polyline::decode(&polyline::encode(&far, 6).unwrap(), 6).unwrap(), far);
+        for coordinate in &[5e12, f64::INFINITY, f64::NAN] {
+            match polyline::encode(&[vec![0., *coordinate]], 6).unwrap_err().kind() {
+                ErrorKind::PolylineOutOfRange { precision, .. } => assert_eq!(*precision, 6),
+                _ => panic!(""),
+            }
+        }
+
+        let c = ContourBuilder::new(10, 10, true).x_origin(-10.).y_origin(-5.).x_step(0.123_456_7);
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let features = c.contours(&values, &[0.5]).unwrap();
+        for precision in &[5, 6] {
+            let encoded = c.contours_encoded_polylines(&values, &[0.5], *precision).unwrap();
+            assert_eq!(encoded[0].value, 0.5);
+            assert_eq!(encoded[0].rings.iter().map(|r| r.hole).collect::<Vec<_>>(), vec![false, true]);
+            match features[0].clone().geometry.unwrap().value {
+                geojson::Value::MultiPolygon(p) => {
+                    for (ring, encoded) in p[0].iter().zip(&encoded[0].rings) {
+                        let decoded = polyline::decode(&encoded.polyline, *precision).unwrap();
+                        assert_eq!(decoded.len(), ring.len());
+                        for (a, b) in decoded.iter().zip(ring) {
+                            let tolerance = 0.5 / 10f64.powi(*precision as i32) + 1e-12;
+                            assert!((a[0] - b[0]).abs() <= tolerance && (a[1] - b[1]).abs() <= tolerance);
+                        }
+                    }
+                }
+                _ => panic!(""),
+            };
+        }
+    }
+
+    #[test]
+    fn test_feature_to_path() {
+        // Records the subpaths and their closing flag.
+        #[derive(Default)]
+        struct Recorder(Vec<(Vec<(f64, f64)>, bool)>);
+        impl path::PathSink for Recorder {
+            fn begin(&mut self, x: f64, y: f64) {
+                self.0.push((vec![(x, y)], false));
+            }
+            fn line_to(&mut self, x: f64, y: f64) {
+                self.0.last_mut().unwrap().0.push((x, y));
+            }
+            fn end(&mut self, close: bool) {
+                self.0.last_mut().unwrap().1 = close;
+            }
+        }
+
+        let c = ContourBuilder::new(10, 10, false);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5]).unwrap();
+        let mut fill = Recorder::default();
+        path::feature_to_path(&res[0], &mut fill, path::PathMode::Fill);
+        assert_eq!(fill.0.len(), 2);
+        assert_eq!((fill.0[0].0.len(), fill.0[1].0.len()), (16, 8));
+        assert!(fill.0.iter().all(|(_, closed)| *closed));
+        // Filling the path excludes the hole: the shell and the hole are wound in opposite
+        // directions and their signed areas sum to the area of the filled region.
+        let signed_area = |points: &[(f64, f64)]| {
+            (0..points.len())
+                .map(|i| {
+                    let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                    a.0 * b.1 - b.0 * a.1
+                })
+                .sum::<f64>() / 2.
+        };
+        let (shell, hole) = (signed_area(&fill.0[0].0), signed_area(&fill.0[1].0));
+        assert_eq!(shell.signum(), -hole.signum());
+        assert_eq!(shell.abs() - hole.abs(), 12.);
+
+        let mut stroke = Recorder::default();
+        path::feature_to_path(&res[0], &mut stroke, path::PathMode::Stroke);
+        assert_eq!((stroke.0[0].0.len(), stroke.0[1].0.len()), (17, 9));
+        assert!(stroke.0.iter().all(|(_, closed)| !*closed));
+
+        let mut svg = path::SvgPathData { precision: Some(1), ..Default::default() };
+        path::feature_to_path(&res[0], &mut svg, path::PathMode::Fill);
+        assert!(svg.data.starts_with("M6.0,7.5 L6.0,6.5 "));
+        assert_eq!(svg.data.matches('Z').count(), 2);
+    }
+
+    #[cfg(feature = "lyon")]
+    #[test]
+    fn test_feature_to_lyon_path() {
+        use lyon_tessellation::geometry_builder::simple_builder;
+        use lyon_tessellation::math::Point;
+        use lyon_tessellation::{FillOptions, FillTessellator, VertexBuffers};
+
+        let mut values = testing::noise(30 * 30, 11);
+        // A ring of higher values around a hole.
+        for (y, x) in (10..20).flat_map(|y| (10..20).map(move |x| (y, x))) {
+            values[y * 30 + x] = if (13..17).contains(&x) && (13..17).contains(&y) { 0. } else { 2. };
+        }
+        for smooth in &[false, true] {
+            let features = ContourBuilder::new(30, 30, *smooth).contours(&values, &[0.5, 1.5]).unwrap();
+            for feature in &features {
+                let polygons = match feature.geometry.as_ref().unwrap().value {
+                    geojson::Value::MultiPolygon(ref p) => p.clone(),
+                    _ => panic!(""),
+                };
+                // `geom::area` is twice the area of the ring.
+                let expected = polygons
+                    .iter()
+                    .map(|polygon| geom::area(&polygon[0]).abs() - polygon[1..].iter().map(|hole| geom::area(hole).abs()).sum::<f64>())
+                    .sum::<f64>()
+                    / 2.;
+
+                let path = path::feature_to_lyon_path(feature, path::PathMode::Fill);
+                let mut buffers: VertexBuffers<Point, u16> = VertexBuffers::new();
+                FillTessellator::new().tessellate_path(&path, &FillOptions::default(), &mut simple_builder(&mut buffers)).unwrap();
+                let tessellated = buffers
+                    .indices
+                    .chunks(3)
+                    .map(|t| {
+                        let (a, b, c) = (buffers.vertices[t[0] as usize], buffers.vertices[t[1] as usize], buffers.vertices[t[2] as usize]);
+                        ((b - a).cross(c - a) / 2.).abs() as f64
+                    })
+                    .sum::<f64>();
+                assert!((tessellated - expected).abs() < 1e-3 * expected.max(1.), "{} != {}", tessellated, expected);
+            }
+            // Along with polygons with holes.
+            match features[1].geometry.as_ref().unwrap().value {
+                geojson::Value::MultiPolygon(ref p) => assert!(p.iter().any(|polygon| polygon.len() > 1)),
+                _ => panic!(""),
+            }
+        }
+    }
+
+    #[test]
+    fn test_draw_contours() {
+        #[derive(Default)]
+        struct Recorder {
+            fills: Vec<(Vec<(f64, f64)>, plot::Rgb)>,
+            strokes: usize,
+        }
+        impl plot::Canvas for Recorder {
+            fn fill_polygon(&mut self, points: &[(f64, f64)], color: plot::Rgb) {
+                self.fills.push((points.to_vec(), color));
+            }
+            fn stroke_polyline(&mut self, _points: &[(f64, f64)], _color: plot::Rgb) {
+                self.strokes += 1;
+            }
+        }
+
+        let c = ContourBuilder::new(10, 10, false);
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let array = c.contours_geoarrow(&values, &[0.5, 2.]).unwrap();
+        let palette = [(255, 0, 0), (0, 0, 255)];
+        let options = plot::DrawOptions { x_step: 2., y_origin: 10., stroke: Some((0, 0, 0)), ..Default::default() };
+        let mut canvas = Recorder::default();
+        plot::draw_contours(&mut canvas, &array, &palette, &options);
+        assert_eq!(canvas.fills.len(), 1);
+        assert_eq!(canvas.strokes, 2);
+        let (points, color) = &canvas.fills[0];
+        assert_eq!(*color, (255, 0, 0));
+        // The shell and the hole (without their closing positions), plus the bridge.
+        assert_eq!(points.len(), 16 + 8 + 2);
+        assert_eq!(points[0], (12., 17.5));
+
+        // The merged ring covers the same cells as the shell and its hole.
+        let res = c.contours(&values, &[0.5]).unwrap();
+        let rings = match res[0].geometry.as_ref().unwrap().value {
+            geojson::Value::MultiPolygon(ref p) => p[0].clone(),
+            _ => panic!(""),
+        };
+        let mut merged = plot::keyhole(&rings);
+        merged.push(merged[0].clone());
+        let (mut expected, mut actual) = (Vec::new(), Vec::new());
+        debug::write_pgm(&mut expected, &rings, 10, 10).unwrap();
+        debug::write_pgm(&mut actual, &[merged], 10, 10).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "plotters")]
+    #[test]
+    fn test_draw_contours_on_area() {
+        use plotters_backend::{BackendColor, DrawingErrorKind};
+        use plotters::coord::types::RangedCoordf64;
+        use plotters::prelude::*;
+
+        let c = ContourBuilder::new(10, 10, false);
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let array = c.contours_geoarrow(&values, &[0.5]).unwrap();
+        // 10 pixels per cell, the y axis of the chart going up.
+        let mut buffer = vec![255; 100 * 100 * 3];
+        {
+            let area = BitMapBackend::with_buffer(&mut buffer, (100, 100))
+                .into_drawing_area()
+                .apply_coord_spec(Cartesian2d::<RangedCoordf64, RangedCoordf64>::new(0. ..10., 10. ..0., (0..100, 0..100)));
+            plot::draw_contours_on_area(&area, &array, &[(255, 0, 0)], &Default::default()).unwrap();
+            area.present().unwrap();
+        }
+        let pixel = |x: usize, y: usize| {
+            let i = 3 * (y * 100 + x);
+            (buffer[i], buffer[i + 1], buffer[i + 2])
+        };
+        // On the ring of ones, and neither outside nor in the hole.
+        assert_eq!(pixel(35, 35), (255, 0, 0));
+        assert_eq!(pixel(55, 65), (255, 0, 0));
+        assert_eq!(pixel(15, 15), (255, 255, 255));
+        assert_eq!(pixel(85, 55), (255, 255, 255));
+        assert_eq!(pixel(45, 55), (255, 255, 255));
+
+        // The errors of the backend are returned.
+        struct Failing;
+        impl DrawingBackend for Failing {
+            type ErrorType = std::io::Error;
+            fn get_size(&self) -> (u32, u32) {
+                (100, 100)
+            }
+            fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<std::io::Error>> {
+                Ok(())
+            }
+            fn present(&mut self) -> Result<(), DrawingErrorKind<std::io::Error>> {
+                Ok(())
+            }
+            fn draw_pixel(&mut self, _: (i32, i32), _: BackendColor) -> Result<(), DrawingErrorKind<std::io::Error>> {
+                Err(DrawingErrorKind::DrawingError(std::io::Error::other("can't draw")))
+            }
+        }
+        let area = Failing
+            .into_drawing_area()
+            .apply_coord_spec(Cartesian2d::<RangedCoordf64, RangedCoordf64>::new(0. ..10., 10. ..0., (0..100, 0..100)));
+        let err = plot::draw_contours_on_area(&area, &array, &[(255, 0, 0)], &Default::default()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Render(_)));
+    }
+
+    #[test]
+    fn test_contour_index() {
+        let c = ContourBuilder::new(10, 10, true);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5, 1.5]).unwrap();
+        let idx = index::ContourIndex::new(&res);
+        assert_eq!(idx.len(), 2);
+        let keys = |entries: Vec<&index::IndexEntry>| entries.iter().map(|e| (e.feature, e.polygon)).collect::<Vec<_>>();
+        assert_eq!(keys(idx.containing_point(5.5, 5.8)), vec![(0, 0), (1, 0)]);
+        assert_eq!(keys(idx.containing_point(3.5, 5.)), vec![(0, 0)]);
+        assert_eq!(keys(idx.containing_point(7., 5.)), vec![(0, 0)]);
+        assert_eq!(keys(idx.containing_point(8., 8.)), vec![]);
+        assert_eq!(keys(idx.locate_in_envelope([0., 0., 3.2, 3.2])), vec![(0, 0)]);
+        assert_eq!(keys(idx.locate_in_envelope([4.8, 5.8, 5.2, 6.2])), vec![(0, 0), (1, 0)]);
+        assert_eq!(idx.locate_in_envelope([0., 5.8, 5.2, 6.2])[1].bbox, [4., 5., 6., 7.]);
+        let band = |lower, upper| Some(index::Band { feature: (lower == 1.5) as usize, lower, upper });
+        assert_eq!(idx.band(5.5, 5.8), band(1.5, None));
+        assert_eq!(idx.band(3.5, 5.), band(0.5, Some(1.5)));
+        assert_eq!(idx.band(4.2, 5.), band(0.5, Some(1.5)));
+        assert_eq!(idx.band(8., 8.), None);
+
+        // Points in a hole aren't contained by the polygon.
+        let c = ContourBuilder::new(5, 5, false);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.
+        ], &[0.5]).unwrap();
+        let idx = index::ContourIndex::new(&res);
+        assert_eq!(keys(idx.containing_point(2.5, 2.5)), vec![]);
+        assert_eq!(keys(idx.containing_point(1.5, 2.5)), vec![(0, 0)]);
+
+        // Enough polygons for a tree of several levels.
+        let values = (0..30 * 30).map(|i| if i % 3 == 1 && i / 30 % 3 == 1 { 1. } else { 0. }).collect::<Vec<_>>();
+        let res = ContourBuilder::new(30, 30, false).contours(&values, &[0.5]).unwrap();
+        let idx = index::ContourIndex::new(&res);
+        assert_eq!(idx.len(), 100);
+        for y in (1..30).step_by(3) {
+            for x in (1..30).step_by(3) {
+                let found = idx.containing_point(x as f64 + 0.5, y as f64 + 0.5);
+                assert_eq!(found.len(), 1);
+                assert_eq!(found[0].bbox, [x as f64, y as f64, x as f64 + 1., y as f64 + 1.]);
+            }
+        }
+        assert_eq!(idx.locate_in_envelope([0., 0., 30., 30.]).len(), 100);
+        assert!(index::ContourIndex::new(&[]).locate_in_envelope([0., 0., 30., 30.]).is_empty());
+    }
+
+    #[test]
+    fn test_contour_index_brute_force() {
+        // The queries of the index (the packed R-tree, or the rstar one with the `rstar` feature)
+        // find the same polygons as a linear scan.
+        let values = testing::noise(60 * 60, 7);
+        let res = ContourBuilder::new(60, 60, false).contours(&values, &[0.3, 0.5, 0.7]).unwrap();
+        let idx = index::ContourIndex::new(&res);
+        let all = idx.locate_in_envelope([f64::NEG_INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::INFINITY]);
+        assert!(all.len() > 50);
+        let mut rng = testing::XorShift::new(11);
+        for _ in 0..200 {
+            let (x, y) = (rng.next_f64() * 60., rng.next_f64() * 60.);
+            let (w, h) = (rng.next_f64() * 10., rng.next_f64() * 10.);
+            let bbox = [x, y, x + w, y + h];
+            let expected = all
+                .iter()
+                .filter(|e| e.bbox[0] <= bbox[2] && bbox[0] <= e.bbox[2] && e.bbox[1] <= bbox[3] && bbox[1] <= e.bbox[3])
+                .cloned()
+                .collect::<Vec<_>>();
+            assert_eq!(idx.locate_in_envelope(bbox), expected);
+            let expected = res
+                .iter()
+                .enumerate()
+                .filter(|(_, feature)| index::contains_point(feature, x, y))
+                .map(|(f, _)| f)
+                .collect::<Vec<_>>();
+            let mut found = idx.containing_point(x, y).iter().map(|e| e.feature).collect::<Vec<_>>();
+            found.dedup();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn test_contours_mesh() {
+        let triangles_area = |mesh: &mesh::Mesh| {
+            mesh.indices
+                .chunks(3)
+                .map(|t| {
+                    let p = |i: u32| {
+                        let i = i as usize * mesh.dimensions;
+                        (mesh.vertices[i] as f64, mesh.vertices[i + 1] as f64)
+                    };
+                    let (a, b, c) = (p(t[0]), p(t[1]), p(t[2]));
+                    ((b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)) / 2.
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let meshes = ContourBuilder::new(10, 10, false).contours_mesh(&values, &[0.5, 2.]).unwrap();
+        assert_eq!(meshes.len(), 2);
+        assert_eq!((meshes[0].value, meshes[0].dimensions), (0.5, 2));
+        assert!(meshes[0].triangle_count() > 0);
+        let areas = triangles_area(&meshes[0]);
+        assert!(areas.iter().all(|a| *a > 0.));
+        // The area of the shell minus the area of the hole.
+        assert!((areas.iter().sum::<f64>() - 12.).abs() < 1e-6);
+        assert_eq!(meshes[1].triangle_count(), 0);
+        assert!(meshes[1].vertices.is_empty());
+
+        let meshes = ContourBuilder::new(10, 10, true).z(true).contours_mesh(&values, &[0.5]).unwrap();
+        assert_eq!(meshes[0].dimensions, 3);
+        assert!(meshes[0].vertices.chunks(3).all(|v| v[2] == 0.5));
+        assert!(triangles_area(&meshes[0]).iter().all(|a| *a > 0.));
+
+        let c = ContourBuilder::new(2, 2, false);
+        assert!(c.contours_mesh(&[0., 0., 0.], &[0.5]).is_err());
+    }
+
+    #[test]
+    fn test_to_d3() {
+        let c = ContourBuilder::new(10, 10, true);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5, 1.5]).unwrap();
+        // Output of `d3.contours().size([10, 10]).thresholds([0.5, 1.5])` on the same values.
+        let expected: serde_json::Value = serde_json::from_str(r#"[
+            {"type":"MultiPolygon","value":0.5,"coordinates":[[[[6,7.5],[6,6.5],[6,5.5],[6,4.5],[6,3.5],
+                [5.5,3],[4.5,3],[3.5,3],[3,3.5],[3,4.5],[3,5.5],[3,6.5],[3,7.5],[3.5,8],[4.5,8],[5.5,8],[6,7.5]]]]},
+            {"type":"MultiPolygon","value":1.5,"coordinates":[]}
+        ]"#).unwrap();
+        let d3 = d3::to_d3(&res);
+        assert_eq!(d3.as_array().unwrap().len(), 2);
+        for (actual, expected) in d3.as_array().unwrap().iter().zip(expected.as_array().unwrap()) {
+            assert_eq!(actual["type"], expected["type"]);
+            assert_eq!(actual["value"].as_f64(), expected["value"].as_f64());
+            let coordinates = |v: &serde_json::Value| serde_json::from_value::<Vec<Vec<Vec<Vec<f64>>>>>(v["coordinates"].clone()).unwrap();
+            assert_eq!(coordinates(actual), coordinates(expected));
+            assert_eq!(actual.as_object().unwrap().len(), 3);
+        }
+        let reparsed: serde_json::Value = serde_json::from_str(&d3::to_d3_string(&res)).unwrap();
+        assert_eq!(reparsed, d3);
+    }
+
+    #[test]
+    fn test_fluent_api() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let thresholds_of = |res: &[geojson::Feature]| {
+            res.iter().map(|f| f.properties.as_ref().unwrap()["value"].as_f64().unwrap()).collect::<Vec<_>>()
+        };
+
+        // d3.contours().size([10, 10]).thresholds([0.5])
+        let res = contours().size((10, 10)).thresholds(vec![0.5]).compute(&values).unwrap();
+        let expected = ContourBuilder::new(10, 10, true).contours(&values, &[0.5]).unwrap();
+        assert_eq!(serde_json::to_value(&res).unwrap(), serde_json::to_value(&expected).unwrap());
+
+        // d3.contours().size([10, 10]) (thresholds following Sturges' formula)
+        let res = contours().size((10, 10)).compute(&values).unwrap();
+        assert_eq!(thresholds_of(&res), vec![0., 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9]);
+
+        // d3.contours().size([10, 10]).thresholds(20)
+        let res = contours().size((10, 10)).thresholds(20).compute(&values).unwrap();
+        assert_eq!(res.len(), 20);
+        assert_eq!(thresholds_of(&res)[..3], [0., 0.05, 0.1]);
+        assert_eq!(thresholds_of(&res)[19], 0.95);
+
+        // d3.contours().size([10, 10]).smooth(false).thresholds(values => [mean(values)])
+        let mean = ThresholdSpec::Fn(Box::new(|values: &[f64]| vec![values.iter().sum::<f64>() / values.len() as f64]));
+        let res = contours().size((10, 10)).smooth(false).thresholds(mean).compute(&values).unwrap();
+        assert_eq!(thresholds_of(&res), vec![0.15]);
+
+        let res = contours().size((10, 10)).thresholds(&[1.5, 0.5][..]).compute(&values).unwrap();
+        assert_eq!(thresholds_of(&res), vec![0.5, 1.5]);
+        assert!(ThresholdSpec::Count(5).thresholds(&[1.; 4]).is_empty());
+        assert!(contours().size((3, 3)).compute(&values).is_err());
+    }
+
+    #[test]
+    fn test_stored_thresholds() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let mut c = ContourBuilder::new(10, 10, true);
+        assert!(c.compute(&values).unwrap().is_empty());
+        c.set_thresholds(&[1.5, 0.5]).unwrap();
+        assert_eq!(c.thresholds(), [0.5, 1.5]);
+        let expected = serde_json::to_value(c.contours(&values, &[0.5, 1.5]).unwrap()).unwrap();
+        for _ in 0..3 {
+            assert_eq!(serde_json::to_value(c.compute(&values).unwrap()).unwrap(), expected);
+        }
+
+        match c.set_thresholds(&[0.5, f64::NAN]).unwrap_err().kind() {
+            ErrorKind::InvalidThreshold(v) => assert!(v.is_nan()),
+            _ => panic!(""),
+        };
+        assert_eq!(c.thresholds(), [0.5, 1.5]);
+        assert!(c.compute(&values[1..]).is_err());
+    }
+
+    #[test]
+    fn test_zonal_stats() {
+        let c = ContourBuilder::new(10, 10, false).zonal_stats(true).x_origin(100.).y_step(-2.);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5, 2.]).unwrap();
+        let properties = res[0].properties.as_ref().unwrap();
+        assert_eq!(properties["cells"], 15);
+        assert_eq!(properties["mean"], 1.);
+        assert_eq!(properties["min"], 1.);
+        assert_eq!(properties["max"], 1.);
+        let properties = res[1].properties.as_ref().unwrap();
+        assert_eq!(properties["cells"], 0);
+        assert!(properties["mean"].is_null());
+
+        // Holes are excluded, and each exploded polygon gets its own statistics.
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 2., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 4., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(10, 10, false).zonal_stats(true);
+        let res = c.contours(&values, &[0.5]).unwrap();
+        let properties = res[0].properties.as_ref().unwrap();
+        assert_eq!(properties["cells"], 13);
+        assert_eq!(properties["mean"], 17. / 13.);
+        assert_eq!((&properties["min"], &properties["max"]), (&serde_json::json!(1.), &serde_json::json!(4.)));
+        let res = c.contours_exploded(&values, &[0.5]).unwrap();
+        let mut cells = res.iter().map(|f| f.properties.as_ref().unwrap()["cells"].as_u64().unwrap()).collect::<Vec<_>>();
+        cells.sort();
+        assert_eq!(cells, vec![1, 12]);
+        assert!(ContourBuilder::new(10, 10, false).contours(&values, &[0.5]).unwrap()[0].properties.as_ref().unwrap().get("cells").is_none());
+    }
+
+    #[test]
+    fn test_contains_point() {
+        let c = ContourBuilder::new(10, 10, false);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5]).unwrap();
+        // Inside the shell.
+        assert!(index::contains_point(&res[0], 3.5, 5.5));
+        // Inside the hole.
+        assert!(!index::contains_point(&res[0], 4.5, 5.5));
+        // On the exterior ring and on the ring of the hole.
+        assert!(index::contains_point(&res[0], 6., 5.));
+        assert!(index::contains_point(&res[0], 5., 5.));
+        // Outside.
+        assert!(!index::contains_point(&res[0], 1., 1.));
+        assert!(!index::contains_point(&res[0], 6.5, 5.5));
+
+        let auto = ContourBuilder::new(3, 3, false).geometry_mode(GeometryMode::Auto);
+        let res = auto.contours(&[0., 0., 0., 0., 1., 0., 0., 0., 0.], &[0.5]).unwrap();
+        assert!(index::contains_point(&res[0], 1.5, 1.5));
+        let empty = ContourBuilder::new(3, 3, false).empty_geometry(EmptyGeometry::Null);
+        let res = empty.contours(&[0.; 9], &[0.5]).unwrap();
+        assert!(!index::contains_point(&res[0], 1.5, 1.5));
+    }
+
+    #[test]
+    fn test_reproject() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Inverse of the spherical (web) mercator projection, failing above 85 degrees of latitude.
+        let inverse_mercator = |x: f64, y: f64| {
+            let r = 6_378_137.;
+            let lat = (2. * (y / r).exp().atan() - std::f64::consts::FRAC_PI_2).to_degrees();
+            if lat > 85. {
+                return Err(format!("latitude {} out of range", lat));
+            }
+            Ok(((x / r).to_degrees(), lat))
+        };
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        // The first position of the ring, (6, 7.5), is moved to the mercator coordinates of (10°, 10°).
+        let c = ContourBuilder::new(10, 10, false)
+            .x_origin(1_113_194.907_932_735_7 - 6.)
+            .y_origin(1_118_889.974_857_959_7 - 7.5)
+            .reproject_with(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, String>(inverse_mercator)
+            });
+        let res = c.contours(&values, &[0.5, 2.]).unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        match res[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => {
+                assert_eq!(p[0][0].len(), 17);
+                assert!((p[0][0][0][0] - 10.).abs() < 1e-6);
+                assert!((p[0][0][0][1] - 10.).abs() < 1e-6);
+                assert_eq!(p[0][0][0], p[0][0][16]);
+            }
+            _ => panic!(""),
+        };
+
+        // Reprojecting back and forth gives the same contours, up to rounding errors.
+        let mercator = |x: f64, y: f64| {
+            let r = 6_378_137.;
+            (x.to_radians() * r, (std::f64::consts::FRAC_PI_4 + y.to_radians() / 2.).tan().ln() * r)
+        };
+        let c = || ContourBuilder::new(10, 10, true).x_step(0.5).y_origin(40.);
+        let round_trip = c().reproject_with(move || Ok::<_, String>(move |x, y| inverse_mercator(mercator(x, y).0, mercator(x, y).1)));
+        testing::assert_contours_approx_eq(&round_trip.contours(&values, &[0.5]).unwrap(), &c().contours(&values, &[0.5]).unwrap(), 1e-9);
+
+        // Positions that can't be reprojected fail the computation or are dropped.
+        let c = ContourBuilder::new(10, 10, false)
+            .y_step(1e7)
+            .reproject_with(move || Ok::<_, String>(inverse_mercator));
+        match c.contours(&values, &[0.5]).unwrap_err().kind() {
+            ErrorKind::Reprojection(err) => assert!(err.to_string().starts_with("latitude")),
+            _ => panic!(""),
+        };
+        let c = ContourBuilder::new(10, 10, false)
+            .y_step(1e7)
+            .reproject_with(move || Ok::<_, String>(inverse_mercator))
+            .reprojection_failure(ReprojectionFailure::Drop);
+        match c.contours(&values, &[0.5]).unwrap()[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => assert!(p.is_empty()),
+            _ => panic!(""),
+        };
+        let c = ContourBuilder::new(10, 10, false)
+            .reproject_with(|| Err::<fn(f64, f64) -> Result<(f64, f64), String>, _>("unknown CRS"))
+            .reprojection_failure(ReprojectionFailure::Drop);
+        assert!(c.contours(&values, &[0.5]).is_err());
+    }
+
+    #[cfg(feature = "proj")]
+    #[test]
+    fn test_reproject_proj() {
+        let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+        let c = || ContourBuilder::new(3, 3, false).x_step(1_000.).y_step(1_000.);
+        let first = |features: &[geojson::Feature]| match features[0].geometry.as_ref().unwrap().value {
+            geojson::Value::MultiPolygon(ref p) => p[0][0][0].clone(),
+            _ => panic!(""),
+        };
+        // The first position of the ring is moved to the UTM zone 33N coordinates of (16.5°, 45°).
+        let position = first(&c().contours(&values, &[0.5]).unwrap());
+        let c = c()
+            .x_origin(618_222.965_887_922_1 - position[0])
+            .y_origin(4_984_044.798_475_794 - position[1])
+            .reproject("+proj=utm +zone=33 +datum=WGS84", "WGS84");
+        let position = first(&c.contours(&values, &[0.5]).unwrap());
+        assert!((position[0] - 16.5).abs() < 1e-6, "{:?}", position);
+        assert!((position[1] - 45.).abs() < 1e-6, "{:?}", position);
+
+        // And back, from longitudes and latitudes in degrees.
+        let c = || ContourBuilder::new(3, 3, false).x_step(0.01).y_step(0.01);
+        let position = first(&c().contours(&values, &[0.5]).unwrap());
+        let c = c()
+            .x_origin(16.5 - position[0])
+            .y_origin(45. - position[1])
+            .reproject("WGS84", "+proj=utm +zone=33 +datum=WGS84");
+        let position = first(&c.contours(&values, &[0.5]).unwrap());
+        assert!((position[0] - 618_222.965_887_922_1).abs() < 1e-3, "{:?}", position);
+        assert!((position[1] - 4_984_044.798_475_794).abs() < 1e-3, "{:?}", position);
+
+        // Unknown reference systems fail the computation.
+        let c = ContourBuilder::new(3, 3, false).reproject("EPSG:32633", "EPSG:4326");
+        match c.contours(&values, &[0.5]).unwrap_err().kind() {
+            ErrorKind::Reprojection(_) => {},
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let res = ContourBuilder::new(10, 10, false).contours(&values, &[0.5]).unwrap();
+        let mask = raster::rasterize_feature(&res[0], 10, 10);
+        assert_eq!(mask, values.iter().map(|v| *v >= 0.5).collect::<Vec<_>>());
+        assert_eq!(mask.iter().filter(|inside| **inside).count(), 12);
+
+        // The rasterized contours of pseudo-random grids are the cells above the thresholds.
+        let mut seed = 42u32;
+        let mut random = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as f64 / 65536.
+        };
+        for _ in 0..10 {
+            let values = (0..12 * 9).map(|_| random()).collect::<Vec<_>>();
+            let thresholds = [0.2, 0.5, 0.8];
+            let res = ContourBuilder::new(12, 9, false).contours(&values, &thresholds).unwrap();
+            for (feature, threshold) in res.iter().zip(&thresholds) {
+                let expected = values.iter().map(|v| v >= threshold).collect::<Vec<_>>();
+                assert_eq!(raster::rasterize_feature(feature, 12, 9), expected);
+            }
+        }
+        assert_eq!(raster::rasterize::<Ring>(&[], 2, 2), vec![false; 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "clip")]
+    fn test_clip_to() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let area = |ring: &[Vec<f64>]| {
+            (0..ring.len() - 1).map(|i| ring[i][0] * ring[i + 1][1] - ring[i + 1][0] * ring[i][1]).sum::<f64>().abs() / 2.
+        };
+        let polygons = |c: ContourBuilder| match c.contours(&values, &[0.5]).unwrap()[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => p,
+            _ => panic!(""),
+        };
+
+        // The triangle cuts the upper right corner of the square.
+        let p = polygons(ContourBuilder::new(10, 10, false).clip_to(&[(0., 0.), (12., 0.), (0., 12.)]));
+        assert_eq!(p.len(), 1);
+        assert_eq!(p[0][0].first(), p[0][0].last());
+        assert!((area(&p[0][0]) - 12.625).abs() < 1e-9);
+        // The orientation and the closing of the clipping ring don't matter,
+        // and clipping happens in output coordinates.
+        let c = ContourBuilder::new(10, 10, false).x_origin(10.).clip_to(&[(10., 12.), (22., 0.), (10., 0.), (10., 12.)]);
+        assert!((area(&polygons(c)[0][0]) - 12.625).abs() < 1e-9);
+        // Polygons outside of the region are dropped.
+        assert!(polygons(ContourBuilder::new(10, 10, false).clip_to(&[(0., 0.), (1., 0.), (0., 1.)])).is_empty());
+        let p = polygons(ContourBuilder::new(10, 10, false).clip_to(&[(0., 0.), (20., 0.), (0., 20.)]));
+        assert!((area(&p[0][0]) - 14.5).abs() < 1e-9);
+
+        // The U-shaped region splits the polygon in two, without bridge between them.
+        let u = [(0., 0.), (10., 0.), (10., 10.), (5., 10.), (5., 2.), (4., 2.), (4., 10.), (0., 10.)];
+        let p = polygons(ContourBuilder::new(10, 10, false).clip_to(&u));
+        assert_eq!(p.len(), 2);
+        for polygon in &p {
+            assert_eq!(polygon.len(), 1);
+            assert!((area(&polygon[0]) - 4.75).abs() < 1e-9);
+            assert!(polygon[0].iter().all(|point| point[0] <= 4. || point[0] >= 5.));
+        }
+
+        // The hole of the region makes a hole in the polygon, or a notch when it crosses its boundary.
+        let signed_area = |ring: &[Vec<f64>]| (0..ring.len() - 1).map(|i| ring[i][0] * ring[i + 1][1] - ring[i + 1][0] * ring[i][1]).sum::<f64>();
+        let square = [(0., 0.), (10., 0.), (10., 10.), (0., 10.)];
+        let c = ContourBuilder::new(10, 10, false).clip_to_polygon(&square, &[vec![(4., 4.), (5., 4.), (5., 5.), (4., 5.)]]);
+        let p = polygons(c);
+        assert_eq!((p.len(), p[0].len()), (1, 2));
+        assert!((area(&p[0][0]) - 14.5).abs() < 1e-9);
+        assert!((area(&p[0][1]) - 1.).abs() < 1e-9);
+        // The rings keep the orientation of the unclipped ones.
+        let unclipped = polygons(ContourBuilder::new(10, 10, false));
+        assert_eq!(signed_area(&p[0][0]).signum(), signed_area(&unclipped[0][0]).signum());
+        assert_eq!(signed_area(&p[0][1]).signum(), -signed_area(&unclipped[0][0]).signum());
+        let p = polygons(ContourBuilder::new(10, 10, false).clip_to_polygon(&square, &[vec![(2., 4.), (4., 4.), (4., 5.), (2., 5.)]]));
+        assert_eq!((p.len(), p[0].len()), (1, 1));
+        assert!((area(&p[0][0]) - 13.5).abs() < 1e-9);
+
+        // The rings of the region need 3 distinct positions.
+        let c = ContourBuilder::new(10, 10, false).clip_to(&[(0., 0.), (1., 1.), (0., 0.), (1., 1.)]);
+        assert!(matches!(c.contours(&values, &[0.5]).unwrap_err().kind(), ErrorKind::DegenerateClipRegion { ring: 0 }));
+        let c = ContourBuilder::new(10, 10, false).clip_to_polygon(&square, &[vec![(4., 4.), (5., 5.)]]);
+        assert!(matches!(c.contours(&values, &[0.5]).unwrap_err().kind(), ErrorKind::DegenerateClipRegion { ring: 1 }));
+    }
+
+    #[test]
+    fn test_antimeridian() {
+        // A grid covering longitudes from 0 to 360, with a blob straddling the antimeridian.
+        let mut values = vec![0.; 360 * 5];
+        for y in 1..4 {
+            for x in 178..182 {
+                values[y * 360 + x] = 1.;
+            }
+        }
+        let c = ContourBuilder::new(360, 5, false).y_origin(-2.5).antimeridian(true);
+        let res = c.contours(&values, &[0.5]).unwrap();
+        let p = match res[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => p,
+            _ => panic!(""),
+        };
+        assert_eq!(p.len(), 2);
+        let extent = |ring: &[Vec<f64>]| ring.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| (min.min(p[0]), max.max(p[0])));
+        let mut extents = p.iter().map(|polygon| extent(&polygon[0])).collect::<Vec<_>>();
+        extents.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(extents, vec![(-180., -178.), (178., 180.)]);
+        assert!(p.iter().all(|polygon| polygon[0].first() == polygon[0].last()));
+        // The latitudes of the positions on the antimeridian are interpolated.
+        let mut seam = p.iter().flatten().flatten().filter(|p| p[0].abs() == 180.).map(|p| p[1]).collect::<Vec<_>>();
+        seam.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        seam.dedup();
+        assert_eq!(seam, vec![-1.5, 1.5]);
+
+        // Without the option, the polygon goes beyond 180.
+        let res = ContourBuilder::new(360, 5, false).contours(&values, &[0.5]).unwrap();
+        match res[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => assert_eq!(extent(&p[0][0]), (178., 182.)),
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    fn test_cross_section() {
+        let c = ContourBuilder::new(10, 10, false);
+        let res = c.contours(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], &[0.5, 1.5]).unwrap();
+        let crossings = section::cross_section(&res, &[(0., 5.5), (10., 5.5)]);
+        assert_eq!(crossings, vec![vec![(3., 3., 5.5), (6., 6., 5.5)], vec![]]);
+        // A vertex of the polyline on a ring doesn't duplicate the crossing.
+        let crossings = section::cross_section(&res, &[(0., 5.5), (3., 5.5), (10., 5.5)]);
+        assert_eq!(crossings[0], vec![(3., 3., 5.5), (6., 6., 5.5)]);
+        // The distances accumulate along the segments of the polyline.
+        let crossings = section::cross_section(&res, &[(4.5, 0.), (4.5, 5.), (8., 5.)]);
+        assert_eq!(crossings[0], vec![(3., 4.5, 3.), (6.5, 6., 5.)]);
+        assert!(section::cross_section(&res, &[(0., 1.), (10., 1.)])[0].is_empty());
+    }
+
+    #[test]
+    fn test_extrema() {
+        // A Gaussian bump centered on the cell (10, 10).
+        let values = (0..21 * 21)
+            .map(|i| {
+                let (x, y) = ((i % 21) as f64 - 10., (i / 21) as f64 - 10.);
+                (-(x * x + y * y) / 20.).exp()
+            })
+            .collect::<Vec<_>>();
+        let c = ContourBuilder::new(21, 21, false).y_origin(100.).y_step(-1.);
+        let res = c.extrema(&values, &extrema::ExtremaOptions::default()).unwrap();
+        let maxima = res
+            .iter()
+            .filter(|f| f.properties.as_ref().unwrap()["kind"] == "max")
+            .collect::<Vec<_>>();
+        assert_eq!(maxima.len(), 1);
+        assert_eq!(maxima[0].properties.as_ref().unwrap()["value"], 1.);
+        assert_eq!(maxima[0].geometry.as_ref().unwrap().value, geojson::Value::Point(vec![10.5, 89.5]));
+        // The four corners are minima.
+        assert_eq!(res.len(), 5);
+
+        // A one-cell spike is only kept without prominence filter.
+        let mut values = values;
+        values[17 * 21 + 3] = values[16 * 21 + 4] + 0.001;
+        let res = c.extrema(&values, &extrema::ExtremaOptions::default()).unwrap();
+        assert_eq!(res.len(), 6);
+        let options = extrema::ExtremaOptions { min_prominence: 0.01, ..Default::default() };
+        let res = c.extrema(&values, &options).unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].geometry.as_ref().unwrap().value, geojson::Value::Point(vec![10.5, 89.5]));
+
+        // A plateau is reported once.
+        let res = ContourBuilder::new(4, 4, false)
+            .extrema(&[0., 0., 0., 0., 0., 2., 2., 0., 0., 2., 2., 0., 0., 0., 0., 0.], &extrema::ExtremaOptions::default())
+            .unwrap();
+        assert_eq!(res.len(), 2);
+    }
+
+    #[test]
+    fn test_threshold_count() {
+        assert_eq!(crate::ticks(0., 100., 5.), vec![0., 20., 40., 60., 80., 100.]);
+        assert_eq!(crate::ticks(0., 1., 10.), vec![0., 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.]);
+        assert_eq!(crate::ticks(1., 0., 2.), vec![1., 0.5, 0.]);
+        assert!(crate::ticks(0., 1., 0.).is_empty());
+
+        // As d3-contour does, the maximum (whose contour is empty) isn't used as threshold.
+        let values = (0..=100).map(f64::from).collect::<Vec<_>>();
+        assert_eq!(ThresholdSpec::Count(5).thresholds(&values), vec![0., 20., 40., 60., 80.]);
+        let res = contours().size((101, 1)).thresholds(5).compute(&values).unwrap();
+        assert_eq!(res.len(), 5);
+        let values = [f64::NAN, 0.3, 7.2, f64::INFINITY];
+        assert_eq!(ThresholdSpec::Count(5).thresholds(&values), vec![0., 1., 2., 3., 4., 5., 6.]);
+
+        // Constant and empty grids don't have thresholds.
+        assert!(contours().size((3, 3)).thresholds(5).compute(&[2.; 9]).unwrap().is_empty());
+        assert!(contours().size((0, 0)).thresholds(5).compute(&[]).unwrap().is_empty());
+        assert!(ThresholdSpec::Count(5).thresholds(&[f64::NAN]).is_empty());
+    }
+
+    #[test]
+    fn test_thresholds_jenks() {
+        use crate::{thresholds_jenks, thresholds_jenks_sampled};
+        let values = [22., 1., 11., 2., 21., f64::NAN, 3., 10., 12., 20., f64::INFINITY];
+        assert_eq!(thresholds_jenks(&values, 3), vec![10., 20.]);
+        assert_eq!(thresholds_jenks(&values, 2), vec![20.]);
+        assert_eq!(thresholds_jenks(&[4., 1., 2., 7., 8., 9.], 2), vec![7.]);
+
+        // More classes than distinct values.
+        assert_eq!(thresholds_jenks(&[1., 1., 1., 2., 2., 100.], 5), vec![2., 100.]);
+        assert!(thresholds_jenks(&[1., 1.], 3).is_empty());
+        assert!(thresholds_jenks(&[], 3).is_empty());
+        assert!(thresholds_jenks(&values, 0).is_empty());
+
+        let values = (0..1000).map(|i| if i < 500 { i as f64 } else { 10000. + i as f64 }).collect::<Vec<_>>();
+        let thresholds = thresholds_jenks_sampled(&values, 2, 50);
+        assert_eq!(thresholds.len(), 1);
+        assert!(thresholds[0] > 10000. && thresholds[0] < 10600.);
+    }
+
+    #[test]
+    fn test_thresholds_equal_area() {
+        use crate::thresholds_equal_area;
+        // 90% of zeros and the values 1 to 10.
+        let mut values = vec![0.; 100];
+        for i in 0..10 {
+            values[i * 10 + 5] = (i + 1) as f64;
+        }
+        assert_eq!(thresholds_equal_area(&values, 5, None), vec![0.]);
+        assert_eq!(thresholds_equal_area(&values, 5, Some(0.)), vec![1., 3., 5., 7., 9.]);
+        values[0] = f64::NAN;
+        assert_eq!(thresholds_equal_area(&values, 2, Some(0.)), vec![1., 6.]);
+
+        let res = ContourBuilder::new(10, 10, false)
+            .zonal_stats(true)
+            .contours(&values, &thresholds_equal_area(&values, 5, Some(0.)))
+            .unwrap();
+        let cells = res.iter().map(|f| f.properties.as_ref().unwrap()["cells"].as_u64().unwrap()).collect::<Vec<_>>();
+        assert_eq!(cells, vec![10, 8, 6, 4, 2]);
+
+        assert!(thresholds_equal_area(&[f64::NAN, 0.], 5, Some(0.)).is_empty());
+        assert!(thresholds_equal_area(&values, 0, None).is_empty());
+    }
+
+    #[test]
+    fn test_grid_stats() {
+        use crate::GridStats;
+        let stats = GridStats::compute(&[f64::NAN; 4]);
+        assert_eq!(stats, GridStats { count: 0, min: None, max: None, mean: None, std_dev: None });
+        assert_eq!(stats.histogram(&[f64::NAN; 4], 3), vec![0, 0, 0]);
+
+        let values = [2., f64::NAN, 4., 4., f64::INFINITY, 4., 5., 5., 7., 9.];
+        let stats = GridStats::compute(&values);
+        assert_eq!(stats.count, 8);
+        assert_eq!((stats.min, stats.max, stats.mean, stats.std_dev), (Some(2.), Some(9.), Some(5.), Some(2.)));
+        assert_eq!(stats.histogram(&values, 2), vec![6, 2]);
+        assert_eq!(stats.histogram(&values, 7), vec![1, 0, 3, 2, 0, 1, 1]);
+        assert!(stats.histogram(&values, 0).is_empty());
+        assert_eq!(GridStats::compute(&[3.; 3]).histogram(&[3.; 3], 2), vec![3, 0]);
+
+        let c = ContourBuilder::new(5, 2, false);
+        assert_eq!(c.stats(&values).unwrap(), stats);
+        assert!(c.stats(&values[1..]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "fast-unchecked")]
+    fn test_contours_unchecked() {
+        let values = (0..30 * 20)
+            .map(|i| (((i % 30) as f64 / 3.).sin() + ((i / 30) as f64 / 4.).cos()) * 10.)
+            .collect::<Vec<_>>();
+        let thresholds = [-10., -5., 0., 5., 10.];
+        for smooth in &[false, true] {
+            let c = ContourBuilder::new(30, 20, *smooth);
+            let expected = c.contours(&values, &thresholds).unwrap();
+            assert_eq!(unsafe { c.contours_unchecked(&values, &thresholds) }.unwrap(), expected);
+            assert_eq!(c.contours_trusted(&values, &thresholds).unwrap(), expected);
+        }
+        let res = std::panic::catch_unwind(|| ContourBuilder::new(30, 20, false).contours_trusted(&values[1..], &thresholds));
+        assert!(res.is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_threads() {
+        let mut rng = testing::XorShift::new(0x2545_f491_4f6c_dd1d);
+        let mut random = move || rng.next_f64();
+        for _ in 0..50 {
+            let (dx, dy) = (1 + (random() * 40.) as u32, 1 + (random() * 40.) as u32);
+            let noisy = random() < 0.5;
+            let values = (0..dx * dy)
+                .map(|i| {
+                    if noisy {
+                        random()
+                    } else {
+                        ((i % dx) as f64 / 3.).sin() + ((i / dx) as f64 / 2.7).cos() + random() * 0.3
+                    }
+                })
+                .collect::<Vec<_>>();
+            let thresholds = [0.2, 0.5, 0.8, 1.1];
+            for smooth in &[false, true] {
+                let expected = ContourBuilder::new(dx, dy, *smooth).contours(&values, &thresholds).unwrap();
+                for threads in &[2, 3, 4, 100] {
+                    let c = ContourBuilder::new(dx, dy, *smooth).threads(*threads);
+                    assert_eq!(c.contours(&values, &thresholds).unwrap(), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_boundary_trace() {
+        let same = |values: &[f64], dx: u32, dy: u32, thresholds: &[f64]| {
+            for smooth in &[false, true] {
+                let expected = ContourBuilder::new(dx, dy, *smooth).contours(values, thresholds).unwrap();
+                let c = ContourBuilder::new(dx, dy, *smooth).algorithm(Algorithm::BoundaryTrace);
+                assert_eq!(c.contours(values, thresholds).unwrap(), expected);
+            }
+        };
+        same(&[
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 3., 3., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0., 3., 3., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 1., 0., 0., 3., 3., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 1., 0., 0., 3., 3., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 1., 0., 0., 3., 3., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 1., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 1., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 2., 2., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 2., 2., 2., 2., 0., 0.,
+            0., 0., 1., 1., 0., 0., 0., 0., 2., 2., 2., 2., 0., 0.,
+            0., 1., 1., 1., 0., 0., 0., 0., 0., 2., 2., 0., 0., 0.,
+            0., 1., 1., 1., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ], 14, 17, &[0.5, 1.5, 2.5]);
+        // Saddles, and values on the border of the grid.
+        same(&[1., 0., 1., 0., 1., 0., 1., 0., 1.], 3, 3, &[0.5]);
+        same(&[1.; 12], 4, 3, &[0.5, 1.5]);
+
+        // Randomized blobs.
+        let mut rng = testing::XorShift::new(0x9e37_79b9_7f4a_7c15);
+        let mut random = move || rng.next_f64();
+        for _ in 0..50 {
+            let (dx, dy) = (1 + (random() * 40.) as u32, 1 + (random() * 40.) as u32);
+            let (fx, fy) = (1. + random() * 5., 1. + random() * 5.);
+            let values = (0..dx * dy)
+                .map(|i| ((i % dx) as f64 / fx).sin() + ((i / dx) as f64 / fy).cos() + random() * 0.3)
+                .collect::<Vec<_>>();
+            same(&values, dx, dy, &[-1., -0.2, 0.5, 1.1]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn test_instrument() {
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+        use tracing_subscriber::registry::LookupSpan;
+
+        // Records the spans as `(depth, name, threshold, rings)`, in the order they are created.
+        type Span = (usize, &'static str, Option<f64>, Option<u64>);
+        #[derive(Clone, Default)]
+        struct Recorder {
+            spans: Arc<Mutex<Vec<Span>>>,
+            ids: Arc<Mutex<Vec<u64>>>,
+        }
+        // Records the fields of a span.
+        struct Fields<'a>(&'a mut Span);
+        impl Visit for Fields<'_> {
+            fn record_f64(&mut self, field: &Field, value: f64) {
+                if field.name() == "threshold" {
+                    self.0.2 = Some(value);
+                }
+            }
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                match field.name() {
+                    "rings" => self.0.3 = Some(value),
+                    "dx" | "dy" => assert_eq!(value, 10),
+                    _ => {}
+                }
+            }
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+        }
+        impl<S: tracing::Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Recorder {
+            fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+                let depth = ctx.span(id).unwrap().scope().count() - 1;
+                let mut span = (depth, attrs.metadata().name(), None, None);
+                attrs.record(&mut Fields(&mut span));
+                self.spans.lock().unwrap().push(span);
+                self.ids.lock().unwrap().push(id.into_u64());
+            }
+            fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+                let i = self.ids.lock().unwrap().iter().rposition(|other| *other == id.into_u64()).unwrap();
+                values.record(&mut Fields(&mut self.spans.lock().unwrap()[i]));
+            }
+        }
+
+        let mut values = vec![0.; 100];
+        for (y, x) in (3..8).flat_map(|y| (3..6).map(move |x| (y, x))) {
+            values[y * 10 + x] = if x == 4 && y > 3 && y < 7 { 0. } else { 1. };
+        }
+        let recorder = Recorder::default();
+        let subscriber = tracing_subscriber::registry().with(recorder.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            ContourBuilder::new(10, 10, true).contours(&values, &[0.5, 1.5]).unwrap();
+        });
+
+        let mut expected = vec![(0, "contours", None, None)];
+        for (threshold, rings) in &[(0.5, 2), (1.5, 0)] {
+            let t = Some(*threshold);
+            expected.extend(vec![
+                (1, "threshold", t, Some(*rings)),
+                (2, "classify", t, None),
+                (2, "stitch", t, Some(*rings)),
+                (2, "smooth", t, None),
+                (2, "group_holes", t, Some(*rings / 2)),
+                (2, "georeference", t, None),
+                (2, "feature", t, None),
+            ]);
+        }
+        assert_eq!(*recorder.spans.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_output_limits() {
+        let values = testing::noise(20 * 20, 0x2545_f491_4f6c_dd1d);
+        let unlimited = ContourBuilder::new(20, 20, false).contours(&values, &[0.5, 0.7]).unwrap();
+        let rings = |f: &geojson::Feature| match f.geometry.as_ref().unwrap().value {
+            geojson::Value::MultiPolygon(ref p) => p.iter().map(|p| p.len()).sum::<usize>(),
+            _ => panic!(""),
+        };
+        assert!(rings(&unlimited[0]) > 5);
+
+        let c = ContourBuilder::new(20, 20, false).max_rings_per_threshold(5);
+        for _ in 0..2 {
+            match c.contours(&values, &[0.5, 0.7]).unwrap_err().into_kind() {
+                ErrorKind::OutputLimitExceeded { rings, vertices } => {
+                    assert!(rings > 5);
+                    assert!(vertices > 0);
+                }
+                _ => panic!(""),
+            }
+        }
+        // The builder is still usable.
+        let max = unlimited.iter().map(rings).max().unwrap();
+        let c = ContourBuilder::new(20, 20, false).max_rings_per_threshold(max);
+        assert_eq!(c.contours(&values, &[0.5, 0.7]).unwrap(), unlimited);
+        for algorithm in &[Algorithm::MarchingSquares, Algorithm::BoundaryTrace] {
+            let c = ContourBuilder::new(20, 20, false).algorithm(*algorithm).max_rings_per_threshold(max - 1);
+            assert!(c.contours(&values, &[0.5, 0.7]).is_err());
+        }
+
+        let vertices = unlimited.iter().map(|f| match f.geometry.as_ref().unwrap().value {
+            geojson::Value::MultiPolygon(ref p) => p.iter().flatten().map(|r| r.len()).sum::<usize>(),
+            _ => 0,
+        });
+        let total = vertices.sum::<usize>();
+        let c = ContourBuilder::new(20, 20, false).max_total_vertices(total);
+        assert_eq!(c.contours(&values, &[0.5, 0.7]).unwrap(), unlimited);
+        let c = ContourBuilder::new(20, 20, false).max_total_vertices(total - 1);
+        match c.contours(&values, &[0.5, 0.7]).unwrap_err().into_kind() {
+            ErrorKind::OutputLimitExceeded { vertices, .. } => assert!(vertices >= total - 1),
+            _ => panic!(""),
+        }
+    }
+
+    #[test]
+    fn test_output_limits_early() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let (dx, dy) = (2000, 2000);
+        let values = testing::noise((dx * dy) as usize, 7);
+        let reads = Arc::new(AtomicUsize::new(0));
+        let counter = reads.clone();
+        let c = ContourBuilder::new(dx, dy, false).max_rings_per_threshold(10).value_transform(move |v| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            v
+        });
+        let trace = c.clone().algorithm(Algorithm::BoundaryTrace);
+        #[cfg(feature = "rayon")]
+        let builders = vec![trace, c.threads(4)];
+        #[cfg(not(feature = "rayon"))]
+        let builders = vec![trace];
+        for c in builders {
+            for _ in 0..2 {
+                reads.store(0, Ordering::Relaxed);
+                match c.contours(&values, &[0.5]).unwrap_err().into_kind() {
+                    ErrorKind::OutputLimitExceeded { rings, .. } => assert!(rings > 10),
+                    _ => panic!(""),
+                }
+                // Stopped within the first rows of the grid (or of each band).
+                assert!(reads.load(Ordering::Relaxed) < values.len() / 10);
+            }
+        }
+    }
+
+    // Mostly ordinary values, along with NaN, infinite and arbitrary ones.
+    fn arbitrary_value() -> impl proptest::strategy::Strategy<Value = f64> {
+        use proptest::prelude::*;
+        prop_oneof![
+            6 => -2f64..2.,
+            1 => Just(f64::NAN),
+            1 => Just(f64::INFINITY),
+            1 => Just(f64::NEG_INFINITY),
+            1 => proptest::num::f64::ANY,
+        ]
+    }
+
+    // Grids of up to 9 x 9 values, whose number of values mostly matches the dimensions.
+    fn arbitrary_grid() -> impl proptest::strategy::Strategy<Value = (u32, u32, Vec<f64>)> {
+        use proptest::prelude::*;
+        (0u32..10, 0u32..10, prop_oneof![8 => Just(0i64), 1 => -2i64..3]).prop_flat_map(|(dx, dy, delta)| {
+            let len = (i64::from(dx * dy) + delta).max(0) as usize;
+            (Just(dx), Just(dy), proptest::collection::vec(arbitrary_value(), len))
+        })
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_entry_points_arbitrary(
+            (dx, dy, values) in arbitrary_grid(),
+            thresholds in proptest::collection::vec(arbitrary_value(), 0..4),
+            smooth: bool,
+        ) {
+            use proptest::prop_assert_eq;
+            let valid = values.len() == dx as usize * dy as usize;
+            let n = thresholds.len();
+            let c = ContourBuilder::new(dx, dy, smooth);
+            let len = |result: crate::Result<usize>| result.ok();
+            let expected = if valid { Some(n) } else { None };
+
+            prop_assert_eq!(len(c.contours(&values, &thresholds).map(|f| f.len())), expected);
+            prop_assert_eq!(len(c.contours_geometries(&values, &thresholds).map(|g| g.len())), expected);
+            prop_assert_eq!(len(c.contours_ewkb(&values, &thresholds, 4326).map(|g| g.len())), expected);
+            prop_assert_eq!(len(c.contours_with_diagnostics(&values, &thresholds).map(|(f, _)| f.len())), expected);
+            let cache = crate::cache::ContourCache::new(4);
+            prop_assert_eq!(len(c.contours_cached(&values, &thresholds, &cache).map(|f| f.len())), expected);
+            let named = thresholds.iter().map(|t| (*t, "name")).collect::<Vec<_>>();
+            prop_assert_eq!(len(c.contours_named(&values, &named).map(|f| f.len())), expected);
+            let props = c.contours_with_props(&values, &thresholds, |i, _| [("i", i)].iter().cloned().collect::<std::collections::BTreeMap<_, _>>());
+            prop_assert_eq!(len(props.map(|f| f.len())), expected);
+            prop_assert_eq!(len(c.contours_geoarrow(&values, &thresholds).map(|a| a.len())), expected);
+            // The positions interpolated with infinite values aren't finite, and can't be encoded.
+            match c.contours_encoded_polylines(&values, &thresholds, 5) {
+                Ok(encoded) => prop_assert_eq!(Some(encoded.len()), expected),
+                Err(err) => {
+                    let out_of_range = matches!(err.kind(), ErrorKind::PolylineOutOfRange { .. });
+                    proptest::prop_assert!(!valid || out_of_range, "{}", err);
+                }
+            }
+            prop_assert_eq!(len(c.contours_mesh(&values, &thresholds).map(|m| m.len())), expected);
+            prop_assert_eq!(len(c.contours_source(&values[..], &thresholds).map(|f| f.len())), expected);
+            // The values of a strided grid may be followed by padding.
+            let strided = values.len() >= dx as usize * dy as usize;
+            prop_assert_eq!(len(c.contours_strided(&values, dx as usize, &thresholds).map(|f| f.len())), strided.then_some(n));
+            prop_assert_eq!(len(c.contours_window(&values, &thresholds, 0, 0, dx, dy).map(|f| f.len())), expected);
+            prop_assert_eq!(len(c.contours_padded(&values, &thresholds, 1, crate::pad::PadMode::Edge).map(|f| f.len())), expected);
+            prop_assert_eq!(len(c.contours_blended(&values, &values, 0.5, &thresholds).map(|f| f.len())), expected);
+            prop_assert_eq!(len(c.contours_sequence(&values, &values, &thresholds, 2).map(|s| s.len())), valid.then_some(2));
+            prop_assert_eq!(len(c.contours_vs_grid(&values, &values, &thresholds).map(|f| f.len())), expected);
+            prop_assert_eq!(len(c.contours_resampled(&values, &thresholds, dx + 1, dy + 1).map(|f| f.len())), expected);
+            let mut transposed = values.clone();
+            if valid {
+                transposed = (0..values.len()).map(|i| values[(i % dy as usize) * dx as usize + i / dy as usize]).collect();
+            }
+            // Compared by their debug output, the positions interpolated with infinite values being NaN.
+            let column_major = c.contours_column_major(&transposed, &thresholds).ok();
+            prop_assert_eq!(format!("{:?}", column_major), format!("{:?}", c.contours(&values, &thresholds).ok()));
+            // The Features of the same thresholds are merged by the banded and exploded
+            // outputs, whose length varies.
+            prop_assert_eq!(c.contours_exploded(&values, &thresholds).is_ok(), valid);
+            prop_assert_eq!(c.contours_banded(&values, &thresholds).is_ok(), valid);
+            prop_assert_eq!(c.contours_kml(&values, &thresholds, &kml::KmlOptions::default()).is_ok(), valid);
+            prop_assert_eq!(c.capacity_hint_for(&values, &thresholds).is_ok(), valid);
+            prop_assert_eq!(c.extrema(&values, &extrema::ExtremaOptions::default()).is_ok(), valid);
+            let mut computed = ContourBuilder::new(dx, dy, smooth);
+            if computed.set_thresholds(&thresholds).is_ok() {
+                prop_assert_eq!(len(computed.compute(&values).map(|f| f.len())), expected);
+            }
+            for threshold in &thresholds {
+                prop_assert_eq!(contour_rings(&values, *threshold, dx, dy).is_ok(), valid);
+                prop_assert_eq!(contour_rings_smooth(&values, *threshold, dx, dy).is_ok(), valid);
+                prop_assert_eq!(contour_rings_with_directions(&values, *threshold, dx, dy, true).is_ok(), valid);
+            }
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_inputs() {
+        let mut rng = testing::XorShift::new(0x9e37_79b9_7f4a_7c15);
+        let mut next = move || rng.next_u64();
+        let special = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, f64::MAX, f64::MIN, f64::MIN_POSITIVE, 0., -0.];
+        let mut value = || match next() % 4 {
+            0 => special[(next() % 8) as usize],
+            1 => (next() % 3) as f64,
+            _ => f64::from_bits(next()),
+        };
+        for _ in 0..2000 {
+            let (dx, dy) = ((value().to_bits() % 9) as u32, (value().to_bits() % 9) as u32);
+            let values = (0..dx * dy).map(|_| value()).collect::<Vec<_>>();
+            let thresholds = (0..value().to_bits() % 4).map(|_| value()).collect::<Vec<_>>();
+            let smooth = value().to_bits() % 2 == 0;
+            let expected = ContourBuilder::new(dx, dy, smooth).zonal_stats(true).bbox(true).contours(&values, &thresholds).unwrap();
+            assert_eq!(expected.len(), thresholds.len());
+            for algorithm in &[Algorithm::MarchingSquares, Algorithm::BoundaryTrace] {
+                let c = ContourBuilder::new(dx, dy, smooth).algorithm(*algorithm).zonal_stats(true).bbox(true);
+                #[cfg(feature = "rayon")]
+                let c = c.threads(3);
+                let features = c.contours(&values, &thresholds).unwrap();
+                if !smooth {
+                    assert_eq!(features, expected);
+                }
+            }
+            for threshold in &thresholds {
+                contour_rings(&values, *threshold, dx, dy).unwrap();
+            }
+            let mut longer = values.clone();
+            longer.push(value());
+            assert!(ContourBuilder::new(dx, dy, smooth).contours(&longer, &thresholds).is_err());
+            assert!(contour_rings(&longer, 0., dx, dy).is_err());
+        }
+
+        // Dimensions whose product (or the number of edges) overflows, or empty grids.
+        for (dx, dy) in &[(u32::MAX, u32::MAX), (1 << 16, 1 << 16), (u32::MAX, 2)] {
+            assert!(ContourBuilder::new(*dx, *dy, true).contours(&[], &[0.]).is_err());
+            assert!(contour_rings(&[1.], 0., *dx, *dy).is_err());
+        }
+        for (dx, dy) in &[(u32::MAX, 0), (0, u32::MAX), (0, 0)] {
+            for algorithm in &[Algorithm::MarchingSquares, Algorithm::BoundaryTrace] {
+                let c = ContourBuilder::new(*dx, *dy, true).algorithm(*algorithm);
+                #[cfg(feature = "rayon")]
+                let c = c.threads(2);
+                let features = c.contours(&[], &[0.]).unwrap();
+                assert_eq!(features[0].geometry.as_ref().unwrap().value, geojson::Value::MultiPolygon(vec![]));
+            }
+            assert!(contour_rings(&[], 0., *dx, *dy).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_ring() {
+        let mut square = Ring::new(vec![vec![0., 0.], vec![2., 0.], vec![2., 1.], vec![0., 1.]]);
+        assert_eq!(square.len(), 4);
+        assert!(!square.is_closed());
+        assert_eq!(square.signed_area(), 2.);
+        assert_eq!(square.orientation(), Winding::CounterClockwise);
+        square.close();
+        assert!(square.is_closed());
+        assert_eq!(square.len(), 5);
+        square.close();
+        assert_eq!(square.len(), 5);
+        assert_eq!(square.signed_area(), 2.);
+        assert_eq!(square.bbox(), Some(vec![0., 0., 2., 1.]));
+        square.reverse();
+        assert_eq!(square.signed_area(), -2.);
+        assert_eq!(square.orientation(), Winding::Clockwise);
+        assert_eq!(square.iter_points().nth(1), Some(&vec![0., 1.]));
+        assert_eq!(square, vec![vec![0., 0.], vec![0., 1.], vec![2., 1.], vec![2., 0.], vec![0., 0.]]);
+        assert_eq!(square.clone().into_inner(), Vec::<Vec<f64>>::from(square));
+
+        let empty = Ring::default();
+        assert!(empty.is_empty());
+        assert!(!empty.is_closed());
+        assert_eq!(empty.bbox(), None);
+        assert_eq!(empty.orientation(), Winding::Degenerate);
+        let mut point = vec![vec![1., 1.]].into_iter().collect::<Ring>();
+        assert!(!point.is_closed());
+        point.close();
+        assert!(point.is_closed());
+        assert_eq!(point.orientation(), Winding::Degenerate);
+        assert_eq!(Ring::new(vec![vec![0., 0.], vec![f64::NAN, 1.], vec![1., 0.]]).orientation(), Winding::Degenerate);
+
+        // Far from the origin.
+        let mut far = Ring::new(vec![vec![1001., 0.5], vec![1000.5, 1.], vec![1000., 0.5], vec![1000.5, 0.], vec![1001., 0.5]]);
+        assert_eq!(far.signed_area(), 0.5);
+        far.reverse();
+        assert_eq!(far.signed_area(), -0.5);
+
+        // Exterior rings are clockwise, holes counterclockwise.
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let rings = contour_rings(&values, 0.5, 5, 5).unwrap();
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0].orientation(), Winding::CounterClockwise);
+        assert_eq!(rings[0].signed_area(), 0.5);
+        assert_eq!(rings[1].orientation(), Winding::Clockwise);
+        assert_eq!(rings[1].signed_area(), -8.5);
+        assert_eq!(rings[1].bbox(), Some(vec![1., 1., 4., 4.]));
+        assert!(rings.iter().all(|ring| ring.is_closed()));
+    }
+
+    #[test]
+    fn test_geom() {
+        let square = vec![vec![0., 0.], vec![0., 2.], vec![2., 2.], vec![2., 0.], vec![0., 0.]];
+        assert_eq!(geom::area(&square), 8.);
+        assert_eq!(geom::area(&square[..4]), 8.);
+        assert_eq!(geom::ring_contains(&square, &[1., 1.]), 1);
+        assert_eq!(geom::ring_contains(&square, &[0., 1.]), 0);
+        assert_eq!(geom::ring_contains(&square, &[2., 2.]), 0);
+        assert_eq!(geom::ring_contains(&square, &[3., 1.]), -1);
+        assert!(geom::point_in_ring(&square, &[0., 1.]));
+        assert!(!geom::point_in_ring(&square, &[-1., 1.]));
+        assert_eq!(geom::ring_bbox(&square), Some(vec![0., 0., 2., 2.]));
+        let hole = vec![vec![0., 0.], vec![1., 1.], vec![1., 0.], vec![0., 0.]];
+        assert_eq!(geom::contains(&square, &hole), 1);
+        assert_eq!(geom::contains(&hole, &square), -1);
+
+        // Degenerate rings.
+        let empty: Vec<Vec<f64>> = Vec::new();
+        let point = vec![vec![1., 1.]];
+        let segment = vec![vec![0., 0.], vec![2., 2.]];
+        assert_eq!(geom::area(&empty), 0.);
+        assert_eq!(geom::area(&point), 0.);
+        assert_eq!(geom::area(&segment), 0.);
+        assert_eq!(geom::ring_contains(&empty, &[1., 1.]), -1);
+        assert_eq!(geom::ring_contains(&point, &[1., 1.]), 0);
+        assert_eq!(geom::ring_contains(&point, &[0., 0.]), -1);
+        assert_eq!(geom::ring_contains(&segment, &[1., 1.]), 0);
+        assert_eq!(geom::ring_contains(&segment, &[1., 0.]), -1);
+        assert!(!geom::point_in_ring(&empty, &[0., 0.]));
+        assert_eq!(geom::contains(&square, &empty), 0);
+        assert_eq!(geom::contains(&empty, &point), -1);
+        assert_eq!(geom::ring_bbox(&empty), None);
+        assert_eq!(geom::ring_bbox(&point), Some(vec![1., 1., 1., 1.]));
+        assert_eq!(geom::ring_bbox(&segment), Some(vec![0., 0., 2., 2.]));
+    }
+
+    #[test]
+    fn test_geom_exact() {
+        // Vertices of smoothed rings (interpolated on the edges of the grid), the points
+        // being the nearest to the segment between them: the rounding errors of the
+        // floating-point computation made them lie on both triangles.
+        let cases = [
+            (3.249105407359684, 3.0289954729746302, [2.729646890416748, 2.8668258180507125], 1),
+            (2.9945132660999327, 2.6399776298650415, [2.7433790194975614, 2.571086417750902], -1),
+            (3.411140384072209, 2.9103738821766907, [3.2983394176414285, 2.5508050914180678], 1),
+        ];
+        for (x, y, point, inside) in cases.iter() {
+            let (a, b) = (vec![*x, 2.5], vec![2.5, *y]);
+            let (first, second) = (vec![a.clone(), b.clone(), vec![2.5, 2.5], a.clone()], vec![a.clone(), vec![3.5, 3.5], b, a]);
+            assert_eq!(geom::ring_contains(&first, point), *inside);
+            assert_eq!(geom::ring_contains(&second, point), -*inside);
+            assert!(geom::orient2d(&first[0], &first[1], point) != 0.);
+        }
+
+        assert_eq!(geom::orient2d(&[0.5, 0.5], &[12., 12.], &[24., 24.]), 0.);
+        assert!(geom::orient2d(&[0.5, 0.5], &[12., 12.], &[24.000000000000004, 24.]) < 0.);
+        assert!(geom::orient2d(&[0.5, 0.5], &[12., 12.], &[24., 24.000000000000004]) > 0.);
+        // The decimal values aren't exactly on the line: (0.3, 0.5) is slightly above it.
+        let (a, b, c) = ([0.1, 0.7], [0.7, 0.1], [0.3, 0.5]);
+        assert!(geom::orient2d(&a, &b, &c) > 0.);
+        assert!(geom::orient2d(&b, &a, &c) < 0.);
+        assert_eq!(geom::ring_contains(&[a.to_vec(), b.to_vec(), vec![0.7, 0.7]], &c), 1);
+    }
+
+    #[test]
+    fn test_normalize_rings() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(10, 10, false).normalize_rings(true);
+        let res = c.contours(&values, &[0.5]).unwrap();
+        match res[0].geometry.as_ref().unwrap().value {
+            geojson::Value::MultiPolygon(ref p) => {
+                assert_eq!(
+                    p,
+                    &vec![vec![vec![
+                        vec![3., 3.5], vec![3., 4.5], vec![3., 5.5], vec![3., 6.5],
+                        vec![3., 7.5], vec![3.5, 8.], vec![4.5, 8.], vec![5.5, 8.],
+                        vec![6., 7.5], vec![6., 6.5], vec![6., 5.5], vec![6., 4.5],
+                        vec![6., 3.5], vec![5.5, 3.], vec![4.5, 3.], vec![3.5, 3.],
+                        vec![3., 3.5]]]]);
+            }
+            _ => panic!(""),
+        };
+        let serialized = serde_json::to_string(&res).unwrap();
+        assert_eq!(serde_json::to_string(&c.contours(&values, &[0.5]).unwrap()).unwrap(), serialized);
+        // The boundary tracing gives the same rings, normalized the same way.
+        let c = c.algorithm(Algorithm::BoundaryTrace);
+        assert_eq!(serde_json::to_string(&c.contours(&values, &[0.5]).unwrap()).unwrap(), serialized);
+
+        // The same cycle, from any starting position and in either orientation.
+        let mut rings = contour_rings(&values, 0.5, 10, 10).unwrap();
+        let mut ring = rings.pop().unwrap();
+        let mut rotated = ring.clone().into_inner();
+        rotated.pop();
+        rotated.rotate_left(5);
+        rotated.push(rotated[0].clone());
+        let mut rotated = Ring::new(rotated);
+        ring.normalize();
+        rotated.normalize();
+        assert_eq!(ring, rotated);
+        assert_eq!(ring.iter_points().next(), Some(&vec![3., 3.5]));
+        assert_eq!(ring.orientation(), Winding::Clockwise);
+        ring.reverse();
+        ring.normalize();
+        assert_eq!(ring.iter_points().take(2).collect::<Vec<_>>(), [&vec![3., 3.5], &vec![3.5, 3.]]);
+        assert!(ring.is_closed());
+
+        // The smallest position appearing twice, and rings which aren't closed.
+        let mut ring = Ring::new(vec![vec![0., 0.], vec![1., 1.], vec![0., 0.], vec![1., 0.], vec![0., 0.]]);
+        ring.normalize();
+        assert_eq!(ring, vec![vec![0., 0.], vec![1., 0.], vec![0., 0.], vec![1., 1.], vec![0., 0.]]);
+        let mut open = Ring::new(vec![vec![1., 1.], vec![0., 0.]]);
+        open.normalize();
+        assert_eq!(open, vec![vec![1., 1.], vec![0., 0.]]);
+    }
+
+    #[test]
+    fn test_merge_collinear() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        for smooth in &[false, true] {
+            let c = ContourBuilder::new(10, 10, *smooth).merge_collinear(true);
+            match c.contours(&values, &[0.5]).unwrap()[0].clone().geometry.unwrap().value {
+                geojson::Value::MultiPolygon(p) => {
+                    assert_eq!(
+                        p,
+                        vec![vec![vec![
+                            vec![6., 7.5], vec![6., 3.5], vec![5.5, 3.], vec![3.5, 3.],
+                            vec![3., 3.5], vec![3., 7.5], vec![3.5, 8.], vec![5.5, 8.],
+                            vec![6., 7.5]]]]);
+                }
+                _ => panic!(""),
+            };
+        }
+
+        // Holes keep their orientation, and the area doesn't change.
+        let values = [
+            0., 0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 1., 0., 0., 1., 0.,
+            0., 1., 0., 0., 1., 0.,
+            0., 1., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0., 0.,
+        ];
+        let rings = contour_rings(&values, 0.5, 6, 6).unwrap();
+        for ring in rings {
+            let mut merged = ring.clone().into_inner();
+            geom::merge_collinear(&mut merged);
+            let merged = Ring::new(merged);
+            assert_eq!(merged.len(), 9);
+            assert!(merged.is_closed());
+            assert_eq!(merged.signed_area(), ring.signed_area());
+        }
+
+        // Collinear positions around the start, degenerate and open rings.
+        let mut ring = vec![vec![1., 0.], vec![2., 0.], vec![2., 2.], vec![0., 2.], vec![0., 0.], vec![1., 0.]];
+        geom::merge_collinear(&mut ring);
+        assert_eq!(ring, vec![vec![2., 0.], vec![2., 2.], vec![0., 2.], vec![0., 0.], vec![2., 0.]]);
+        let mut flat = vec![vec![0., 0.], vec![1., 1.], vec![2., 2.], vec![0., 0.]];
+        geom::merge_collinear(&mut flat);
+        assert_eq!(flat.len(), 4);
+        let mut open = vec![vec![0., 0.], vec![1., 0.], vec![2., 0.]];
+        geom::merge_collinear(&mut open);
+        assert_eq!(open.len(), 3);
+    }
+
+    #[test]
+    fn test_densify() {
+        let values = testing::noise(12 * 12, 0x853c_49e6_748f_ea9b);
+        let multipolygon = |c: ContourBuilder| match c.contours(&values, &[0.5]).unwrap()[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => p,
+            _ => panic!(""),
+        };
+        let segments = |ring: &[Vec<f64>]| ring.windows(2).map(|s| (s[1][0] - s[0][0]).hypot(s[1][1] - s[0][1])).collect::<Vec<_>>();
+        for (smooth, merge) in &[(false, false), (true, false), (false, true), (true, true)] {
+            let c = || ContourBuilder::new(12, 12, *smooth).merge_collinear(*merge);
+            let expected = multipolygon(c());
+            let densified = multipolygon(c().densify(0.3));
+            assert_eq!(densified.len(), expected.len());
+            let positions = |p: &Vec<Vec<Vec<Vec<f64>>>>| p.iter().flatten().map(|ring| ring.len()).sum::<usize>();
+            assert!(positions(&densified) > positions(&expected));
+            for (polygon, expected) in densified.iter().zip(&expected) {
+                for (ring, expected) in polygon.iter().zip(expected) {
+                    assert!(segments(ring).iter().all(|length| *length <= 0.3 + 1e-12));
+                    assert_eq!(ring.first(), ring.last());
+                    assert!((geom::area(ring) - geom::area(expected)).abs() < 1e-9);
+                }
+            }
+        }
+
+        // The maximum length is in grid coordinates.
+        let scaled = multipolygon(ContourBuilder::new(12, 12, false).x_step(10.).y_step(10.).densify(0.3));
+        assert!(scaled.iter().flatten().flat_map(|ring| segments(ring)).all(|length| length <= 3. + 1e-9));
+        assert!(scaled.iter().flatten().flat_map(|ring| segments(ring)).any(|length| length > 2.));
+
+        // Disabled for lengths which aren't positive.
+        assert_eq!(multipolygon(ContourBuilder::new(12, 12, false).densify(0.)), multipolygon(ContourBuilder::new(12, 12, false)));
+        assert_eq!(multipolygon(ContourBuilder::new(12, 12, false).densify(f64::NAN)), multipolygon(ContourBuilder::new(12, 12, false)));
+    }
+
+    #[test]
+    fn test_validate_output() {
+        // The value in the middle of the grid is the threshold: the smoothed ring goes
+        // through its position twice, touching itself.
+        let values = vec![
+            0., 0., 2.,
+            1., 1., 2.,
+            2., 0., 1.,
+        ];
+        let multipolygon = |c: ContourBuilder| match c.contours(&values, &[1.]).unwrap()[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => p,
+            _ => panic!(""),
+        };
+        let smoothed = multipolygon(ContourBuilder::new(3, 3, true));
+        assert_eq!(smoothed[0][0].iter().filter(|point| **point == vec![1.5, 1.5]).count(), 2);
+
+        let err = ContourBuilder::new(3, 3, true).validate_output(Validation::Check).contours(&values, &[1.]).unwrap_err();
+        match err.kind() {
+            ErrorKind::InvalidGeometry(intersections) => {
+                let segment = |segment| SegmentId { polygon: 0, ring: 0, segment };
+                let pairs = intersections.iter().map(|i| (i.first.segment, i.second.segment)).collect::<Vec<_>>();
+                assert_eq!(pairs, vec![(4, 10), (4, 11), (5, 10), (5, 11)]);
+                assert_eq!(intersections[0], Intersection { threshold: 1., first: segment(4), second: segment(10) });
+            }
+            _ => panic!("unexpected error kind"),
+        }
+        assert!(ContourBuilder::new(3, 3, false).validate_output(Validation::Check).contours(&values, &[1.]).is_ok());
+
+        // The positions of the intersecting segments are moved back to the middle of their edges.
+        let repaired = multipolygon(ContourBuilder::new(3, 3, true).validate_output(Validation::Repair));
+        assert_eq!(
+            repaired,
+            vec![vec![vec![
+                vec![3., 2.5], vec![3., 1.5], vec![3., 0.5], vec![2.5, 0.], vec![2., 0.5],
+                vec![1.5, 1.], vec![0.5, 1.], vec![0., 1.5], vec![0., 2.5], vec![0.5, 3.],
+                vec![1., 2.5], vec![1.5, 2.], vec![2., 2.5], vec![2.5, 3.], vec![3., 2.5],
+            ]]]
+        );
+
+        // On a grid with many values equal to the thresholds, the repaired polygons are valid:
+        // closed simple rings, without intersections between them.
+        let values = (0..400).map(|i| ((i * 7919) % 13 % 4) as f64).collect::<Vec<_>>();
+        let thresholds = [1., 2., 3.];
+        let c = || ContourBuilder::new(20, 20, true);
+        assert!(c().validate_output(Validation::Check).contours(&values, &thresholds).is_err());
+        for feature in c().validate_output(Validation::Repair).contours(&values, &thresholds).unwrap() {
+            let polygons = match feature.geometry.unwrap().value {
+                geojson::Value::MultiPolygon(p) => p,
+                _ => panic!(""),
+            };
+            let rings = polygons.iter().flatten().collect::<Vec<_>>();
+            assert!(validate::find_intersections(&rings).is_empty());
+            for ring in rings {
+                assert!(ring.len() >= 4);
+                assert_eq!(ring.first(), ring.last());
+                assert_ne!(geom::area(ring), 0.);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplify_preserving_topology() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let exterior = |feature: &geojson::Feature| match feature.geometry.clone().unwrap().value {
+            geojson::Value::MultiPolygon(p) => p[0][0].clone(),
+            _ => panic!(""),
+        };
+        let mut res = ContourBuilder::new(10, 10, true).contours(&values, &[0.5, 1.5]).unwrap();
+        let inner = exterior(&res[1]);
+        simplify::simplify_preserving_topology(&mut res, 100.);
+        let (simplified_outer, simplified_inner) = (exterior(&res[0]), exterior(&res[1]));
+        assert_eq!(simplified_outer.len(), 5);
+        assert_eq!(simplified_inner.len(), 4);
+        assert_eq!(simplified_outer.first(), simplified_outer.last());
+        // The 1.5 contour remains strictly inside the 0.5 contour, before and after simplification.
+        assert!(inner.iter().chain(&simplified_inner).all(|point| geom::ring_contains(&simplified_outer, point) == 1));
+
+        // Simplified on its own, the 0.5 contour crosses the 1.5 contour.
+        let mut alone = ContourBuilder::new(10, 10, true).contours(&values, &[0.5]).unwrap();
+        simplify::simplify_preserving_topology(&mut alone, 100.);
+        assert!(inner.iter().any(|point| geom::ring_contains(&exterior(&alone[0]), point) == -1));
+
+        // The positions shared by the rings are kept: the contours of a cliff stay identical.
+        let mut cliff = vec![0.; 100];
+        for i in &[33, 34, 35, 43, 44, 45, 53, 54, 55] {
+            cliff[*i] = 2.;
+        }
+        let mut res = ContourBuilder::new(10, 10, false).contours(&cliff, &[0.5, 1.5]).unwrap();
+        simplify::simplify_preserving_topology(&mut res, 100.);
+        assert_eq!(exterior(&res[0]), exterior(&res[1]));
+
+        // Nothing is removed without a positive tolerance.
+        let expected = ContourBuilder::new(10, 10, true).contours(&values, &[0.5, 1.5]).unwrap();
+        let mut res = expected.clone();
+        simplify::simplify_preserving_topology(&mut res, 0.);
+        simplify::simplify_preserving_topology(&mut res, f64::NAN);
+        assert_eq!(res, expected);
+    }
+
+    #[test]
+    fn test_contours_window() {
+        let (dx, dy) = (12, 10);
+        let values = (0..dx * dy).map(|i| ((i * 37 % 11) as f64 / 3.).sin() * 2. + (i % dx) as f64 / 4.).collect::<Vec<f64>>();
+        let thresholds = [0.5, 1.5, 2.5];
+        let (x, y, width, height) = (3, 2, 6, 5);
+        let window = (y..y + height).flat_map(|row| values[(row * dx + x) as usize..(row * dx + x + width) as usize].to_vec()).collect::<Vec<_>>();
+        for smooth in &[false, true] {
+            let c = ContourBuilder::new(dx, dy, *smooth).x_origin(10.).x_step(2.).bbox(true).zonal_stats(true);
+            let res = c.contours_window(&values, &thresholds, x, y, width, height).unwrap();
+            let expected = ContourBuilder::new(width, height, *smooth)
+                .x_origin(10. + x as f64 * 2.)
+                .y_origin(y as f64)
+                .x_step(2.)
+                .bbox(true)
+                .zonal_stats(true)
+                .contours(&window, &thresholds)
+                .unwrap();
+            assert_eq!(res, expected);
+            assert_eq!(c.contours_window(&values, &thresholds, 0, 0, dx, dy).unwrap(), c.contours(&values, &thresholds).unwrap());
+        }
+
+        let c = ContourBuilder::new(dx, dy, false);
+        assert_eq!(c.contours_window(&values, &thresholds, 0, 0, 0, 0).unwrap(), ContourBuilder::new(0, 0, false).contours(&[], &thresholds).unwrap());
+        match c.contours_window(&values, &thresholds, 7, 2, 6, 5).unwrap_err().kind() {
+            ErrorKind::InvalidWindow { x: 7, y: 2, width: 6, height: 5 } => (),
+            _ => panic!("unexpected error kind"),
+        }
+        assert!(c.contours_window(&values, &thresholds, 0, u32::MAX, 1, 2).is_err());
+    }
+
+    #[test]
+    fn test_resample_bilinear() {
+        // The value at each position is `x + 2 * y`.
+        let values = resample::resample_bilinear(&[0., 1., 2., 3.], 2, 2, 4, 4).unwrap();
+        let positions = [0., 0.25, 0.75, 1.];
+        let expected = positions.iter().flat_map(|y| positions.iter().map(move |x| x + 2. * y)).collect::<Vec<_>>();
+        assert_eq!(values, expected);
+        assert_eq!(resample::resample_bilinear(&values, 4, 4, 4, 4).unwrap(), values);
+        assert_eq!(resample::resample_bilinear(&[0., 4.], 2, 1, 4, 1).unwrap(), vec![0., 1., 3., 4.]);
+        assert!(resample::resample_bilinear(&[0., 1., 2.], 2, 2, 4, 4).is_err());
+
+        // A NaN value only poisons the quarter of the cells nearest to it.
+        let nan = |mode| {
+            let values = resample::resample_bilinear_with(&[f64::NAN, 1., 2., 3.], 2, 2, 4, 4, mode).unwrap();
+            (values.iter().filter(|v| v.is_nan()).count(), values[2])
+        };
+        assert_eq!(nan(resample::NanMode::Quadrant), (4, 1.));
+        assert_eq!(nan(resample::NanMode::Propagate).0, 9);
+        // Only the resampled values at the NaN one are NaN.
+        assert_eq!(nan(resample::NanMode::Ignore), (1, 1.));
+
+        // Contours of the upsampled grid, in the coordinates of the original grid.
+        let values = (0..100).map(|i| 5. - ((i % 10) as f64 - 4.3).hypot((i / 10) as f64 - 4.6)).collect::<Vec<_>>();
+        let c = ContourBuilder::new(10, 10, true).x_origin(100.).y_step(2.);
+        let ring = |features: Vec<geojson::Feature>| match features[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => p[0][0].clone(),
+            _ => panic!(""),
+        };
+        let expected = ring(c.contours(&values, &[1.5]).unwrap());
+        let upsampled = ring(c.contours_resampled(&values, &[1.5], 40, 40).unwrap());
+        assert!(upsampled.len() > 3 * expected.len());
+        assert!((geom::area(&upsampled) / geom::area(&expected) - 1.).abs() < 0.01);
+        let (bbox, expected_bbox) = (geom::ring_bbox(&upsampled).unwrap(), geom::ring_bbox(&expected).unwrap());
+        assert!(bbox.iter().zip(&expected_bbox).all(|(a, b)| (a - b).abs() < 0.1));
+    }
+
+    #[test]
+    fn test_gradient() {
+        // The gradient of a linear ramp is constant, on the border of the grid too.
+        let values = (0..30).map(|i| 3. * (i % 6) as f64 + 4. * (i / 6) as f64).collect::<Vec<_>>();
+        let magnitude = gradient::gradient_magnitude(&values, 6, 5, 2., 0.5).unwrap();
+        assert_eq!(magnitude, vec![1.5f64.hypot(8.); 30]);
+        let aspect = gradient::aspect(&values, 6, 5, 2., 0.5).unwrap();
+        assert!(aspect.iter().all(|a| *a == aspect[0] && *a > 270. && *a < 360.));
+        assert_eq!(gradient::aspect(&[0., 0., 1., 1.], 2, 2, 1., 1.).unwrap(), vec![0.; 4]);
+        assert_eq!(gradient::aspect(&[1., 0., 1., 0.], 2, 2, 1., 1.).unwrap(), vec![90.; 4]);
+        assert_eq!(gradient::aspect(&[1., 1., 0., 0.], 2, 2, 1., 1.).unwrap(), vec![180.; 4]);
+        assert!(gradient::aspect(&[1.; 4], 2, 2, 1., 1.).unwrap().iter().all(|a| a.is_nan()));
+        assert!(gradient::gradient_magnitude(&values, 6, 4, 1., 1.).is_err());
+
+        // A NaN value only gives NaN gradients where the differences use it.
+        let nan_at = |i: usize| {
+            let mut values = values.clone();
+            values[i] = f64::NAN;
+            let magnitude = gradient::gradient_magnitude(&values, 6, 5, 2., 0.5).unwrap();
+            (0..30).filter(|j| magnitude[*j].is_nan()).collect::<Vec<_>>()
+        };
+        assert_eq!(nan_at(14), vec![8, 13, 14, 15, 20]);
+        assert_eq!(nan_at(0), vec![0, 1, 6]);
+    }
+
+    #[test]
+    fn test_sample_bilinear() {
+        // The smoothed vertices of the contours of a linear ramp sample to their threshold.
+        let values = (0..80).map(|i| (i % 10) as f64 + 0.3 * (i / 10) as f64).collect::<Vec<_>>();
+        let features = ContourBuilder::new(10, 8, true).contours(&values, &[2.7, 5.2]).unwrap();
+        let mut sampled = 0;
+        for (feature, threshold) in features.iter().zip(&[2.7, 5.2]) {
+            let polygons = match feature.geometry.clone().unwrap().value {
+                geojson::Value::MultiPolygon(p) => p,
+                _ => panic!(""),
+            };
+            for p in polygons.iter().flatten().flatten().filter(|p| p[0] > 0. && p[0] < 10. && p[1] > 0. && p[1] < 8.) {
+                let value = resample::sample_bilinear(&values, 10, 8, p[0], p[1]).unwrap();
+                assert!((value - threshold).abs() < 1e-9);
+                sampled += 1;
+            }
+        }
+        assert!(sampled > 10);
+
+        assert_eq!(resample::sample_bilinear(&values, 10, 8, 0., 0.), Some(0.));
+        assert_eq!(resample::sample_bilinear(&values, 10, 8, 10., 8.), Some(9. + 0.3 * 7.));
+        assert_eq!(resample::sample_bilinear(&values, 10, 8, -0.1, 4.), None);
+        assert_eq!(resample::sample_bilinear(&values, 10, 8, 4., 8.1), None);
+        assert_eq!(resample::sample_bilinear(&values, 10, 8, f64::NAN, 4.), None);
+        assert_eq!(resample::sample_bilinear(&values, 10, 7, 4., 4.), None);
+        assert_eq!(resample::sample_bilinear(&[], 0, 0, 0., 0.), None);
+
+        // NaN neighbours.
+        let values = [1., f64::NAN, 3., 4.];
+        assert_eq!(resample::sample_bilinear(&values, 2, 2, 0.9, 1.2), None);
+        assert_eq!(resample::sample_bilinear_with(&values, 2, 2, 0.8, 0.9, true), Some(1.));
+        assert_eq!(resample::sample_bilinear_with(&values, 2, 2, 1.2, 1.4, true), Some(4.));
+        assert_eq!(resample::sample_bilinear_with(&values, 2, 2, 0.5, 1.5, true), Some(3.));
+        assert_eq!(resample::sample_bilinear_with(&values, 2, 2, 1.5, 0.5, true), None);
+        assert_eq!(resample::sample_bilinear_with(&[1., 2., 3., 4.], 2, 2, 1.2, 1.6, true), resample::sample_bilinear(&[1., 2., 3., 4.], 2, 2, 1.2, 1.6));
+    }
+
+    #[test]
+    fn test_contours_strided() {
+        let (dx, dy) = (7, 6);
+        let values = (0..dx * dy).map(|i| ((i * 13 % 9) as f64 / 2.).cos() * 2. + (i / dx) as f64 / 3.).collect::<Vec<f64>>();
+        let thresholds = [0.5, 1.5, 2.5];
+        // The same grid with rows padded by 3 NaN values, the last row without its padding.
+        let padded = values.chunks(dx as usize).flat_map(|row| row.iter().cloned().chain(vec![f64::NAN; 3])).collect::<Vec<_>>();
+        let padded = &padded[..padded.len() - 3];
+        for smooth in &[false, true] {
+            let c = ContourBuilder::new(dx, dy, *smooth);
+            let expected = c.contours(&values, &thresholds).unwrap();
+            assert_eq!(c.contours_strided(&values, dx as usize, &thresholds).unwrap(), expected);
+            assert_eq!(c.contours_strided(padded, dx as usize + 3, &thresholds).unwrap(), expected);
+        }
+
+        let c = ContourBuilder::new(dx, dy, false);
+        match c.contours_strided(&values, dx as usize - 1, &thresholds).unwrap_err().kind() {
+            ErrorKind::InvalidStride { row_stride: 6, dx: 7 } => (),
+            _ => panic!("unexpected error kind"),
+        }
+        match c.contours_strided(&padded[..padded.len() - 1], dx as usize + 3, &thresholds).unwrap_err().kind() {
+            ErrorKind::BadDimension => (),
+            _ => panic!("unexpected error kind"),
+        }
+        assert!(c.contours_strided(&values, usize::MAX, &thresholds).is_err());
+        assert!(ContourBuilder::new(0, 0, false).contours_strided(&[], 0, &thresholds).is_ok());
+    }
+
+    #[test]
+    fn test_validate_input() {
+        let c = ContourBuilder::new(4, 3, false);
+        let values = (0..12).map(|i| i as f64 / 2.).collect::<Vec<_>>();
+        let report = c.validate_input(&values);
+        assert!(report.is_valid());
+        assert_eq!((report.len, report.expected_len, report.min, report.max), (12, Some(12), Some(0.), Some(5.5)));
+        assert_eq!(report.to_string(), "no problem found");
+
+        // Wrong length.
+        let report = c.validate_input(&values[..11]);
+        assert!(!report.is_valid() && !report.has_valid_len());
+        assert_eq!((report.len, report.expected_len), (11, Some(12)));
+
+        // NaN and infinite values.
+        let mut invalid = values.clone();
+        invalid[2] = f64::NAN;
+        invalid[7] = f64::NAN;
+        invalid[5] = f64::INFINITY;
+        invalid[11] = f64::NEG_INFINITY;
+        let report = c.validate_input(&invalid);
+        assert!(!report.is_valid() && report.has_valid_len());
+        assert_eq!((report.nan_count, report.nan_indices.clone()), (2, vec![2, 7]));
+        assert_eq!((report.infinite_count, report.infinite_indices.clone()), (2, vec![5, 11]));
+        assert_eq!((report.min, report.max), (Some(0.), Some(5.)));
+        assert!(!report.constant);
+        assert_eq!(report.to_string(), "2 NaN values (at indexes [2, 7]); 2 infinite values (at indexes [5, 11])");
+
+        // Only the first indexes are reported.
+        let report = ContourBuilder::new(5, 5, false).validate_input(&[f64::NAN; 25]);
+        assert_eq!(report.nan_count, 25);
+        assert_eq!(report.nan_indices, (0..MAX_REPORTED_INDICES).collect::<Vec<_>>());
+        assert!(!report.constant && report.min.is_none());
+
+        // Constant grid.
+        let mut constant = vec![2.; 12];
+        constant[3] = f64::NAN;
+        let report = c.validate_input(&constant);
+        assert!(report.constant);
+        assert_eq!((report.min, report.max), (Some(2.), Some(2.)));
+        assert!(ContourBuilder::new(0, 0, false).validate_input(&[]).is_valid());
+
+        // Strict mode.
+        let strict = c.clone().strict_input(true);
+        assert_eq!(strict.contours(&values, &[1.]).unwrap(), c.contours(&values, &[1.]).unwrap());
+        assert!(c.contours(&invalid, &[1.]).is_ok());
+        for values in &[&invalid[..], &values[..11], &constant[..]] {
+            let err = strict.contours(values, &[1.]).unwrap_err();
+            match err.kind() {
+                ErrorKind::InvalidInput(report) => assert_eq!(*report, c.validate_input(values)),
+                _ => panic!("unexpected error kind"),
+            }
+        }
+        assert!(strict.contours(&values[..11], &[1.]).unwrap_err().to_string().contains("11 values instead of 12"));
+        let mut strict = strict;
+        strict.set_thresholds(&[1.]).unwrap();
+        assert!(strict.compute(&constant).is_err());
+
+        // Every entry point checks the values in strict mode, the ones read in place in
+        // row-major order.
+        let invalid_input = |result: crate::Result<()>| match result.unwrap_err().into_kind() {
+            ErrorKind::InvalidInput(report) => report,
+            _ => panic!("unexpected error kind"),
+        };
+        let t = [1.];
+        let strict = &strict;
+        type Check<'a> = Box<dyn Fn(&[f64]) -> crate::Result<()> + 'a>;
+        let checks: Vec<Check> = vec![
+            Box::new(|v| strict.contours_geometries(v, &t).map(drop)),
+            Box::new(|v| strict.contours_ewkb(v, &t, 4326).map(drop)),
+            Box::new(|v| strict.contours_named(v, &[(1., "one")]).map(drop)),
+            Box::new(|v| strict.contours_with_props(v, &t, |_, value| [("v", value)].iter().cloned().collect::<std::collections::BTreeMap<_, _>>()).map(drop)),
+            Box::new(|v| strict.contours_cached(v, &t, &crate::cache::ContourCache::new(1)).map(drop)),
+            Box::new(|v| strict.contours_exploded(v, &t).map(drop)),
+            Box::new(|v| strict.contours_banded(v, &t).map(drop)),
+            Box::new(|v| strict.contours_geoarrow(v, &t).map(drop)),
+            Box::new(|v| strict.contours_encoded_polylines(v, &t, 5).map(drop)),
+            Box::new(|v| strict.contours_mesh(v, &t).map(drop)),
+            Box::new(|v| strict.contours_resampled(v, &t, 8, 6).map(drop)),
+            Box::new(|v| strict.contours_source(v, &t).map(drop)),
+            Box::new(|v| strict.contours_strided(v, 4, &t).map(drop)),
+            Box::new(|v| strict.capacity_hint_for(v, &t).map(drop)),
+            Box::new(|v| strict.extrema(v, &extrema::ExtremaOptions::default()).map(drop)),
+            Box::new(|v| strict.stats(v).map(drop)),
+        ];
+        for check in &checks {
+            check(&values).unwrap();
+            assert_eq!(invalid_input(check(&invalid)), c.validate_input(&invalid));
+        }
+        let transposed = (0..12).map(|i| invalid[(i % 3) * 4 + i / 3]).collect::<Vec<_>>();
+        assert_eq!(invalid_input(strict.contours_column_major(&transposed, &t).map(drop)), c.validate_input(&invalid));
+        // Only the values of the window are checked.
+        assert!(strict.contours_window(&invalid, &t, 0, 2, 3, 1).is_ok());
+        assert!(strict.contours_window(&invalid, &t, 0, 0, 3, 2).is_err());
+    }
+
+    #[test]
+    fn test_simplestyle() {
+        let values = (0..100).map(|i| ((i % 10) as f64 - 4.5).hypot((i / 10) as f64 - 4.5)).collect::<Vec<_>>();
+        let colormap = style::Colormap::custom(|t| ((t * 255.) as u8, 0, 255 - (t * 255.) as u8));
+        let c = ContourBuilder::new(10, 10, true).simplestyle(style::SimpleStyle::new(colormap.clone()));
+        let features = c.contours(&values, &[1., 2., 3., 4., 5.]).unwrap();
+        let property = |i: usize, name: &str| features[i].properties.as_ref().unwrap()[name].clone();
+        assert_eq!(property(0, "fill"), "#0000ff");
+        assert_eq!(property(2, "fill"), "#7f0080");
+        assert_eq!(property(4, "fill"), "#ff0000");
+        assert_eq!(property(4, "stroke"), "#ff0000");
+        assert_eq!(property(0, "fill-opacity"), 0.6);
+        assert_eq!(property(0, "stroke-width"), 1.);
+        for feature in &features {
+            for name in &["fill", "stroke"] {
+                let color = feature.properties.as_ref().unwrap()[*name].as_str().unwrap().to_string();
+                assert!(color.len() == 7 && color.starts_with('#'));
+                assert!(color[1..].chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+            }
+        }
+
+        // A stroke color, and the exploded contours.
+        let mut style = style::SimpleStyle::new(colormap);
+        style.stroke = Some((16, 32, 255));
+        style.stroke_width = 2.5;
+        let features = ContourBuilder::new(10, 10, true).simplestyle(style.clone()).contours_exploded(&values, &[1., 5.]).unwrap();
+        assert_eq!(features[0].properties.as_ref().unwrap()["stroke"], "#1020ff");
+        assert_eq!(features[0].properties.as_ref().unwrap()["stroke-width"], 2.5);
+        assert_eq!(features.last().unwrap().properties.as_ref().unwrap()["fill"], "#ff0000");
+
+        // A single threshold gets the first color.
+        let features = ContourBuilder::new(10, 10, true).simplestyle(style).contours(&values, &[3.]).unwrap();
+        assert_eq!(features[0].properties.as_ref().unwrap()["fill"], "#0000ff");
+        assert_eq!(style::hex((0, 10, 255)), "#000aff");
+    }
+
+    #[test]
+    #[cfg(feature = "colormaps")]
+    fn test_simplestyle_colormaps() {
+        for (colormap, first, last) in &[(style::Colormap::Viridis, "#440154", "#fde725"), (style::Colormap::Turbo, "#23171b", "#900c00")] {
+            let features = ContourBuilder::new(2, 2, false)
+                .simplestyle(style::SimpleStyle::new(colormap.clone()))
+                .contours(&[0., 1., 2., 3.], &[0.5, 1., 1.5, 2.5])
+                .unwrap();
+            assert_eq!(features[0].properties.as_ref().unwrap()["fill"], *first);
+            assert_eq!(features[3].properties.as_ref().unwrap()["fill"], *last);
+        }
+        assert_eq!(style::Colormap::Viridis.color(0.5), (33, 145, 140));
+        assert_eq!(style::Colormap::Turbo.color(-1.), (35, 23, 27));
+    }
+
+    #[test]
+    fn test_ring_directions() {
+        // A radial bump: every ring has the higher values inside it.
+        let values = (0..144).map(|i| 6. - ((i % 12) as f64 - 5.5).hypot((i / 12) as f64 - 5.5)).collect::<Vec<_>>();
+        for threshold in &[1., 2., 3., 4., 5.] {
+            let (rings, directions) = contour_rings_with_directions(&values, *threshold, 12, 12, true).unwrap();
+            assert_eq!(rings.len(), 1);
+            assert_eq!(directions[0].higher_side, Side::Right);
+            assert_eq!(rings[0].orientation(), Winding::Clockwise);
+            let angles = &directions[0].tangent_angles;
+            assert_eq!(angles.len(), rings[0].len());
+            assert_eq!(angles[0], angles[angles.len() - 1]);
+            // The uphill direction (on the right) points towards the center.
+            for (p, angle) in rings[0].iter_points().zip(angles) {
+                let uphill = (angle - 90.).to_radians();
+                assert!(uphill.cos() * (6. - p[0]) + uphill.sin() * (6. - p[1]) > 0.);
+            }
+            let (_, directions) = contour_rings_with_directions(&values, *threshold, 12, 12, false).unwrap();
+            assert!(directions[0].tangent_angles.is_empty());
+        }
+
+        // The sides of the output rings, holes included.
+        let values = (0..144).map(|i| (3. - (((i % 12) as f64 - 5.5).hypot((i / 12) as f64 - 5.5) - 3.).abs()).max(0.)).collect::<Vec<_>>();
+        let higher_side = |builder: ContourBuilder| {
+            let features = builder.contours(&values, &[1.5, 2.]).unwrap();
+            features.iter().map(|feature| feature.properties.as_ref().unwrap()["higher_side"].clone()).collect::<Vec<_>>()
+        };
+        let c = ContourBuilder::new(12, 12, true).ring_directions(RingDirections::HigherSide);
+        assert_eq!(higher_side(c.clone()), vec![serde_json::json!([["right", "right"]]); 2]);
+        assert_eq!(higher_side(c.clone().y_step(-1.)), vec![serde_json::json!([["left", "left"]]); 2]);
+        let features = c.contours(&values, &[1.5]).unwrap();
+        assert!(features[0].properties.as_ref().unwrap().get("tangent_angles").is_none());
+        let features = c.ring_directions(RingDirections::WithTangentAngles).contours(&values, &[1.5]).unwrap();
+        let angles = features[0].properties.as_ref().unwrap()["tangent_angles"].as_array().unwrap().clone();
+        match features[0].geometry.clone().unwrap().value {
+            geojson::Value::MultiPolygon(p) => {
+                assert_eq!(angles[0].as_array().unwrap().len(), p[0].len());
+                assert_eq!(angles[0][1].as_array().unwrap().len(), p[0][1].len());
+                assert_eq!(angles[0][1][0].as_f64().unwrap(), Ring::from(p[0][1].clone()).tangent_angles()[0]);
+            }
+            _ => panic!(""),
+        };
+        assert!(ContourBuilder::new(12, 12, true).contours(&values, &[1.5]).unwrap()[0].properties.as_ref().unwrap().get("higher_side").is_none());
+    }
+
+    #[test]
+    fn test_label_positions() {
+        // A square ring of perimeter 40.
+        let square = vec![vec![0., 0.], vec![10., 0.], vec![10., 10.], vec![0., 10.], vec![0., 0.]];
+        let positions = label::ring_label_positions(std::slice::from_ref(&square), 2., 3., 40.);
+        assert_eq!(positions.len(), 13);
+        let along = |p: &label::LabelPos| match (p.x, p.y) {
+            (x, 0.) => x,
+            (10., y) => 10. + y,
+            (x, 10.) => 30. - x,
+            (_, y) => 40. - y,
+        };
+        for (i, p) in positions.iter().enumerate() {
+            assert!((along(p) - (1.5 + 3. * i as f64)).abs() < 1e-9);
+            assert_eq!(p.threshold, 2.);
+            assert!(p.angle == 0. || p.angle == 90.);
+        }
+        assert!(label::ring_label_positions(std::slice::from_ref(&square), 2., 3., 40.1).is_empty());
+        assert!(label::ring_label_positions(std::slice::from_ref(&square), 2., 0., 0.).is_empty());
+        assert!(label::ring_label_positions(std::slice::from_ref(&square), 2., f64::NAN, 0.).is_empty());
+        assert_eq!(label::ring_label_positions(&[square], 2., 50., 0.).len(), 1);
+
+        // The angles are upright.
+        let diamond = vec![vec![0., 5.], vec![5., 0.], vec![10., 5.], vec![5., 10.], vec![0., 5.]];
+        let angles = label::ring_label_positions(&[diamond], 0., 5f64.hypot(5.), 0.).iter().map(|p| p.angle.round()).collect::<Vec<_>>();
+        assert_eq!(angles, vec![-45., 45., -45., 45.]);
+
+        let values = (0..100).map(|i| 5. - ((i % 10) as f64 - 4.5).hypot((i / 10) as f64 - 4.5)).collect::<Vec<_>>();
+        let features = ContourBuilder::new(10, 10, true).contours(&values, &[1., 4.]).unwrap();
+        let positions = label::label_positions(&features, 2., 10.);
+        assert!(positions.iter().all(|p| p.threshold == 1. && p.angle > -90. && p.angle <= 90.));
+        assert!(positions.len() > 10);
+    }
+
+    #[test]
+    fn test_contours_approx_eq() {
+        // A disk, and two rings.
+        let values = (0..144).map(|i| (3. - (((i % 12) as f64 - 5.5).hypot((i / 12) as f64 - 5.5) - 3.).abs()).max(0.)).collect::<Vec<_>>();
+        let thresholds = [0.5, 1.5, 2.];
+        let c = ContourBuilder::new(12, 12, true);
+        let expected = c.contours(&values, &thresholds).unwrap();
+        let ring_of = |feature: &geojson::Feature, polygon: usize, ring: usize| match feature.geometry.clone().unwrap().value {
+            geojson::Value::MultiPolygon(p) => p[polygon][ring].clone(),
+            _ => panic!(""),
+        };
+        assert_eq!(ring_of(&expected[1], 0, 1).first(), ring_of(&expected[1], 0, 1).last());
+        assert!(testing::contours_approx_eq(&expected, &expected, 0.).is_ok());
+
+        // Shifted positions, rotated rings, reordered features and polygons.
+        let shifted = c.clone().x_origin(1e-10).contours(&values, &thresholds).unwrap();
+        testing::assert_contours_approx_eq(&expected, &shifted, 1e-9);
+        assert_eq!(
+            testing::contours_approx_eq(&expected, &shifted, 1e-11),
+            Err(testing::Difference::Vertex {
+                threshold: 0.5,
+                polygon: 0,
+                ring: 0,
+                vertex: 0,
+                first: ring_of(&expected[0], 0, 0)[0].clone(),
+                second: vec![ring_of(&expected[0], 0, 0)[0][0] + 1e-10, ring_of(&expected[0], 0, 0)[0][1]],
+            })
+        );
+        let mut reordered = c.clone().normalize_rings(true).contours(&values, &thresholds).unwrap();
+        reordered.reverse();
+        testing::assert_contours_approx_eq(&expected, &reordered, 0.);
+        let exploded = c.contours_exploded(&values, &thresholds).unwrap();
+        testing::assert_contours_approx_eq(&expected, &exploded, 0.);
+        testing::assert_contours_approx_eq(&exploded, &expected, 0.);
+
+        // The first divergent position.
+        let mut moved = expected.clone();
+        if let Some(geojson::Value::MultiPolygon(ref mut p)) = moved[1].geometry.as_mut().map(|g| &mut g.value) {
+            p[0][1][3][1] += 0.01;
+            let n = p[0][1].len();
+            p[0][1][..n - 1].rotate_left(2);
+            p[0][1][n - 1] = p[0][1][0].clone();
+        }
+        match testing::contours_approx_eq(&expected, &moved, 1e-3) {
+            Err(testing::Difference::Vertex { threshold, polygon: 0, ring: 1, vertex: 3, first, second }) => {
+                assert_eq!(threshold, 1.5);
+                assert!((second[1] - first[1] - 0.01).abs() < 1e-12);
+            }
+            other => panic!("unexpected difference {:?}", other),
+        }
+        assert!(testing::contours_approx_eq(&expected, &moved, 0.1).is_ok());
+
+        // Other differences.
+        let other = c.contours(&values, &[0.5, 1.5]).unwrap();
+        assert_eq!(testing::contours_approx_eq(&expected, &other, 1.), Err(testing::Difference::Thresholds { first: thresholds.to_vec(), second: vec![0.5, 1.5] }));
+        let unsmoothed = ContourBuilder::new(12, 12, false).contours(&values, &thresholds).unwrap();
+        assert!(testing::contours_approx_eq(&expected, &unsmoothed, 0.1).is_err());
+        let result = std::panic::catch_unwind(|| testing::assert_contours_approx_eq(&expected, &moved, 1e-3));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_contour_options() {
+        let options = ContourOptions::default();
+        assert_eq!((options.dx, options.dy, options.smooth), (0, 0, true));
+        assert_eq!((options.x_origin, options.y_origin, options.x_step, options.y_step), (0., 0., 1., 1.));
+        assert_eq!((options.threads, options.capacity_hint, options.densify), (1, (0, 0), None));
+
+        let mut options = ContourOptions::default();
+        options.dx = 10;
+        options.dy = 10;
+        options.x_step = 2.;
+        options.max_total_vertices = Some(100);
+        let builder = ContourBuilder::from(options.clone());
+        assert_eq!(builder.options(), &options);
+        assert_eq!(builder, ContourBuilder::new(10, 10, true).x_step(2.).max_total_vertices(100));
+        assert_ne!(builder, ContourBuilder::new(10, 10, true).x_step(2.));
+        assert_ne!(builder, ContourBuilder::new(10, 10, false).x_step(2.).max_total_vertices(100));
+        assert_eq!(builder.clone(), builder);
+        assert!(format!("{:?}", builder).contains("x_step: 2.0"));
+
+        let values = (0..100).map(|i| f64::from(i % 10 + i / 10)).collect::<Vec<_>>();
+        let a = ContourBuilder::new(10, 10, true).x_step(2.).contours(&values, &[4.5, 9.5]).unwrap();
+        let b = ContourBuilder::from(options).max_total_vertices(1000).contours(&values, &[4.5, 9.5]).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_isoring_hash_index() {
+        let values = crate::testing::noise(60 * 40, 7);
+        let mut hashed = IsoRingBuilder::with_hash_index(60, 40);
+        for threshold in &[0.2, 0.5, 0.8] {
+            assert_eq!(hashed.compute(&values, *threshold).unwrap(), IsoRingBuilder::new(60, 40).compute(&values, *threshold).unwrap());
+        }
+        // The number of edges of the grid overflows: the builder falls back to hash maps
+        // instead of allocating tables, and the values can't match the dimensions.
+        let mut huge = IsoRingBuilder::new(u32::MAX, u32::MAX);
+        assert!(matches!(huge.compute(&[], 0.5).unwrap_err().kind(), ErrorKind::BadDimension));
+    }
+
+    #[test]
+    fn test_isoring_compute_into() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 2., 1., 2., 0.,
+            0., 1., 0., 1., 0.,
+            0., 2., 1., 2., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let mut isoring = IsoRingBuilder::new(5, 5);
+        let mut rings = Vec::new();
+        for threshold in &[0.5, 1.5, 0.5] {
+            isoring.compute_into(&values, *threshold, &mut rings).unwrap();
+            assert_eq!(rings, IsoRingBuilder::new(5, 5).compute(&values, *threshold).unwrap());
+        }
+        assert_eq!(rings.len(), 2);
+
+        // The rings of the previous call are reused.
+        let capacities = rings.iter().map(|ring| ring.capacity()).collect::<Vec<_>>();
+        let outer = rings.capacity();
+        isoring.compute_into(&values, 0.5, &mut rings).unwrap();
+        assert_eq!(rings.capacity(), outer);
+        let mut reused = rings.iter().map(|ring| ring.capacity()).collect::<Vec<_>>();
+        let mut expected = capacities.clone();
+        reused.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(reused, expected);
+
+        isoring.compute_into(&values, 1.5, &mut rings).unwrap();
+        assert_eq!(rings.len(), 4);
+        assert!(isoring.compute_into(&values[1..], 0.5, &mut rings).is_err());
+        assert!(rings.is_empty());
+    }
+
+    #[test]
+    fn test_vectorize_classes() {
+        #[rustfmt::skip]
+        let values = [
+            1, 1, 2, 2,
+            1, 3, 3, 2,
+            1, 3, 1, 2,
+            2, 2, 1, 1,
+        ];
+        let classes = classes::vectorize_classes(&values, 4, 4).unwrap();
+        assert_eq!(classes.iter().map(|(class, _)| *class).collect::<Vec<_>>(), [1, 2, 3]);
+        let areas = classes
+            .iter()
+            .map(|(_, polygons)| polygons.iter().flatten().map(|ring| geom::area(ring) / 2.).sum::<f64>())
+            .collect::<Vec<_>>();
+        assert_eq!(areas, [7., 6., 3.]);
+        assert_eq!(areas.iter().sum::<f64>(), 16.);
+        // The regions of 1 and of 2 are made of two polygons each.
+        assert_eq!(classes.iter().map(|(_, polygons)| polygons.len()).collect::<Vec<_>>(), [2, 2, 1]);
+        // The positions are corners of the cells.
+        assert!(classes.iter().flat_map(|(_, polygons)| polygons.iter().flatten().flatten()).all(|p| p[0].fract() == 0. && p[1].fract() == 0.));
+
+        let values = [0.5, f64::NAN, 0.5, 0.5];
+        let classes = classes::vectorize_classes(&values, 2, 2).unwrap();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(geom::area(&classes[0].1[0][0]), 6.);
+        assert!(classes::vectorize_classes(&values[1..], 2, 2).is_err());
+    }
+
+    #[test]
+    fn test_area_above_thresholds() {
+        #[rustfmt::skip]
+        let values = [
+            0., 1., 2., 1.,
+            1., 3., 4., 2.,
+            2., 4., f64::NAN, 1.,
+            1., 2., 1., 0.,
+        ];
+        assert_eq!(area_above_thresholds(&values, &[-1., 0.5, 1.5, 3., 4., 4.5], 1.), [15., 13., 7., 3., 2., 0.]);
+        assert_eq!(area_above_thresholds(&values, &[1.5, f64::NAN], 0.25), [1.75, 0.]);
+        assert_eq!(area_above_thresholds(&[], &[0.], 1.), [0.]);
+
+        // The area of the polygons is close to the one of the cells for smooth grids.
+        let (dx, dy) = (40, 30);
+        let values = (0..dx * dy)
+            .map(|i| {
+                let (x, y) = (f64::from(i % dx) - 20., f64::from(i / dx) - 15.);
+                100. - x.hypot(y) * 4.
+            })
+            .collect::<Vec<_>>();
+        let thresholds = [10., 30., 50., 70.];
+        let features = ContourBuilder::new(dx, dy, true).x_step(2.).area(true).contours(&values, &thresholds).unwrap();
+        let cells = area_above_thresholds(&values, &thresholds, 2.);
+        for (feature, cells) in features.iter().zip(cells) {
+            let area = feature.properties.as_ref().unwrap()["area"].as_f64().unwrap();
+            assert!((area - cells).abs() < cells * 0.05, "{} != {}", area, cells);
+        }
+        let features = contours().size((dx as usize, dy as usize)).thresholds(&thresholds[..]).area(true).compute(&values).unwrap();
+        assert!(features.iter().all(|feature| feature.properties.as_ref().unwrap().contains_key("area")));
+        let features = ContourBuilder::new(dx, dy, true).contours(&values, &thresholds).unwrap();
+        assert!(!features[0].properties.as_ref().unwrap().contains_key("area"));
+    }
+
+    #[test]
+    fn test_contours_blended() {
+        let (dx, dy) = (12, 10);
+        let cone = |cx: f64, cy: f64| (0..dx * dy).map(move |i| 10. - (f64::from(i % dx) - cx).hypot(f64::from(i / dx) - cy)).collect::<Vec<_>>();
+        let (a, b) = (cone(3., 3.), cone(8., 6.));
+        let thresholds = [4., 6., 8.];
+        let c = ContourBuilder::new(dx, dy, true);
+        assert_eq!(c.contours_blended(&a, &b, 0., &thresholds).unwrap(), c.contours(&a, &thresholds).unwrap());
+        assert_eq!(c.contours_blended(&a, &b, 1., &thresholds).unwrap(), c.contours(&b, &thresholds).unwrap());
+        let middle = a.iter().zip(&b).map(|(a, b)| 0.75 * a + 0.25 * b).collect::<Vec<_>>();
+        assert_eq!(c.contours_blended(&a, &b, 0.25, &thresholds).unwrap(), c.contours(&middle, &thresholds).unwrap());
+
+        let sequence = c.contours_sequence(&a, &b, &thresholds, 5).unwrap();
+        assert_eq!(sequence.len(), 5);
+        assert_eq!(sequence[0], c.contours(&a, &thresholds).unwrap());
+        assert_eq!(sequence[1], c.contours(&middle, &thresholds).unwrap());
+        assert_eq!(sequence[4], c.contours(&b, &thresholds).unwrap());
+        assert_eq!(c.contours_sequence(&a, &b, &thresholds, 1).unwrap(), [c.contours(&a, &thresholds).unwrap()]);
+        assert!(c.contours_sequence(&a, &b, &thresholds, 0).unwrap().is_empty());
+
+        let mut nan = b.clone();
+        nan[0] = f64::NAN;
+        assert_eq!(c.contours_blended(&a, &nan, 0., &thresholds).unwrap(), c.contours(&a, &thresholds).unwrap());
+        assert!(c.contours_blended(&a, &b[1..], 0.5, &thresholds).is_err());
+    }
+
+    #[test]
+    fn test_contours_with_diagnostics() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(10, 10, true);
+        let (features, diagnostics) = c.contours_with_diagnostics(&values, &[0.5, 1.5]).unwrap();
+        assert_eq!(features, c.contours(&values, &[0.5, 1.5]).unwrap());
+        assert_eq!(
+            diagnostics,
+            [
+                ThresholdDiagnostics { threshold: 0.5, rings: 2, shells: 1, holes: 1, orphans: 0, dropped: 0, vertices: 26, repaired_rings: 0 },
+                ThresholdDiagnostics { threshold: 1.5, ..ThresholdDiagnostics::default() },
+            ]
+        );
+
+        // The hole is outside of the clipping region.
+        #[cfg(feature = "clip")]
+        {
+            let clip = [(0., 0.), (3.8, 0.), (3.8, 10.), (0., 10.)];
+            let (_, diagnostics) = c.clip_to(&clip).contours_with_diagnostics(&values, &[0.5]).unwrap();
+            assert_eq!((diagnostics[0].rings, diagnostics[0].dropped), (2, 1));
+        }
+    }
+
+    #[test]
+    fn test_geodesic_metrics() {
+        use crate::geom::{MEAN_EARTH_RADIUS, Metric};
+        let square = vec![vec![0., 0.], vec![0., 1.], vec![1., 1.], vec![1., 0.], vec![0., 0.]];
+        let spherical = Metric::Spherical { radius: MEAN_EARTH_RADIUS };
+        // About 12,300 km² for a 1° by 1° square near the equator.
+        assert!((spherical.area(&square) / 1e6 - 12_364.).abs() < 1.);
+        assert!((Metric::Wgs84.area(&square) / 1e6 - 12_309.).abs() < 1.);
+        assert_eq!(Metric::Planar.area(&square), 1.);
+        let reversed = square.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(spherical.area(&reversed), spherical.area(&square));
+        // The area shrinks with the cosine of the latitude.
+        let north = square.iter().map(|p| vec![p[0], p[1] + 60.]).collect::<Vec<_>>();
+        assert!((spherical.area(&north) / spherical.area(&square) - 0.5).abs() < 0.01);
+
+        assert!((spherical.perimeter(&square) / 1e3 - 444.76).abs() < 0.01);
+        assert_eq!(Metric::Planar.perimeter(&square[..4]), 4.);
+        // One degree along the equator and along a meridian on the ellipsoid.
+        assert!((Metric::Wgs84.distance(&[0., 0.], &[1., 0.]) - 111_319.49).abs() < 0.01);
+        assert!((Metric::Wgs84.distance(&[0., 0.], &[0., 1.]) - 110_574.39).abs() < 0.01);
+        assert_eq!(Metric::Wgs84.distance(&[3., 4.], &[3., 4.]), 0.);
+
+        // The properties of the contours, a square ring with a hole on the equator.
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let c = ContourBuilder::new(5, 5, false).x_origin(-2.5).y_origin(-2.5).area(true).perimeter(true);
+        let planar = c.contours(&values, &[0.5]).unwrap();
+        let properties = planar[0].properties.as_ref().unwrap();
+        // The exterior ring has chamfered corners and the hole is a diamond.
+        assert!((properties["area"].as_f64().unwrap() - 8.).abs() < 1e-12);
+        assert!((properties["perimeter"].as_f64().unwrap() - (8. + 4. * 2f64.sqrt())).abs() < 1e-12);
+        let geodesic = c.metric(spherical).contours(&values, &[0.5]).unwrap();
+        let area = geodesic[0].properties.as_ref().unwrap()["area"].as_f64().unwrap();
+        assert!((area / 1e6 / 12_364. - 8.).abs() < 0.01);
+        // The polygons themselves don't depend on the metric.
+        assert_eq!(geodesic[0].geometry, planar[0].geometry);
+    }
+
+    #[test]
+    fn test_contours_padded() {
+        use crate::pad::{PadMode, pad_grid};
+        let (values, dx, dy) = pad_grid(&[1., 2., 3., 4., 5., 6.], 3, 2, 1, PadMode::Reflect).unwrap();
+        assert_eq!((dx, dy), (5, 4));
+        assert_eq!(&values[..5], [5., 4., 5., 6., 5.]);
+        let (values, _, _) = pad_grid(&[1., 2., 3., 4.], 2, 2, 2, PadMode::Constant(0.)).unwrap();
+        assert_eq!(values.iter().sum::<f64>(), 10.);
+        match pad_grid(&[1.], 2, 2, 1, PadMode::Edge).unwrap_err().kind() {
+            ErrorKind::BadDimension => {}
+            _ => panic!(""),
+        };
+
+        // A blob touching the border of the grid.
+        #[rustfmt::skip]
+        let values = [
+            1., 1., 0., 0.,
+            1., 1., 0., 0.,
+            0., 0., 0., 0.,
+        ];
+        let c = ContourBuilder::new(4, 3, true).bbox(true);
+        // Padding with cells below the thresholds closes the contours like the border does.
+        let closed = c.contours_padded(&values, &[0.5], 2, PadMode::Constant(f64::NEG_INFINITY)).unwrap();
+        assert_eq!(closed, c.contours(&values, &[0.5]).unwrap());
+        assert_eq!(closed[0].bbox, Some(vec![0., 0., 2., 2.]));
+        // Padding with cells above them leaves the blob open to the edge, the polygon
+        // extending over the padding.
+        let open = c.contours_padded(&values, &[0.5], 1, PadMode::Constant(f64::INFINITY)).unwrap();
+        assert_eq!(open[0].bbox, Some(vec![-1., -1., 5., 4.]));
+        // The padding by the border cells continues the blob beyond the border only.
+        let edge = c.contours_padded(&values, &[0.5], 1, PadMode::Edge).unwrap();
+        assert_eq!(edge[0].bbox, Some(vec![-1., -1., 2., 2.]));
+    }
+
+    #[test]
+    fn test_threshold_epsilon() {
+        let (below, above) = (0.5 - 1e-12, 0.5 + 1e-12);
+        // A plateau at the threshold, with values a bit below it (making holes)
+        // and a speck a bit above it in the background.
+        #[rustfmt::skip]
+        let values = [
+            0., 0.,    0.,  0.,    0.,  0.,    0.,
+            0., 0.5,   0.5, 0.5,   0.5, 0.5,   0.,
+            0., 0.5, below, 0.5, below, 0.5,   0.,
+            0., 0.5,   0.5, 0.5,   0.5, 0.5,   0.,
+            0., 0.,    0.,  0.,    0.,  0.,    0.,
+            0., 0.,    0.,  0.,    0.,  above, 0.,
+            0., 0.,    0.,  0.,    0.,  0.,    0.,
+        ];
+        let rings = |c: &ContourBuilder| match c.contours(&values, &[0.5]).unwrap()[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => p.iter().map(|polygon| polygon.len()).collect::<Vec<_>>(),
+            _ => panic!(""),
+        };
+        // Without smoothing, the speck is a ring of its own.
+        let c = ContourBuilder::new(7, 7, false);
+        assert_eq!(rings(&c), [3, 1]);
+        // The values within the epsilon are taken as equal to the threshold,
+        // the holes are gone.
+        let c = c.threshold_epsilon(1e-9);
+        assert_eq!(rings(&c), [1, 1]);
+        // And the smoothed rings don't move.
+        let snapped = values.iter().map(|v| if (v - 0.5).abs() < 1e-9 { 0.5 } else { *v }).collect::<Vec<_>>();
+        let smooth = ContourBuilder::new(7, 7, true);
+        assert_eq!(smooth.clone().threshold_epsilon(1e-9).contours(&values, &[0.5]).unwrap(), smooth.contours(&snapped, &[0.5]).unwrap());
+        // Or as right below it, the plateau and the speck being gone.
+        let down = c.clone().epsilon_rounding(EpsilonRounding::Down);
+        assert!(rings(&down).is_empty());
+        // A smaller epsilon keeps them.
+        assert_eq!(rings(&c.threshold_epsilon(1e-13)), [3, 1]);
+        assert_eq!(down.options().epsilon_rounding, EpsilonRounding::Down);
+    }
+
+    #[test]
+    fn test_bin_points() {
+        use crate::histogram::{Extent, bin_points};
+        let extent = Extent { x_min: -1., y_min: 0., x_max: 1., y_max: 3. };
+        // The points on the max edges are in the last column or row, the ones outside dropped.
+        let points = [(-1., 0.), (-0.5, 0.5), (0., 1.), (1., 3.), (0.9, 2.9), (1.1, 2.), (0., -0.1), (f64::NAN, 1.)];
+        let counts = bin_points(points.iter().copied(), extent, 2, 3, None).unwrap();
+        assert_eq!(counts, [2., 0., 0., 1., 0., 2.]);
+        let weights = [1., 2., 3., 4., 5., 6., 7., 8.];
+        let sums = bin_points(points.iter().copied(), extent, 2, 3, Some(&weights)).unwrap();
+        assert_eq!(sums, [3., 0., 0., 3., 0., 9.]);
+        match bin_points(points.iter().copied(), extent, 2, 3, Some(&weights[1..])).unwrap_err().kind() {
+            ErrorKind::BadDimension => {}
+            _ => panic!(""),
+        };
+        let flat = Extent { y_max: 0., ..extent };
+        match bin_points(points.iter().copied(), flat, 2, 3, None).unwrap_err().kind() {
+            ErrorKind::InvalidExtent(e) => assert_eq!(*e, flat),
+            _ => panic!(""),
+        };
+
+        // A cluster of points around (2.5, 12.5), on a grid of cells of 1 by 5.
+        let extent = Extent { x_min: 0., y_min: 0., x_max: 5., y_max: 25. };
+        let cluster = (0..100).map(|i| (2.5 + f64::from(i % 10) * 0.09 - 0.4, 12.5 + f64::from(i / 10) * 0.45 - 2.));
+        let background = (0..25).map(|i| (f64::from(i % 5) + 0.5, f64::from(i / 5) * 5. + 2.5));
+        let points = cluster.chain(background).collect::<Vec<_>>();
+        let counts = bin_points(points.iter().copied(), extent, 5, 5, None).unwrap();
+        assert_eq!(counts.iter().sum::<f64>(), 125.);
+        assert_eq!(counts[12], 101.);
+        let c = ContourBuilder::new(5, 5, true).bbox(true);
+        let features = c.contours_binned(points.into_iter(), extent, None, &[50.]).unwrap();
+        assert_eq!(features, c.x_step(1.).y_step(5.).contours(&counts, &[50.]).unwrap());
+        // The interpolated ring around the center cell, in the coordinates of the points.
+        let bbox = features[0].bbox.clone().unwrap();
+        // A bit larger than the cell, the threshold being below the middle of 1 and 101.
+        assert!((bbox[0] - 1.99).abs() < 1e-9 && (bbox[2] - 3.01).abs() < 1e-9);
+        assert!((bbox[1] - 9.95).abs() < 1e-9 && (bbox[3] - 15.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_contours_named() {
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 3., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.,
+        ];
+        let c = ContourBuilder::new(5, 5, true);
+        // The names are kept in the order of the thresholds, which doesn't need to be sorted.
+        let levels = [(2., "major \"flood\""), (0.5, "minor flood"), (1.5, "moderate\nflood")];
+        let features = c.contours_named(&values, &levels).unwrap();
+        let json = serde_json::to_string(&features).unwrap();
+        assert!(json.contains(r#""name":"major \"flood\"""#));
+        assert!(json.contains(r#""name":"moderate\nflood""#));
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let parsed = parsed.as_array().unwrap();
+        assert_eq!(parsed.len(), 3);
+        for (feature, (value, name)) in parsed.iter().zip(&levels) {
+            assert_eq!(feature["properties"]["value"], *value);
+            assert_eq!(feature["properties"]["name"], *name);
+        }
+        // The polygons are the ones of `contours`.
+        let unnamed = c.contours(&values, &[2., 0.5, 1.5]).unwrap();
+        assert_eq!(features.iter().map(|f| &f.geometry).collect::<Vec<_>>(), unnamed.iter().map(|f| &f.geometry).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_circular() {
+        // Directions turning smoothly through north, along the rows.
+        let row = [330., 340., 350., 0., 10., 20., 30.];
+        let values = row.iter().chain(&row).chain(&row).copied().collect::<Vec<_>>();
+        let polygons = |c: &ContourBuilder, threshold: f64| match c.contours(&values, &[threshold]).unwrap()[0].clone().geometry.unwrap().value {
+            geojson::Value::MultiPolygon(p) => p,
+            _ => panic!(""),
+        };
+        let c = ContourBuilder::new(7, 3, true);
+        // Linearly, the values before north are above 5 too, with a seam at north.
+        let linear = polygons(&c, 5.);
+        assert_eq!(linear.len(), 2);
+        let circular = polygons(&c.clone().circular(360.), 5.);
+        assert_eq!(circular.len(), 1);
+        assert!(linear.contains(&circular[0]));
+        let min_x = |p: &Vec<Vec<Vec<Vec<f64>>>>| p.iter().flatten().flatten().map(|pt| pt[0]).fold(f64::INFINITY, f64::min);
+        assert_eq!(min_x(&circular), 4.);
+        // Smoothing along the shorter arc, from 350 to 0 (360).
+        assert!(polygons(&c, 358.).is_empty());
+        let circular = polygons(&c.clone().circular(360.), 358.);
+        assert_eq!(circular.len(), 1);
+        assert!((min_x(&circular) - 3.3).abs() < 1e-9);
+        // The same with values in (-180, 180].
+        let signed = values.iter().map(|v| if *v > 180. { v - 360. } else { *v }).collect::<Vec<_>>();
+        let features = c.clone().circular(360.).contours(&signed, &[358.]).unwrap();
+        assert_eq!(features, c.clone().circular(360.).contours(&values, &[358.]).unwrap());
+
+        for threshold in [-1., 360., f64::NAN] {
+            match c.clone().circular(360.).contours(&values, &[threshold]).unwrap_err().kind() {
+                ErrorKind::ThresholdOutOfPeriod { period, .. } => assert_eq!(*period, 360.),
+                _ => panic!(""),
+            };
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "stream")]
+    fn test_contours_stream() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::thread::sleep;
+        use std::time::Duration;
+
+        // Counts the positions of the computed rings, 5 per threshold.
+        let positions = Arc::new(AtomicUsize::new(0));
+        let counter = positions.clone();
+        let c = ContourBuilder::new(3, 3, false).reproject_with(move || {
+            let counter = counter.clone();
+            Ok::<_, String>(move |x, y| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, String>((x, y))
+            })
+        });
+        let values: Arc<[f64]> = Arc::from(vec![0., 0., 0., 0., 1., 0., 0., 0., 0.]);
+        let thresholds = (1..10).map(|t| f64::from(t) / 10.).collect::<Vec<_>>();
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        // All the Features, in order.
+        let mut stream = runtime.block_on(async { c.contours_stream(values.clone(), thresholds.clone(), 2) }).unwrap();
+        let mut features = Vec::new();
+        while let Some(feature) = runtime.block_on(stream.next()) {
+            features.push(feature.unwrap());
+        }
+        assert_eq!(features, c.contours(&values, &thresholds).unwrap());
+        positions.store(0, Ordering::SeqCst);
+
+        // At most 2 thresholds are computed ahead of the consumption of the stream.
+        let mut stream = c.contours_stream_on(runtime.handle(), values, thresholds, 2).unwrap();
+        let first = runtime.block_on(stream.next()).unwrap().unwrap();
+        assert_eq!(first.properties.unwrap()["value"], 0.1);
+        sleep(Duration::from_millis(100));
+        assert_eq!(positions.load(Ordering::SeqCst), 3 * 5);
+        // Dropping the stream stops the computation.
+        drop(stream);
+        sleep(Duration::from_millis(100));
+        assert_eq!(positions.load(Ordering::SeqCst), 3 * 5);
+    }
+
+    #[test]
+    fn test_contours_cached() {
+        use crate::cache::ContourCache;
+        let mut values = vec![0.; 100];
+        for (y, x) in (3..8).flat_map(|y| (3..6).map(move |x| (y, x))) {
+            values[y * 10 + x] = if x == 4 && y > 3 && y < 7 { 0. } else { 1. };
+        }
+        let cache = ContourCache::new(3);
+        let c = ContourBuilder::new(10, 10, true).bbox(true);
+        let fresh = c.contours(&values, &[0.5, 1.5]).unwrap();
+        let first = c.contours_cached(&values, &[0.5, 1.5], &cache).unwrap();
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (0, 2, 2));
+        // The second call only reads the cache, the output being identical.
+        let second = c.contours_cached(&values, &[0.5, 1.5], &cache).unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (2, 2));
+        assert_eq!(serde_json::to_string(&first).unwrap(), serde_json::to_string(&fresh).unwrap());
+        assert_eq!(serde_json::to_string(&second).unwrap(), serde_json::to_string(&fresh).unwrap());
+
+        // Other options, values or thresholds are other keys.
+        ContourBuilder::new(10, 10, false).bbox(true).contours_cached(&values, &[0.5], &cache).unwrap();
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (2, 3, 3));
+        values[0] = 2.;
+        let changed = c.contours_cached(&values, &[0.5], &cache).unwrap();
+        assert_eq!(changed, c.contours(&values, &[0.5]).unwrap());
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (2, 4, 3));
+        // The least recently used Feature (of 0.5, with the first values) was evicted.
+        values[0] = 0.;
+        c.contours_cached(&values, &[1.5], &cache).unwrap();
+        assert_eq!(cache.hits(), 3);
+        c.contours_cached(&values, &[0.5], &cache).unwrap();
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (3, 5, 3));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        let disabled = ContourCache::new(0);
+        assert_eq!(c.contours_cached(&values, &[0.5], &disabled).unwrap(), c.contours(&values, &[0.5]).unwrap());
+        assert!(disabled.is_empty());
+
+        // The input is checked before looking up the cache.
+        let (hits, misses) = (cache.hits(), cache.misses());
+        assert!(c.contours_cached(&values[1..], &[0.5], &cache).is_err());
+        let mut invalid = values.clone();
+        invalid[0] = f64::NAN;
+        assert!(c.clone().strict_input(true).contours_cached(&invalid, &[0.5], &cache).is_err());
+        assert_eq!((cache.hits(), cache.misses()), (hits, misses));
+
+        // The key of the values is their XXH3 hash, across chunks.
+        let values = (0..1500).map(|i| i as f64 / 7.).collect::<Vec<_>>();
+        let mut bytes = (values.len() as u64).to_le_bytes().to_vec();
+        bytes.extend(values.iter().flat_map(|value| value.to_bits().to_le_bytes()));
+        assert_eq!(crate::cache::hash_values(&values), xxhash_rust::xxh3::xxh3_64(&bytes));
+    }
+
+    #[test]
+    fn test_contours_diff() {
+        // The square of `test_simple_polygon`, shifted by one cell to the right and down.
+        let square = |dx: usize, dy: usize| {
+            let mut values = vec![0.; 100];
+            for (y, x) in (3..8).flat_map(|y| (3..6).map(move |x| (y, x))) {
+                values[(y + dy) * 10 + x + dx] = 1.;
+            }
+            values
+        };
+        let c = ContourBuilder::new(10, 10, true);
+        let before = c.contours(&square(0, 0), &[0.5]).unwrap();
+        let after = c.contours(&square(1, 1), &[0.5]).unwrap();
+        let diff = diff::diff_contours(&before, &after, 10, 10).unwrap();
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].threshold, 0.5);
+        assert_eq!(diff[0].gained.properties.as_ref().unwrap()["value"], 0.5);
+
+        // The L-shaped strips along the right and bottom sides of the shifted square,
+        // and along the left and top sides of the first one.
+        let cells = |feature: &geojson::Feature| {
+            let mask = raster::rasterize_feature(feature, 10, 10);
+            (0..100).filter(|i| mask[*i]).map(|i| (i % 10, i / 10)).collect::<Vec<_>>()
+        };
+        let mut gained = (4..9).map(|y| (6, y)).chain(vec![(4, 8), (5, 8)]).collect::<Vec<_>>();
+        gained.sort_by_key(|(x, y)| (*y, *x));
+        assert_eq!(cells(&diff[0].gained), gained);
+        let mut lost = (3..8).map(|y| (3, y)).chain(vec![(4, 3), (5, 3)]).collect::<Vec<_>>();
+        lost.sort_by_key(|(x, y)| (*y, *x));
+        assert_eq!(cells(&diff[0].lost), lost);
+        match diff[0].gained.geometry.as_ref().unwrap().value {
+            geojson::Value::MultiPolygon(ref p) => assert_eq!((p.len(), p[0].len()), (1, 1)),
+            _ => panic!(""),
+        };
+
+        // The same with an origin and a step, the regions being in output coordinates.
+        let c = c.x_origin(100.).x_step(2.).bbox(true);
+        let before = c.contours(&square(0, 0), &[0.5]).unwrap();
+        let after = c.contours(&square(1, 1), &[0.5]).unwrap();
+        let diff = c.contours_diff(&before, &after).unwrap();
+        assert_eq!(diff[0].gained.bbox, Some(vec![108., 4., 114., 9.]));
+        assert_eq!(diff[0].lost.bbox, Some(vec![106., 3., 112., 8.]));
+
+        // Nothing changed.
+        let same = c.contours_diff(&before, &before).unwrap();
+        assert_eq!(same[0].gained.bbox, None);
+        match c.contours_diff(&before, &[]).unwrap_err().kind() {
+            ErrorKind::MismatchedThresholds { before: 1, after: 0 } => {}
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    #[cfg(feature = "buffer")]
+    fn test_buffer() {
+        use crate::buffer::buffer_polygons;
+        use crate::geom::area;
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(10, 10, true).area(true).perimeter(true);
+        let properties = |c: &ContourBuilder| {
+            let features = c.contours(&values, &[0.5]).unwrap();
+            let properties = features[0].properties.clone().unwrap();
+            (properties["area"].as_f64().unwrap(), properties["perimeter"].as_f64().unwrap())
+        };
+        // The square (with chamfered corners) being convex, its area grows by exactly
+        // `perimeter * d + pi * d^2`, the arcs being approximated.
+        let (square, perimeter) = properties(&c);
+        let (grown, _) = properties(&c.clone().buffer(1.));
+        assert!((grown - (square + perimeter + std::f64::consts::PI)).abs() < 0.1);
+        let (grown, _) = properties(&c.clone().x_step(2.).y_step(2.).buffer(1.));
+        assert!((grown - (4. * square + 2. * perimeter + std::f64::consts::PI)).abs() < 0.1);
+        // Shrinking it too much leaves nothing.
+        let (shrunk, _) = properties(&c.clone().buffer(-0.5));
+        assert!(shrunk > 0. && shrunk < square);
+        let (vanished, _) = properties(&c.clone().buffer(-2.));
+        assert_eq!(vanished, 0.);
+
+        // The holes shrink as the polygons grow, until they vanish.
+        let ring = |min: f64, max: f64| vec![vec![min, min], vec![min, max], vec![max, max], vec![max, min], vec![min, min]];
+        let polygon = vec![ring(0., 10.), ring(3., 7.)];
+        let grown = buffer_polygons(std::slice::from_ref(&polygon), 1.).unwrap();
+        assert_eq!((grown.len(), grown[0].len()), (1, 2));
+        // `area` being twice the signed area, the hole of side 2 has an area of 8.
+        assert!((area(&grown[0][1]).abs() - 8.).abs() < 0.1);
+        let filled = buffer_polygons(std::slice::from_ref(&polygon), 2.5).unwrap();
+        assert_eq!((filled.len(), filled[0].len()), (1, 1));
+        // Two squares merging into one polygon.
+        let squares = vec![vec![ring(0., 2.)], vec![ring(3., 5.)]];
+        assert_eq!(buffer_polygons(&squares, 1.).unwrap().len(), 1);
+        assert_eq!(buffer_polygons(&squares, 0.).unwrap(), squares);
+    }
+
+    #[test]
+    fn test_hex_contours() {
+        use hex::{HexCoordinates, HexGrid, HexOrientation};
+        // A hexagon and its six neighbors, in odd-r offset coordinates.
+        let grid = HexGrid::new(5, 5, HexOrientation::PointyTop, HexCoordinates::Offset, 1.);
+        let rings = grid
+            .contour_rings(&[
+                0., 0., 0., 0., 0.,
+                0., 1., 1., 0., 0.,
+                0., 1., 1., 1., 0.,
+                0., 1., 1., 0., 0.,
+                0., 0., 0., 0., 0.
+            ], 0.5, true)
+            .unwrap();
+        assert_eq!(rings.len(), 1);
+        let ring = &rings[0];
+        // One position on each of the 18 edges between the blob and the hexagons around it.
+        assert_eq!(ring.len(), 19);
+        assert!(ring.is_closed());
+        assert_eq!(ring.orientation(), Winding::Clockwise);
+        // Six triangles of the lattice inside of the contour, six three quarters inside
+        // and twelve a quarter inside, the triangles having sides of √3.
+        let triangle = 3. * 3f64.sqrt() / 4.;
+        let expected = (6. + 6. * 0.75 + 12. * 0.25) * triangle;
+        assert!((ring.signed_area() + expected).abs() < 1e-9);
+        // Almost the area of the seven hexagons.
+        assert!((-ring.signed_area() / (7. * 2. * triangle) - 0.96).abs() < 0.01);
+
+        // The same blob in axial coordinates, with flat-top hexagons.
+        let grid = HexGrid::new(3, 3, HexOrientation::FlatTop, HexCoordinates::Axial, 1.);
+        let features = grid
+            .contours(&[
+                0., 1., 1.,
+                1., 1., 1.,
+                1., 1., 0.
+            ], &[0.5], true)
+            .unwrap();
+        match features[0].geometry.as_ref().map(|g| &g.value) {
+            Some(geojson::Value::MultiPolygon(polygons)) => {
+                assert_eq!(polygons.len(), 1);
+                assert_eq!(polygons[0].len(), 1);
+                assert!((geom::area(&polygons[0][0]) / 2. - expected).abs() < 1e-9);
+            }
+            _ => panic!(""),
+        }
+        assert_eq!(grid.center(1, 1), [1.5, 3f64.sqrt() * 1.5]);
+    }
+
+    #[test]
+    #[cfg(feature = "tin")]
+    fn test_tin_contours() {
+        use crate::tin::Tin;
+        // Irregular points sampled from the plane `x + 2y`.
+        let mut points = Vec::new();
+        for i in 0..8 {
+            for j in 0..8 {
+                let x = i as f64 + ((i * 7 + j * 3) % 5) as f64 * 0.1;
+                let y = j as f64 + ((i * 3 + j * 5) % 7) as f64 * 0.1;
+                points.push((x, y, x + 2. * y));
+            }
+        }
+        let tin = Tin::new(&points);
+        assert_eq!(tin.points().len(), 64);
+
+        let isolines = tin.isolines(&[7.3]);
+        match isolines[0].geometry.as_ref().map(|g| &g.value) {
+            Some(geojson::Value::MultiLineString(lines)) => {
+                // A single straight line across the points, ending on their convex hull.
+                assert_eq!(lines.len(), 1);
+                assert!(lines[0].len() > 2);
+                assert_ne!(lines[0].first(), lines[0].last());
+                assert!(lines[0].iter().all(|p| (p[0] + 2. * p[1] - 7.3).abs() < 1e-9));
+            }
+            _ => panic!(""),
+        }
+
+        let contours = tin.contours(&[7.3]);
+        match contours[0].geometry.as_ref().map(|g| &g.value) {
+            Some(geojson::Value::MultiPolygon(polygons)) => {
+                assert_eq!(polygons.len(), 1);
+                assert_eq!(polygons[0].len(), 1);
+                let ring = &polygons[0][0];
+                assert_eq!(ring.first(), ring.last());
+                assert!(geom::area(ring) > 0.);
+                // Along the isoline, then along the convex hull on the side of the higher values.
+                assert!(ring.iter().all(|p| p[0] + 2. * p[1] - 7.3 > -1e-9));
+            }
+            _ => panic!(""),
+        }
+    }
+
+    #[test]
+    fn test_ring_closure() {
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.
+        ];
+        let rings_of = |c: ContourBuilder| match c.contours(&values, &[0.5]).unwrap()[0].geometry.as_ref().map(|g| g.value.clone()) {
+            Some(geojson::Value::MultiPolygon(polygons)) => polygons.into_iter().flatten().collect::<Vec<_>>(),
+            _ => panic!(""),
+        };
+        // Valid GeoJSON: closed rings of at least four positions.
+        let closed = rings_of(ContourBuilder::new(5, 5, true));
+        assert_eq!(closed.len(), 2);
+        assert!(closed.iter().all(|ring| ring.len() >= 4 && ring.first() == ring.last()));
+        let open = rings_of(ContourBuilder::new(5, 5, true).ring_closure(RingClosure::Open));
+        assert_eq!(open.len(), 2);
+        for (open, closed) in open.iter().zip(closed.iter()) {
+            assert_eq!(open.len(), closed.len() - 1);
+            assert_eq!(open[..], closed[..open.len()]);
+            assert_ne!(open.first(), open.last());
+        }
+        // The properties don't depend on the closure of the rings.
+        let area = |c: ContourBuilder| c.area(true).contours(&values, &[0.5]).unwrap()[0].properties.as_ref().unwrap()["area"].clone();
+        assert_eq!(area(ContourBuilder::new(5, 5, true)), area(ContourBuilder::new(5, 5, true).ring_closure(RingClosure::Open)));
+
+        let mut ring = Ring::new(closed[0].clone());
+        ring.open();
+        assert_eq!(ring.len(), closed[0].len() - 1);
+        assert!(!ring.is_closed());
+        ring.close();
+        assert_eq!(ring, closed[0]);
+
+        let open = IsoRingBuilder::new(5, 5).ring_closure(RingClosure::Open).compute(&values, 0.5).unwrap();
+        let closed = IsoRingBuilder::new(5, 5).compute(&values, 0.5).unwrap();
+        assert_eq!(open.iter().map(|ring| ring.len() + 1).collect::<Vec<_>>(), closed.iter().map(|ring| ring.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_contour_rings_smooth() {
+        let values = [
+            0., 0., 0., 0., 0., 0.,
+            0., 0.7, 1.2, 0.9, 0.1, 0.,
+            0., 2., 0.2, 0.3, 1.5, 0.,
+            0., 0.8, 1.1, 4., 0.6, 0.,
+            0., 0., 0., 0., 0., 0.
+        ];
+        let rings = contour_rings_smooth(&values, 0.5, 6, 5).unwrap();
+        let features = ContourBuilder::new(6, 5, true).contours(&values, &[0.5]).unwrap();
+        let expected = match features[0].geometry.as_ref().map(|g| &g.value) {
+            Some(geojson::Value::MultiPolygon(polygons)) => polygons.iter().flatten().cloned().collect::<Vec<_>>(),
+            _ => panic!(""),
+        };
+        assert_eq!(rings.len(), expected.len());
+        assert!(rings.iter().all(|ring| expected.iter().any(|e| ring == e)));
+        // The smoothed positions differ from the unsmoothed ones.
+        let unsmoothed = contour_rings(&values, 0.5, 6, 5).unwrap();
+        assert_eq!(rings.len(), unsmoothed.len());
+        assert!(rings.iter().zip(unsmoothed.iter()).any(|(a, b)| a != b));
+        match contour_rings_smooth(&values, 0.5, 5, 5).unwrap_err().kind() {
+            ErrorKind::BadDimension => {}
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    fn test_enclosures() {
+        // A peak and a pit, on a plateau surrounded by lower values.
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 5., 5., 5., 5., 5., 5., 0.,
+            0., 5., 9., 5., 5., 1., 5., 0.,
+            0., 5., 5., 5., 5., 5., 5., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(8, 5, true).enclosures(true);
+        let features = c.contours(&values, &[-1., 3., 7.]).unwrap();
+        let encloses = |i: usize| features[i].properties.as_ref().unwrap()["encloses"].clone();
+        // The whole grid, reaching its border.
+        assert_eq!(encloses(0), serde_json::json!([[null]]));
+        // The plateau, with the pit as hole.
+        assert_eq!(encloses(1), serde_json::json!([["maximum", "minimum"]]));
+        // The peak.
+        assert_eq!(encloses(2), serde_json::json!([["maximum"]]));
+        assert!(!ContourBuilder::new(8, 5, true).contours(&values, &[3.]).unwrap()[0].properties.as_ref().unwrap().contains_key("encloses"));
+
+        // A peak within a pit, within a plateau: the peak is enclosed by its own contour,
+        // not by the hole around it.
+        #[rustfmt::skip]
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 6., 5., 5., 5., 5., 5., 5., 5., 5., 0.,
+            0., 5., 5., 5., 5., 5., 5., 5., 5., 5., 0.,
+            0., 5., 5., 1., 1., 1., 1., 1., 5., 5., 0.,
+            0., 5., 5., 1., 1., 1., 1., 1., 5., 5., 0.,
+            0., 5., 5., 1., 1., 9., 1., 1., 5., 5., 0.,
+            0., 5., 5., 1., 1., 1., 1., 1., 5., 5., 0.,
+            0., 5., 5., 1., 1., 1., 1., 0.5, 5., 5., 0.,
+            0., 5., 5., 5., 5., 5., 5., 5., 5., 5., 0.,
+            0., 5., 5., 5., 5., 5., 5., 5., 5., 5., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(11, 11, true).enclosures(true);
+        let features = c.contours(&values, &[3.]).unwrap();
+        let encloses = features[0].properties.as_ref().unwrap()["encloses"].clone();
+        assert_eq!(encloses, serde_json::json!([["maximum"], ["maximum", "minimum"]]));
+        let parts = c.contours_exploded(&values, &[3.]).unwrap();
+        let encloses = parts.iter().map(|part| part.properties.as_ref().unwrap()["encloses"].clone()).collect::<Vec<_>>();
+        assert_eq!(encloses, vec![serde_json::json!([["maximum"]]), serde_json::json!([["maximum", "minimum"]])]);
+        // The hole of the band between 3 and 7 around the top of the peak encloses the maximum,
+        // its exterior ring nothing.
+        let bands = c.contours_banded(&values, &[3., 7.]).unwrap();
+        let encloses = bands.iter().map(|band| band.properties.as_ref().unwrap()["encloses"].clone()).collect::<Vec<_>>();
+        assert_eq!(encloses, vec![serde_json::json!([[null, "maximum"], ["maximum", "minimum"]]), serde_json::json!([["maximum"]])]);
+    }
+
+    #[test]
+    fn test_contours_banded() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 2., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 2., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(10, 10, true).area(true);
+        let cumulative = c.contours(&values, &[0.5, 1.5]).unwrap();
+        let banded = c.contours_banded(&values, &[1.5, 0.5]).unwrap();
+        let polygons = |feature: &geojson::Feature| match feature.geometry.as_ref().map(|g| &g.value) {
+            Some(geojson::Value::MultiPolygon(p)) => p.clone(),
+            _ => panic!(""),
+        };
+        // The 0.5 band has a hole where the 1.5 contour is.
+        let (lower, higher) = (polygons(&banded[0]), polygons(&cumulative[1]));
+        assert_eq!(lower.len(), 1);
+        assert_eq!(lower[0].len(), 2);
+        assert_eq!(lower[0][0], polygons(&cumulative[0])[0][0]);
+        assert_eq!(lower[0][1], higher[0][0].iter().rev().cloned().collect::<Vec<_>>());
+        // The last band is the same as its contour.
+        assert_eq!(polygons(&banded[1]), higher);
+
+        let property = |feature: &geojson::Feature, name: &str| feature.properties.as_ref().unwrap()[name].clone();
+        assert_eq!((property(&banded[0], "min"), property(&banded[0], "max")), (0.5.into(), 1.5.into()));
+        assert_eq!((property(&banded[1], "min"), property(&banded[1], "max")), (1.5.into(), serde_json::Value::Null));
+        let area = |feature: &geojson::Feature| property(feature, "area").as_f64().unwrap();
+        assert!((area(&banded[0]) + area(&banded[1]) - area(&cumulative[0])).abs() < 1e-9);
+        assert!(area(&banded[0]) < area(&cumulative[0]));
+    }
+
+    #[test]
+    fn test_contours_vs_grid() {
+        let (dx, dy) = (9, 7);
+        let values = (0..dx * dy).map(|i| ((i % dx) as f64 * 0.7).sin() * 3. + (i / dx) as f64).collect::<Vec<_>>();
+        let reference = (0..dx * dy).map(|i| (i / dx) as f64 * 0.8 + (i % 3) as f64 * 0.1).collect::<Vec<_>>();
+        let difference = values.iter().zip(&reference).map(|(v, r)| v - r).collect::<Vec<_>>();
+        let offsets = [-1., 0., 0.5, 2.];
+        for smooth in &[true, false] {
+            let c = ContourBuilder::new(dx as u32, dy as u32, *smooth).zonal_stats(true);
+            assert_eq!(c.contours_vs_grid(&values, &reference, &offsets).unwrap(), c.contours(&difference, &offsets).unwrap());
+        }
+        let c = ContourBuilder::new(dx as u32, dy as u32, true);
+        match c.contours_vs_grid(&values, &reference[1..], &offsets).unwrap_err().kind() {
+            ErrorKind::BadDimension => {}
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn test_contours_from_raw_file() {
+        use crate::mmap::{Endianness, RawType};
+        let (dx, dy) = (7, 5);
+        let values = (0..dx * dy).map(|i| ((i % dx) as f32 - 3.).powi(2) + ((i / dx) as f32 - 2.).powi(2)).collect::<Vec<f32>>();
+        let expected = ContourBuilder::new(dx as u32, dy as u32, true)
+            .contours(&values.iter().map(|v| f64::from(*v)).collect::<Vec<_>>(), &[1.5, 4.5])
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("contour-raw-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let c = ContourBuilder::new(dx as u32, dy as u32, true);
+        let path = dir.join("be.f32");
+        std::fs::write(&path, values.iter().flat_map(|v| v.to_be_bytes().to_vec()).collect::<Vec<u8>>()).unwrap();
+        assert_eq!(c.contours_from_raw_file(&path, RawType::F32, Endianness::Big, &[1.5, 4.5]).unwrap(), expected);
+        let path = dir.join("le.f64");
+        std::fs::write(&path, values.iter().flat_map(|v| f64::from(*v).to_le_bytes().to_vec()).collect::<Vec<u8>>()).unwrap();
+        assert_eq!(c.contours_from_raw_file(&path, RawType::F64, Endianness::Little, &[1.5, 4.5]).unwrap(), expected);
+
+        // Truncated, and not made of whole values.
+        for len in &[dx * dy * 4 - 4, dx * dy * 4 + 2] {
+            let path = dir.join("invalid.f32");
+            std::fs::write(&path, vec![0u8; *len]).unwrap();
+            match c.contours_from_raw_file(&path, RawType::F32, Endianness::Little, &[1.5]).unwrap_err().kind() {
+                ErrorKind::RawFileLength { len: l, expected } => assert_eq!((*l, *expected), (*len as u64, (dx * dy * 4) as u64)),
+                _ => panic!(""),
+            };
+        }
+        match c.contours_from_raw_file(dir.join("missing.f32"), RawType::F32, Endianness::Little, &[1.5]).unwrap_err().kind() {
+            ErrorKind::Io(_) => {}
+            _ => panic!(""),
+        };
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "f16")]
+    fn test_contours_f16() {
+        let values = [
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0.3, 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 0.7, f64::NAN, 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 1., 1., 1., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0., 0., 0., 0., 0., 0., 0., 0., 0., 0.
+        ];
+        let halves = values.iter().map(|v| half::f16::from_f64(*v)).collect::<Vec<_>>();
+        // The values exactly representable in half precision.
+        let quantized = halves.iter().map(|v| f64::from(*v)).collect::<Vec<_>>();
+        for smooth in &[false, true] {
+            let c = ContourBuilder::new(10, 10, *smooth);
+            let features = c.contours_source(&halves[..], &[0.5, 0.8]).unwrap();
+            assert_eq!(features, c.contours(&quantized, &[0.5, 0.8]).unwrap());
+            if !smooth {
+                assert_eq!(features, c.contours(&values, &[0.5, 0.8]).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_contours_geometries() {
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 2.
+        ];
+        let thresholds = [0.5, 1.5, 3.];
+        let builders = vec![
+            ContourBuilder::new(5, 5, true),
+            ContourBuilder::new(5, 5, false).x_origin(10.).y_step(-2.),
+            ContourBuilder::new(5, 5, true).geometry_mode(GeometryMode::Auto).area(true).bbox(true),
+            ContourBuilder::new(5, 5, true).ring_closure(RingClosure::Open),
+        ];
+        for c in builders {
+            let features = c.contours(&values, &thresholds).unwrap();
+            let geometries = c.contours_geometries(&values, &thresholds).unwrap();
+            assert_eq!(geometries.len(), features.len());
+            for (geometry, feature) in geometries.iter().zip(features.iter()) {
+                assert_eq!(Some(geometry), feature.geometry.as_ref());
+            }
+        }
+        // Without polygons, an empty MultiPolygon even when the Features get no geometry.
+        let c = ContourBuilder::new(5, 5, true).empty_geometry(EmptyGeometry::Null);
+        assert!(c.contours(&values, &[3.]).unwrap()[0].geometry.is_none());
+        assert_eq!(c.contours_geometries(&values, &[3.]).unwrap()[0].value, geojson::Value::MultiPolygon(vec![]));
+        match c.contours_geometries(&values[1..], &[0.5]).unwrap_err().kind() {
+            ErrorKind::BadDimension => {},
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_verify_nesting() {
+        // A plateau at 1 around a peak, the second threshold only differing by less than
+        // the tolerance: both contours follow the plateau through the centers of its cells.
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 2., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.
+        ];
+        let thresholds = [1., 1. + 1e-12];
+        let c = ContourBuilder::new(5, 5, true).threshold_epsilon(1e-9).verify_nesting(true);
+        let features = c.contours(&values, &thresholds).unwrap();
+        let positions = |i: usize| match features[i].geometry.as_ref().map(|g| g.value.clone()) {
+            Some(geojson::Value::MultiPolygon(polygons)) => polygons.into_iter().flatten().flatten().collect::<Vec<_>>(),
+            _ => panic!(""),
+        };
+        assert_eq!(positions(0), positions(1));
+        assert!(positions(0).contains(&vec![1.5, 1.5]));
+
+        // The contour of the plateau goes exactly through the centers of its cells.
+        let c = ContourBuilder::new(5, 5, true).verify_nesting(true);
+        let features = c.contours(&values, &[1., 0.5, 2.]).unwrap();
+        let positions = |i: usize| match features[i].geometry.as_ref().map(|g| g.value.clone()) {
+            Some(geojson::Value::MultiPolygon(polygons)) => polygons.into_iter().flatten().flatten().collect::<Vec<_>>(),
+            _ => panic!(""),
+        };
+        assert!(positions(0).contains(&vec![3.5, 3.5]));
+        assert!(positions(0).iter().all(|p| p.iter().all(|v| v.fract() == 0.5)));
+        // The NaN thresholds are left out of the check.
+        let features = c.contours(&values, &[1., f64::NAN, 0.5]).unwrap();
+        assert_eq!(features.len(), 3);
+
+        // Contours moved apart by the transformation aren't nested anymore.
+        let c = c.reproject_with(|| {
+            let mut calls = 0.;
+            Ok::<_, String>(move |x: f64, y: f64| {
+                calls += 1.;
+                Ok::<_, String>((x + 10. * calls, y))
+            })
+        });
+        match c.contours(&values, &[0.5, 1.]).unwrap_err().kind() {
+            ErrorKind::NotNested { lower, higher } => assert_eq!((*lower, *higher), (0.5, 1.)),
+            _ => panic!(""),
+        };
+        assert!(c.verify_nesting(false).contours(&values, &[0.5, 1.]).is_ok());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_smoother() {
+        use crate::smooth::{GridView, LinearSmoother, VertexSmoother};
+
+        struct NoOp;
+
+        impl VertexSmoother for NoOp {
+            fn smooth(&self, _: &mut Ring, _: &GridView, _: f64) {}
+        }
+
+        // The linear interpolation, the positions staying within a quarter of a cell
+        // of the middle of their edge.
+        struct Clamped;
+
+        impl VertexSmoother for Clamped {
+            fn smooth(&self, ring: &mut Ring, grid: &GridView, threshold: f64) {
+                let middle = ring.clone();
+                LinearSmoother.smooth(ring, grid, threshold);
+                for (point, middle) in ring.iter_points_mut().zip(middle.iter_points()) {
+                    point[0] = middle[0] + (point[0] - middle[0]).clamp(-0.25, 0.25);
+                    point[1] = middle[1] + (point[1] - middle[1]).clamp(-0.25, 0.25);
+                }
+            }
+        }
+
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 2., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.
+        ];
+        let thresholds = [0.5, 0.9, 1.5];
+        let positions = |c: &ContourBuilder| c.contours(&values, &thresholds).unwrap().into_iter().map(|feature| match feature.geometry.unwrap().value {
+            geojson::Value::MultiPolygon(polygons) => polygons.into_iter().flatten().flatten().collect::<Vec<_>>(),
+            _ => panic!(""),
+        }).collect::<Vec<_>>();
+        let linear = positions(&ContourBuilder::new(5, 5, true));
+        let unsmoothed = positions(&ContourBuilder::new(5, 5, false));
+        assert_eq!(positions(&ContourBuilder::new(5, 5, true).smoother(LinearSmoother)), linear);
+        assert_eq!(positions(&ContourBuilder::new(5, 5, true).smoother(NoOp)), unsmoothed);
+        // Not used without smoothing.
+        assert_eq!(positions(&ContourBuilder::new(5, 5, false).smoother(Clamped)), unsmoothed);
+
+        let clamped = positions(&ContourBuilder::new(5, 5, true).smoother(Clamped));
+        // Halfway between the values, the linear interpolation doesn't move the positions.
+        assert_eq!(clamped[0], linear[0]);
+        assert_eq!(clamped[2], linear[2]);
+        // 0.9 is reached 0.4 past the middle of the edges from 0 to 1.
+        assert_ne!(clamped[1], linear[1]);
+        for (clamped, middle) in clamped[1].iter().zip(unsmoothed[1].iter()) {
+            assert!((clamped[0] - middle[0]).abs() <= 0.25 && (clamped[1] - middle[1]).abs() <= 0.25);
+        }
+        assert!(clamped[1].contains(&vec![1.25, 2.5]));
+        assert!(linear[1].contains(&vec![1.4, 2.5]));
+
+        let grid = GridView::new(&values, 5, 5).unwrap();
+        assert_eq!((grid.dx(), grid.dy()), (5, 5));
+        assert_eq!(grid.get(2, 2), Some(2.));
+        assert_eq!(grid.get(5, 0), None);
+        assert_eq!(grid.value(24), Some(0.));
+        assert_eq!(grid.value(25), None);
+        match GridView::new(&values[1..], 5, 5).unwrap_err().kind() {
+            ErrorKind::BadDimension => {},
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_render_svg() {
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 2.
+        ];
+        let palette = vec![(255, 255, 255), (128, 128, 128), (0, 0, 0)];
+        let style = svg::SvgStyle { palette, stroke: Some((0, 0, 0)), background: Some((255, 255, 255)), ..svg::SvgStyle::default() };
+        let c = ContourBuilder::new(5, 5, true).x_origin(100.).y_origin(55.).y_step(-2.);
+        let svg = c.render_svg(&values, &[0.5, 1.5, 5.], &style).unwrap();
+
+        // Well-formed: every element is closed, in order.
+        let mut stack = Vec::new();
+        for tag in svg.split('<').skip(1).map(|tag| &tag[..tag.find('>').unwrap()]) {
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(stack.pop(), Some(name));
+            } else if !tag.ends_with('/') {
+                stack.push(tag.split_whitespace().next().unwrap());
+            }
+        }
+        assert!(stack.is_empty());
+        assert!(svg.starts_with("<svg ") && svg.trim_end().ends_with("</svg>"));
+
+        // One path per non-empty threshold, holes included.
+        assert_eq!(svg.matches("<path ").count(), 2);
+        assert_eq!(svg.matches(r#"fill-rule="evenodd""#).count(), 2);
+        assert!(svg.contains(r#"data-value="0.5""#) && svg.contains(r#"data-value="1.5""#));
+        assert!(svg.contains(r##"fill="#ffffff" fill-rule"##) && svg.contains(r##"fill="#808080" fill-rule"##));
+
+        // The y axis pointing up is flipped, the coordinates being inside the viewBox.
+        assert!(svg.contains(r#"viewBox="100 45 5 10""#));
+        assert!(svg.contains(r#"transform="matrix(1 0 0 -1 0 100)""#));
+        for d in svg.split(r#" d=""#).skip(1).map(|d| &d[..d.find('"').unwrap()]) {
+            let numbers = d.split(|c: char| c.is_ascii_alphabetic() || c == ' ').filter(|n| !n.is_empty()).collect::<Vec<_>>();
+            assert!(!numbers.is_empty());
+            for position in numbers {
+                let (x, y) = position.split_once(',').unwrap();
+                let (x, y) = (x.parse::<f64>().unwrap(), y.parse::<f64>().unwrap());
+                assert!((100. ..=105.).contains(&x) && (45. ..=55.).contains(&y));
+            }
+        }
+
+        let mut written = Vec::new();
+        c.write_svg(&mut written, &values, &[0.5, 1.5, 5.], &style).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), svg);
+        // Without flipping nor palette.
+        let style = svg::SvgStyle { palette: Vec::new(), ..svg::SvgStyle::default() };
+        let svg = ContourBuilder::new(5, 5, false).render_svg(&values, &[0.5], &style).unwrap();
+        assert!(svg.contains(r#"viewBox="0 0 5 5""#) && svg.contains("<g>") && svg.contains(r#"fill="none""#));
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    #[rustfmt::skip]
+    fn test_render_png() {
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 2., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.
+        ];
+        let palette = [(255, 0, 0), (0, 0, 255)];
+        let c = ContourBuilder::new(5, 5, true);
+        for anti_alias in [true, false].iter() {
+            let options = crate::png::PngOptions { background: Some((255, 255, 255)), stroke: Some((0, 0, 0)), anti_alias: *anti_alias, ..crate::png::PngOptions::default() };
+            let bytes = c.render_png_with(&values, &[0.5, 1.5], 50, 50, &palette, &options).unwrap();
+            let image = tiny_skia::Pixmap::decode_png(&bytes).unwrap();
+            assert_eq!((image.width(), image.height()), (50, 50));
+            let rgba = |x: u32, y: u32| {
+                let pixel = image.pixel(x, y).unwrap();
+                (pixel.red(), pixel.green(), pixel.blue(), pixel.alpha())
+            };
+            // The center of the blob has the color of the top band, a corner the background.
+            assert_eq!(rgba(25, 25), (0, 0, 255, 255));
+            assert_eq!(rgba(0, 0), (255, 255, 255, 255));
+            assert_eq!(rgba(15, 35), (255, 0, 0, 255));
+        }
+
+        // Transparent background by default.
+        let image = tiny_skia::Pixmap::decode_png(&c.render_png(&values, &[0.5, 1.5], 50, 50, &palette).unwrap()).unwrap();
+        assert_eq!(image.pixel(0, 0).unwrap().alpha(), 0);
+        assert_eq!(image.pixel(25, 25).unwrap().blue(), 255);
+        match c.render_png(&values, &[0.5], 0, 50, &palette).unwrap_err().kind() {
+            ErrorKind::Render(_) => {},
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_union_touching() {
+        let polygons = |c: &ContourBuilder, values: &[f64]| {
+            let feature = c.contours(values, &[0.5]).unwrap().remove(0);
+            let area = feature.properties.as_ref().unwrap()["area"].as_f64().unwrap();
+            match feature.geometry.unwrap().value {
+                geojson::Value::MultiPolygon(polygons) => (polygons, area),
+                _ => panic!(""),
+            }
+        };
+
+        // Two blobs moved next to each other by the transformation, sharing a side.
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(5, 5, false)
+            .area(true)
+            .reproject_with(|| Ok::<_, String>(|x: f64, y: f64| Ok::<_, String>(if x > 2.5 { (x - 1., y) } else { (x, y) })));
+        let (apart, area) = polygons(&c, &values);
+        assert_eq!(apart.len(), 2);
+        let (merged, merged_area) = polygons(&c.clone().union_touching(true), &values);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].len(), 1);
+        assert_eq!(merged_area, area);
+        assert_eq!(area, 5.);
+        // The shared side is dissolved.
+        assert!(!merged[0][0].contains(&vec![2., 2.5]));
+        assert_eq!(merged[0][0].first(), merged[0][0].last());
+
+        // Two blobs moved to meet at a pinch point.
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 0., 0., 0.,
+            0., 0., 0., 0., 0.,
+            0., 0., 0., 1., 0.,
+            0., 0., 0., 0., 0.
+        ];
+        let c = ContourBuilder::new(5, 5, false)
+            .area(true)
+            .reproject_with(|| Ok::<_, String>(|x: f64, y: f64| Ok::<_, String>(if x > 2.5 && y > 2.5 { (x - 1., y - 2.) } else { (x, y) })));
+        let (apart, area) = polygons(&c, &values);
+        assert_eq!(apart.len(), 2);
+        let (merged, merged_area) = polygons(&c.clone().union_touching(true), &values);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].len(), 1);
+        assert_eq!((merged_area, area), (1., 1.));
+        // Through the pinch point twice.
+        assert_eq!(merged[0][0].len(), 9);
+        assert_eq!(merged[0][0][..8].iter().filter(|p| **p == vec![2., 1.5]).count(), 2);
+
+        // The polygons that don't touch are left as is.
+        let c = ContourBuilder::new(5, 5, false).area(true);
+        assert_eq!(polygons(&c.clone().union_touching(true), &values), polygons(&c, &values));
+    }
+
+    #[test]
+    #[cfg(feature = "gpkg")]
+    #[rustfmt::skip]
+    fn test_write_gpkg() {
+        let values = [
+            0., 0., 0., 0.,
+            0., 1., 1., 0.,
+            0., 1., 1., 0.,
+            0., 0., 0., 0.
+        ];
+        let features = ContourBuilder::new(4, 4, false).contours(&values, &[0.5, 2.]).unwrap();
+        let path = std::env::temp_dir().join(format!("contour-{}.gpkg", std::process::id()));
+        let options = crate::gpkg::GpkgOptions { isolines: Some(String::from("isolines")), srid: 4326, ..crate::gpkg::GpkgOptions::default() };
+        crate::gpkg::write_gpkg(&path, &features, &options).unwrap();
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let application_id: i64 = connection.query_row("PRAGMA application_id", [], |row| row.get(0)).unwrap();
+        assert_eq!(application_id, 0x4750_4B47);
+        let mut statement = connection.prepare("SELECT table_name, data_type, srs_id, min_x, max_y FROM gpkg_contents ORDER BY table_name").unwrap();
+        let contents = statement
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i32>(2)?, row.get::<_, f64>(3)?, row.get::<_, f64>(4)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(contents, vec![
+            (String::from("contours"), String::from("features"), 4326, 1., 3.),
+            (String::from("isolines"), String::from("features"), 4326, 1., 3.),
+        ]);
+        let mut statement = connection.prepare("SELECT table_name, column_name, geometry_type_name, srs_id, z FROM gpkg_geometry_columns ORDER BY table_name").unwrap();
+        let columns = statement
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, i32>(3)?, row.get::<_, i32>(4)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(columns, vec![
+            (String::from("contours"), String::from("geom"), String::from("MULTIPOLYGON"), 4326, 0),
+            (String::from("isolines"), String::from("geom"), String::from("MULTILINESTRING"), 4326, 0),
+        ]);
+        let srs: i64 = connection.query_row("SELECT COUNT(*) FROM gpkg_spatial_ref_sys WHERE srs_id IN (-1, 0, 4326)", [], |row| row.get(0)).unwrap();
+        assert_eq!(srs, 3);
+
+        let rows = connection
+            .prepare("SELECT geom, value FROM contours ORDER BY fid")
+            .unwrap()
+            .query_map([], |row| Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, f64>(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(rows.iter().map(|row| row.1).collect::<Vec<_>>(), vec![0.5, 2.]);
+        let read_u32 = |bytes: &[u8], at: usize| u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        let read_f64 = |bytes: &[u8], at: usize| {
+            let mut b = [0; 8];
+            b.copy_from_slice(&bytes[at..at + 8]);
+            f64::from_le_bytes(b)
+        };
+        // The header: magic, version, flags (little-endian, [minx, maxx, miny, maxy] envelope), srs id.
+        let blob = &rows[0].0;
+        assert_eq!(&blob[..4], &[b'G', b'P', 0, 0b11]);
+        assert_eq!(read_u32(blob, 4) as i32, 4326);
+        assert_eq!((read_f64(blob, 8), read_f64(blob, 16), read_f64(blob, 24), read_f64(blob, 32)), (1., 3., 1., 3.));
+        // The WKB: a MultiPolygon of one Polygon of one ring.
+        let wkb = &blob[40..];
+        assert_eq!((wkb[0], read_u32(wkb, 1), read_u32(wkb, 5)), (1, 6, 1));
+        assert_eq!((wkb[9], read_u32(wkb, 10), read_u32(wkb, 14)), (1, 3, 1));
+        let ring = match features[0].geometry.as_ref().unwrap().value {
+            geojson::Value::MultiPolygon(ref polygons) => polygons[0][0].clone(),
+            _ => panic!(""),
+        };
+        assert_eq!(read_u32(wkb, 18) as usize, ring.len());
+        let points = (0..ring.len()).map(|i| vec![read_f64(wkb, 22 + 16 * i), read_f64(wkb, 30 + 16 * i)]).collect::<Vec<_>>();
+        assert_eq!(points, ring);
+        // The empty geometry has no envelope.
+        assert_eq!(&rows[1].0, &[b'G', b'P', 0, 0b1_0001, 0xE6, 0x10, 0, 0, 1, 6, 0, 0, 0, 0, 0, 0, 0]);
+
+        let blob: Vec<u8> = connection.query_row("SELECT geom FROM isolines WHERE fid = 1", [], |row| row.get(0)).unwrap();
+        let wkb = &blob[40..];
+        assert_eq!((wkb[0], read_u32(wkb, 1), read_u32(wkb, 5)), (1, 5, 1));
+        assert_eq!((wkb[9], read_u32(wkb, 10), read_u32(wkb, 14) as usize), (1, 2, ring.len()));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_contours_ewkb() {
+        // A WKB parser of MultiPolygons skipping the SRID of EWKB.
+        fn parse(wkb: &[u8]) -> (Option<u32>, Vec<Vec<crate::contour::LinearRing>>) {
+            let mut at = 0;
+            let u32_at = |wkb: &[u8], at: &mut usize| {
+                let v = u32::from_le_bytes([wkb[*at], wkb[*at + 1], wkb[*at + 2], wkb[*at + 3]]);
+                *at += 4;
+                v
+            };
+            let f64_at = |wkb: &[u8], at: &mut usize| {
+                let mut b = [0; 8];
+                b.copy_from_slice(&wkb[*at..*at + 8]);
+                *at += 8;
+                f64::from_le_bytes(b)
+            };
+            let header = |at: &mut usize| {
+                assert_eq!(wkb[*at], 1);
+                *at += 1;
+                let geometry_type = u32_at(wkb, at);
+                let srid = if geometry_type & 0x2000_0000 != 0 { Some(u32_at(wkb, at)) } else { None };
+                (geometry_type & 0xFFFF, geometry_type & 0x8000_0000 != 0, srid)
+            };
+            let (geometry_type, z, srid) = header(&mut at);
+            assert_eq!(geometry_type, 6);
+            let mut polygons = Vec::new();
+            for _ in 0..u32_at(wkb, &mut at) {
+                assert_eq!(header(&mut at), (3, z, None));
+                let mut rings = Vec::new();
+                for _ in 0..u32_at(wkb, &mut at) {
+                    let points = (0..u32_at(wkb, &mut at))
+                        .map(|_| (0..if z { 3 } else { 2 }).map(|_| f64_at(wkb, &mut at)).collect())
+                        .collect();
+                    rings.push(points);
+                }
+                polygons.push(rings);
+            }
+            assert_eq!(at, wkb.len());
+            (srid, polygons)
+        }
+
+        let values = [
+            0., 0., 0., 0., 0.,
+            0., 1., 1., 1., 0.,
+            0., 1., 0., 1., 0.,
+            0., 1., 1., 1., 0.,
+            0., 0., 0., 0., 0.
+        ];
+        for c in [ContourBuilder::new(5, 5, true), ContourBuilder::new(5, 5, false).z(true).ring_closure(RingClosure::Open)].iter() {
+            let rows = c.contours_ewkb(&values, &[0.5, 2.], 4326).unwrap();
+            assert_eq!(rows.iter().map(|row| row.0).collect::<Vec<_>>(), vec![0.5, 2.]);
+            // Little-endian, MultiPolygon with the SRID flag (and the z one), SRID 4326.
+            let z = if c.options().z { 0x80 } else { 0 };
+            assert_eq!(&rows[0].1[..9], &[1, 6, 0, 0, 0x20 | z, 0xE6, 0x10, 0, 0]);
+            let (srid, polygons) = parse(&rows[0].1);
+            assert_eq!(srid, Some(4326));
+            // The same coordinates as the Features, with closed rings.
+            let features = c.clone().ring_closure(RingClosure::Closed).contours(&values, &[0.5]).unwrap();
+            match features[0].geometry.as_ref().unwrap().value {
+                geojson::Value::MultiPolygon(ref expected) => assert_eq!(&polygons, expected),
+                _ => panic!(""),
+            };
+            assert_eq!(polygons[0].len(), 2);
+            // An empty MultiPolygon.
+            assert_eq!(rows[1].1, vec![1, 6, 0, 0, 0x20, 0xE6, 0x10, 0, 0, 0, 0, 0, 0]);
+        }
+
+        assert_eq!(wkb::to_hex(&[0x01, 0xAB, 0x0F]), "01AB0F");
+        let rows = vec![(0.5, vec![1, 6, 0, 0, 0x20]), (-1.5, vec![0xFF])];
+        let mut copy = Vec::new();
+        wkb::write_copy_rows(&mut copy, &rows).unwrap();
+        assert_eq!(String::from_utf8(copy).unwrap(), "0.5\t0106000020\n-1.5\tFF\n");
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_contours_with_props() {
+        #[derive(serde::Serialize)]
+        struct Unit {
+            name: &'static str,
+            scale: f64,
+        }
+        #[derive(serde::Serialize)]
+        struct Props {
+            value: f64,
+            run_id: u32,
+            unit: Unit,
+            tags: Vec<&'static str>,
+        }
+        let values = [
+            0., 0., 0.,
+            0., 1., 0.,
+            0., 0., 0.
+        ];
+        let make = |i: usize, threshold: f64| Props {
+            value: threshold * 100.,
+            run_id: 7,
+            unit: Unit { name: "cm", scale: 0.01 },
+            tags: if i == 0 { vec!["low"] } else { Vec::new() },
+        };
+        let c = ContourBuilder::new(3, 3, false).area(true);
+        let features = c.contours_with_props(&values, &[0.5, 2.], make).unwrap();
+        let area = c.contours(&values, &[0.5]).unwrap()[0].properties.as_ref().unwrap()["area"].clone();
+        // Merged over the default properties, the serialized `value` replacing the threshold.
+        let expected = serde_json::json!({
+            "area": area,
+            "run_id": 7,
+            "tags": ["low"],
+            "unit": { "name": "cm", "scale": 0.01 },
+            "value": 50.0
+        });
+        assert_eq!(features[0].properties.as_ref(), expected.as_object());
+        assert_eq!(features[1].properties.as_ref().unwrap()["tags"], serde_json::json!([]));
+        let json = serde_json::to_value(&features[0]).unwrap();
+        assert_eq!(json["properties"]["unit"]["name"], "cm");
+
+        // Replacing them.
+        let features = c.clone().replace_properties(true).contours_with_props(&values, &[0.5], make).unwrap();
+        let mut keys = features[0].properties.as_ref().unwrap().keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["run_id", "tags", "unit", "value"]);
+
+        // The properties must be objects.
+        match c.contours_with_props(&values, &[0.5, 2.], |i, _| if i == 1 { None } else { Some(make(i, 0.)) }).unwrap_err().kind() {
+            ErrorKind::PropertiesNotObject { index: 1, value: serde_json::Value::Null } => {},
+            _ => panic!(""),
+        };
+        match c.contours_with_props(&values, &[0.5], |_, threshold| threshold).unwrap_err().kind() {
+            ErrorKind::PropertiesNotObject { index: 0, .. } => {},
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_value_transform() {
+        let values = [
+            1., 3., 12., 40., 7.,
+            2., 90., 350., 120., 5.,
+            4., 800., 2500., 600., 9.,
+            3., 60., 400., 80., 6.,
+            1., 2., 15., 8., 1.5
+        ];
+        let logs = values.iter().map(|v: &f64| v.log10()).collect::<Vec<_>>();
+        let thresholds = [0.5, 1., 1.75, 2.5, 3.];
+        for smooth in [true, false].iter() {
+            let c = ContourBuilder::new(5, 5, *smooth).area(true);
+            let transformed = c.clone().value_transform(f64::log10);
+            assert_eq!(transformed.contours(&values, &thresholds).unwrap(), c.contours(&logs, &thresholds).unwrap());
+            // Along with the other options reading the values.
+            let c = c.threshold_epsilon(0.01);
+            #[cfg(feature = "rayon")]
+            let c = c.threads(2);
+            assert_eq!(c.clone().value_transform(f64::log10).contours(&values, &thresholds).unwrap(), c.contours(&logs, &thresholds).unwrap());
+
+            let mut c = ContourBuilder::new(5, 5, *smooth);
+            c.set_thresholds(&thresholds).unwrap();
+            let expected = c.compute(&logs).unwrap();
+            let mut transformed = ContourBuilder::new(5, 5, *smooth).value_transform(f64::log10);
+            transformed.set_thresholds(&thresholds).unwrap();
+            assert_eq!(transformed.compute(&values).unwrap(), expected);
+        }
+        // The original values are left untouched, the builders compared by identity.
+        assert_eq!(values[12], 2500.);
+        let c = ContourBuilder::new(5, 5, true).value_transform(f64::log10);
+        assert_eq!(c.clone(), c);
+        assert_ne!(c, ContourBuilder::new(5, 5, true).value_transform(f64::log10));
+
+        // The non-finite results are checked like the non-finite values.
+        let mut values = values;
+        values[0] = 0.;
+        values[1] = -1.;
+        let c = ContourBuilder::new(5, 5, true).strict_input(true);
+        assert!(c.contours(&values, &thresholds).is_ok());
+        match c.value_transform(f64::log10).contours(&values, &thresholds).unwrap_err().kind() {
+            ErrorKind::InvalidInput(report) => {
+                assert_eq!(report.nan_indices, [1]);
+                assert_eq!(report.infinite_indices, [0]);
+            }
+            _ => panic!(""),
+        };
+    }
+
+    #[test]
+    #[cfg(feature = "netcdf")]
+    fn test_contours_from_netcdf() {
+        use crate::netcdf::{contours_from_netcdf, read_netcdf_grid};
+        // A bump at the second time step of a (time, lat, lon) variable, the latitudes
+        // decreasing from north to south, next to a nodata value.
+        let path = std::env::temp_dir().join(format!("contour-{}.nc", std::process::id()));
+        {
+            let mut file = ::netcdf::create(&path).unwrap();
+            file.add_dimension("time", 2).unwrap();
+            file.add_dimension("lat", 4).unwrap();
+            file.add_dimension("lon", 5).unwrap();
+            file.add_variable::<f64>("lat", &["lat"]).unwrap().put_values(&[40., 30., 20., 10.], ..).unwrap();
+            file.add_variable::<f64>("lon", &["lon"]).unwrap().put_values(&[0., 10., 20., 30., 40.], ..).unwrap();
+            let mut t = file.add_variable::<f32>("t", &["time", "lat", "lon"]).unwrap();
+            t.put_attribute("_FillValue", -999f32).unwrap();
+            #[rustfmt::skip]
+            let values: [f32; 20] = [
+                0., 0., 0., 0., 0.,
+                0., 0., 1., 0., -999.,
+                0., 0., 0., 0., 0.,
+                0., 0., 0., 0., 0.,
+            ];
+            t.put_values(&values, (1, .., ..)).unwrap();
+        }
+
+        let grid = read_netcdf_grid(&path, "t", 1).unwrap();
+        assert_eq!((grid.dx, grid.dy), (5, 4));
+        // The rows are reversed, from south to north.
+        assert_eq!((grid.x, grid.y), (Some((-5., 10.)), Some((5., 10.))));
+        assert_eq!(grid.values[2 * 5 + 2], 1.);
+        assert!(grid.values[2 * 5 + 4].is_nan());
+        // The first time step, never written, is nodata.
+        assert!(read_netcdf_grid(&path, "t", 0).unwrap().values.iter().all(|v| v.is_nan()));
+
+        let features = contours_from_netcdf(&path, "t", 1, &[0.5]).unwrap();
+        match features[0].geometry.as_ref().map(|g| &g.value) {
+            Some(geojson::Value::MultiPolygon(polygons)) => {
+                assert_eq!(polygons.len(), 1);
+                // Around (20, 30), half-way to the centers of the neighboring cells.
+                assert!(polygons[0][0].iter().all(|p| (15. ..=25.).contains(&p[0]) && (25. ..=35.).contains(&p[1])));
+            }
+            _ => panic!(""),
+        }
+
+        match read_netcdf_grid(&path, "lat", 0).unwrap_err().kind() {
+            ErrorKind::NetcdfDimensions { variable, dimensions } => assert_eq!((variable.as_str(), *dimensions), ("lat", 1)),
+            _ => panic!(""),
+        }
+        assert!(matches!(read_netcdf_grid(&path, "missing", 0).unwrap_err().kind(), ErrorKind::Netcdf(_)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "flatgeobuf")]
+    #[rustfmt::skip]
+    fn test_write_fgb() {
+        use crate::flatgeobuf::{FgbOptions, write_fgb};
+        // A minimal reader of the FlatBuffers tables, the flatgeobuf crate not being a dependency.
+        fn u16_at(bytes: &[u8], at: usize) -> usize {
+            u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize
+        }
+        fn u32_at(bytes: &[u8], at: usize) -> usize {
+            u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]) as usize
+        }
+        fn f64_at(bytes: &[u8], at: usize) -> f64 {
+            let mut b = [0; 8];
+            b.copy_from_slice(&bytes[at..at + 8]);
+            f64::from_le_bytes(b)
+        }
+        // The position of the field of the table, if set.
+        fn field(bytes: &[u8], table: usize, id: usize) -> Option<usize> {
+            let vtable = (table as i64 - u32_at(bytes, table) as i32 as i64) as usize;
+            let slot = 4 + 2 * id;
+            match if slot < u16_at(bytes, vtable) { u16_at(bytes, vtable + slot) } else { 0 } {
+                0 => None,
+                offset => Some(table + offset),
+            }
+        }
+        // The position of the table (or the vector) referenced at the position.
+        fn deref(bytes: &[u8], at: usize) -> usize {
+            at + u32_at(bytes, at)
+        }
+        // The position of the first element and the length of the vector of the field.
+        fn vector(bytes: &[u8], table: usize, id: usize) -> (usize, usize) {
+            let vector = deref(bytes, field(bytes, table, id).unwrap());
+            (vector + 4, u32_at(bytes, vector))
+        }
+        // The bounding box of the xy of the parts of the Feature at the position, and its value.
+        fn read_feature(bytes: &[u8], at: usize) -> ([f64; 4], f64) {
+            let feature = deref(bytes, at + 4);
+            let geometry = deref(bytes, field(bytes, feature, 0).unwrap());
+            assert_eq!(bytes[field(bytes, geometry, 6).unwrap()], 6);
+            let (parts, count) = vector(bytes, geometry, 7);
+            let mut bbox = [f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY];
+            for i in 0..count {
+                let part = deref(bytes, parts + 4 * i);
+                let (xy, len) = vector(bytes, part, 1);
+                let (ends, rings) = vector(bytes, part, 0);
+                assert_eq!(u32_at(bytes, ends + 4 * (rings - 1)), len / 2);
+                for j in 0..len / 2 {
+                    let (x, y) = (f64_at(bytes, xy + 16 * j), f64_at(bytes, xy + 16 * j + 8));
+                    bbox = [bbox[0].min(x), bbox[1].min(y), bbox[2].max(x), bbox[3].max(y)];
+                }
+            }
+            let (properties, len) = vector(bytes, feature, 1);
+            assert_eq!((len, u16_at(bytes, properties)), (10, 0));
+            (bbox, f64_at(bytes, properties + 2))
+        }
+        let read_bbox = |bytes: &[u8], at: usize| [f64_at(bytes, at), f64_at(bytes, at + 8), f64_at(bytes, at + 16), f64_at(bytes, at + 24)];
+
+        let values = [
+            0., 0., 0., 0., 0., 0., 0.,
+            0., 1., 0., 2., 0., 3., 0.,
+            0., 0., 0., 0., 0., 0., 0.
+        ];
+        let features = ContourBuilder::new(7, 3, false).bbox(true).contours(&values, &[0.5, 1.5, 2.5]).unwrap();
+        let bboxes = features.iter().map(|feature| {
+            let bbox = feature.bbox.as_ref().unwrap();
+            [bbox[0], bbox[1], bbox[2], bbox[3]]
+        }).collect::<Vec<_>>();
+        let mut bytes = Vec::new();
+        write_fgb(&mut bytes, &features, &FgbOptions { epsg: Some(4326), index_node_size: 2, ..FgbOptions::default() }).unwrap();
+
+        assert_eq!(&bytes[..8], b"fgb\x03fgb\x00");
+        let header = deref(&bytes, 12);
+        let (name, len) = vector(&bytes, header, 0);
+        assert_eq!(&bytes[name..name + len], b"contours");
+        let (envelope, len) = vector(&bytes, header, 1);
+        assert_eq!((len, read_bbox(&bytes, envelope)), (4, bboxes[0]));
+        assert_eq!(bytes[field(&bytes, header, 2).unwrap()], 6);
+        assert_eq!(field(&bytes, header, 3), None);
+        let (columns, len) = vector(&bytes, header, 7);
+        assert_eq!(len, 1);
+        let column = deref(&bytes, columns);
+        let (name, len) = vector(&bytes, column, 0);
+        assert_eq!((&bytes[name..name + len], bytes[field(&bytes, column, 1).unwrap()]), (&b"value"[..], 10));
+        assert_eq!(u32_at(&bytes, field(&bytes, header, 8).unwrap()), 3);
+        assert_eq!(u16_at(&bytes, field(&bytes, header, 9).unwrap()), 2);
+        let crs = deref(&bytes, field(&bytes, header, 10).unwrap());
+        assert_eq!(u32_at(&bytes, field(&bytes, crs, 1).unwrap()), 4326);
+
+        // The index: the root, its two children, and the three Features.
+        let index = 12 + u32_at(&bytes, 8);
+        let nodes = (0..6).map(|i| (read_bbox(&bytes, index + 40 * i), u32_at(&bytes, index + 40 * i + 32))).collect::<Vec<_>>();
+        let features_start = index + 40 * 6;
+        let leaves = nodes[3..].iter().map(|&(bbox, offset)| {
+            let (xy_bbox, value) = read_feature(&bytes, features_start + offset);
+            assert_eq!(xy_bbox, bbox);
+            (bbox, value)
+        }).collect::<Vec<_>>();
+        let mut values = leaves.iter().map(|leaf| leaf.1).collect::<Vec<_>>();
+        values.sort_by(f64::total_cmp);
+        assert_eq!(values, [0.5, 1.5, 2.5]);
+        for (bbox, value) in &leaves {
+            assert_eq!(*bbox, bboxes[[0.5, 1.5, 2.5].iter().position(|v| v == value).unwrap()]);
+        }
+        let union = |a: [f64; 4], b: [f64; 4]| [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])];
+        assert_eq!(nodes[0], (bboxes[0], 1));
+        assert_eq!(nodes[1], (union(leaves[0].0, leaves[1].0), 3));
+        assert_eq!(nodes[2], (leaves[2].0, 5));
+        // The last Feature ends the file.
+        let last = features_start + nodes[5].1;
+        assert_eq!(last + 4 + u32_at(&bytes, last), bytes.len());
+
+        // Without index, the Features follow the header, in their order.
+        let mut bytes = Vec::new();
+        write_fgb(&mut bytes, &features, &FgbOptions { index_node_size: 0, ..FgbOptions::default() }).unwrap();
+        let header = deref(&bytes, 12);
+        assert_eq!(u16_at(&bytes, field(&bytes, header, 9).unwrap()), 0);
+        assert_eq!(field(&bytes, header, 10), None);
+        let mut at = 12 + u32_at(&bytes, 8);
+        for (bbox, value) in bboxes.iter().zip(&[0.5, 1.5, 2.5]) {
+            assert_eq!(read_feature(&bytes, at), (*bbox, *value));
+            at += 4 + u32_at(&bytes, at);
+        }
+        assert_eq!(at, bytes.len());
+    }
 }
@@ -0,0 +1,96 @@
+//! Triangulated output of the contours, as indexed triangle meshes ready
+//! to be uploaded to vertex and index buffers.
+//!
+//! Polygons are triangulated by ear clipping, after their holes have been merged
+//! into their exterior ring (see [`plot::keyhole`]).
+//!
+//! [`plot::keyhole`]: ../plot/fn.keyhole.html
+
+use crate::contour::LinearRing;
+use crate::plot::keyhole;
+
+/// The triangles of one threshold.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    /// The threshold value.
+    pub value: f64,
+    /// The number of coordinates of each vertex: `2` (`x, y`),
+    /// or `3` (`x, y, z`) when the threshold value is used as z coordinate.
+    pub dimensions: usize,
+    /// The coordinates of the vertices (`dimensions` values per vertex).
+    pub vertices: Vec<f32>,
+    /// The indices of the vertices of the triangles (three per triangle),
+    /// in counter-clockwise order (the y axis pointing up).
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    pub(crate) fn new(value: f64, dimensions: usize) -> Self {
+        Mesh { value, dimensions, ..Default::default() }
+    }
+
+    /// The number of triangles of the mesh.
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    // Triangulates the given polygon (its exterior ring followed by its holes) and appends
+    // its triangles to the mesh. Degenerate polygons (or parts of them) are skipped.
+    pub(crate) fn push_polygon(&mut self, polygon: &[LinearRing]) {
+        let ring = keyhole(polygon);
+        if ring.len() < 3 || ring.iter().any(|p| p.len() < self.dimensions) {
+            return;
+        }
+        let dimensions = self.dimensions;
+        let base = (self.vertices.len() / dimensions) as u32;
+        self.vertices.extend(ring.iter().flat_map(|p| p[..dimensions].iter().map(|c| *c as f32)));
+
+        let mut remaining: Vec<usize> = (0..ring.len()).collect();
+        if signed_area(&ring) < 0. {
+            remaining.reverse();
+        }
+        let mut i = 0;
+        let mut since_last_ear = 0;
+        while remaining.len() >= 3 && since_last_ear < remaining.len() {
+            let n = remaining.len();
+            let (prev, curr, next) = (remaining[(i + n - 1) % n], remaining[i % n], remaining[(i + 1) % n]);
+            let (a, b, c) = (&ring[prev], &ring[curr], &ring[next]);
+            let turn = cross(a, b, c);
+            if turn == 0. {
+                // Collinear (or repeated) vertices don't form a triangle.
+                remaining.remove(i % n);
+                since_last_ear = 0;
+            } else if turn > 0. && !remaining.iter().any(|v| blocks(&ring[*v], a, b, c)) {
+                self.indices.extend([prev, curr, next].iter().map(|v| base + *v as u32));
+                remaining.remove(i % n);
+                since_last_ear = 0;
+            } else {
+                i += 1;
+                since_last_ear += 1;
+            }
+            i %= remaining.len().max(1);
+        }
+    }
+}
+
+// Twice the signed area of the ring, positive when counter-clockwise (the y axis pointing up).
+fn signed_area(ring: &[Vec<f64>]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (&ring[i], &ring[(i + 1) % n]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum()
+}
+
+fn cross(a: &[f64], b: &[f64], c: &[f64]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+// Whether `p` prevents the counter-clockwise triangle `abc` from being clipped, that is,
+// whether it is inside the triangle or on its boundary without being one of its vertices.
+fn blocks(p: &[f64], a: &[f64], b: &[f64], c: &[f64]) -> bool {
+    let same = |q: &[f64]| p[0] == q[0] && p[1] == q[1];
+    !same(a) && !same(b) && !same(c) && cross(a, b, p) >= 0. && cross(b, c, p) >= 0. && cross(c, a, p) >= 0.
+}
@@ -0,0 +1,106 @@
+//! Grids read in place from raw binary files, e.g. elevation models dumped as `f32`
+//! values, memory-mapped instead of being copied into a `Vec<f64>` (only available with
+//! the `mmap` feature).
+//!
+//! The file holds the `dx * dy` values of the grid in row-major order, without header
+//! nor padding. The values are converted to `f64` as they are read.
+
+use crate::error::{ErrorKind, Result, new_error};
+use crate::source::GridSource;
+use memmap2::Mmap;
+use std::convert::TryInto;
+use std::fs::File;
+use std::path::Path;
+
+/// The type of the values of a raw file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RawType {
+    F32,
+    F64,
+}
+
+impl RawType {
+    /// Returns the size of a value, in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            RawType::F32 => 4,
+            RawType::F64 => 8,
+        }
+    }
+}
+
+/// The byte order of the values of a raw file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+/// A grid of values memory-mapped from a raw file.
+#[derive(Debug)]
+pub struct MappedGrid {
+    map: Option<Mmap>,
+    dtype: RawType,
+    endianness: Endianness,
+    len: usize,
+}
+
+impl MappedGrid {
+    /// Maps the raw file at `path`, holding the values of a grid of `dx` columns
+    /// and `dy` rows of the given type and byte order.
+    ///
+    /// Returns an [`ErrorKind::Io`] error if the file can't be opened or mapped, or an
+    /// [`ErrorKind::RawFileLength`] error if its length isn't the one of the values
+    /// (e.g. when the file is truncated, or its length isn't a multiple of the size
+    /// of a value).
+    ///
+    /// The file mustn't be modified while it is mapped.
+    ///
+    /// [`ErrorKind::Io`]: ../enum.ErrorKind.html#variant.Io
+    /// [`ErrorKind::RawFileLength`]: ../enum.ErrorKind.html#variant.RawFileLength
+    pub fn open<P: AsRef<Path>>(path: P, dx: u32, dy: u32, dtype: RawType, endianness: Endianness) -> Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let expected = u64::from(dx) * u64::from(dy) * dtype.size() as u64;
+        if len != expected {
+            return Err(new_error(ErrorKind::RawFileLength { len, expected }));
+        }
+        // An empty file can't be mapped on every platform.
+        let map = if len == 0 { None } else { Some(map(&file)?) };
+        Ok(MappedGrid {
+            map,
+            dtype,
+            endianness,
+            len: dx as usize * dy as usize,
+        })
+    }
+}
+
+impl GridSource for MappedGrid {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn value(&self, i: usize) -> f64 {
+        let size = self.dtype.size();
+        // The bytes are copied, the values of the map not being aligned for their type.
+        let bytes = match self.map {
+            Some(ref map) => &map[i * size..(i + 1) * size],
+            None => &[][..],
+        };
+        match (self.dtype, self.endianness) {
+            (RawType::F32, Endianness::Little) => f64::from(f32::from_le_bytes(bytes.try_into().unwrap())),
+            (RawType::F32, Endianness::Big) => f64::from(f32::from_be_bytes(bytes.try_into().unwrap())),
+            (RawType::F64, Endianness::Little) => f64::from_le_bytes(bytes.try_into().unwrap()),
+            (RawType::F64, Endianness::Big) => f64::from_be_bytes(bytes.try_into().unwrap()),
+        }
+    }
+}
+
+// Maps the whole file, read only.
+#[allow(unsafe_code)]
+fn map(file: &File) -> std::io::Result<Mmap> {
+    // SAFETY: the file isn't modified while it is mapped (as required by `MappedGrid::open`),
+    // so that the mapped bytes don't change under the slices read from them.
+    unsafe { Mmap::map(file) }
+}
@@ -0,0 +1,141 @@
+//! NetCDF input (only available with the `netcdf` feature), e.g. for the gridded outputs
+//! of atmospheric and ocean models.
+//!
+//! A variable is read as a grid of rows along its next to last dimension and of columns
+//! along its last one: `(lat, lon)` variables as they are, and `(time, lat, lon)` ones at
+//! a given index of `time`. Its `_FillValue` and `missing_value` attributes, if any, are
+//! read as nodata (see [`io::mask_nodata`]), and the 1-D coordinate variables of its two
+//! dimensions (the variables named after them), if any and evenly spaced, give the origin
+//! and the step of the grid (see [`io::origin_and_step`]). The rows (or the columns) of
+//! decreasing coordinates, like latitudes from north to south, are reversed for the steps
+//! to be positive.
+//!
+//! [`io::mask_nodata`]: ../io/fn.mask_nodata.html
+//! [`io::origin_and_step`]: ../io/fn.origin_and_step.html
+
+use crate::contour::ContourBuilder;
+use crate::error::{ErrorKind, Result, new_error};
+use crate::io::{mask_nodata, origin_and_step};
+use ::netcdf::{AttributeValue, Dimension, File};
+use geojson::Feature;
+use std::convert::TryFrom;
+use std::path::Path;
+
+/// A grid read from a NetCDF variable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetcdfGrid {
+    /// The values, in row-major order, the nodata ones being `NaN`.
+    pub values: Vec<f64>,
+    /// The number of columns.
+    pub dx: u32,
+    /// The number of rows.
+    pub dy: u32,
+    /// The origin and the step of the columns, if given by a coordinate variable.
+    pub x: Option<(f64, f64)>,
+    /// The origin and the step of the rows, if given by a coordinate variable.
+    pub y: Option<(f64, f64)>,
+}
+
+impl NetcdfGrid {
+    /// Returns a [`ContourBuilder`] for the grid, with the origin and the step given
+    /// by its coordinate variables, if any.
+    ///
+    /// [`ContourBuilder`]: ../struct.ContourBuilder.html
+    pub fn builder(&self, smooth: bool) -> ContourBuilder {
+        let mut builder = ContourBuilder::new(self.dx, self.dy, smooth);
+        if let Some((origin, step)) = self.x {
+            builder = builder.x_origin(origin).x_step(step);
+        }
+        if let Some((origin, step)) = self.y {
+            builder = builder.y_origin(origin).y_step(step);
+        }
+        builder
+    }
+}
+
+/// Reads the grid of the `variable` of the NetCDF file at `path`, at the index `time_index`
+/// of its first dimension if it has three (`time_index` must be `0` for a variable of two
+/// dimensions).
+///
+/// Fails with an [`ErrorKind::Netcdf`] error if the file can't be read, e.g. when it doesn't
+/// have the variable or when `time_index` is out of range, and with an
+/// [`ErrorKind::NetcdfDimensions`] error if the variable doesn't have two or three dimensions.
+///
+/// [`ErrorKind::Netcdf`]: ../enum.ErrorKind.html#variant.Netcdf
+/// [`ErrorKind::NetcdfDimensions`]: ../enum.ErrorKind.html#variant.NetcdfDimensions
+pub fn read_netcdf_grid<P: AsRef<Path>>(path: P, variable: &str, time_index: usize) -> Result<NetcdfGrid> {
+    let file = ::netcdf::open(path)?;
+    let var = file
+        .variable(variable)
+        .ok_or_else(|| ::netcdf::Error::NotFound(format!("variable {}", variable)))?;
+    let dimensions = var.dimensions();
+    let mut values = match dimensions.len() {
+        2 if time_index == 0 => var.get_values::<f64, _>(..)?,
+        2 => return Err(::netcdf::Error::IndexMismatch.into()),
+        3 => var.get_values::<f64, _>((time_index, .., ..))?,
+        n => {
+            return Err(new_error(ErrorKind::NetcdfDimensions {
+                variable: variable.to_string(),
+                dimensions: n,
+            }))
+        }
+    };
+    for attribute in &["_FillValue", "missing_value"] {
+        if let Some(value) = var.attribute_value(attribute).transpose()? {
+            for nodata in nodata_values(value)? {
+                mask_nodata(&mut values, nodata);
+            }
+        }
+    }
+
+    let (rows, columns) = (&dimensions[dimensions.len() - 2], &dimensions[dimensions.len() - 1]);
+    let (dx, dy) = (columns.len(), rows.len());
+    let (x, reverse_columns) = axis(&file, columns)?;
+    let (y, reverse_rows) = axis(&file, rows)?;
+    if reverse_rows {
+        values = values.chunks(dx).rev().flatten().copied().collect();
+    }
+    if reverse_columns {
+        values.chunks_mut(dx).for_each(|row| row.reverse());
+    }
+    Ok(NetcdfGrid {
+        values,
+        dx: dx as u32,
+        dy: dy as u32,
+        x,
+        y,
+    })
+}
+
+/// Computes the contours of the given `thresholds` of the grid of the `variable` of the
+/// NetCDF file at `path` (see [`read_netcdf_grid`]), smoothed, in the coordinates given
+/// by its coordinate variables, if any.
+///
+/// [`read_netcdf_grid`]: fn.read_netcdf_grid.html
+pub fn contours_from_netcdf<P: AsRef<Path>>(path: P, variable: &str, time_index: usize, thresholds: &[f64]) -> Result<Vec<Feature>> {
+    let grid = read_netcdf_grid(path, variable, time_index)?;
+    grid.builder(true).contours(&grid.values, thresholds)
+}
+
+// The values of a nodata attribute, which may hold several of them.
+fn nodata_values(value: AttributeValue) -> Result<Vec<f64>> {
+    Ok(match value {
+        AttributeValue::Doubles(values) => values,
+        AttributeValue::Floats(values) => values.into_iter().map(f64::from).collect(),
+        value => vec![f64::try_from(value)?],
+    })
+}
+
+// The origin and the step along the dimension, from its coordinate variable, if any and
+// evenly spaced, along with whether the values must be reversed along it for the step
+// to be positive.
+fn axis(file: &File, dimension: &Dimension) -> Result<(Option<(f64, f64)>, bool)> {
+    let coordinates = match file.variable(&dimension.name()) {
+        Some(variable) if variable.dimensions().len() == 1 => variable.get_values::<f64, _>(..)?,
+        _ => return Ok((None, false)),
+    };
+    Ok(match origin_and_step(&coordinates) {
+        Some((origin, step)) if step < 0. => (Some((origin + step * coordinates.len() as f64, -step)), true),
+        axis => (axis, false),
+    })
+}
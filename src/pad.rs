@@ -0,0 +1,78 @@
+//! Padding of grids, to control how the contours are closed along the border of a grid.
+//!
+//! The cells outside of a grid are below every threshold, so that the polygons touching
+//! its border are closed along it (half a cell away from the centers of the border cells).
+//! Padding the grid with cells above the thresholds leaves them open to the border instead,
+//! and padding it with copies of its border cells continues the contours beyond it (see
+//! [`ContourBuilder::contours_padded`], keeping the coordinates of the grid).
+//!
+//! [`ContourBuilder::contours_padded`]: ../struct.ContourBuilder.html#method.contours_padded
+
+use crate::contour::check_dimensions;
+use crate::error::{ErrorKind, Result, new_error};
+
+/// The values of the cells added by [`pad_grid`].
+///
+/// [`pad_grid`]: fn.pad_grid.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PadMode {
+    /// The given value: `f64::NEG_INFINITY` closes the contours like the border of the
+    /// grid does, `f64::INFINITY` leaves them open.
+    Constant(f64),
+    /// The value of the nearest border cell.
+    Edge,
+    /// The values mirrored around the border cells (not repeated), e.g. `c b | a b c | b a`.
+    Reflect,
+}
+
+/// Pads the `dx` * `dy` grid of `values` with `width` cells on each side, returning
+/// the values of the padded grid along with its number of columns and rows (the cells
+/// of an empty grid being padded with NaN values, but for [`PadMode::Constant`]).
+///
+/// ```
+/// # use contour::pad::{PadMode, pad_grid};
+/// let (values, dx, dy) = pad_grid(&[1., 2., 3., 4.], 2, 2, 1, PadMode::Edge)?;
+/// assert_eq!((dx, dy), (4, 4));
+/// assert_eq!(&values[4..8], [1., 1., 2., 2.]);
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`PadMode::Constant`]: enum.PadMode.html#variant.Constant
+pub fn pad_grid(values: &[f64], dx: u32, dy: u32, width: u32, mode: PadMode) -> Result<(Vec<f64>, u32, u32)> {
+    check_dimensions(dx, dy, values.len())?;
+    let pad = |n: u32| width.checked_mul(2).and_then(|w| n.checked_add(w)).ok_or(new_error(ErrorKind::BadDimension));
+    let (new_dx, new_dy) = (pad(dx)?, pad(dy)?);
+    check_dimensions(new_dx, new_dy, new_dx as usize * new_dy as usize)?;
+    let (dx, dy, width) = (dx as usize, dy as usize, width as usize);
+    let mut padded = Vec::with_capacity(new_dx as usize * new_dy as usize);
+    for y in 0..new_dy as usize {
+        for x in 0..new_dx as usize {
+            let inside = (width..width + dx).contains(&x) && (width..width + dy).contains(&y);
+            padded.push(match mode {
+                _ if inside => values[(y - width) * dx + x - width],
+                PadMode::Constant(value) => value,
+                // The cells of an empty grid are all constant.
+                _ if dx == 0 || dy == 0 => f64::NAN,
+                PadMode::Edge | PadMode::Reflect => {
+                    let (x, y) = (x as isize - width as isize, y as isize - width as isize);
+                    values[source(y, dy, mode) * dx + source(x, dx, mode)]
+                }
+            });
+        }
+    }
+    Ok((padded, new_dx, new_dy))
+}
+
+// The index, in `0..n`, of the value copied to the index `i` by the padding.
+fn source(i: isize, n: usize, mode: PadMode) -> usize {
+    let n = n as isize;
+    match mode {
+        PadMode::Reflect if n > 1 => {
+            // Mirroring is periodic, every `2 * (n - 1)` cells.
+            let period = 2 * (n - 1);
+            let i = i.rem_euclid(period);
+            (if i < n { i } else { period - i }) as usize
+        }
+        _ => i.clamp(0, n - 1) as usize,
+    }
+}
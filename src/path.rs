@@ -0,0 +1,145 @@
+//! Output of the contours as vector path commands, for rendering libraries
+//! exposing a path builder (such as `lyon`'s `PathBuilder`, for GPU tessellation,
+//! see [`LyonPath`] with the `lyon` feature) or for SVG path data.
+//!
+//! A Feature is written as a single path, the holes being subpaths of the same path,
+//! so that filling the path with the even-odd (or non-zero, as holes are wound
+//! in the opposite direction) fill rule excludes them.
+//!
+//! [`LyonPath`]: struct.LyonPath.html
+
+use geojson::{Feature, Value};
+#[cfg(feature = "lyon")]
+use lyon_path::builder::PathBuilder;
+#[cfg(feature = "lyon")]
+use lyon_path::math::point;
+use std::fmt::Write;
+
+/// A receiver of path commands.
+///
+/// Its methods map directly to the ones of `lyon_path::builder::PathBuilder`, which
+/// [`LyonPath`] forwards the calls to.
+///
+/// [`LyonPath`]: struct.LyonPath.html
+pub trait PathSink {
+    /// Starts a new subpath at the given position.
+    fn begin(&mut self, x: f64, y: f64);
+    /// Adds a line segment to the given position to the current subpath.
+    fn line_to(&mut self, x: f64, y: f64);
+    /// Ends the current subpath, closing it (back to its first position) if `close` is `true`.
+    fn end(&mut self, close: bool);
+}
+
+/// How the rings are written as subpaths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathMode {
+    /// Closed subpaths (without the repeated closing position), for filling.
+    Fill,
+    /// Open subpaths going back to their first position, for stroking the isolines.
+    Stroke,
+}
+
+/// Writes the rings of the geometry of the given `feature` to `sink`,
+/// one subpath per ring.
+pub fn feature_to_path<S: PathSink>(feature: &Feature, sink: &mut S, mode: PathMode) {
+    let rings = match feature.geometry.as_ref().map(|g| &g.value) {
+        Some(Value::Polygon(polygon)) => polygon.iter().collect(),
+        Some(Value::MultiPolygon(polygons)) => polygons.iter().flatten().collect(),
+        _ => Vec::new(),
+    };
+    for ring in rings {
+        let closed = ring.len() > 1 && ring.first() == ring.last();
+        let points = match mode {
+            PathMode::Fill if closed => &ring[..ring.len() - 1],
+            _ => &ring[..],
+        };
+        if let Some((first, rest)) = points.split_first() {
+            sink.begin(first[0], first[1]);
+            for point in rest {
+                sink.line_to(point[0], point[1]);
+            }
+            // The rings left open (see `ContourBuilder::ring_closure`) go back to their first position.
+            if mode == PathMode::Stroke && !closed && !rest.is_empty() {
+                sink.line_to(first[0], first[1]);
+            }
+            sink.end(mode == PathMode::Fill);
+        }
+    }
+}
+
+/// A [`PathSink`] writing SVG path data (the `d` attribute of a `<path>` element).
+///
+/// [`PathSink`]: trait.PathSink.html
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SvgPathData {
+    /// The path data.
+    pub data: String,
+    /// The number of decimals of the coordinates (all the significant digits when `None`).
+    pub precision: Option<usize>,
+}
+
+impl SvgPathData {
+    fn push(&mut self, command: char, x: f64, y: f64) {
+        if !self.data.is_empty() {
+            self.data.push(' ');
+        }
+        match self.precision {
+            Some(p) => write!(self.data, "{}{:.*},{:.*}", command, p, x, p, y).unwrap(),
+            None => write!(self.data, "{}{},{}", command, x, y).unwrap(),
+        }
+    }
+}
+
+impl PathSink for SvgPathData {
+    fn begin(&mut self, x: f64, y: f64) {
+        self.push('M', x, y);
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.push('L', x, y);
+    }
+
+    fn end(&mut self, close: bool) {
+        if close {
+            self.data.push('Z');
+        }
+    }
+}
+
+/// A [`PathSink`] forwarding the commands to a lyon path builder (the positions being
+/// converted to `f32`), e.g. a `lyon_path::path::Builder`.
+///
+/// Only available with the `lyon` feature.
+///
+/// [`PathSink`]: trait.PathSink.html
+#[cfg(feature = "lyon")]
+#[derive(Clone, Debug, Default)]
+pub struct LyonPath<B>(pub B);
+
+#[cfg(feature = "lyon")]
+impl<B: PathBuilder> PathSink for LyonPath<B> {
+    fn begin(&mut self, x: f64, y: f64) {
+        self.0.begin(point(x as f32, y as f32), &[]);
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        self.0.line_to(point(x as f32, y as f32), &[]);
+    }
+
+    fn end(&mut self, close: bool) {
+        self.0.end(close);
+    }
+}
+
+/// Builds the lyon path of the rings of the geometry of the given `feature`, like
+/// [`feature_to_path`] does.
+///
+/// Only available with the `lyon` feature.
+///
+/// [`feature_to_path`]: fn.feature_to_path.html
+#[cfg(feature = "lyon")]
+pub fn feature_to_lyon_path(feature: &Feature, mode: PathMode) -> lyon_path::Path {
+    let mut sink = LyonPath(lyon_path::Path::builder());
+    feature_to_path(feature, &mut sink, mode);
+    sink.0.build()
+}
@@ -0,0 +1,219 @@
+//! Drawing of filled contours on a 2D canvas, such as a `plotters` `DrawingArea`.
+//!
+//! The drawing is made through the [`Canvas`] trait, whose two methods map to
+//! `plotters`' `Polygon` and `PathElement` elements. With the `plotters` feature,
+//! [`draw_contours_on_area`] draws the contours on a `plotters` `DrawingArea` with
+//! these elements.
+//!
+//! As such canvases fill a single simple polygon at a time, each polygon and its
+//! holes are merged into one ring (see [`keyhole`]) before being filled.
+//!
+//! [`Canvas`]: trait.Canvas.html
+//! [`keyhole`]: fn.keyhole.html
+//! [`draw_contours_on_area`]: fn.draw_contours_on_area.html
+
+#[cfg(feature = "plotters")]
+use crate::error::{ErrorKind, Result, new_error};
+use crate::geoarrow::MultiPolygonArray;
+#[cfg(feature = "plotters")]
+use plotters::coord::CoordTranslate;
+#[cfg(feature = "plotters")]
+use plotters::drawing::{DrawingArea, DrawingAreaErrorKind};
+#[cfg(feature = "plotters")]
+use plotters::element::{PathElement, Polygon};
+#[cfg(feature = "plotters")]
+use plotters::prelude::{DrawingBackend, RGBColor};
+use std::cmp::Ordering;
+
+/// An RGB color.
+pub type Rgb = (u8, u8, u8);
+
+/// A drawing surface.
+pub trait Canvas {
+    /// Fills the polygon going through the given points (in chart coordinates).
+    fn fill_polygon(&mut self, points: &[(f64, f64)], color: Rgb);
+    /// Draws a line going through the given points (in chart coordinates).
+    fn stroke_polyline(&mut self, points: &[(f64, f64)], color: Rgb);
+}
+
+/// Options of [`draw_contours`].
+///
+/// Chart coordinates are computed as `x * x_step + x_origin` and
+/// `y * y_step + y_origin`, `x` and `y` being the coordinates of the contours
+/// (the grid coordinates, unless the contours were computed with an origin and a step).
+///
+/// [`draw_contours`]: fn.draw_contours.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawOptions {
+    /// The x coordinate of the origin of the grid in the chart (default: `0`).
+    pub x_origin: f64,
+    /// The y coordinate of the origin of the grid in the chart (default: `0`).
+    pub y_origin: f64,
+    /// The size of a grid cell along the x axis in the chart (default: `1`).
+    pub x_step: f64,
+    /// The size of a grid cell along the y axis in the chart (default: `1`).
+    pub y_step: f64,
+    /// The color of the isolines drawn on top of the filled polygons,
+    /// if any (default: `None`).
+    pub stroke: Option<Rgb>,
+}
+
+impl Default for DrawOptions {
+    fn default() -> Self {
+        DrawOptions {
+            x_origin: 0.,
+            y_origin: 0.,
+            x_step: 1.,
+            y_step: 1.,
+            stroke: None,
+        }
+    }
+}
+
+/// Draws the given contours on `canvas`, filling the polygons of each threshold
+/// with a color of `palette` (in order, the palette being spread over the thresholds
+/// when it has fewer colors than there are thresholds), then stroking all the rings
+/// when `options.stroke` is set.
+///
+/// Nothing is drawn when `palette` is empty.
+pub fn draw_contours<C: Canvas>(canvas: &mut C, contours: &MultiPolygonArray, palette: &[Rgb], options: &DrawOptions) {
+    if palette.is_empty() {
+        return;
+    }
+    let to_chart = |point: &[f64]| {
+        (point[0] * options.x_step + options.x_origin, point[1] * options.y_step + options.y_origin)
+    };
+    let n = contours.len();
+    for row in 0..n {
+        let color = palette[row * palette.len().min(n) / n];
+        for polygon in polygons(contours, row) {
+            let points: Vec<_> = keyhole(&polygon).iter().map(|p| to_chart(p)).collect();
+            canvas.fill_polygon(&points, color);
+        }
+    }
+    if let Some(color) = options.stroke {
+        for row in 0..n {
+            for ring in polygons(contours, row).iter().flatten() {
+                let points: Vec<_> = ring.iter().map(|p| to_chart(p)).collect();
+                canvas.stroke_polyline(&points, color);
+            }
+        }
+    }
+}
+
+/// Draws the given contours on a `plotters` drawing area (whose coordinates are the
+/// chart coordinates), like [`draw_contours`] does on a [`Canvas`], with `Polygon`
+/// and `PathElement` elements.
+///
+/// The drawing stops at the first error of the drawing area, returned as an
+/// [`ErrorKind::Render`] error. Only available with the `plotters` feature.
+///
+/// [`draw_contours`]: fn.draw_contours.html
+/// [`Canvas`]: trait.Canvas.html
+/// [`ErrorKind::Render`]: ../enum.ErrorKind.html#variant.Render
+#[cfg(feature = "plotters")]
+pub fn draw_contours_on_area<DB, CT>(
+    area: &DrawingArea<DB, CT>,
+    contours: &MultiPolygonArray,
+    palette: &[Rgb],
+    options: &DrawOptions,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    CT: CoordTranslate<From = (f64, f64)>,
+{
+    let mut canvas = AreaCanvas { area, error: None };
+    draw_contours(&mut canvas, contours, palette, options);
+    match canvas.error {
+        Some(err) => Err(new_error(ErrorKind::Render(Box::new(err)))),
+        None => Ok(()),
+    }
+}
+
+// Forwards the calls to the drawing area, keeping its first error.
+#[cfg(feature = "plotters")]
+struct AreaCanvas<'a, DB: DrawingBackend, CT: CoordTranslate> {
+    area: &'a DrawingArea<DB, CT>,
+    error: Option<DrawingAreaErrorKind<DB::ErrorType>>,
+}
+
+#[cfg(feature = "plotters")]
+impl<DB: DrawingBackend, CT: CoordTranslate<From = (f64, f64)>> Canvas for AreaCanvas<'_, DB, CT> {
+    fn fill_polygon(&mut self, points: &[(f64, f64)], (r, g, b): Rgb) {
+        if self.error.is_none() {
+            self.error = self.area.draw(&Polygon::new(points.to_vec(), RGBColor(r, g, b))).err();
+        }
+    }
+
+    fn stroke_polyline(&mut self, points: &[(f64, f64)], (r, g, b): Rgb) {
+        if self.error.is_none() {
+            self.error = self.area.draw(&PathElement::new(points.to_vec(), RGBColor(r, g, b))).err();
+        }
+    }
+}
+
+/// Merges the rings of a polygon (its exterior ring followed by its holes)
+/// into a single ring, connecting each hole to the closest vertex of the ring
+/// built so far with a bridge going back and forth.
+///
+/// The holes are wound in the opposite direction of the exterior ring and the returned
+/// ring is not closed (its last position isn't a repetition of the first one).
+/// Filling it covers the same area as filling the polygon.
+pub fn keyhole(polygon: &[Vec<Vec<f64>>]) -> Vec<Vec<f64>> {
+    let open = |ring: &[Vec<f64>]| -> Vec<Vec<f64>> {
+        match ring.split_last() {
+            Some((last, rest)) if ring.len() > 1 && Some(last) == ring.first() => rest.to_vec(),
+            _ => ring.to_vec(),
+        }
+    };
+    let mut rings = polygon.iter();
+    let mut merged = match rings.next() {
+        Some(shell) => open(shell),
+        None => return Vec::new(),
+    };
+    let orientation = |ring: &[Vec<f64>]| {
+        let n = ring.len();
+        (0..n).map(|i| ring[i][0] * ring[(i + 1) % n][1] - ring[(i + 1) % n][0] * ring[i][1]).sum::<f64>() > 0.
+    };
+    for hole in rings {
+        let mut hole = open(hole);
+        // The holes have to be wound in the opposite direction of the exterior ring.
+        if orientation(&hole) == orientation(&merged) {
+            hole.reverse();
+        }
+        let hole = &hole;
+        let closest = merged
+            .iter()
+            .enumerate()
+            .flat_map(|(i, a)| hole.iter().enumerate().map(move |(j, b)| (i, j, distance2(a, b))))
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(Ordering::Equal));
+        if let Some((i, j, _)) = closest {
+            let tail = merged.split_off(i + 1);
+            merged.extend(hole[j..].iter().chain(hole[..=j].iter()).cloned());
+            merged.push(merged[i].clone());
+            merged.extend(tail);
+        }
+    }
+    merged
+}
+
+fn distance2(a: &[f64], b: &[f64]) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)
+}
+
+// Returns the polygons (lists of rings) of the given row of the array.
+fn polygons(contours: &MultiPolygonArray, row: usize) -> Vec<Vec<Vec<Vec<f64>>>> {
+    let polygon_range = contours.geom_offsets[row] as usize..contours.geom_offsets[row + 1] as usize;
+    polygon_range
+        .map(|p| {
+            let ring_range = contours.polygon_offsets[p] as usize..contours.polygon_offsets[p + 1] as usize;
+            ring_range
+                .map(|r| {
+                    let (start, end) = (contours.ring_offsets[r] as usize, contours.ring_offsets[r + 1] as usize);
+                    contours.coords[2 * start..2 * end].chunks(2).map(|c| c.to_vec()).collect()
+                })
+                .collect()
+        })
+        .collect()
+}
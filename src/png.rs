@@ -0,0 +1,127 @@
+//! Rendering of the filled contours to PNG images with `tiny-skia` (only available with
+//! the `png` feature), see [`ContourBuilder::render_png`].
+//!
+//! The polygons of each threshold are filled with the even-odd fill rule, so that their
+//! holes stay empty, from the lowest threshold to the highest one.
+//!
+//! [`ContourBuilder::render_png`]: ../struct.ContourBuilder.html#method.render_png
+
+use crate::error::{ErrorKind, Result, new_error};
+use crate::histogram::Extent;
+use crate::path::{PathMode, PathSink, feature_to_path};
+use crate::plot::Rgb;
+use geojson::Feature;
+use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+/// Options of the PNG rendering.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PngOptions {
+    /// The color of the pixels outside of every polygon, transparent when `None`
+    /// (default: `None`).
+    pub background: Option<Rgb>,
+    /// The color of the isolines stroked along the rings, if any (default: `None`).
+    pub stroke: Option<Rgb>,
+    /// The width of the isolines, in pixels (default: `1`).
+    pub stroke_width: f32,
+    /// Whether the edges of the polygons and the isolines are anti-aliased (default: `true`).
+    pub anti_alias: bool,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions {
+            background: None,
+            stroke: None,
+            stroke_width: 1.,
+            anti_alias: true,
+        }
+    }
+}
+
+/// Renders the given `features` (as returned by [`ContourBuilder::contours`]) to a PNG image
+/// of `width` x `height` pixels covering `extent`, the fill colors being taken from `palette`
+/// in order (and spread over the features when it has fewer colors than there are features).
+///
+/// With `y_up`, the y axis of the coordinates is taken as pointing up and flipped, the first
+/// row of pixels being the top one.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn render_png(features: &[Feature], extent: Extent, y_up: bool, (width, height): (u32, u32), palette: &[Rgb], options: &PngOptions) -> Result<Vec<u8>> {
+    let mut pixmap = Pixmap::new(width, height).ok_or_else(|| {
+        new_error(ErrorKind::Render(format!("Can't create an image of {}x{} pixels", width, height).into()))
+    })?;
+    if let Some((r, g, b)) = options.background {
+        pixmap.fill(Color::from_rgba8(r, g, b, 255));
+    }
+    let scale = (f64::from(width) / (extent.x_max - extent.x_min), f64::from(height) / (extent.y_max - extent.y_min));
+    let to_pixel = |x: f64, y: f64| {
+        let px = (x - extent.x_min) * scale.0;
+        let py = if y_up { (extent.y_max - y) * scale.1 } else { (y - extent.y_min) * scale.1 };
+        (px as f32, py as f32)
+    };
+    let paint = |(r, g, b): Rgb| {
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(r, g, b, 255);
+        paint.anti_alias = options.anti_alias;
+        paint
+    };
+
+    let n = features.len();
+    if !palette.is_empty() {
+        for (i, feature) in features.iter().enumerate() {
+            let mut sink = Sink::new(&to_pixel);
+            feature_to_path(feature, &mut sink, PathMode::Fill);
+            if let Some(path) = sink.builder.finish() {
+                let color = palette[i * palette.len().min(n) / n];
+                pixmap.fill_path(&path, &paint(color), FillRule::EvenOdd, Transform::identity(), None);
+            }
+        }
+    }
+    if let Some(color) = options.stroke {
+        let stroke = Stroke {
+            width: options.stroke_width,
+            ..Stroke::default()
+        };
+        for feature in features {
+            let mut sink = Sink::new(&to_pixel);
+            feature_to_path(feature, &mut sink, PathMode::Stroke);
+            if let Some(path) = sink.builder.finish() {
+                pixmap.stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+            }
+        }
+    }
+    pixmap.encode_png().map_err(|err| new_error(ErrorKind::Render(Box::new(err))))
+}
+
+// A `PathSink` building a tiny-skia path in pixel coordinates.
+struct Sink<'a, F: Fn(f64, f64) -> (f32, f32)> {
+    builder: PathBuilder,
+    to_pixel: &'a F,
+}
+
+impl<'a, F: Fn(f64, f64) -> (f32, f32)> Sink<'a, F> {
+    fn new(to_pixel: &'a F) -> Self {
+        Sink {
+            builder: PathBuilder::new(),
+            to_pixel,
+        }
+    }
+}
+
+impl<F: Fn(f64, f64) -> (f32, f32)> PathSink for Sink<'_, F> {
+    fn begin(&mut self, x: f64, y: f64) {
+        let (x, y) = (self.to_pixel)(x, y);
+        self.builder.move_to(x, y);
+    }
+
+    fn line_to(&mut self, x: f64, y: f64) {
+        let (x, y) = (self.to_pixel)(x, y);
+        self.builder.line_to(x, y);
+    }
+
+    fn end(&mut self, close: bool) {
+        if close {
+            self.builder.close();
+        }
+    }
+}
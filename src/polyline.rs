@@ -0,0 +1,111 @@
+//! [Encoded polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)
+//! output.
+//!
+//! Following the format, each point is encoded as a `(y, x)` (i.e. `(latitude, longitude)`)
+//! pair, the coordinates being rounded to `precision` decimals
+//! (5 for the usual "polyline5", 6 for "polyline6").
+//!
+//! The rounded coordinates (times `10^precision`) must be less than `2^62` in magnitude,
+//! for the differences between them to fit in 64-bit integers.
+
+use crate::error::{ErrorKind, Result, new_error};
+
+// The bound of the magnitude of the rounded coordinates (times `10^precision`).
+const MAX_SCALED: f64 = (1u64 << 62) as f64;
+
+/// A ring encoded as a polyline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncodedRing {
+    /// The encoded polyline.
+    pub polyline: String,
+    /// Whether the ring is a hole of the previous exterior ring.
+    pub hole: bool,
+}
+
+/// The encoded rings of one threshold.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncodedContour {
+    /// The threshold value.
+    pub value: f64,
+    /// The rings (each exterior ring being followed by its holes).
+    pub rings: Vec<EncodedRing>,
+}
+
+/// Encodes the given points (`[x, y]` positions) as a polyline.
+///
+/// Fails with an [`ErrorKind::PolylineOutOfRange`] error if a coordinate isn't finite
+/// or is `2^62 / 10^precision` or more in magnitude.
+///
+/// [`ErrorKind::PolylineOutOfRange`]: ../enum.ErrorKind.html#variant.PolylineOutOfRange
+pub fn encode(points: &[Vec<f64>], precision: u32) -> Result<String> {
+    let factor = 10f64.powi(precision as i32);
+    let mut out = String::with_capacity(points.len() * 8);
+    let (mut prev_x, mut prev_y) = (0i64, 0i64);
+    for point in points {
+        let (x, y) = match (scale(point[0], factor), scale(point[1], factor)) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err(new_error(ErrorKind::PolylineOutOfRange { x: point[0], y: point[1], precision })),
+        };
+        encode_value(y - prev_y, &mut out);
+        encode_value(x - prev_x, &mut out);
+        prev_x = x;
+        prev_y = y;
+    }
+    Ok(out)
+}
+
+/// Decodes a polyline into `[x, y]` positions.
+/// Returns `None` when the polyline is malformed.
+pub fn decode(polyline: &str, precision: u32) -> Option<Vec<Vec<f64>>> {
+    let factor = 10f64.powi(precision as i32);
+    let mut bytes = polyline.bytes();
+    let mut points = Vec::new();
+    let (mut x, mut y) = (0i64, 0i64);
+    while let Some(dy) = decode_value(&mut bytes)? {
+        let dx = decode_value(&mut bytes)??;
+        y = y.checked_add(dy)?;
+        x = x.checked_add(dx)?;
+        points.push(vec![x as f64 / factor, y as f64 / factor]);
+    }
+    Some(points)
+}
+
+// Rounds the coordinate times `factor`, if it is within the bound.
+fn scale(coordinate: f64, factor: f64) -> Option<i64> {
+    let scaled = (coordinate * factor).round();
+    if scaled.abs() < MAX_SCALED { Some(scaled as i64) } else { None }
+}
+
+fn encode_value(value: i64, out: &mut String) {
+    // Zigzag encoding, the sign going to the lowest bit.
+    let mut value = ((value << 1) ^ (value >> 63)) as u64;
+    while value >= 0x20 {
+        out.push((((value & 0x1f) | 0x20) as u8 + 63) as char);
+        value >>= 5;
+    }
+    out.push((value as u8 + 63) as char);
+}
+
+// Returns `Some(None)` at the end of the input and `None` on malformed input.
+fn decode_value<I: Iterator<Item = u8>>(bytes: &mut I) -> Option<Option<i64>> {
+    let (mut result, mut shift) = (0u64, 0);
+    let mut first = true;
+    loop {
+        let byte = match bytes.next() {
+            Some(b) => b,
+            None if first => return Some(None),
+            None => return None,
+        };
+        first = false;
+        if !(63..127).contains(&byte) || shift > 60 {
+            return None;
+        }
+        let chunk = (byte - 63) as u64;
+        result |= (chunk & 0x1f) << shift;
+        shift += 5;
+        if chunk < 0x20 {
+            break;
+        }
+    }
+    Some(Some((result >> 1) as i64 ^ -((result & 1) as i64)))
+}
@@ -0,0 +1,58 @@
+//! Rasterization of contours back to a grid mask.
+//!
+//! Rasterization happens in grid coordinates: the cell of the value at column `x`
+//! and row `y` is inside the contours when its center, `(x + 0.5, y + 0.5)`, is.
+//! Contours computed with an origin or a step (see [`ContourBuilder::x_origin`] and
+//! the following methods) have to be converted back to grid coordinates first.
+//!
+//! [`ContourBuilder::x_origin`]: ../struct.ContourBuilder.html#method.x_origin
+
+use crate::contour::Pt;
+use geojson::{Feature, Value};
+
+/// Rasterizes the given `rings` onto a `dx` * `dy` grid, returning a mask
+/// (in row-major order) where the cells whose center is inside the rings are `true`.
+///
+/// The rings are filled using the even-odd rule, so holes are excluded as long as
+/// they are part of `rings`.
+pub fn rasterize<R: AsRef<[Pt]>>(rings: &[R], dx: u32, dy: u32) -> Vec<bool> {
+    let (dx, dy) = (dx as usize, dy as usize);
+    let mut mask = vec![false; dx * dy];
+    let mut crossings = Vec::new();
+    for y in 0..dy {
+        let yc = y as f64 + 0.5;
+        crossings.clear();
+        for ring in rings {
+            let ring = ring.as_ref();
+            for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                if (a[1] > yc) != (b[1] > yc) {
+                    crossings.push(a[0] + (yc - a[1]) * (b[0] - a[0]) / (b[1] - a[1]));
+                }
+            }
+        }
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        // A cell is inside when its center is in [crossings[2k], crossings[2k + 1]).
+        for span in crossings.chunks_exact(2) {
+            let start = (span[0] - 0.5).ceil().max(0.).min(dx as f64) as usize;
+            let end = (span[1] - 0.5).ceil().max(0.).min(dx as f64) as usize;
+            for cell in &mut mask[y * dx + start..y * dx + end.max(start)] {
+                *cell = true;
+            }
+        }
+    }
+    mask
+}
+
+/// Rasterizes the polygons of the given `feature` (as returned by
+/// [`ContourBuilder::contours`]) like [`rasterize`] does.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+/// [`rasterize`]: fn.rasterize.html
+pub fn rasterize_feature(feature: &Feature, dx: u32, dy: u32) -> Vec<bool> {
+    let rings = match feature.geometry.as_ref().map(|g| &g.value) {
+        Some(Value::Polygon(polygon)) => polygon.clone(),
+        Some(Value::MultiPolygon(polygons)) => polygons.iter().flatten().cloned().collect(),
+        _ => Vec::new(),
+    };
+    rasterize(&rings, dx, dy)
+}
@@ -0,0 +1,158 @@
+//! Resampling of grids to another resolution, e.g. to compute smoother contours
+//! of a coarse grid (see [`ContourBuilder::contours_resampled`]), and sampling
+//! of their values at any position.
+//!
+//! As for the contours, each value stands for the center of its cell: the value at
+//! column `x` and row `y` is at `(x + 0.5, y + 0.5)`, the grid covering `[0, dx] x [0, dy]`.
+//! The resampled grid covers the same extent with cells of another size.
+//!
+//! [`ContourBuilder::contours_resampled`]: ../struct.ContourBuilder.html#method.contours_resampled
+
+use crate::contour::check_dimensions;
+use crate::error::{ErrorKind, Result, new_error};
+
+/// How the NaN values are handled by [`resample_bilinear_with`].
+///
+/// [`resample_bilinear_with`]: fn.resample_bilinear_with.html
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NanMode {
+    /// A NaN value only makes NaN the resampled values closer to it than to the other
+    /// values around them (the quarter of each cell between the four values around it
+    /// nearest to the NaN one); the others are interpolated from the values which
+    /// aren't NaN (default).
+    #[default]
+    Quadrant,
+    /// A NaN value makes NaN all the resampled values interpolated from it.
+    Propagate,
+    /// The resampled values are interpolated from the values which aren't NaN, only
+    /// being NaN when all of them are.
+    Ignore,
+}
+
+/// Resamples the grid of `dx` columns and `dy` rows to a grid of `new_dx` columns and
+/// `new_dy` rows, with bilinear interpolation of the values (see [`NanMode::Quadrant`]
+/// for the NaN ones). Returns an [`ErrorKind::BadDimension`] error if the length
+/// of `values` doesn't match the dimensions of the grid.
+///
+/// The resampled values at the border of the grid, beyond the centers of the cells
+/// of the border, take their nearest value.
+///
+/// ```
+/// # use contour::resample::resample_bilinear;
+/// let values = resample_bilinear(&[0., 1., 2., 3.], 2, 2, 4, 1)?;
+/// assert_eq!(values, [1., 1.25, 1.75, 2.]);
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`NanMode::Quadrant`]: enum.NanMode.html#variant.Quadrant
+/// [`ErrorKind::BadDimension`]: ../enum.ErrorKind.html#variant.BadDimension
+pub fn resample_bilinear(values: &[f64], dx: u32, dy: u32, new_dx: u32, new_dy: u32) -> Result<Vec<f64>> {
+    resample_bilinear_with(values, dx, dy, new_dx, new_dy, NanMode::default())
+}
+
+/// Resamples the grid like [`resample_bilinear`], handling the NaN values as
+/// given by `nan_mode`.
+///
+/// [`resample_bilinear`]: fn.resample_bilinear.html
+pub fn resample_bilinear_with(values: &[f64], dx: u32, dy: u32, new_dx: u32, new_dy: u32, nan_mode: NanMode) -> Result<Vec<f64>> {
+    check_dimensions(dx, dy, values.len())?;
+    let (dx, dy) = (dx as usize, dy as usize);
+    let len = (new_dx as usize).checked_mul(new_dy as usize).ok_or_else(|| new_error(ErrorKind::BadDimension))?;
+    if dx == 0 || dy == 0 {
+        return Ok(vec![f64::NAN; len]);
+    }
+    let xs = (0..new_dx as usize).map(|i| sources(i, dx, new_dx as usize)).collect::<Vec<_>>();
+    let ys = (0..new_dy as usize).map(|i| sources(i, dy, new_dy as usize)).collect::<Vec<_>>();
+    let mut resampled = Vec::with_capacity(len);
+    for &(y0, y1, ty) in &ys {
+        for &(x0, x1, tx) in &xs {
+            let corners = [
+                (values[y0 * dx + x0], (1. - tx) * (1. - ty)),
+                (values[y0 * dx + x1], tx * (1. - ty)),
+                (values[y1 * dx + x0], (1. - tx) * ty),
+                (values[y1 * dx + x1], tx * ty),
+            ];
+            resampled.push(interpolate(&corners, nan_mode));
+        }
+    }
+    Ok(resampled)
+}
+
+/// Samples the grid of `dx` columns and `dy` rows at the position `(x, y)` (in the
+/// coordinates of the contours computed without origin nor step), with bilinear
+/// interpolation of the values around it, like [`resample_bilinear`] does.
+///
+/// Returns `None` outside of the grid (`[0, dx] x [0, dy]`), when the length of `values`
+/// doesn't match its dimensions, or when one of the values around the position is NaN
+/// (see [`sample_bilinear_with`] to get the nearest of the other ones instead).
+///
+/// ```
+/// # use contour::resample::sample_bilinear;
+/// let values = [0., 1., 2., 3.];
+/// assert_eq!(sample_bilinear(&values, 2, 2, 1., 1.), Some(1.5));
+/// assert_eq!(sample_bilinear(&values, 2, 2, 0.5, 1.5), Some(2.));
+/// assert_eq!(sample_bilinear(&values, 2, 2, 2.5, 1.), None);
+/// ```
+///
+/// [`resample_bilinear`]: fn.resample_bilinear.html
+/// [`sample_bilinear_with`]: fn.sample_bilinear_with.html
+pub fn sample_bilinear(values: &[f64], dx: u32, dy: u32, x: f64, y: f64) -> Option<f64> {
+    sample_bilinear_with(values, dx, dy, x, y, false)
+}
+
+/// Samples the grid like [`sample_bilinear`], returning the nearest of the finite values
+/// around the position, if any, when `nearest_finite` is set and the interpolated
+/// value is NaN.
+///
+/// [`sample_bilinear`]: fn.sample_bilinear.html
+pub fn sample_bilinear_with(values: &[f64], dx: u32, dy: u32, x: f64, y: f64, nearest_finite: bool) -> Option<f64> {
+    check_dimensions(dx, dy, values.len()).ok()?;
+    let inside = |position: f64, n: u32| n > 0 && (0. ..=f64::from(n)).contains(&position);
+    if !inside(x, dx) || !inside(y, dy) {
+        return None;
+    }
+    let (dx, dy) = (dx as usize, dy as usize);
+    let ((x0, x1, tx), (y0, y1, ty)) = (around(x, dx), around(y, dy));
+    let corners = [
+        (values[y0 * dx + x0], (1. - tx) * (1. - ty)),
+        (values[y0 * dx + x1], tx * (1. - ty)),
+        (values[y1 * dx + x0], (1. - tx) * ty),
+        (values[y1 * dx + x1], tx * ty),
+    ];
+    let value = interpolate(&corners, NanMode::Propagate);
+    if !value.is_nan() {
+        Some(value)
+    } else if nearest_finite {
+        let corners = corners.iter().filter(|(value, weight)| value.is_finite() && *weight > 0.);
+        corners.max_by(|a, b| a.1.total_cmp(&b.1)).map(|(value, _)| *value)
+    } else {
+        None
+    }
+}
+
+// The indexes of the two values around the resampled value `i`, along an axis of `n`
+// values resampled to `new_n` values, and the weight of the second one.
+fn sources(i: usize, n: usize, new_n: usize) -> (usize, usize, f64) {
+    around((i as f64 + 0.5) * n as f64 / new_n as f64, n)
+}
+
+// The indexes of the two values around the position `x`, along an axis of `n` values
+// (the one at index `i` being at `i + 0.5`), and the weight of the second one.
+fn around(x: f64, n: usize) -> (usize, usize, f64) {
+    let position = (x - 0.5).max(0.).min((n - 1) as f64);
+    let first = position.floor() as usize;
+    (first, (first + 1).min(n - 1), position - first as f64)
+}
+
+// Interpolates the `(value, weight)` corners, the ones of zero weight being left out.
+fn interpolate(corners: &[(f64, f64); 4], nan_mode: NanMode) -> f64 {
+    let corners = corners.iter().filter(|(_, weight)| *weight > 0.);
+    let nearest = corners.clone().map(|(_, weight)| *weight).fold(0., f64::max);
+    let poisoned = match nan_mode {
+        NanMode::Quadrant => corners.clone().any(|(value, weight)| value.is_nan() && *weight == nearest),
+        NanMode::Propagate => corners.clone().any(|(value, _)| value.is_nan()),
+        NanMode::Ignore => false,
+    };
+    let (sum, total) = corners.filter(|(value, _)| !value.is_nan()).fold((0., 0.), |(sum, total), (value, weight)| (sum + value * weight, total + weight));
+    if poisoned || total == 0. { f64::NAN } else { sum / total }
+}
@@ -0,0 +1,288 @@
+use crate::contour::{LinearRing, Pt};
+use crate::geom::{area, ring_bbox};
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::slice;
+
+/// The winding order of a [`Ring`], with the y axis pointing up
+/// (see [`Ring::signed_area`]).
+///
+/// [`Ring`]: struct.Ring.html
+/// [`Ring::signed_area`]: struct.Ring.html#method.signed_area
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Winding {
+    /// A negative signed area: the exterior rings computed by [`contour_rings`].
+    ///
+    /// [`contour_rings`]: fn.contour_rings.html
+    Clockwise,
+    /// A positive signed area: the holes computed by [`contour_rings`].
+    ///
+    /// [`contour_rings`]: fn.contour_rings.html
+    CounterClockwise,
+    /// A zero (or NaN) signed area.
+    Degenerate,
+}
+
+/// The side of a ring where the values are higher than the threshold, relative to the
+/// direction of travel along the ring, with the y axis pointing up (like [`Winding`]).
+///
+/// The rings computed by [`contour_rings`] always have the higher values on their right
+/// (inside the exterior rings, which are [`Winding::Clockwise`], and outside the holes),
+/// as the direction of the marching squares traversal keeps them on the same side.
+///
+/// [`Winding`]: enum.Winding.html
+/// [`Winding::Clockwise`]: enum.Winding.html#variant.Clockwise
+/// [`contour_rings`]: fn.contour_rings.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Side {
+    /// The higher values are on the left of the ring.
+    Left,
+    /// The higher values are on the right of the ring.
+    Right,
+}
+
+impl Side {
+    /// Returns the name of the side, as written in the `higher_side` property of
+    /// the Features: `"left"` or `"right"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::Left => "left",
+            Side::Right => "right",
+        }
+    }
+}
+
+/// The direction of a ring, as computed by [`contour_rings_with_directions`]: the side
+/// of its higher values, and the tangent angles at its positions, if requested
+/// (see [`Ring::tangent_angles`]).
+///
+/// [`contour_rings_with_directions`]: fn.contour_rings_with_directions.html
+/// [`Ring::tangent_angles`]: struct.Ring.html#method.tangent_angles
+#[derive(Clone, Debug, PartialEq)]
+pub struct RingDirection {
+    /// The side of the higher values.
+    pub higher_side: Side,
+    /// The tangent angle at each position of the ring (empty if not requested).
+    pub tangent_angles: Vec<f64>,
+}
+
+/// A ring, as returned by [`contour_rings`]: a sequence of positions (`[x, y]`,
+/// or `[x, y, z]`), closed when its last position is the same as its first one.
+///
+/// Converts to and from the nested `Vec` of positions used by GeoJSON
+/// (`Vec<Vec<f64>>`), which it can also be compared with.
+///
+/// [`contour_rings`]: fn.contour_rings.html
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ring {
+    points: Vec<Pt>,
+}
+
+impl Ring {
+    /// Constructs a ring from its positions.
+    pub fn new(points: Vec<Vec<f64>>) -> Self {
+        Ring { points }
+    }
+
+    /// Returns the number of positions of the ring (the closing one included).
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns whether the ring has no position.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns an iterator over the positions of the ring.
+    pub fn iter_points(&self) -> slice::Iter<'_, Vec<f64>> {
+        self.points.iter()
+    }
+
+    /// Returns an iterator over the positions of the ring, allowing to move them.
+    pub fn iter_points_mut(&mut self) -> slice::IterMut<'_, Vec<f64>> {
+        self.points.iter_mut()
+    }
+
+    /// Computes the signed area of the ring (shoelace formula), positive when the ring
+    /// is counterclockwise with the y axis pointing up. The ring doesn't need to be closed.
+    ///
+    /// In the grid coordinates of [`contour_rings`] the y axis points down, so the exterior
+    /// rings, clockwise by this convention, look counterclockwise when drawn.
+    ///
+    /// [`contour_rings`]: fn.contour_rings.html
+    pub fn signed_area(&self) -> f64 {
+        -area(&self.points) / 2.
+    }
+
+    /// Returns the winding order of the ring, from the sign of its [`signed_area`].
+    ///
+    /// [`signed_area`]: #method.signed_area
+    pub fn orientation(&self) -> Winding {
+        let area = self.signed_area();
+        if area < 0. {
+            Winding::Clockwise
+        } else if area > 0. {
+            Winding::CounterClockwise
+        } else {
+            Winding::Degenerate
+        }
+    }
+
+    /// Returns whether the ring has at least two positions, the last one being the same
+    /// as the first one.
+    pub fn is_closed(&self) -> bool {
+        self.points.len() > 1 && self.points.first() == self.points.last()
+    }
+
+    /// Closes the ring, appending its first position unless it's empty or already closed.
+    pub fn close(&mut self) {
+        if !self.points.is_empty() && !self.is_closed() {
+            self.points.push(self.points[0].clone());
+        }
+    }
+
+    /// Opens the ring, removing its last position if it's a repetition of the first one.
+    pub fn open(&mut self) {
+        open(&mut self.points);
+    }
+
+    /// Computes the bounding box (`[min x, min y, max x, max y]`) of the ring,
+    /// or `None` if it's empty.
+    pub fn bbox(&self) -> Option<Vec<f64>> {
+        ring_bbox(&self.points)
+    }
+
+    /// Reverses the order of the positions of the ring (and so its winding order).
+    pub fn reverse(&mut self) {
+        self.points.reverse();
+    }
+
+    /// Rotates the closed ring so that it starts (and ends) at its lexicographically
+    /// smallest position (by x, then y), keeping its orientation: the same cycle of
+    /// positions always gives the same ring. Rings which aren't closed are left unchanged.
+    pub fn normalize(&mut self) {
+        normalize(&mut self.points);
+    }
+
+    /// Computes the tangent angle of the ring at each of its positions, in degrees from
+    /// `0` to `360`, counterclockwise from the x axis with the y axis pointing up: the angle
+    /// of the direction from the previous position to the next one (wrapping around the
+    /// closed rings, the closing position getting the angle of the first one).
+    ///
+    /// With the higher values on the [`Side::Right`], the uphill direction is the angle
+    /// minus `90`, and plus `90` with the higher values on the [`Side::Left`].
+    ///
+    /// [`Side::Right`]: enum.Side.html#variant.Right
+    /// [`Side::Left`]: enum.Side.html#variant.Left
+    pub fn tangent_angles(&self) -> Vec<f64> {
+        tangent_angles(&self.points)
+    }
+
+    /// Returns the positions of the ring, as a nested `Vec`.
+    pub fn into_inner(self) -> Vec<Vec<f64>> {
+        self.points
+    }
+}
+
+impl From<LinearRing> for Ring {
+    fn from(points: LinearRing) -> Self {
+        Ring { points }
+    }
+}
+
+impl From<Ring> for LinearRing {
+    fn from(ring: Ring) -> Self {
+        ring.points
+    }
+}
+
+impl FromIterator<Pt> for Ring {
+    fn from_iter<I: IntoIterator<Item = Pt>>(iter: I) -> Self {
+        Ring {
+            points: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl AsRef<[Pt]> for Ring {
+    fn as_ref(&self) -> &[Pt] {
+        &self.points
+    }
+}
+
+impl PartialEq<LinearRing> for Ring {
+    fn eq(&self, other: &LinearRing) -> bool {
+        &self.points == other
+    }
+}
+
+// Computes the tangent angles of the ring `points` like `Ring::tangent_angles` does,
+// with one-sided directions at the ends of the rings which aren't closed.
+pub(crate) fn tangent_angles(points: &[Pt]) -> Vec<f64> {
+    let closed = points.len() > 1 && points.first() == points.last();
+    let n = if closed { points.len() - 1 } else { points.len() };
+    let mut angles = (0..n)
+        .map(|i| {
+            let (prev, next) = if closed { ((i + n - 1) % n, (i + 1) % n) } else { (i.saturating_sub(1), (i + 1).min(n - 1)) };
+            let (a, b) = (&points[prev], &points[next]);
+            (b[1] - a[1]).atan2(b[0] - a[0]).to_degrees().rem_euclid(360.)
+        })
+        .collect::<Vec<_>>();
+    if closed {
+        angles.push(angles[0]);
+    }
+    angles
+}
+
+// The side of the higher values of the ring `points`, an exterior ring of a polygon
+// (having the higher values inside) or one of its holes, from its winding order.
+pub(crate) fn higher_side(points: &[Pt], exterior: bool) -> Option<Side> {
+    // A positive area for the clockwise rings (see `Ring::signed_area`).
+    let area = area(points);
+    if area > 0. {
+        Some(if exterior { Side::Right } else { Side::Left })
+    } else if area < 0. {
+        Some(if exterior { Side::Left } else { Side::Right })
+    } else {
+        None
+    }
+}
+
+// Removes the closing position of the ring `points`, if any.
+pub(crate) fn open(points: &mut LinearRing) {
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+}
+
+// Rotates the closed ring `points` like `Ring::normalize` does. Among several occurrences
+// of the smallest position, the one starting the smallest sequence of positions is chosen.
+pub(crate) fn normalize(points: &mut LinearRing) {
+    if points.len() < 2 || points.first() != points.last() {
+        return;
+    }
+    points.pop();
+    let n = points.len();
+    let rotation = |start: usize| (0..n).map(move |i| (start + i) % n);
+    let start = (0..n)
+        .min_by(|a, b| {
+            rotation(*a)
+                .zip(rotation(*b))
+                .map(|(i, j)| compare(&points[i], &points[j]))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        })
+        .unwrap_or(0);
+    points.rotate_left(start);
+    points.push(points[0].clone());
+}
+
+// Compares the positions `a` and `b` lexicographically (NaN coordinates included).
+fn compare(a: &[f64], b: &[f64]) -> Ordering {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| x.total_cmp(y))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
@@ -0,0 +1,81 @@
+//! Cross-sections: the positions where the contours cross a polyline
+//! (such as a transect or a road), for terrain profiles.
+
+use geojson::{Feature, Value};
+
+/// Computes, for each of the given `features` (as returned by [`ContourBuilder::contours`]),
+/// the crossings of the rings of its polygons with the polyline going through the given
+/// points, as `(distance along the polyline, x, y)` tuples sorted by distance.
+///
+/// Each segment of a ring includes its first position but not its last one, and so do
+/// the segments of the polyline (except the last one, which includes both ends): a ring
+/// crossing the polyline or touching it at one of its vertices gives exactly one crossing.
+/// Ring segments parallel to a segment of the polyline (including collinear ones,
+/// along which the polyline runs) don't give any crossing.
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn cross_section(features: &[Feature], line: &[(f64, f64)]) -> Vec<Vec<(f64, f64, f64)>> {
+    let segments: Vec<_> = line
+        .windows(2)
+        .scan(0., |distance, w| {
+            let (p, q) = (w[0], w[1]);
+            let start = *distance;
+            *distance += (q.0 - p.0).hypot(q.1 - p.1);
+            Some((p, q, start, bbox(&[p, q])))
+        })
+        .collect();
+    features
+        .iter()
+        .map(|feature| {
+            let rings = match feature.geometry.as_ref().map(|g| &g.value) {
+                Some(Value::Polygon(polygon)) => polygon.iter().collect(),
+                Some(Value::MultiPolygon(polygons)) => polygons.iter().flatten().collect(),
+                _ => Vec::new(),
+            };
+            let mut crossings = Vec::new();
+            for ring in rings {
+                let points: Vec<_> = ring.iter().map(|p| (p[0], p[1])).collect();
+                let ring_bbox = bbox(&points);
+                for (i, &(p, q, start, segment_bbox)) in segments.iter().enumerate() {
+                    if !intersects(&ring_bbox, &segment_bbox) {
+                        continue;
+                    }
+                    let last = i == segments.len() - 1;
+                    for edge in points.windows(2) {
+                        if let Some(t) = intersection(p, q, edge[0], edge[1], last) {
+                            let (x, y) = (p.0 + t * (q.0 - p.0), p.1 + t * (q.1 - p.1));
+                            crossings.push((start + t * (q.0 - p.0).hypot(q.1 - p.1), x, y));
+                        }
+                    }
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            crossings
+        })
+        .collect()
+}
+
+// Returns the position, along `pq` (from 0 to 1), of its intersection with `ab`
+// (which includes `a` but not `b`; `pq` includes `p`, and `q` when `include_q` is set).
+fn intersection(p: (f64, f64), q: (f64, f64), a: (f64, f64), b: (f64, f64), include_q: bool) -> Option<f64> {
+    let cross = |u: (f64, f64), v: (f64, f64)| u.0 * v.1 - u.1 * v.0;
+    let (r, s, ap) = ((q.0 - p.0, q.1 - p.1), (b.0 - a.0, b.1 - a.1), (a.0 - p.0, a.1 - p.1));
+    let denominator = cross(r, s);
+    if denominator == 0. {
+        return None;
+    }
+    let t = cross(ap, s) / denominator;
+    let u = cross(ap, r) / denominator;
+    let on_line = t >= 0. && (t < 1. || include_q && t <= 1.);
+    if on_line && (0. ..1.).contains(&u) { Some(t) } else { None }
+}
+
+fn bbox(points: &[(f64, f64)]) -> [f64; 4] {
+    points.iter().fold([f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY], |b, p| {
+        [b[0].min(p.0), b[1].min(p.1), b[2].max(p.0), b[3].max(p.1)]
+    })
+}
+
+fn intersects(a: &[f64; 4], b: &[f64; 4]) -> bool {
+    a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3]
+}
@@ -0,0 +1,200 @@
+//! ESRI Shapefile writer.
+//!
+//! Each Feature is written as one Polygon record (or PolygonZ record when its
+//! positions have a third coordinate, see [`ContourBuilder::z`]), with its
+//! `value` property stored in the `VALUE` numeric field of the attribute table.
+//! Features without geometry or with an empty geometry are written as null shapes.
+//!
+//! [`ContourBuilder::z`]: ../struct.ContourBuilder.html#method.z
+
+use crate::geom::area;
+use crate::contour::LinearRing;
+use crate::error::Result;
+use geojson::{Feature, Value};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+const SHAPE_NULL: i32 = 0;
+const SHAPE_POLYGON: i32 = 5;
+const SHAPE_POLYGONZ: i32 = 15;
+const VALUE_FIELD_LENGTH: usize = 24;
+const VALUE_FIELD_DECIMALS: usize = 15;
+
+/// Writes the given `features` to the `.shp`, `.shx` and `.dbf` files
+/// of the shapefile at `path` (whose extension, if any, is replaced).
+pub fn write_shapefile<P: AsRef<Path>>(path: P, features: &[Feature]) -> Result<()> {
+    let path = path.as_ref();
+    let create = |extension| -> Result<BufWriter<File>> { Ok(BufWriter::new(File::create(path.with_extension(extension))?)) };
+    let (mut shp, mut shx, mut dbf) = (create("shp")?, create("shx")?, create("dbf")?);
+    write_shapefile_to(&mut shp, &mut shx, &mut dbf, features)?;
+    shp.flush()?;
+    shx.flush()?;
+    dbf.flush()?;
+    Ok(())
+}
+
+/// Writes the given `features` as a shapefile to the three given writers
+/// (for the `.shp`, `.shx` and `.dbf` files).
+pub fn write_shapefile_to<S: Write, X: Write, D: Write>(shp: S, shx: X, dbf: D, features: &[Feature]) -> Result<()> {
+    let records = features.iter().map(ring_parts).collect::<Vec<_>>();
+    let has_z = records.iter().flatten().flatten().any(|point| point.len() > 2);
+    let contents = records
+        .iter()
+        .map(|rings| record_content(rings, has_z))
+        .collect::<Vec<_>>();
+    let values = features
+        .iter()
+        .map(|feature| {
+            feature
+                .properties
+                .as_ref()
+                .and_then(|p| p.get("value"))
+                .and_then(|v| v.as_f64())
+        })
+        .collect::<Vec<_>>();
+    write_shp(shp, shx, &contents, &records, has_z)?;
+    write_dbf(dbf, &values)?;
+    Ok(())
+}
+
+// Returns the rings of the feature, the exterior rings being clockwise
+// and the holes counterclockwise, as required by the shapefile format.
+fn ring_parts(feature: &Feature) -> Vec<LinearRing> {
+    let polygons = match feature.geometry.as_ref().map(|g| &g.value) {
+        Some(Value::Polygon(polygon)) => vec![polygon.clone()],
+        Some(Value::MultiPolygon(polygons)) => polygons.clone(),
+        _ => Vec::new(),
+    };
+    let mut parts = Vec::new();
+    for polygon in polygons {
+        for (i, mut ring) in polygon.into_iter().enumerate() {
+            if ring.len() < 4 {
+                continue;
+            }
+            // The crate's `area` is positive for rings which are clockwise
+            // in a y-up coordinate system.
+            let clockwise = area(&ring) > 0.;
+            if clockwise != (i == 0) {
+                ring.reverse();
+            }
+            parts.push(ring);
+        }
+    }
+    parts
+}
+
+fn bbox<'a, I: Iterator<Item = &'a Vec<f64>>>(points: I) -> Option<[f64; 6]> {
+    points.fold(None, |bbox, p| {
+        let z = p.get(2).cloned().unwrap_or(0.);
+        Some(match bbox {
+            None => [p[0], p[1], p[0], p[1], z, z],
+            Some(b) => [b[0].min(p[0]), b[1].min(p[1]), b[2].max(p[0]), b[3].max(p[1]), b[4].min(z), b[5].max(z)],
+        })
+    })
+}
+
+fn record_content(rings: &[LinearRing], has_z: bool) -> Vec<u8> {
+    let mut content = Vec::new();
+    let bbox = match bbox(rings.iter().flatten()) {
+        Some(bbox) => bbox,
+        None => {
+            content.extend_from_slice(&SHAPE_NULL.to_le_bytes());
+            return content;
+        }
+    };
+    let shape_type = if has_z { SHAPE_POLYGONZ } else { SHAPE_POLYGON };
+    content.extend_from_slice(&shape_type.to_le_bytes());
+    for v in &bbox[..4] {
+        content.extend_from_slice(&v.to_le_bytes());
+    }
+    let num_points = rings.iter().map(|r| r.len()).sum::<usize>();
+    content.extend_from_slice(&(rings.len() as i32).to_le_bytes());
+    content.extend_from_slice(&(num_points as i32).to_le_bytes());
+    let mut start = 0;
+    for ring in rings {
+        content.extend_from_slice(&(start as i32).to_le_bytes());
+        start += ring.len();
+    }
+    for point in rings.iter().flatten() {
+        content.extend_from_slice(&point[0].to_le_bytes());
+        content.extend_from_slice(&point[1].to_le_bytes());
+    }
+    if has_z {
+        content.extend_from_slice(&bbox[4].to_le_bytes());
+        content.extend_from_slice(&bbox[5].to_le_bytes());
+        for point in rings.iter().flatten() {
+            content.extend_from_slice(&point.get(2).cloned().unwrap_or(0.).to_le_bytes());
+        }
+    }
+    content
+}
+
+fn write_header<W: Write>(w: &mut W, file_length: usize, shape_type: i32, bbox: [f64; 6]) -> std::io::Result<()> {
+    w.write_all(&9994i32.to_be_bytes())?;
+    w.write_all(&[0; 20])?;
+    w.write_all(&((file_length / 2) as i32).to_be_bytes())?;
+    w.write_all(&1000i32.to_le_bytes())?;
+    w.write_all(&shape_type.to_le_bytes())?;
+    for v in &bbox[..4] {
+        w.write_all(&v.to_le_bytes())?;
+    }
+    w.write_all(&bbox[4].to_le_bytes())?;
+    w.write_all(&bbox[5].to_le_bytes())?;
+    w.write_all(&[0; 16])
+}
+
+fn write_shp<S: Write, X: Write>(mut shp: S, mut shx: X, contents: &[Vec<u8>], records: &[Vec<LinearRing>], has_z: bool) -> Result<()> {
+    let shape_type = if has_z { SHAPE_POLYGONZ } else { SHAPE_POLYGON };
+    let bbox = bbox(records.iter().flatten().flatten()).unwrap_or([0.; 6]);
+    let shp_length = 100 + contents.iter().map(|c| 8 + c.len()).sum::<usize>();
+    write_header(&mut shp, shp_length, shape_type, bbox)?;
+    write_header(&mut shx, 100 + 8 * contents.len(), shape_type, bbox)?;
+    let mut offset = 100;
+    for (i, content) in contents.iter().enumerate() {
+        shp.write_all(&(i as i32 + 1).to_be_bytes())?;
+        shp.write_all(&((content.len() / 2) as i32).to_be_bytes())?;
+        shp.write_all(content)?;
+        shx.write_all(&((offset / 2) as i32).to_be_bytes())?;
+        shx.write_all(&((content.len() / 2) as i32).to_be_bytes())?;
+        offset += 8 + content.len();
+    }
+    Ok(())
+}
+
+fn write_dbf<D: Write>(mut dbf: D, values: &[Option<f64>]) -> Result<()> {
+    let header_length = 32 + 32 + 1;
+    let record_length = 1 + VALUE_FIELD_LENGTH;
+    // dBase III without memo, last update date (YY MM DD) left to 1900-01-01.
+    dbf.write_all(&[0x03, 0, 1, 1])?;
+    dbf.write_all(&(values.len() as u32).to_le_bytes())?;
+    dbf.write_all(&(header_length as u16).to_le_bytes())?;
+    dbf.write_all(&(record_length as u16).to_le_bytes())?;
+    dbf.write_all(&[0; 20])?;
+    let mut field = [0u8; 32];
+    field[..5].copy_from_slice(b"VALUE");
+    field[11] = b'N';
+    field[16] = VALUE_FIELD_LENGTH as u8;
+    field[17] = VALUE_FIELD_DECIMALS as u8;
+    dbf.write_all(&field)?;
+    dbf.write_all(&[0x0D])?;
+    for value in values {
+        dbf.write_all(b" ")?;
+        dbf.write_all(format!("{:>width$}", format_number(*value), width = VALUE_FIELD_LENGTH).as_bytes())?;
+    }
+    dbf.write_all(&[0x1A])?;
+    Ok(())
+}
+
+// Formats the value to fit in the numeric field, dropping decimals when needed
+// (values which can't fit, as well as missing values, are left blank).
+fn format_number(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.is_finite() => (0..=VALUE_FIELD_DECIMALS)
+            .rev()
+            .map(|decimals| format!("{:.*}", decimals, v))
+            .find(|text| text.len() <= VALUE_FIELD_LENGTH)
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
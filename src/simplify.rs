@@ -0,0 +1,252 @@
+//! Simplification of the rings of contours of several thresholds at once, preserving
+//! their topology: unlike the simplification of each contour on its own, the nested
+//! contours never end up crossing each other.
+//!
+//! The rings are simplified with the
+//! [Visvalingam–Whyatt](https://en.wikipedia.org/wiki/Visvalingam%E2%80%93Whyatt_algorithm)
+//! algorithm, like TopoJSON does, except that a position is only removed when no other
+//! position (of any ring of any threshold) lies in the triangle it forms with its
+//! neighbours: the new segment then can't cross any ring, and no position moves
+//! from one side of the ring to the other.
+
+use crate::contour::LinearRing;
+use crate::geom::orient2d;
+use geojson::{Feature, Value};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Simplifies the rings of the polygons of the given `features` (as returned by
+/// [`ContourBuilder::contours`], with all their thresholds), removing the positions
+/// whose triangle with their neighbours has an area smaller than `tolerance` (in
+/// squared output units), smallest first, as long as no other position lies in this
+/// triangle: the rings (of all the thresholds) which didn't cross each other still don't,
+/// and the rings and positions inside a ring stay inside it.
+///
+/// The positions shared by several rings (or by a ring with itself), such as the ones
+/// where contours meet on steep slopes, are kept, so that the rings sharing them still do.
+/// Each ring keeps at least three positions (and its closing one); the rings which aren't
+/// closed are left unchanged. Nothing is removed when `tolerance` isn't positive.
+///
+/// ```
+/// # use contour::ContourBuilder;
+/// # use contour::simplify::simplify_preserving_topology;
+/// let values = (0..100).map(|i| ((i % 10) as f64 - 4.5).hypot((i / 10) as f64 - 4.5)).collect::<Vec<_>>();
+/// let mut features = ContourBuilder::new(10, 10, true).contours(&values, &[2., 4.])?;
+/// simplify_preserving_topology(&mut features, 0.5);
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+pub fn simplify_preserving_topology(features: &mut [Feature], tolerance: f64) {
+    let mut rings: Vec<&mut LinearRing> = features
+        .iter_mut()
+        .filter_map(|feature| feature.geometry.as_mut())
+        .flat_map(|geometry| match geometry.value {
+            Value::Polygon(ref mut polygon) => polygon.iter_mut().collect(),
+            Value::MultiPolygon(ref mut polygons) => polygons.iter_mut().flatten().collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+    simplify_rings(&mut rings, tolerance);
+}
+
+// A position of a ring (its closing position left out) being simplified.
+struct Vertex {
+    ring: usize,
+    // The index of the position in its ring.
+    index: usize,
+    // The vertices before and after this one in the simplified ring.
+    prev: usize,
+    next: usize,
+    removed: bool,
+    // Whether the position can be removed.
+    removable: bool,
+}
+
+// A vertex to remove, with the area of its triangle when it was queued (the vertices
+// being queued again with a new `version` when their neighbours change).
+struct Candidate {
+    area: f64,
+    vertex: usize,
+    version: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Reversed, for the `BinaryHeap` to give the smallest area first.
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.area.total_cmp(&self.area).then_with(|| other.vertex.cmp(&self.vertex))
+    }
+}
+
+pub(crate) fn simplify_rings(rings: &mut [&mut LinearRing], tolerance: f64) {
+    let mut vertices = Vec::new();
+    // The number of vertices left in each ring, if it's simplified.
+    let mut lengths = vec![None; rings.len()];
+    for (r, ring) in rings.iter().enumerate() {
+        let n = ring.len();
+        let closed = n > 1 && ring[0][..2] == ring[n - 1][..2];
+        let first = vertices.len();
+        for index in 0..if closed { n - 1 } else { n } {
+            let (prev, next) = if closed {
+                (first + (index + n - 2) % (n - 1), first + (index + 1) % (n - 1))
+            } else {
+                (first + index, first + index)
+            };
+            let finite = ring[index][0].is_finite() && ring[index][1].is_finite();
+            vertices.push(Vertex { ring: r, index, prev, next, removed: false, removable: closed && finite });
+        }
+        if closed && n > 4 {
+            lengths[r] = Some(n - 1);
+        }
+    }
+    let point = |rings: &[&mut LinearRing], v: &Vertex| [rings[v.ring][v.index][0], rings[v.ring][v.index][1]];
+
+    // The positions shared by several vertices are kept.
+    let mut shared = HashMap::new();
+    for v in &vertices {
+        let [x, y] = point(rings, v);
+        *shared.entry(((x + 0.).to_bits(), (y + 0.).to_bits())).or_insert(0) += 1;
+    }
+    for v in vertices.iter_mut() {
+        let [x, y] = point(rings, v);
+        v.removable &= lengths[v.ring].is_some() && shared[&((x + 0.).to_bits(), (y + 0.).to_bits())] == 1;
+    }
+
+    let grid = Grid::new(vertices.iter().map(|v| point(rings, v)).collect());
+    let triangle = |vertices: &[Vertex], i: usize| {
+        let v = &vertices[i];
+        [point(rings, &vertices[v.prev]), point(rings, v), point(rings, &vertices[v.next])]
+    };
+    let area = |t: &[[f64; 2]; 3]| ((t[1][0] - t[0][0]) * (t[2][1] - t[0][1]) - (t[2][0] - t[0][0]) * (t[1][1] - t[0][1])).abs() / 2.;
+
+    let mut versions = vec![0; vertices.len()];
+    // The vertices whose triangle has a NaN area (next to a NaN position) aren't queued.
+    let mut heap = (0..vertices.len())
+        .filter(|i| vertices[*i].removable)
+        .map(|i| Candidate { area: area(&triangle(&vertices, i)), vertex: i, version: 0 })
+        .filter(|candidate| !candidate.area.is_nan())
+        .collect::<BinaryHeap<_>>();
+    while let Some(Candidate { area: a, vertex: i, version }) = heap.pop() {
+        if vertices[i].removed || version != versions[i] {
+            continue;
+        }
+        if a.partial_cmp(&tolerance) != Some(Ordering::Less) {
+            break;
+        }
+        let t = triangle(&vertices, i);
+        let (ring, prev, next) = (vertices[i].ring, vertices[i].prev, vertices[i].next);
+        let left = lengths[ring].unwrap_or(0);
+        // The ring must keep three positions, not all collinear.
+        let degenerate = left <= 3 || (left == 4 && orient2d(&t[0], &t[2], &point(rings, &vertices[vertices[next].next])) == 0.);
+        if degenerate || grid.any_in_triangle(&t, |j| j != i && j != prev && j != next && !vertices[j].removed) {
+            continue;
+        }
+        vertices[i].removed = true;
+        vertices[prev].next = next;
+        vertices[next].prev = prev;
+        lengths[ring] = Some(left - 1);
+        for j in [prev, next].iter().cloned() {
+            versions[j] += 1;
+            let area = area(&triangle(&vertices, j));
+            if vertices[j].removable && !area.is_nan() {
+                heap.push(Candidate { area, vertex: j, version: versions[j] });
+            }
+        }
+    }
+
+    let mut first = 0;
+    for (r, ring) in rings.iter_mut().enumerate() {
+        let n = vertices[first..].iter().take_while(|v| v.ring == r).count();
+        if lengths[r].is_some() {
+            let mut kept = vertices[first..first + n].iter().filter(|v| !v.removed).map(|v| ring[v.index].clone()).collect::<Vec<_>>();
+            kept.push(kept[0].clone());
+            **ring = kept;
+        }
+        first += n;
+    }
+}
+
+// The positions of the vertices, bucketed in a grid of about one position per cell
+// (over their bounding box) to find the ones in a triangle.
+struct Grid {
+    points: Vec<[f64; 2]>,
+    min: [f64; 2],
+    size: f64,
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    fn new(points: Vec<[f64; 2]>) -> Self {
+        let finite = points.iter().filter(|p| p[0].is_finite() && p[1].is_finite());
+        let (min, max) = finite.fold(([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]), |(min, max), p| {
+            ([min[0].min(p[0]), min[1].min(p[1])], [max[0].max(p[0]), max[1].max(p[1])])
+        });
+        let extent = (max[0] - min[0]).max(max[1] - min[1]);
+        let size = if extent > 0. { extent / (points.len() as f64).sqrt().ceil() } else { 1. };
+        let (columns, rows) = if min[0] <= max[0] {
+            (((max[0] - min[0]) / size) as usize + 1, ((max[1] - min[1]) / size) as usize + 1)
+        } else {
+            (0, 0)
+        };
+        let mut grid = Grid { points, min, size, columns, rows, cells: vec![Vec::new(); columns * rows] };
+        for (i, p) in grid.points.iter().enumerate() {
+            if let Some((column, row)) = grid.cell(p) {
+                grid.cells[row * grid.columns + column].push(i);
+            }
+        }
+        grid
+    }
+
+    // The cell of the position, if it's finite.
+    fn cell(&self, p: &[f64; 2]) -> Option<(usize, usize)> {
+        if !(p[0].is_finite() && p[1].is_finite()) || self.columns == 0 {
+            return None;
+        }
+        let column = (((p[0] - self.min[0]) / self.size) as usize).min(self.columns - 1);
+        let row = (((p[1] - self.min[1]) / self.size) as usize).min(self.rows - 1);
+        Some((column, row))
+    }
+
+    // Whether one of the positions passing `filter` lies in the triangle `t` (or on its sides).
+    fn any_in_triangle<F: Fn(usize) -> bool>(&self, t: &[[f64; 2]; 3], filter: F) -> bool {
+        let low = [t[0][0].min(t[1][0]).min(t[2][0]), t[0][1].min(t[1][1]).min(t[2][1])];
+        let high = [t[0][0].max(t[1][0]).max(t[2][0]), t[0][1].max(t[1][1]).max(t[2][1])];
+        let (start, end) = match (self.cell(&low), self.cell(&high)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return false,
+        };
+        (start.1..=end.1).flat_map(|row| (start.0..=end.0).map(move |column| row * self.columns + column)).any(|cell| {
+            self.cells[cell].iter().any(|j| {
+                let p = &self.points[*j];
+                p[0] >= low[0] && p[0] <= high[0] && p[1] >= low[1] && p[1] <= high[1] && in_triangle(t, p) && filter(*j)
+            })
+        })
+    }
+}
+
+// Whether `p` lies in the triangle `t` (or on its sides), with exact orientation predicates.
+fn in_triangle(t: &[[f64; 2]; 3], p: &[f64; 2]) -> bool {
+    let d = [orient2d(&t[0], &t[1], p), orient2d(&t[1], &t[2], p), orient2d(&t[2], &t[0], p)];
+    if orient2d(&t[0], &t[1], &t[2]) == 0. {
+        // A flat triangle: `p` has to lie on one of its two first sides.
+        let on = |a: &[f64; 2], b: &[f64; 2], o: f64| o == 0. && p[0] >= a[0].min(b[0]) && p[0] <= a[0].max(b[0]) && p[1] >= a[1].min(b[1]) && p[1] <= a[1].max(b[1]);
+        return on(&t[0], &t[1], d[0]) || on(&t[1], &t[2], d[1]);
+    }
+    !(d.iter().any(|o| *o < 0.) && d.iter().any(|o| *o > 0.))
+}
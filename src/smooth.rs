@@ -0,0 +1,175 @@
+//! The placement of the positions of the rings along the edges of the grid when smoothing
+//! them (see [`ContourBuilder::smoother`]).
+//!
+//! The rings are first computed with their positions in the middle of the edges between
+//! the centers of two cells (at `x` integer and `y + 0.5`, or at `x + 0.5` and `y` integer,
+//! the center of the cell at column `x` and row `y` being at `(x + 0.5, y + 0.5)`), then
+//! a [`VertexSmoother`] moves them along their edge. The positions on the border of the grid
+//! are on no edge between two cells.
+//!
+//! ```
+//! # use contour::ContourBuilder;
+//! # use contour::smooth::{GridView, VertexSmoother};
+//! # use contour::Ring;
+//! // Moves the positions halfway between the middle of their edge and the linear interpolation.
+//! struct Halfway;
+//!
+//! impl VertexSmoother for Halfway {
+//!     fn smooth(&self, ring: &mut Ring, grid: &GridView, threshold: f64) {
+//!         let middle = ring.clone();
+//!         contour::smooth::LinearSmoother.smooth(ring, grid, threshold);
+//!         for (point, middle) in ring.iter_points_mut().zip(middle.iter_points()) {
+//!             point[0] = (point[0] + middle[0]) / 2.;
+//!             point[1] = (point[1] + middle[1]) / 2.;
+//!         }
+//!     }
+//! }
+//!
+//! let values = [0., 0., 0., 0., 1., 0., 0., 0., 0.];
+//! let c = ContourBuilder::new(3, 3, true).smoother(Halfway);
+//! let features = c.contours(&values, &[0.75])?;
+//! assert_eq!(features.len(), 1);
+//! # Ok::<(), contour::Error>(())
+//! ```
+//!
+//! [`ContourBuilder::smoother`]: ../struct.ContourBuilder.html#method.smoother
+//! [`VertexSmoother`]: trait.VertexSmoother.html
+
+use crate::contour::{Pt, check_dimensions};
+use crate::error::Result;
+use crate::ring::Ring;
+use std::fmt;
+
+/// Moves the positions of the rings of a threshold when smoothing them.
+pub trait VertexSmoother: Send + Sync {
+    /// Moves the positions of `ring`, the contour of `threshold` on `grid`, in grid
+    /// coordinates (see the [module documentation](index.html)). The ring may be
+    /// closed, in which case its last position should stay equal to its first one.
+    fn smooth(&self, ring: &mut Ring, grid: &GridView, threshold: f64);
+}
+
+/// The default smoother: the positions are moved to where the values, linearly interpolated
+/// between the centers of the cells of their edge, cross the threshold. The positions next
+/// to an infinite value are left in the middle of their edge.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinearSmoother;
+
+impl VertexSmoother for LinearSmoother {
+    fn smooth(&self, ring: &mut Ring, grid: &GridView, threshold: f64) {
+        smooth_linear(ring.iter_points_mut(), grid, threshold);
+    }
+}
+
+/// A read-only view of the values of a grid of `dx` columns and `dy` rows, in row-major order.
+pub struct GridView<'a> {
+    values: Values<'a>,
+    dx: u32,
+    dy: u32,
+}
+
+enum Values<'a> {
+    Slice(&'a [f64]),
+    Fn(&'a dyn Fn(usize) -> f64),
+}
+
+impl<'a> GridView<'a> {
+    /// Constructs a view of the given `values`, failing if their number isn't `dx * dy`.
+    pub fn new(values: &'a [f64], dx: u32, dy: u32) -> Result<Self> {
+        check_dimensions(dx, dy, values.len())?;
+        Ok(GridView {
+            values: Values::Slice(values),
+            dx,
+            dy,
+        })
+    }
+
+    pub(crate) fn from_fn(values: &'a dyn Fn(usize) -> f64, dx: u32, dy: u32) -> Self {
+        GridView {
+            values: Values::Fn(values),
+            dx,
+            dy,
+        }
+    }
+
+    /// Returns the number of columns of the grid.
+    pub fn dx(&self) -> u32 {
+        self.dx
+    }
+
+    /// Returns the number of rows of the grid.
+    pub fn dy(&self) -> u32 {
+        self.dy
+    }
+
+    /// Returns the value at the given column and row, or `None` outside of the grid.
+    pub fn get(&self, x: u32, y: u32) -> Option<f64> {
+        if x < self.dx && y < self.dy {
+            self.value(y as usize * self.dx as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value at the given (row-major) index, or `None` outside of the grid.
+    pub fn value(&self, i: usize) -> Option<f64> {
+        if i >= self.dx as usize * self.dy as usize {
+            return None;
+        }
+        match self.values {
+            Values::Slice(values) => values.get(i).copied(),
+            Values::Fn(values) => Some(values(i)),
+        }
+    }
+}
+
+impl fmt::Debug for GridView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("GridView").field("dx", &self.dx).field("dy", &self.dy).finish()
+    }
+}
+
+// Moves the positions, in the middle of the edges of the grid, to where the values
+// linearly interpolated along the edges cross the threshold.
+pub(crate) fn smooth_linear<'p, I: Iterator<Item = &'p mut Pt>>(points: I, grid: &GridView, value: f64) {
+    let (dx, dy) = (grid.dx as usize, grid.dy as usize);
+    for point in points {
+        let x = point[0];
+        let y = point[1];
+        let xt = x.trunc() as usize;
+        let yt = y.trunc() as usize;
+        if let Some(v1) = grid.value(yt * dx + xt) {
+            // Like on the border of the grid, the positions next to an infinite
+            // value are left in the middle of their edge.
+            if x > 0.0 && x < (dx as f64) && (xt as f64 - x).abs() < f64::EPSILON {
+                if let Some(v0) = grid.value(yt * dx + xt - 1) {
+                    if !v0.is_infinite() && !v1.is_infinite() {
+                        point[0] = interpolate(x, v0, v1, value);
+                    }
+                }
+            }
+            if y > 0.0 && y < (dy as f64) && (yt as f64 - y).abs() < f64::EPSILON {
+                if let Some(v0) = grid.value((yt - 1) * dx + xt) {
+                    if !v0.is_infinite() && !v1.is_infinite() {
+                        point[1] = interpolate(y, v0, v1, value);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The position where the values `v0`, at the center `x - 0.5` of a cell, and `v1`, at the
+// center `x + 0.5` of the next one, linearly interpolated, cross the threshold `value`.
+// Only the fraction depends on the threshold: it's monotonic in the threshold, and the
+// thresholds equal to `v0` or `v1` land exactly on the centers, so that the contours of
+// different thresholds are nested and share the exact coordinates of the centers.
+fn interpolate(x: f64, v0: f64, v1: f64, value: f64) -> f64 {
+    let t = if value == v0 {
+        0.
+    } else if value == v1 {
+        1.
+    } else {
+        (value - v0) / (v1 - v0)
+    };
+    x + t - 0.5
+}
@@ -0,0 +1,54 @@
+//! Grids whose values are read by index, possibly converted on the fly, rather than
+//! stored in a slice of `f64` (see [`ContourBuilder::contours_source`]).
+//!
+//! Slices of `f32` values, and of `half::f16` ones with the `f16` feature, are converted
+//! to `f64` value by value, NaN values staying NaN (below every threshold).
+//!
+//! [`ContourBuilder::contours_source`]: ../struct.ContourBuilder.html#method.contours_source
+
+/// A grid of values, in row-major order.
+pub trait GridSource: Sync {
+    /// Returns the number of values of the grid.
+    fn len(&self) -> usize;
+
+    /// Returns whether the grid has no value.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the value at the given (row-major) index, which is less than [`len`].
+    ///
+    /// [`len`]: #tymethod.len
+    fn value(&self, i: usize) -> f64;
+}
+
+impl GridSource for [f64] {
+    fn len(&self) -> usize {
+        <[f64]>::len(self)
+    }
+
+    fn value(&self, i: usize) -> f64 {
+        self[i]
+    }
+}
+
+impl GridSource for [f32] {
+    fn len(&self) -> usize {
+        <[f32]>::len(self)
+    }
+
+    fn value(&self, i: usize) -> f64 {
+        f64::from(self[i])
+    }
+}
+
+#[cfg(feature = "f16")]
+impl GridSource for [half::f16] {
+    fn len(&self) -> usize {
+        <[half::f16]>::len(self)
+    }
+
+    fn value(&self, i: usize) -> f64 {
+        f64::from(self[i])
+    }
+}
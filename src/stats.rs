@@ -0,0 +1,216 @@
+use std::fmt;
+
+/// Statistics of the finite values of a grid (`NaN` and infinite values being ignored),
+/// to help choosing thresholds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GridStats {
+    /// The number of finite values.
+    pub count: usize,
+    /// The lowest finite value (`None` when there is no finite value).
+    pub min: Option<f64>,
+    /// The highest finite value.
+    pub max: Option<f64>,
+    /// The mean of the finite values.
+    pub mean: Option<f64>,
+    /// The (population) standard deviation of the finite values.
+    pub std_dev: Option<f64>,
+}
+
+impl GridStats {
+    /// Computes the statistics of the given `values`.
+    pub fn compute(values: &[f64]) -> Self {
+        let mut stats = GridStats::default();
+        // Welford's algorithm, for the mean and the sum of the squared deviations.
+        let (mut mean, mut m2) = (0., 0.);
+        for v in values.iter().cloned().filter(|v| v.is_finite()) {
+            stats.count += 1;
+            stats.min = Some(stats.min.map_or(v, |min| min.min(v)));
+            stats.max = Some(stats.max.map_or(v, |max| max.max(v)));
+            let delta = v - mean;
+            mean += delta / stats.count as f64;
+            m2 += delta * (v - mean);
+        }
+        if stats.count > 0 {
+            stats.mean = Some(mean);
+            stats.std_dev = Some((m2 / stats.count as f64).sqrt());
+        }
+        stats
+    }
+
+    /// Counts the finite `values` (the ones these statistics were computed from)
+    /// in `bins` bins of equal width between the minimum and the maximum, the maximum
+    /// being counted in the last bin. All the values are counted in the first bin
+    /// when they are equal; the bins are empty when there is no finite value.
+    pub fn histogram(&self, values: &[f64], bins: usize) -> Vec<usize> {
+        let mut histogram = vec![0; bins];
+        if let (Some(min), Some(max), true) = (self.min, self.max, bins > 0) {
+            let width = (max - min) / bins as f64;
+            for v in values.iter().filter(|v| v.is_finite()) {
+                let bin = if width > 0. { ((v - min) / width) as usize } else { 0 };
+                histogram[bin.min(bins - 1)] += 1;
+            }
+        }
+        histogram
+    }
+}
+
+/// Diagnostics of the computation of the contours of one threshold, as returned by
+/// [`ContourBuilder::contours_with_diagnostics`].
+///
+/// [`ContourBuilder::contours_with_diagnostics`]: struct.ContourBuilder.html#method.contours_with_diagnostics
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ThresholdDiagnostics {
+    /// The threshold.
+    pub threshold: f64,
+    /// The number of rings stitched.
+    pub rings: usize,
+    /// The number of exterior rings (one per polygon).
+    pub shells: usize,
+    /// The number of holes, in the exterior ring of a polygon.
+    pub holes: usize,
+    /// The number of holes dropped because they are in no exterior ring.
+    pub orphans: usize,
+    /// The number of rings dropped by the reprojection (left with less than 3 distinct
+    /// positions) and the clipping.
+    pub dropped: usize,
+    /// The number of positions of the polygons of the output.
+    pub vertices: usize,
+    /// The number of smoothed rings moved back to the grid edges to stay valid
+    /// (see [`Validation::Repair`]).
+    ///
+    /// [`Validation::Repair`]: enum.Validation.html#variant.Repair
+    pub repaired_rings: usize,
+}
+
+/// Computes the area of the cells whose value is greater than or equal to each of the
+/// `thresholds` (a hypsometric curve), each cell covering `cell_area`, without computing
+/// any contour. The `NaN` cells are excluded.
+///
+/// ```
+/// # use contour::area_above_thresholds;
+/// let values = [0., 1., 2., f64::NAN, 3., 4.];
+/// assert_eq!(area_above_thresholds(&values, &[0.5, 2., 5.], 10.), [40., 30., 0.]);
+/// ```
+///
+/// Use [`ContourBuilder::area`] to get the area of the polygons of the contours instead.
+///
+/// [`ContourBuilder::area`]: struct.ContourBuilder.html#method.area
+pub fn area_above_thresholds(values: &[f64], thresholds: &[f64], cell_area: f64) -> Vec<f64> {
+    let mut sorted = values.iter().cloned().filter(|v| !v.is_nan()).collect::<Vec<_>>();
+    sorted.sort_unstable_by(f64::total_cmp);
+    thresholds
+        .iter()
+        .map(|threshold| {
+            let count = if threshold.is_nan() { 0 } else { sorted.len() - sorted.partition_point(|v| v < threshold) };
+            count as f64 * cell_area
+        })
+        .collect()
+}
+
+/// The number of offending indexes reported by a [`ValidationReport`] for each kind of value.
+///
+/// [`ValidationReport`]: struct.ValidationReport.html
+pub const MAX_REPORTED_INDICES: usize = 10;
+
+/// A report on the input values of a grid, as returned by
+/// [`ContourBuilder::validate_input`], to find out why they fail to contour sensibly.
+///
+/// [`ContourBuilder::validate_input`]: struct.ContourBuilder.html#method.validate_input
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    /// The number of values.
+    pub len: usize,
+    /// The number of values of the grid, `dx * dy` (`None` when it overflows).
+    pub expected_len: Option<usize>,
+    /// The number of NaN values.
+    pub nan_count: usize,
+    /// The indexes of the first NaN values (at most [`MAX_REPORTED_INDICES`]).
+    ///
+    /// [`MAX_REPORTED_INDICES`]: constant.MAX_REPORTED_INDICES.html
+    pub nan_indices: Vec<usize>,
+    /// The number of infinite values (positive or negative).
+    pub infinite_count: usize,
+    /// The indexes of the first infinite values (at most [`MAX_REPORTED_INDICES`]).
+    ///
+    /// [`MAX_REPORTED_INDICES`]: constant.MAX_REPORTED_INDICES.html
+    pub infinite_indices: Vec<usize>,
+    /// Whether all the finite values are equal (there being at least one), giving
+    /// no contour but the whole grid or nothing.
+    pub constant: bool,
+    /// The lowest finite value (`None` when there is no finite value).
+    pub min: Option<f64>,
+    /// The highest finite value.
+    pub max: Option<f64>,
+}
+
+impl ValidationReport {
+    /// Checks the given `values` of a grid of `dx` columns and `dy` rows.
+    pub fn compute(values: &[f64], dx: u32, dy: u32) -> Self {
+        let mut report = ValidationReport {
+            len: values.len(),
+            expected_len: (dx as usize).checked_mul(dy as usize),
+            ..ValidationReport::default()
+        };
+        for (i, v) in values.iter().enumerate() {
+            if v.is_nan() {
+                report.nan_count += 1;
+                if report.nan_indices.len() < MAX_REPORTED_INDICES {
+                    report.nan_indices.push(i);
+                }
+            } else if v.is_infinite() {
+                report.infinite_count += 1;
+                if report.infinite_indices.len() < MAX_REPORTED_INDICES {
+                    report.infinite_indices.push(i);
+                }
+            } else {
+                report.min = Some(report.min.map_or(*v, |min| min.min(*v)));
+                report.max = Some(report.max.map_or(*v, |max| max.max(*v)));
+            }
+        }
+        report.constant = report.min.is_some() && report.min == report.max;
+        report
+    }
+
+    /// Whether the length of the values matches the dimensions of the grid.
+    pub fn has_valid_len(&self) -> bool {
+        self.expected_len == Some(self.len)
+    }
+
+    /// Whether no problem was found: the length of the values matches the dimensions
+    /// of the grid, they are all finite and they aren't all equal. An empty grid is valid.
+    pub fn is_valid(&self) -> bool {
+        self.has_valid_len() && self.nan_count == 0 && self.infinite_count == 0 && !self.constant
+    }
+}
+
+/// Lists the problems found, separated by semicolons.
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut problems = Vec::new();
+        if !self.has_valid_len() {
+            match self.expected_len {
+                Some(expected) => problems.push(format!("{} values instead of {}", self.len, expected)),
+                None => problems.push(format!("{} values for a grid too large", self.len)),
+            }
+        }
+        if self.nan_count > 0 {
+            problems.push(format!("{} NaN values (at indexes {:?}{})", self.nan_count, self.nan_indices, if self.nan_count > self.nan_indices.len() { "..." } else { "" }));
+        }
+        if self.infinite_count > 0 {
+            problems.push(format!(
+                "{} infinite values (at indexes {:?}{})",
+                self.infinite_count,
+                self.infinite_indices,
+                if self.infinite_count > self.infinite_indices.len() { "..." } else { "" }
+            ));
+        }
+        if let (true, Some(min)) = (self.constant, self.min) {
+            problems.push(format!("constant grid (all the finite values are {})", min));
+        }
+        if problems.is_empty() {
+            write!(f, "no problem found")
+        } else {
+            write!(f, "{}", problems.join("; "))
+        }
+    }
+}
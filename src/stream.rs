@@ -0,0 +1,45 @@
+//! Streaming of the contours of the thresholds as they're computed, for async code such
+//! as web services (only available with the `stream` feature, relying on tokio).
+//!
+//! A [`ContourStream`] is returned by [`ContourBuilder::contours_stream`]; it can be polled
+//! as a `futures_core::Stream` or with its own [`next`] method.
+//!
+//! [`ContourStream`]: struct.ContourStream.html
+//! [`next`]: struct.ContourStream.html#method.next
+//! [`ContourBuilder::contours_stream`]: ../struct.ContourBuilder.html#method.contours_stream
+
+use crate::error::Result;
+use futures_core::Stream;
+use geojson::Feature;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc::Receiver;
+
+/// The Features of the thresholds given to [`ContourBuilder::contours_stream`], in the order
+/// of the thresholds, the stream ending after the last one or after the first error.
+/// Dropping it stops the computation of the thresholds.
+///
+/// [`ContourBuilder::contours_stream`]: ../struct.ContourBuilder.html#method.contours_stream
+#[derive(Debug)]
+pub struct ContourStream {
+    receiver: Receiver<Result<Feature>>,
+}
+
+impl ContourStream {
+    pub(crate) fn new(receiver: Receiver<Result<Feature>>) -> Self {
+        ContourStream { receiver }
+    }
+
+    /// Waits for the Feature of the next threshold, returning `None` at the end of the stream.
+    pub async fn next(&mut self) -> Option<Result<Feature>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for ContourStream {
+    type Item = Result<Feature>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
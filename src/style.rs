@@ -0,0 +1,196 @@
+//! Styling of the contours with the properties of the
+//! [simplestyle-spec](https://github.com/mapbox/simplestyle-spec) (`fill`, `fill-opacity`,
+//! `stroke` and `stroke-width`), understood by geojson.io, Leaflet or Mapbox renderers.
+//!
+//! The colors are picked in a [`Colormap`], the lowest threshold getting its first color
+//! and the highest threshold its last one. The built-in colormaps are only available with
+//! the `colormaps` feature.
+//!
+//! [`Colormap`]: enum.Colormap.html
+
+use crate::plot::Rgb;
+use geojson::Feature;
+use serde_json::json;
+use serde_json::map::Map;
+use std::fmt;
+use std::sync::Arc;
+
+/// Viridis, sampled every tenth (from matplotlib).
+#[cfg(feature = "colormaps")]
+const VIRIDIS: [Rgb; 11] = [
+    (68, 1, 84),
+    (72, 36, 117),
+    (65, 68, 135),
+    (53, 95, 141),
+    (42, 120, 142),
+    (33, 145, 140),
+    (34, 168, 132),
+    (68, 191, 112),
+    (122, 209, 81),
+    (189, 223, 38),
+    (253, 231, 37),
+];
+
+/// Turbo, sampled every tenth (from d3's polynomial approximation).
+#[cfg(feature = "colormaps")]
+const TURBO: [Rgb; 11] = [
+    (35, 23, 27),
+    (74, 88, 221),
+    (47, 157, 245),
+    (39, 215, 196),
+    (77, 248, 132),
+    (149, 251, 81),
+    (222, 221, 50),
+    (255, 164, 35),
+    (246, 95, 24),
+    (186, 34, 8),
+    (144, 12, 0),
+];
+
+/// A colormap, giving the color at a position from `0` to `1`.
+#[derive(Clone)]
+pub enum Colormap {
+    /// The perceptually uniform Viridis colormap, from dark purple to yellow.
+    #[cfg(feature = "colormaps")]
+    Viridis,
+    /// The Turbo colormap, a rainbow from dark blue to dark red.
+    #[cfg(feature = "colormaps")]
+    Turbo,
+    /// A user colormap (see [`Colormap::custom`]).
+    ///
+    /// [`Colormap::custom`]: #method.custom
+    Custom(Arc<dyn Fn(f64) -> Rgb + Send + Sync>),
+}
+
+impl Colormap {
+    /// A colormap calling `f` with the position (clamped from `0` to `1`).
+    pub fn custom<F: Fn(f64) -> Rgb + Send + Sync + 'static>(f: F) -> Self {
+        Colormap::Custom(Arc::new(f))
+    }
+
+    /// The color at the position `t`, clamped from `0` to `1` (a NaN position
+    /// giving the first color).
+    pub fn color(&self, t: f64) -> Rgb {
+        let t = if t.is_nan() { 0. } else { t.clamp(0., 1.) };
+        match self {
+            #[cfg(feature = "colormaps")]
+            Colormap::Viridis => interpolate(&VIRIDIS, t),
+            #[cfg(feature = "colormaps")]
+            Colormap::Turbo => interpolate(&TURBO, t),
+            Colormap::Custom(f) => f(t),
+        }
+    }
+}
+
+impl PartialEq for Colormap {
+    // The custom colormaps are compared by identity.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            #[cfg(feature = "colormaps")]
+            (Colormap::Viridis, Colormap::Viridis) | (Colormap::Turbo, Colormap::Turbo) => true,
+            (Colormap::Custom(a), Colormap::Custom(b)) => Arc::as_ptr(a) as *const () == Arc::as_ptr(b) as *const (),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Debug for Colormap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "colormaps")]
+            Colormap::Viridis => write!(f, "Viridis"),
+            #[cfg(feature = "colormaps")]
+            Colormap::Turbo => write!(f, "Turbo"),
+            Colormap::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// The simplestyle properties written by [`apply_simplestyle`] (and
+/// [`ContourBuilder::simplestyle`]).
+///
+/// [`apply_simplestyle`]: fn.apply_simplestyle.html
+/// [`ContourBuilder::simplestyle`]: ../struct.ContourBuilder.html#method.simplestyle
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimpleStyle {
+    /// The colormap giving the `fill` color of each threshold.
+    pub colormap: Colormap,
+    /// The `fill-opacity` (default: `0.6`).
+    pub fill_opacity: f64,
+    /// The `stroke` color, the `fill` color when `None` (default: `None`).
+    pub stroke: Option<Rgb>,
+    /// The `stroke-width` (default: `1`).
+    pub stroke_width: f64,
+}
+
+impl SimpleStyle {
+    /// A style filling the polygons with the colors of `colormap`, with the default options.
+    pub fn new(colormap: Colormap) -> Self {
+        SimpleStyle {
+            colormap,
+            fill_opacity: 0.6,
+            stroke: None,
+            stroke_width: 1.,
+        }
+    }
+}
+
+/// Writes the `fill`, `fill-opacity`, `stroke` and `stroke-width` properties of the
+/// given `features` (as returned by [`ContourBuilder::contours`] or
+/// [`ContourBuilder::contours_exploded`]), the colors being picked in the colormap
+/// of `style` according to the position of their `value` property between the lowest
+/// and the highest ones. The features without `value` property are left unchanged.
+///
+/// ```
+/// # use contour::ContourBuilder;
+/// # use contour::style::{Colormap, SimpleStyle, apply_simplestyle};
+/// let mut features = ContourBuilder::new(2, 2, false).contours(&[0., 1., 2., 3.], &[1., 2.])?;
+/// let colormap = Colormap::custom(|t| ((t * 255.) as u8, 0, 0));
+/// apply_simplestyle(&mut features, &SimpleStyle::new(colormap));
+/// assert_eq!(features[1].properties.as_ref().unwrap()["fill"], "#ff0000");
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+/// [`ContourBuilder::contours_exploded`]: ../struct.ContourBuilder.html#method.contours_exploded
+pub fn apply_simplestyle(features: &mut [Feature], style: &SimpleStyle) {
+    let range = features
+        .iter()
+        .filter_map(|feature| value(feature.properties.as_ref()?))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)));
+    apply_simplestyle_over(features, style, range);
+}
+
+// Writes the simplestyle properties like `apply_simplestyle` does, the colors being
+// spread over the given range of values instead of the one of the features.
+pub(crate) fn apply_simplestyle_over(features: &mut [Feature], style: &SimpleStyle, (min, max): (f64, f64)) {
+    for properties in features.iter_mut().filter_map(|feature| feature.properties.as_mut()) {
+        if let Some(v) = value(properties) {
+            let fill = style.colormap.color(if max > min { (v - min) / (max - min) } else { 0. });
+            properties.insert(String::from("fill"), json!(hex(fill)));
+            properties.insert(String::from("fill-opacity"), json!(style.fill_opacity));
+            properties.insert(String::from("stroke"), json!(hex(style.stroke.unwrap_or(fill))));
+            properties.insert(String::from("stroke-width"), json!(style.stroke_width));
+        }
+    }
+}
+
+/// Formats the color as `#rrggbb`.
+pub fn hex(color: Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.0, color.1, color.2)
+}
+
+fn value(properties: &Map<String, serde_json::Value>) -> Option<f64> {
+    properties.get("value")?.as_f64()
+}
+
+// Picks the color at `t` (between 0 and 1) in the given colormap.
+#[cfg(feature = "colormaps")]
+fn interpolate(colors: &[Rgb], t: f64) -> Rgb {
+    let t = t * (colors.len() - 1) as f64;
+    let i = (t.floor() as usize).min(colors.len() - 2);
+    let (a, b, f) = (colors[i], colors[i + 1], t - i as f64);
+    let mix = |a: u8, b: u8| (f64::from(a) + (f64::from(b) - f64::from(a)) * f).round() as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
@@ -0,0 +1,134 @@
+//! Output of the contours as a standalone SVG document (see [`ContourBuilder::render_svg`]).
+//!
+//! Each threshold is written as a single `<path>` element, its holes being subpaths filled
+//! with the `evenodd` fill rule (see the [`path`] module), the lowest threshold first so that
+//! the higher ones are drawn on top of it.
+//!
+//! [`ContourBuilder::render_svg`]: ../struct.ContourBuilder.html#method.render_svg
+//! [`path`]: ../path/index.html
+
+use crate::histogram::Extent;
+use crate::path::{PathMode, SvgPathData, feature_to_path};
+use crate::plot::Rgb;
+use crate::style::hex;
+use geojson::Feature;
+use std::fmt::Write as _;
+use std::io;
+
+/// Options of the SVG output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgStyle {
+    /// The fill colors of the thresholds, in order, the palette being spread over the
+    /// thresholds when it has fewer colors than there are thresholds (default: from
+    /// white to black). The polygons aren't filled when it's empty.
+    pub palette: Vec<Rgb>,
+    /// The opacity of the fills (default: `1`).
+    pub fill_opacity: f64,
+    /// The color of the isolines stroked along the rings, if any (default: `None`).
+    pub stroke: Option<Rgb>,
+    /// The width of the isolines, in pixels whatever the size of the image (default: `1`).
+    pub stroke_width: f64,
+    /// The color of a rectangle drawn behind the contours over the whole image, if any
+    /// (default: `None`).
+    pub background: Option<Rgb>,
+    /// The number of decimals of the coordinates (all the significant digits when `None`,
+    /// default: `Some(3)`).
+    pub precision: Option<usize>,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        SvgStyle {
+            palette: vec![(255, 255, 255), (0, 0, 0)],
+            fill_opacity: 1.,
+            stroke: None,
+            stroke_width: 1.,
+            background: None,
+            precision: Some(3),
+        }
+    }
+}
+
+/// Converts the given `features` (as returned by [`ContourBuilder::contours`]) to an SVG
+/// document whose `viewBox` is `extent`, with one `<path>` per Feature with a non-empty
+/// geometry, carrying its `value` property in a `data-value` attribute.
+///
+/// With `y_up`, the y axis of the coordinates is taken as pointing up (as with a negative
+/// [`ContourBuilder::y_step`]) and flipped, the y axis of SVG pointing down.
+///
+/// ```
+/// # use contour::ContourBuilder;
+/// # use contour::histogram::Extent;
+/// # use contour::svg::{SvgStyle, to_svg};
+/// let features = ContourBuilder::new(3, 3, false).contours(&[0., 0., 0., 0., 1., 0., 0., 0., 0.], &[0.5])?;
+/// let extent = Extent { x_min: 0., y_min: 0., x_max: 3., y_max: 3. };
+/// let svg = to_svg(&features, extent, false, &SvgStyle::default());
+/// assert!(svg.contains(r#"viewBox="0 0 3 3""#));
+/// assert_eq!(svg.matches("<path ").count(), 1);
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+/// [`ContourBuilder::y_step`]: ../struct.ContourBuilder.html#method.y_step
+pub fn to_svg(features: &[Feature], extent: Extent, y_up: bool, style: &SvgStyle) -> String {
+    let Extent { x_min, y_min, x_max, y_max } = extent;
+    let (width, height) = (x_max - x_min, y_max - y_min);
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        x_min, y_min, width, height, width, height
+    )
+    .unwrap();
+    if let Some(background) = style.background {
+        writeln!(out, r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#, x_min, y_min, width, height, hex(background)).unwrap();
+    }
+    if y_up {
+        // Mirrors the y axis around the middle of the extent.
+        writeln!(out, r#"  <g transform="matrix(1 0 0 -1 0 {})">"#, y_min + y_max).unwrap();
+    } else {
+        out.push_str("  <g>\n");
+    }
+    let paths = features
+        .iter()
+        .map(|feature| {
+            let mut path = SvgPathData {
+                data: String::new(),
+                precision: style.precision,
+            };
+            feature_to_path(feature, &mut path, PathMode::Fill);
+            (feature, path.data)
+        })
+        .collect::<Vec<_>>();
+    let n = paths.len();
+    for (i, (feature, data)) in paths.iter().enumerate() {
+        if data.is_empty() {
+            continue;
+        }
+        let fill = match style.palette.len() {
+            0 => String::from("none"),
+            len => hex(style.palette[i * len.min(n) / n]),
+        };
+        write!(out, r#"    <path d="{}" fill="{}" fill-rule="evenodd""#, data, fill).unwrap();
+        if style.fill_opacity != 1. {
+            write!(out, r#" fill-opacity="{}""#, style.fill_opacity).unwrap();
+        }
+        match style.stroke {
+            Some(stroke) => write!(out, r#" stroke="{}" stroke-width="{}" vector-effect="non-scaling-stroke""#, hex(stroke), style.stroke_width).unwrap(),
+            None => out.push_str(r#" stroke="none""#),
+        }
+        if let Some(value) = feature.properties.as_ref().and_then(|properties| properties.get("value")?.as_f64()) {
+            write!(out, r#" data-value="{}""#, value).unwrap();
+        }
+        out.push_str("/>\n");
+    }
+    out.push_str("  </g>\n</svg>\n");
+    out
+}
+
+/// Writes the SVG document of the given `features` (see [`to_svg`]) to `writer`.
+///
+/// [`to_svg`]: fn.to_svg.html
+pub fn write_svg<W: io::Write>(mut writer: W, features: &[Feature], extent: Extent, y_up: bool, style: &SvgStyle) -> io::Result<()> {
+    writer.write_all(to_svg(features, extent, y_up, style).as_bytes())
+}
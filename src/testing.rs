@@ -0,0 +1,276 @@
+//! Approximate comparison of contours, for regression tests pinning the output of the
+//! crate without depending on the exact floating point values of the positions (which
+//! change slightly with the smoothing or the order of the operations).
+//!
+//! The Features are matched by threshold (their `value` property), the polygons of each
+//! threshold by the proximity of their exterior rings (centroid and area), and the holes
+//! of each polygon likewise. The rings are compared as cyclic sequences of positions:
+//! they may start at different positions, but must have the same orientation.
+//!
+//! [`noise`] generates noisy grids, giving many small rings, for stress tests and benchmarks,
+//! with the [`XorShift`] generator of the randomized tests.
+//!
+//! [`noise`]: fn.noise.html
+//! [`XorShift`]: struct.XorShift.html
+
+use crate::contour::{LinearRing, Pt};
+use crate::geom::area;
+use geojson::{Feature, Value};
+use std::fmt;
+
+/// The first difference found by [`contours_approx_eq`] between two lists of Features.
+///
+/// The indexes are the ones of the first list: `polygon` is the index of the polygon
+/// among the ones of all the Features of the threshold, `ring` the index of the ring in
+/// the polygon (`0` for its exterior ring) and `vertex` the index of the position
+/// in the ring.
+///
+/// [`contours_approx_eq`]: fn.contours_approx_eq.html
+#[derive(Clone, Debug, PartialEq)]
+pub enum Difference {
+    /// The thresholds of the Features differ (listed in the order of their first Feature).
+    Thresholds { first: Vec<f64>, second: Vec<f64> },
+    /// The numbers of polygons of a threshold differ.
+    PolygonCount { threshold: f64, first: usize, second: usize },
+    /// The numbers of rings of a polygon differ.
+    RingCount { threshold: f64, polygon: usize, first: usize, second: usize },
+    /// The numbers of positions of a ring differ (the closing position left out).
+    RingLength { threshold: f64, polygon: usize, ring: usize, first: usize, second: usize },
+    /// A position of a ring is farther than the tolerance from the matching position
+    /// of the other ring (the first divergent position, with the rotation of the other
+    /// ring matching the most positions).
+    Vertex { threshold: f64, polygon: usize, ring: usize, vertex: usize, first: Pt, second: Pt },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difference::Thresholds { first, second } => write!(f, "thresholds differ: {:?} != {:?}", first, second),
+            Difference::PolygonCount { threshold, first, second } => {
+                write!(f, "threshold {}: {} polygons != {} polygons", threshold, first, second)
+            }
+            Difference::RingCount { threshold, polygon, first, second } => {
+                write!(f, "threshold {}, polygon {}: {} rings != {} rings", threshold, polygon, first, second)
+            }
+            Difference::RingLength { threshold, polygon, ring, first, second } => {
+                write!(f, "threshold {}, polygon {}, ring {}: {} positions != {} positions", threshold, polygon, ring, first, second)
+            }
+            Difference::Vertex { threshold, polygon, ring, vertex, first, second } => write!(
+                f,
+                "threshold {}, polygon {}, ring {}, position {}: {:?} != {:?}",
+                threshold, polygon, ring, vertex, first, second
+            ),
+        }
+    }
+}
+
+/// Compares the given lists of Features (as returned by [`ContourBuilder::contours`] or
+/// [`ContourBuilder::contours_exploded`]), their positions being equal when none of their
+/// coordinates differ by more than `tolerance`. Returns the first [`Difference`] found,
+/// if any.
+///
+/// ```
+/// # use contour::ContourBuilder;
+/// # use contour::testing::contours_approx_eq;
+/// let values = [0., 1., 2., 3., 4., 5., 6., 7., 8.];
+/// let a = ContourBuilder::new(3, 3, true).contours(&values, &[2.5])?;
+/// let b = ContourBuilder::new(3, 3, true).x_step(1. + 1e-12).contours(&values, &[2.5])?;
+/// assert_ne!(a, b);
+/// assert!(contours_approx_eq(&a, &b, 1e-9).is_ok());
+/// assert!(contours_approx_eq(&a, &b, 1e-15).is_err());
+/// # Ok::<(), contour::Error>(())
+/// ```
+///
+/// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+/// [`ContourBuilder::contours_exploded`]: ../struct.ContourBuilder.html#method.contours_exploded
+/// [`Difference`]: enum.Difference.html
+pub fn contours_approx_eq(a: &[Feature], b: &[Feature], tolerance: f64) -> Result<(), Difference> {
+    let (a, b) = (by_threshold(a), by_threshold(b));
+    let thresholds = |groups: &[(f64, Vec<&[LinearRing]>)]| groups.iter().map(|(threshold, _)| *threshold).collect::<Vec<_>>();
+    let mut sorted = (thresholds(&a), thresholds(&b));
+    sorted.0.sort_by(f64::total_cmp);
+    sorted.1.sort_by(f64::total_cmp);
+    if sorted.0.len() != sorted.1.len() || sorted.0.iter().zip(&sorted.1).any(|(x, y)| x.total_cmp(y).is_ne()) {
+        return Err(Difference::Thresholds { first: thresholds(&a), second: thresholds(&b) });
+    }
+    for (threshold, first) in &a {
+        let second = &b.iter().find(|(t, _)| t.total_cmp(threshold).is_eq()).unwrap().1;
+        if first.len() != second.len() {
+            return Err(Difference::PolygonCount { threshold: *threshold, first: first.len(), second: second.len() });
+        }
+        let matches = matching(&exteriors(first), &exteriors(second));
+        for (p, (polygon, other)) in first.iter().zip(matches.into_iter().map(|i| second[i])).enumerate() {
+            if polygon.len() != other.len() {
+                return Err(Difference::RingCount { threshold: *threshold, polygon: p, first: polygon.len(), second: other.len() });
+            }
+            let holes = matching(&holes(polygon), &holes(other));
+            let pairs = polygon.iter().zip(other.iter().take(1).chain(holes.into_iter().map(|i| &other[i + 1])));
+            for (r, (ring, other)) in pairs.enumerate() {
+                compare_rings(ring, other, tolerance).map_err(|difference| match difference {
+                    RingDifference::Length(first, second) => {
+                        Difference::RingLength { threshold: *threshold, polygon: p, ring: r, first, second }
+                    }
+                    RingDifference::Vertex(vertex, first, second) => {
+                        Difference::Vertex { threshold: *threshold, polygon: p, ring: r, vertex, first, second }
+                    }
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Asserts that the given lists of Features are equal within `tolerance`
+/// (see [`contours_approx_eq`]), panicking with the first difference found otherwise.
+///
+/// [`contours_approx_eq`]: fn.contours_approx_eq.html
+#[track_caller]
+pub fn assert_contours_approx_eq(a: &[Feature], b: &[Feature], tolerance: f64) {
+    if let Err(difference) = contours_approx_eq(a, b, tolerance) {
+        panic!("contours differ (tolerance {}): {}", tolerance, difference);
+    }
+}
+
+// Groups the polygons of the features by threshold, in the order of their first feature.
+fn by_threshold(features: &[Feature]) -> Vec<(f64, Vec<&[LinearRing]>)> {
+    let mut groups: Vec<(f64, Vec<&[LinearRing]>)> = Vec::new();
+    for feature in features {
+        let threshold = feature
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.get("value")?.as_f64())
+            .unwrap_or(f64::NAN);
+        let polygons = match feature.geometry.as_ref().map(|g| &g.value) {
+            Some(Value::Polygon(polygon)) => vec![&polygon[..]],
+            Some(Value::MultiPolygon(polygons)) => polygons.iter().map(|polygon| &polygon[..]).collect(),
+            _ => Vec::new(),
+        };
+        match groups.iter_mut().find(|(t, _)| t.total_cmp(&threshold).is_eq()) {
+            Some((_, group)) => group.extend(polygons),
+            None => groups.push((threshold, polygons)),
+        }
+    }
+    groups
+}
+
+// The exterior rings of the polygons (empty for a polygon without rings).
+fn exteriors<'a>(polygons: &[&'a [LinearRing]]) -> Vec<&'a [Pt]> {
+    polygons.iter().map(|polygon| polygon.first().map_or(&[][..], |ring| &ring[..])).collect()
+}
+
+// The holes of the polygon.
+fn holes(polygon: &[LinearRing]) -> Vec<&[Pt]> {
+    polygon.iter().skip(1).map(|ring| &ring[..]).collect()
+}
+
+// Matches each of the `first` rings with the nearest of the `second` rings not matched
+// yet (by the distance of their centroids plus the difference of their areas),
+// returning the index of its match (there being as many `second` rings as `first` ones).
+fn matching(first: &[&[Pt]], second: &[&[Pt]]) -> Vec<usize> {
+    let key = |ring: &[Pt]| {
+        let points = open(ring);
+        let n = points.len().max(1) as f64;
+        let centroid = points.iter().fold([0., 0.], |c, p| [c[0] + p[0] / n, c[1] + p[1] / n]);
+        (centroid, area(points) / 2.)
+    };
+    let keys = second.iter().map(|ring| key(ring)).collect::<Vec<_>>();
+    let mut matched = vec![false; second.len()];
+    first
+        .iter()
+        .map(|ring| {
+            let (centroid, area) = key(ring);
+            let distance = |j: &usize| {
+                let (other, other_area) = keys[*j];
+                let d = (centroid[0] - other[0]).hypot(centroid[1] - other[1]) + (area - other_area).abs();
+                if d.is_nan() { f64::INFINITY } else { d }
+            };
+            let j = (0..second.len()).filter(|j| !matched[*j]).min_by(|i, j| distance(i).total_cmp(&distance(j))).unwrap_or(0);
+            matched[j] = true;
+            j
+        })
+        .collect()
+}
+
+enum RingDifference {
+    Length(usize, usize),
+    Vertex(usize, Pt, Pt),
+}
+
+// Compares the rings as cyclic sequences of positions.
+fn compare_rings(first: &[Pt], second: &[Pt], tolerance: f64) -> Result<(), RingDifference> {
+    let (first, second) = (open(first), open(second));
+    let n = first.len();
+    if n != second.len() {
+        return Err(RingDifference::Length(n, second.len()));
+    }
+    if n == 0 {
+        return Ok(());
+    }
+    // The rotation of the second ring matching the longest prefix of the first one.
+    let (mut best, mut best_rotation) = (0, 0);
+    for rotation in 0..n {
+        let matched = (0..n).take_while(|i| close(&first[*i], &second[(i + rotation) % n], tolerance)).count();
+        if matched == n {
+            return Ok(());
+        }
+        if matched > best {
+            best = matched;
+            best_rotation = rotation;
+        }
+    }
+    Err(RingDifference::Vertex(best, first[best].clone(), second[(best + best_rotation) % n].clone()))
+}
+
+// The positions of the ring, without its closing position.
+fn open(ring: &[Pt]) -> &[Pt] {
+    match ring.split_last() {
+        Some((last, rest)) if ring.len() > 1 && ring.first() == Some(last) => rest,
+        _ => ring,
+    }
+}
+
+fn close(a: &[f64], b: &[f64], tolerance: f64) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x == y || (x - y).abs() <= tolerance || (x.is_nan() && y.is_nan()))
+}
+
+/// Returns `len` pseudo-random values in `[0, 1)`, generated from `seed` by a [`XorShift`]
+/// generator: the same `seed` always gives the same values.
+///
+/// ```
+/// # use contour::testing::noise;
+/// let values = noise(100, 42);
+/// assert!(values.iter().all(|v| (0. ..1.).contains(v)));
+/// assert_eq!(values, noise(100, 42));
+/// assert_ne!(values, noise(100, 43));
+/// ```
+///
+/// [`XorShift`]: struct.XorShift.html
+pub fn noise(len: usize, seed: u64) -> Vec<f64> {
+    let mut rng = XorShift::new(seed);
+    (0..len).map(|_| rng.next_f64()).collect()
+}
+
+/// A xorshift pseudo-random generator (not suitable for cryptography), for reproducible
+/// randomized tests.
+#[derive(Clone, Debug)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// Creates a generator from `seed` (a `0` seed is taken as `1`).
+    pub fn new(seed: u64) -> Self {
+        XorShift(seed.max(1))
+    }
+
+    /// Returns the next pseudo-random 64-bit integer.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns the next pseudo-random value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,260 @@
+//! Contours of scattered points, computed on their Delaunay triangulation (a TIN,
+//! triangulated irregular network) rather than on a grid (only available with the `tin` feature).
+//!
+//! The values are linearly interpolated along the edges of the triangles (marching
+//! triangles): each triangle has at most one segment of the isoline of a threshold, and
+//! the segments are stitched into lines by the edges they end on. The polygons of a
+//! threshold are closed along the convex hull of the points.
+//!
+//! The triangulation is computed by the Bowyer-Watson algorithm, in `O(n²)` time
+//! at worst for `n` points.
+//!
+//! ```
+//! # use contour::tin::Tin;
+//! let tin = Tin::new(&[(0., 0., 0.), (2., 0., 2.), (2., 2., 2.), (0., 3., 0.)]);
+//! assert_eq!(tin.triangles().len(), 2);
+//! let features = tin.isolines(&[1.]);
+//! assert_eq!(features.len(), 1);
+//! ```
+
+use crate::contour::{LinearRing, Pt, group_holes};
+use geojson::{Feature, Geometry, Value};
+use serde_json::map::Map;
+use std::collections::{HashMap, HashSet};
+
+/// The Delaunay triangulation of scattered points and their values.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tin {
+    points: Vec<[f64; 2]>,
+    values: Vec<f64>,
+    triangles: Vec<[usize; 3]>,
+}
+
+// An endpoint of the segments: a point of the TIN, or the position of the threshold
+// on the edge between two points (the lowest index first).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Key {
+    Point(usize),
+    Edge(usize, usize),
+}
+
+impl Tin {
+    /// Triangulates the given `(x, y, value)` points. The points with non-finite coordinates
+    /// and the ones at the same coordinates as a previous point are left out. The NaN values
+    /// are below every threshold.
+    pub fn new(points: &[(f64, f64, f64)]) -> Self {
+        let mut seen = HashSet::new();
+        let (points, values): (Vec<[f64; 2]>, Vec<f64>) = points
+            .iter()
+            .filter(|(x, y, _)| x.is_finite() && y.is_finite() && seen.insert(((x + 0.).to_bits(), (y + 0.).to_bits())))
+            .map(|&(x, y, value)| ([x, y], value))
+            .unzip();
+        let triangles = triangulate(&points);
+        Tin { points, values, triangles }
+    }
+
+    /// Returns the coordinates of the points kept in the triangulation.
+    pub fn points(&self) -> &[[f64; 2]] {
+        &self.points
+    }
+
+    /// Returns the triangles, as the indices of their points (see [`points`]) in
+    /// counter-clockwise order (the y axis pointing up).
+    ///
+    /// [`points`]: #method.points
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    /// Computes a contour (a MultiPolygon Feature with its threshold as `value` property)
+    /// for each of the given thresholds, like [`ContourBuilder::contours`] does for a grid.
+    ///
+    /// [`ContourBuilder::contours`]: ../struct.ContourBuilder.html#method.contours
+    pub fn contours(&self, thresholds: &[f64]) -> Vec<Feature> {
+        thresholds
+            .iter()
+            .map(|&threshold| self.feature(threshold, Value::MultiPolygon(group_holes(self.rings(threshold)))))
+            .collect()
+    }
+
+    /// Computes the isolines (a MultiLineString Feature with its threshold as `value` property)
+    /// of each of the given thresholds, the higher values being on the right of the lines
+    /// (the y axis pointing up). The lines are closed, or end on the convex hull of the points.
+    pub fn isolines(&self, thresholds: &[f64]) -> Vec<Feature> {
+        thresholds
+            .iter()
+            .map(|&threshold| self.feature(threshold, Value::MultiLineString(self.lines(threshold))))
+            .collect()
+    }
+
+    fn feature(&self, threshold: f64, value: Value) -> Feature {
+        let mut properties = Map::with_capacity(1);
+        properties.insert(String::from("value"), serde_json::Value::from(threshold));
+        Feature {
+            bbox: None,
+            geometry: Some(Geometry::new(value)),
+            properties: Some(properties),
+            id: None,
+            foreign_members: None,
+        }
+    }
+
+    // The rings of the regions above the threshold, clockwise (holes counter-clockwise).
+    fn rings(&self, threshold: f64) -> Vec<LinearRing> {
+        let mut segments = self.segments(threshold);
+        // The parts of the convex hull above the threshold, walked clockwise.
+        let edges: Vec<(usize, usize)> = self.triangles.iter().flat_map(|t| (0..3).map(move |k| (t[k], t[(k + 1) % 3]))).collect();
+        let inner: HashSet<(usize, usize)> = edges.iter().map(|(a, b)| (*b, *a)).collect();
+        for &(a, b) in edges.iter().filter(|edge| !inner.contains(edge)) {
+            match (self.above(a, threshold), self.above(b, threshold)) {
+                (true, true) => segments.push((Key::Point(b), Key::Point(a))),
+                (false, true) => segments.push((Key::Point(b), edge(a, b))),
+                (true, false) => segments.push((edge(a, b), Key::Point(a))),
+                (false, false) => {}
+            }
+        }
+        stitch(&segments)
+            .into_iter()
+            .map(|keys| keys.iter().map(|key| self.position(*key, threshold)).collect())
+            .collect()
+    }
+
+    fn lines(&self, threshold: f64) -> Vec<LinearRing> {
+        stitch(&self.segments(threshold))
+            .into_iter()
+            .map(|keys| keys.iter().map(|key| self.position(*key, threshold)).collect())
+            .collect()
+    }
+
+    // The segments of the isoline in the triangles, going clockwise around the values above
+    // the threshold: from the edge going from below to above it (counter-clockwise), to the
+    // one going back below it.
+    fn segments(&self, threshold: f64) -> Vec<(Key, Key)> {
+        let mut segments = Vec::new();
+        for triangle in &self.triangles {
+            let (mut start, mut end) = (None, None);
+            for k in 0..3 {
+                let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+                match (self.above(a, threshold), self.above(b, threshold)) {
+                    (false, true) => start = Some(edge(a, b)),
+                    (true, false) => end = Some(edge(a, b)),
+                    _ => {}
+                }
+            }
+            if let (Some(start), Some(end)) = (start, end) {
+                segments.push((start, end));
+            }
+        }
+        segments
+    }
+
+    fn above(&self, i: usize, threshold: f64) -> bool {
+        self.values[i] >= threshold
+    }
+
+    fn position(&self, key: Key, threshold: f64) -> Pt {
+        match key {
+            Key::Point(i) => self.points[i].to_vec(),
+            Key::Edge(a, b) => {
+                let (below, above) = if self.above(a, threshold) { (b, a) } else { (a, b) };
+                let (v0, v1) = (self.values[below], self.values[above]);
+                let t = if v0.is_finite() && v1.is_finite() { (threshold - v0) / (v1 - v0) } else { 0.5 };
+                let (p0, p1) = (self.points[below], self.points[above]);
+                vec![p0[0] + t * (p1[0] - p0[0]), p0[1] + t * (p1[1] - p0[1])]
+            }
+        }
+    }
+}
+
+fn edge(a: usize, b: usize) -> Key {
+    Key::Edge(a.min(b), a.max(b))
+}
+
+// Stitches the segments by their endpoints, each endpoint starting at most one segment
+// and ending at most one. Returns the endpoints of each line, the closed ones ending
+// on their first endpoint.
+fn stitch(segments: &[(Key, Key)]) -> Vec<Vec<Key>> {
+    let next: HashMap<Key, usize> = segments.iter().enumerate().map(|(i, segment)| (segment.0, i)).collect();
+    let ends: HashSet<Key> = segments.iter().map(|segment| segment.1).collect();
+    let mut stitched = vec![false; segments.len()];
+    let mut lines = Vec::new();
+    // The open lines first, from their first segment, then the closed ones.
+    let firsts = (0..segments.len()).filter(|i| !ends.contains(&segments[*i].0)).chain(0..segments.len());
+    for first in firsts.collect::<Vec<_>>() {
+        if stitched[first] {
+            continue;
+        }
+        let mut line = Vec::new();
+        let mut i = first;
+        loop {
+            stitched[i] = true;
+            line.push(segments[i].0);
+            match next.get(&segments[i].1) {
+                Some(&j) if !stitched[j] => i = j,
+                _ => break,
+            }
+        }
+        line.push(segments[i].1);
+        lines.push(line);
+    }
+    lines
+}
+
+// The Delaunay triangles of the points, counter-clockwise, computed by the Bowyer-Watson
+// algorithm: the points are inserted one at a time in a triangle containing all of them,
+// replacing the triangles whose circumcircle contains the point by triangles joining it
+// to the boundary of the cavity.
+fn triangulate(points: &[[f64; 2]]) -> Vec<[usize; 3]> {
+    if points.len() < 3 {
+        return Vec::new();
+    }
+    let (mut min, mut max) = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+    for p in points {
+        min = [min[0].min(p[0]), min[1].min(p[1])];
+        max = [max[0].max(p[0]), max[1].max(p[1])];
+    }
+    let size = (max[0] - min[0]).max(max[1] - min[1]).max(1.);
+    let (cx, cy) = ((min[0] + max[0]) / 2., (min[1] + max[1]) / 2.);
+    let n = points.len();
+    let mut vertices = points.to_vec();
+    vertices.extend_from_slice(&[[cx - 100. * size, cy - 100. * size], [cx + 100. * size, cy - 100. * size], [cx, cy + 100. * size]]);
+
+    let mut triangles = vec![Triangle::new(&vertices, [n, n + 1, n + 2])];
+    for i in 0..n {
+        let p = vertices[i];
+        let (bad, good): (Vec<Triangle>, Vec<Triangle>) = triangles.into_iter().partition(|t| t.circumcircle_contains(p));
+        triangles = good;
+        // The edges of the cavity are the ones of a single bad triangle.
+        let edges: Vec<(usize, usize)> = bad.iter().flat_map(|t| (0..3).map(move |k| (t.vertices[k], t.vertices[(k + 1) % 3]))).collect();
+        let inner: HashSet<(usize, usize)> = edges.iter().map(|(a, b)| (*b, *a)).collect();
+        for &(a, b) in edges.iter().filter(|edge| !inner.contains(edge)) {
+            triangles.push(Triangle::new(&vertices, [a, b, i]));
+        }
+    }
+    triangles.into_iter().map(|t| t.vertices).filter(|t| t.iter().all(|v| *v < n)).collect()
+}
+
+struct Triangle {
+    vertices: [usize; 3],
+    center: [f64; 2],
+    radius2: f64,
+}
+
+impl Triangle {
+    fn new(points: &[[f64; 2]], vertices: [usize; 3]) -> Self {
+        let [a, b, c] = [points[vertices[0]], points[vertices[1]], points[vertices[2]]];
+        let d = 2. * (a[0] * (b[1] - c[1]) + b[0] * (c[1] - a[1]) + c[0] * (a[1] - b[1]));
+        let (a2, b2, c2) = (a[0] * a[0] + a[1] * a[1], b[0] * b[0] + b[1] * b[1], c[0] * c[0] + c[1] * c[1]);
+        // A degenerate (flat) triangle gets a NaN circumcircle, containing no point.
+        let center = [
+            (a2 * (b[1] - c[1]) + b2 * (c[1] - a[1]) + c2 * (a[1] - b[1])) / d,
+            (a2 * (c[0] - b[0]) + b2 * (a[0] - c[0]) + c2 * (b[0] - a[0])) / d,
+        ];
+        let radius2 = (a[0] - center[0]).powi(2) + (a[1] - center[1]).powi(2);
+        Triangle { vertices, center, radius2 }
+    }
+
+    fn circumcircle_contains(&self, p: [f64; 2]) -> bool {
+        (p[0] - self.center[0]).powi(2) + (p[1] - self.center[1]).powi(2) < self.radius2
+    }
+}
@@ -0,0 +1,79 @@
+use crate::contour::{CASES, Limits, LinearRing};
+use crate::error::{ErrorKind, Result, new_error};
+
+// Computes the rings like `IsoRingBuilder::compute_by` does (the same rings, in the same
+// order), by walking along each ring from the first of its positions found by scanning
+// the rows of the grid.
+//
+// The cells of the marching squares are designated as in `fill_cases`: the cell `(x, r)`
+// has the value at column `x` and row `r` as lower right corner, and the positions
+// of its segments are the ones of `CASES` shifted by `(x - 1, r - 1)`.
+//
+// The limits are checked as soon as a ring is traced, `vertices` being the number of
+// positions of the rings computed before (updated with the ones of the traced rings).
+pub(crate) fn trace_rings<V: Fn(usize) -> f64>(values: &V, threshold: f64, dx: usize, dy: usize, limits: &Limits, vertices: &mut usize) -> Result<Vec<LinearRing>> {
+    let above = |x: i64, r: i64| x >= 0 && r >= 0 && (x as usize) < dx && (r as usize) < dy && values(r as usize * dx + x as usize) >= threshold;
+    let case = |x: i64, r: i64| {
+        above(x - 1, r) as usize | (above(x, r) as usize) << 1 | (above(x, r - 1) as usize) << 2 | (above(x - 1, r - 1) as usize) << 3
+    };
+    // The index of the segment of the cell `(x, r)` starting at `p`, if any.
+    let line_from = |x: i64, r: i64, p: [f64; 2]| {
+        CASES[case(x, r)]
+            .iter()
+            .position(|line| line[0][0] + (x - 1) as f64 == p[0] && line[0][1] + (r - 1) as f64 == p[1])
+    };
+
+    // Whether the ring through the position between the values `x - 1` and `x` of the row `r`
+    // has been traced.
+    let mut traced = vec![false; (dx + 1) * dy];
+    let mut rings = Vec::new();
+    for r in 0..dy as i64 {
+        for x in 0..=dx as i64 {
+            if above(x - 1, r) == above(x, r) || traced[r as usize * (dx + 1) + x as usize] {
+                continue;
+            }
+            let seed = [x as f64, r as f64 + 0.5];
+            let (mut cell, mut line) = match (line_from(x, r, seed), line_from(x, r + 1, seed)) {
+                (Some(line), _) => ((x, r), line),
+                (None, Some(line)) => ((x, r + 1), line),
+                (None, None) => return Err(new_error(ErrorKind::Unexpected)),
+            };
+
+            // The positions of the ring and the key of its closing segment (the last one
+            // stitched by the marching squares), along with the position of its end.
+            let mut points: Vec<[f64; 2]> = Vec::new();
+            let mut closing = (0, 0);
+            loop {
+                let (cx, cr) = cell;
+                let segment = &CASES[case(cx, cr)][line];
+                let (ox, oy) = ((cx - 1) as f64, (cr - 1) as f64);
+                let (a, b) = ([segment[0][0] + ox, segment[0][1] + oy], [segment[1][0] + ox, segment[1][1] + oy]);
+                if a[0].fract() == 0. {
+                    traced[a[1] as usize * (dx + 1) + a[0] as usize] = true;
+                }
+                points.push(a);
+                closing = closing.max(((cr as usize * (dx + 1) + cx as usize) * 2 + line, points.len()));
+                if b == seed {
+                    break;
+                }
+                // The next segment is in the cell on the other side of the edge of `b`.
+                cell = match (segment[1][0], segment[1][1]) {
+                    (0.5, _) => (cx - 1, cr),
+                    (1.5, _) => (cx + 1, cr),
+                    (_, 0.5) => (cx, cr - 1),
+                    _ => (cx, cr + 1),
+                };
+                line = line_from(cell.0, cell.1, b).ok_or_else(|| new_error(ErrorKind::Unexpected))?;
+            }
+
+            // The ring starts at the end of its closing segment.
+            let n = points.len();
+            let ring = (0..=n).map(|i| points[(closing.1 + i) % n].to_vec()).collect();
+            rings.push((closing.0, ring));
+            *vertices += n + 1;
+            limits.check(rings.len(), *vertices)?;
+        }
+    }
+    rings.sort_by_key(|(key, _)| *key);
+    Ok(rings.into_iter().map(|(_, ring)| ring).collect())
+}
@@ -0,0 +1,195 @@
+use crate::contour::{LinearRing, Pt};
+use crate::geom::{orient2d, segment_contains};
+
+/// A segment of the polygons of a threshold, given by its indexes in the MultiPolygon
+/// (see [`Intersection`]).
+///
+/// [`Intersection`]: struct.Intersection.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SegmentId {
+    /// The index of the polygon.
+    pub polygon: usize,
+    /// The index of the ring in the polygon: `0` for its exterior ring, then its holes.
+    pub ring: usize,
+    /// The index of the segment in the ring: the one starting at the position of this index.
+    pub segment: usize,
+}
+
+/// Two segments making the polygons of a threshold invalid, as found by
+/// [`Validation::Check`]: segments of two rings crossing or overlapping each other,
+/// or segments of the same ring meeting anywhere else than at their common position.
+///
+/// The indexes are the ones of the polygons before they are georeferenced, reprojected
+/// or clipped.
+///
+/// [`Validation::Check`]: enum.Validation.html#variant.Check
+#[derive(Clone, Debug, PartialEq)]
+pub struct Intersection {
+    /// The threshold of the polygons.
+    pub threshold: f64,
+    /// The first of the segments (in the order of the polygons, rings and segments).
+    pub first: SegmentId,
+    /// The second of the segments.
+    pub second: SegmentId,
+}
+
+// How two segments meet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Contact {
+    None,
+    // At a single position, an end of one of them.
+    Point,
+    // At a single position inside both of them.
+    Crossing,
+    // Along a part of both of them (collinear segments).
+    Overlap,
+}
+
+// A segment of a ring, from its position `start` to its position `end` (the next
+// position different from the one of `start`), with its bounding box.
+struct Edge {
+    ring: usize,
+    start: usize,
+    end: usize,
+    // The index of the edge among the ones of its ring.
+    rank: usize,
+    min: [f64; 2],
+    max: [f64; 2],
+}
+
+// Finds the pairs of segments making the rings invalid, as `(ring, segment)` pairs
+// sorted by ring then segment (the segments starting at a repeated position, of zero
+// length, are left out), with a sweep along the x axis over the bounding boxes
+// of the segments: only the ones overlapping are tested.
+//
+// Two rings are allowed to touch at single positions (like two polygons or a polygon
+// and one of its holes may), but not to cross or overlap; a ring isn't allowed to touch
+// itself. The segments with a NaN coordinate are ignored.
+pub(crate) fn find_intersections<R: AsRef<[Pt]>>(rings: &[R]) -> Vec<[(usize, usize); 2]> {
+    let mut edges = Vec::new();
+    // The number of edges of each ring.
+    let mut counts = Vec::with_capacity(rings.len());
+    for (r, ring) in rings.iter().enumerate() {
+        let ring = ring.as_ref();
+        let first = edges.len();
+        let mut start = 0;
+        for end in 1..ring.len() {
+            let (a, b) = (&ring[start], &ring[end]);
+            if a[..2] == b[..2] {
+                continue;
+            }
+            if !(a[0].is_nan() || a[1].is_nan() || b[0].is_nan() || b[1].is_nan()) {
+                edges.push(Edge {
+                    ring: r,
+                    start,
+                    end,
+                    rank: edges.len() - first,
+                    min: [a[0].min(b[0]), a[1].min(b[1])],
+                    max: [a[0].max(b[0]), a[1].max(b[1])],
+                });
+            }
+            start = end;
+        }
+        counts.push(edges.len() - first);
+    }
+
+    let mut order = (0..edges.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| edges[*a].min[0].total_cmp(&edges[*b].min[0]));
+    let mut active: Vec<usize> = Vec::new();
+    let mut found = Vec::new();
+    for i in order {
+        let e = &edges[i];
+        active.retain(|j| edges[*j].max[0] >= e.min[0]);
+        for &j in &active {
+            let f = &edges[j];
+            if f.max[1] < e.min[1] || f.min[1] > e.max[1] {
+                continue;
+            }
+            let (p, q) = (rings[e.ring].as_ref(), rings[f.ring].as_ref());
+            let contact = contact(&p[e.start], &p[e.end], &q[f.start], &q[f.end]);
+            let invalid = if e.ring != f.ring {
+                contact == Contact::Crossing || contact == Contact::Overlap
+            } else {
+                let n = counts[e.ring];
+                let gap = e.rank.abs_diff(f.rank);
+                if gap == 1 || gap == n - 1 {
+                    // Consecutive segments share a position.
+                    contact == Contact::Overlap
+                } else {
+                    contact != Contact::None
+                }
+            };
+            if invalid {
+                let pair = [(e.ring, e.start), (f.ring, f.start)];
+                found.push(if pair[0] < pair[1] { pair } else { [pair[1], pair[0]] });
+            }
+        }
+        active.push(i);
+    }
+    found.sort_unstable();
+    found
+}
+
+// Moves the positions of the segments found by `find_intersections` back to their
+// positions in `original` (the same rings, before smoothing), until none is found. This
+// always ends, with valid rings: the segments of the unsmoothed rings never meet (except
+// consecutive ones), and the smoothing keeps each position on its edge of the grid, so
+// the segments only meet the other segment of their cell, for the saddle cases.
+// The rings collapsed to a single position are moved back too. Returns the number
+// of rings moved back (partly or entirely).
+pub(crate) fn repair(rings: &mut [LinearRing], original: &[LinearRing]) -> usize {
+    let mut repaired = vec![false; rings.len()];
+    for ((ring, original), repaired) in rings.iter_mut().zip(original).zip(repaired.iter_mut()) {
+        if ring.iter().all(|point| point[..2] == ring[0][..2]) {
+            *repaired = *ring != *original;
+            ring.clone_from(original);
+        }
+    }
+    loop {
+        let mut moved = false;
+        for (r, start) in find_intersections(rings).into_iter().flatten() {
+            let (ring, original) = (&mut rings[r], &original[r]);
+            let last = ring.len() - 1;
+            let end = (start + 1..last).find(|i| ring[*i][..2] != ring[start][..2]).unwrap_or(last);
+            // The last position of the ring is the same as the first one.
+            for i in (start..=end).map(|i| if i == last { 0 } else { i }) {
+                if ring[i] != original[i] {
+                    ring[i] = original[i].clone();
+                    moved = true;
+                    repaired[r] = true;
+                }
+            }
+            ring[last] = ring[0].clone();
+        }
+        if !moved {
+            return repaired.iter().filter(|repaired| **repaired).count();
+        }
+    }
+}
+
+// Tells how the segments from `p1` to `p2` and from `q1` to `q2` meet (`p1` and `p2`
+// being different), with exact orientation predicates.
+fn contact(p1: &[f64], p2: &[f64], q1: &[f64], q2: &[f64]) -> Contact {
+    let sign = |value: f64| (value > 0.) as i32 - (value < 0.) as i32;
+    let (d1, d2) = (sign(orient2d(q1, q2, p1)), sign(orient2d(q1, q2, p2)));
+    let (d3, d4) = (sign(orient2d(p1, p2, q1)), sign(orient2d(p1, p2, q2)));
+    if d1 * d2 < 0 && d3 * d4 < 0 {
+        Contact::Crossing
+    } else if d1 == 0 && d2 == 0 && d3 == 0 && d4 == 0 {
+        // Compares the extents of the collinear segments along an axis they aren't
+        // perpendicular to.
+        let axis = if p1[0] != p2[0] { 0 } else { 1 };
+        let (low, high) = (q1[axis].min(q2[axis]).max(p1[axis].min(p2[axis])), q1[axis].max(q2[axis]).min(p1[axis].max(p2[axis])));
+        if low < high {
+            Contact::Overlap
+        } else if low == high {
+            Contact::Point
+        } else {
+            Contact::None
+        }
+    } else if segment_contains(q1, q2, p1) || segment_contains(q1, q2, p2) || segment_contains(p1, p2, q1) || segment_contains(p1, p2, q2) {
+        Contact::Point
+    } else {
+        Contact::None
+    }
+}
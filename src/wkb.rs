@@ -0,0 +1,144 @@
+//! Well-Known Binary output of the polygons, as extended WKB (EWKB) for PostGIS.
+//!
+//! The geometries are written in little-endian byte order (NDR). EWKB flags the geometry type
+//! with bits instead of the ISO offsets: `0x20000000` when an SRID follows the type, and
+//! `0x80000000` when the positions have a z coordinate (see [`ContourBuilder::z`]). A threshold
+//! without any polygon is written as an empty MultiPolygon, with no Polygon.
+//!
+//! ```
+//! # use contour::ContourBuilder;
+//! # use contour::wkb::write_copy_rows;
+//! let c = ContourBuilder::new(3, 3, false);
+//! let rows = c.contours_ewkb(&[0., 0., 0., 0., 1., 0., 0., 0., 0.], &[0.5], 4326)?;
+//! let mut copy = Vec::new();
+//! write_copy_rows(&mut copy, &rows)?;
+//! // COPY contours (value, geom) FROM STDIN
+//! assert!(String::from_utf8(copy).unwrap().starts_with("0.5\t0106000020E6100000"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`ContourBuilder::z`]: ../struct.ContourBuilder.html#method.z
+
+use crate::contour::LinearRing;
+use std::fmt::Write as _;
+use std::io;
+
+#[cfg(feature = "gpkg")]
+const WKB_LINESTRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+#[cfg(feature = "gpkg")]
+const WKB_MULTILINESTRING: u32 = 5;
+const WKB_MULTIPOLYGON: u32 = 6;
+// Added to the geometry types of the geometries with z coordinates in ISO WKB.
+#[cfg(feature = "gpkg")]
+const ISO_Z: u32 = 1000;
+const EWKB_Z: u32 = 0x8000_0000;
+const EWKB_SRID: u32 = 0x2000_0000;
+
+// How the z coordinates and the SRID of a geometry are flagged in its type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Dialect {
+    // The dialect of GeoPackages, with the ISO offsets.
+    #[cfg(feature = "gpkg")]
+    Iso,
+    Extended { srid: Option<u32> },
+}
+
+impl Dialect {
+    // The dialect of the parts of a geometry, which carry no SRID.
+    fn part(self) -> Self {
+        match self {
+            #[cfg(feature = "gpkg")]
+            Dialect::Iso => Dialect::Iso,
+            Dialect::Extended { .. } => Dialect::Extended { srid: None },
+        }
+    }
+}
+
+/// Returns the EWKB of the MultiPolygon of the given `polygons`, with the given `srid`, if any.
+pub fn ewkb_multipolygon(polygons: &[Vec<LinearRing>], srid: Option<u32>) -> Vec<u8> {
+    let mut wkb = Vec::new();
+    write_multipolygon(&mut wkb, polygons, has_z(polygons.iter().flatten()), Dialect::Extended { srid });
+    wkb
+}
+
+/// Returns the uppercase hexadecimal encoding of `bytes`, as printed by PostGIS
+/// (and accepted by its geometry input).
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(hex, "{:02X}", byte).unwrap();
+    }
+    hex
+}
+
+/// Writes the given rows of values and geometries (as returned by
+/// [`ContourBuilder::contours_ewkb`]) in the text format of `COPY ... FROM STDIN`: one line
+/// per row, with the value and the hexadecimal EWKB of the geometry separated by a tab.
+///
+/// [`ContourBuilder::contours_ewkb`]: ../struct.ContourBuilder.html#method.contours_ewkb
+pub fn write_copy_rows<W: io::Write>(mut writer: W, rows: &[(f64, Vec<u8>)]) -> io::Result<()> {
+    for (value, wkb) in rows {
+        writeln!(writer, "{}\t{}", value, to_hex(wkb))?;
+    }
+    Ok(())
+}
+
+pub(crate) fn has_z<'a, I: Iterator<Item = &'a LinearRing>>(mut rings: I) -> bool {
+    rings.any(|ring| ring.iter().any(|point| point.len() > 2))
+}
+
+pub(crate) fn write_multipolygon(wkb: &mut Vec<u8>, polygons: &[Vec<LinearRing>], z: bool, dialect: Dialect) {
+    write_header(wkb, WKB_MULTIPOLYGON, z, dialect);
+    write_count(wkb, polygons.len());
+    for polygon in polygons {
+        write_header(wkb, WKB_POLYGON, z, dialect.part());
+        write_count(wkb, polygon.len());
+        for ring in polygon {
+            write_points(wkb, ring, z);
+        }
+    }
+}
+
+#[cfg(feature = "gpkg")]
+pub(crate) fn write_multilinestring(wkb: &mut Vec<u8>, rings: &[&LinearRing], z: bool, dialect: Dialect) {
+    write_header(wkb, WKB_MULTILINESTRING, z, dialect);
+    write_count(wkb, rings.len());
+    for ring in rings {
+        write_header(wkb, WKB_LINESTRING, z, dialect.part());
+        write_points(wkb, ring, z);
+    }
+}
+
+// Writes the byte order (little-endian), the type of a geometry and its SRID, if any.
+fn write_header(wkb: &mut Vec<u8>, geometry_type: u32, z: bool, dialect: Dialect) {
+    wkb.push(1);
+    match dialect {
+        #[cfg(feature = "gpkg")]
+        Dialect::Iso => wkb.extend_from_slice(&(geometry_type + if z { ISO_Z } else { 0 }).to_le_bytes()),
+        Dialect::Extended { srid } => {
+            let mut flags = if z { EWKB_Z } else { 0 };
+            if srid.is_some() {
+                flags |= EWKB_SRID;
+            }
+            wkb.extend_from_slice(&(geometry_type | flags).to_le_bytes());
+            if let Some(srid) = srid {
+                wkb.extend_from_slice(&srid.to_le_bytes());
+            }
+        }
+    }
+}
+
+fn write_count(wkb: &mut Vec<u8>, count: usize) {
+    wkb.extend_from_slice(&(count as u32).to_le_bytes());
+}
+
+fn write_points(wkb: &mut Vec<u8>, ring: &[Vec<f64>], z: bool) {
+    write_count(wkb, ring.len());
+    let dims = if z { 3 } else { 2 };
+    for point in ring {
+        for i in 0..dims {
+            wkb.extend_from_slice(&point.get(i).copied().unwrap_or(0.).to_le_bytes());
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::geom::ring_contains;
+use crate::contour::LinearRing;
+use crate::error::Result;
+use serde_json::map::Map;
+use serde_json::to_value;
+
+/// Statistics of the grid values inside polygons.
+///
+/// The value at column `x` and row `y` is inside a polygon when the center
+/// of its cell, `(x + 0.5, y + 0.5)` in grid coordinates, is inside the exterior
+/// ring of the polygon (or on it) and isn't strictly inside one of its holes.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ZonalStats {
+    cells: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for ZonalStats {
+    fn default() -> Self {
+        ZonalStats {
+            cells: 0,
+            sum: 0.,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl ZonalStats {
+    /// Accumulates the values inside the given polygon (in grid coordinates).
+    pub fn add_polygon<V: Fn(usize) -> f64>(&mut self, values: &V, dx: u32, dy: u32, polygon: &[LinearRing]) {
+        let shell = match polygon.first() {
+            Some(shell) if !shell.is_empty() => shell,
+            _ => return,
+        };
+        let (mut x0, mut y0, mut x1, mut y1) = (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for p in shell {
+            x0 = x0.min(p[0]);
+            y0 = y0.min(p[1]);
+            x1 = x1.max(p[0]);
+            y1 = y1.max(p[1]);
+        }
+        let range = |min: f64, max: f64, len: u32| {
+            let start = (min - 0.5).ceil().max(0.) as u32;
+            let end = ((max - 0.5).floor() + 1.).max(0.).min(len as f64) as u32;
+            start..end
+        };
+        for y in range(y0, y1, dy) {
+            for x in range(x0, x1, dx) {
+                let center = [x as f64 + 0.5, y as f64 + 0.5];
+                if ring_contains(shell, &center) != -1 && polygon[1..].iter().all(|hole| ring_contains(hole, &center) != 1) {
+                    let value = values(y as usize * dx as usize + x as usize);
+                    self.cells += 1;
+                    self.sum += value;
+                    self.min = self.min.min(value);
+                    self.max = self.max.max(value);
+                }
+            }
+        }
+    }
+
+    /// Writes the statistics in the `cells`, `mean`, `min` and `max` properties
+    /// (the last three being `null` when there is no cell).
+    pub fn insert_into(&self, properties: &mut Map<String, serde_json::Value>) -> Result<()> {
+        let stat = |value: f64| if self.cells > 0 { Some(value) } else { None };
+        properties.insert(String::from("cells"), to_value(self.cells)?);
+        properties.insert(String::from("mean"), to_value(stat(self.sum / self.cells as f64))?);
+        properties.insert(String::from("min"), to_value(stat(self.min))?);
+        properties.insert(String::from("max"), to_value(stat(self.max))?);
+        Ok(())
+    }
+}
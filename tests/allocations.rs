@@ -0,0 +1,169 @@
+use contour::{ContourBuilder, contour_rings};
+use contour::testing::noise;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts the allocations and reallocations of each thread, so that the tests
+// running in parallel don't count the allocations of one another.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    // The count of a thread being destroyed is gone.
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// The allocations made by `f` on the current thread.
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn capacity_hint_reduces_allocations() {
+    // Concentric rings around the center of the grid.
+    let values = (0..100 * 100)
+        .map(|i| {
+            let (x, y) = ((i % 100) as f64 - 49.5, (i / 100) as f64 - 49.5);
+            (x * x + y * y).sqrt()
+        })
+        .collect::<Vec<_>>();
+    let thresholds = [10., 20., 30., 40.];
+
+    let c = ContourBuilder::new(100, 100, false);
+    let (rings, ring_len) = c.capacity_hint_for(&values, &thresholds).unwrap();
+    assert_eq!(rings, 2);
+    let hinted = c.with_capacity_hint(rings, ring_len);
+    let c = ContourBuilder::new(100, 100, false);
+
+    let expected = c.contours(&values, &thresholds).unwrap();
+    let mut res = Vec::new();
+    let without_hint = allocations(|| res = c.contours(&values, &thresholds).unwrap());
+    assert_eq!(res, expected);
+    let with_hint = allocations(|| res = hinted.contours(&values, &thresholds).unwrap());
+    assert_eq!(res, expected);
+    assert!(with_hint < without_hint, "{} allocations with the hint, {} without", with_hint, without_hint);
+}
+
+#[test]
+fn short_rings_allocate_once_per_position() {
+    let values = noise(300 * 300, 0x2545_f491_4f6c_dd1d);
+    let mut rings = Vec::new();
+    let count = allocations(|| rings = contour_rings(&values, 0.7, 300, 300).unwrap());
+    let positions = rings.iter().map(|ring| ring.len()).sum::<usize>();
+    assert!(rings.len() > 10000);
+    // One allocation per position and per ring of the output, plus a few for the whole grid.
+    let overhead = count - positions - rings.len();
+    assert!(overhead < 256, "{} allocations for {} positions in {} rings", count, positions, rings.len());
+}
+
+#[test]
+fn short_rings_are_stitched_inline() {
+    // A row of 2000 single value blobs, whose rings are all being stitched at the same time.
+    let values = (0..6000 * 3).map(|i| if i / 6000 == 1 && i % 3 == 1 { 1. } else { 0. }).collect::<Vec<_>>();
+    let c = ContourBuilder::new(6000, 3, false);
+    // Hinting rings longer than the inline capacity of their pieces allocates the pieces
+    // on the heap, as all of them were before.
+    let heap = c.clone().with_capacity_hint(0, 17);
+    let (mut res, mut expected) = (Vec::new(), Vec::new());
+    let inline_count = allocations(|| res = c.contours(&values, &[0.5]).unwrap());
+    let heap_count = allocations(|| expected = heap.contours(&values, &[0.5]).unwrap());
+    assert_eq!(res, expected);
+    let rings = match res[0].geometry.as_ref().map(|g| &g.value) {
+        Some(geojson::Value::MultiPolygon(polygons)) => polygons.len(),
+        _ => panic!("no MultiPolygon"),
+    };
+    assert_eq!(rings, 2000);
+    // At least one allocation less per ring.
+    assert!(inline_count + rings <= heap_count, "{} allocations inline, {} on the heap", inline_count, heap_count);
+}
+
+#[test]
+fn fragments_are_reused_across_thresholds() {
+    let values = noise(200 * 200, 0x2545_f491_4f6c_dd1d);
+    let thresholds = (1..10).map(|t| t as f64 / 10.).collect::<Vec<_>>();
+    let c = ContourBuilder::new(200, 200, false);
+    let expected = thresholds
+        .iter()
+        .flat_map(|t| c.contours(&values, &[*t]).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut res = Vec::new();
+    let count = allocations(|| res = c.contours(&values, &thresholds).unwrap());
+    assert_eq!(res, expected);
+    let (mut positions, mut rings) = (0, 0);
+    for feature in &res {
+        if let Some(geojson::Value::MultiPolygon(ref polygons)) = feature.geometry.as_ref().map(|g| &g.value) {
+            rings += polygons.iter().map(|polygon| polygon.len()).sum::<usize>();
+            positions += polygons.iter().flatten().map(|ring| ring.len()).sum::<usize>();
+        }
+    }
+    // One allocation per position and about two per ring (the ring and its polygon),
+    // the buffers of the fragments coming from the pool.
+    assert!(count < positions + 3 * rings, "{} allocations for {} positions and {} rings", count, positions, rings);
+}
+
+#[test]
+fn polygons_are_allocated_once() {
+    // Peaks and pits: polygons with many holes for the negative thresholds.
+    let values = (0..60 * 60)
+        .map(|i| {
+            let (x, y) = ((i % 60) as f64 / 2., (i / 60) as f64 / 2.);
+            x.sin() * y.sin()
+        })
+        .collect::<Vec<_>>();
+    let thresholds = (0..20).map(|t| -0.95 + t as f64 * 0.1).collect::<Vec<_>>();
+    let c = ContourBuilder::new(60, 60, true);
+    let expected = thresholds
+        .iter()
+        .flat_map(|t| c.contours(&values, &[*t]).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut res = Vec::new();
+    let count = allocations(|| res = c.contours(&values, &thresholds).unwrap());
+    assert_eq!(res, expected);
+    let (mut positions, mut rings, mut polygons) = (0, 0, 0);
+    for feature in &res {
+        if let Some(geojson::Value::MultiPolygon(ref p)) = feature.geometry.as_ref().map(|g| &g.value) {
+            polygons += p.len();
+            rings += p.iter().map(|polygon| polygon.len()).sum::<usize>();
+            positions += p.iter().flatten().map(|ring| ring.len()).sum::<usize>();
+        }
+    }
+    assert!(polygons < rings);
+    // One allocation per position, ring and polygon (with its holes), the grouping of the
+    // rings reusing its buffers: the rest is a few allocations per threshold.
+    let overhead = count - positions - rings - polygons;
+    assert!(
+        overhead < 32 * thresholds.len(),
+        "{} allocations for {} positions, {} rings and {} polygons over {} thresholds",
+        count,
+        positions,
+        rings,
+        polygons,
+        thresholds.len()
+    );
+}
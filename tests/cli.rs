@@ -0,0 +1,117 @@
+#![cfg(feature = "cli")]
+
+use assert_cmd::Command;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+// Writes a file in the given directory, removed with it at the end of the test.
+fn fixture(dir: &TempDir, name: &str, content: &[u8]) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn contour() -> Command {
+    Command::cargo_bin("contour").unwrap()
+}
+
+const CSV: &str = "\
+0,0,0,0,0
+0,1,1,1,0
+0,1,1,1,0
+0,0,0,0,0
+";
+
+#[test]
+fn csv_to_geojson() {
+    let dir = TempDir::new().unwrap();
+    let input = fixture(&dir, "grid.csv", CSV.as_bytes());
+    let output = contour().arg(&input).args(["--thresholds", "0.5"]).assert().success().get_output().clone();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["type"], "FeatureCollection");
+    assert_eq!(json["features"].as_array().unwrap().len(), 1);
+    assert_eq!(json["features"][0]["properties"]["value"], 0.5);
+    assert_eq!(json["features"][0]["geometry"]["coordinates"][0][0][0], serde_json::json!([4., 2.5]));
+}
+
+#[test]
+fn raw_f32_with_origin_and_step_to_file() {
+    let bytes = CSV
+        .split([',', '\n'])
+        .filter(|v| !v.is_empty())
+        .flat_map(|v| v.parse::<f32>().unwrap().to_le_bytes().to_vec())
+        .collect::<Vec<u8>>();
+    let dir = TempDir::new().unwrap();
+    let input = fixture(&dir, "grid.f32", &bytes);
+    let out = dir.path().join("out.geojson");
+    contour()
+        .arg(&input)
+        .args(["--format", "f32", "--width", "5", "--height", "4", "--levels", "1"])
+        .args(["--origin", "100,50", "--step", "10,-10", "--smooth", "--output"])
+        .arg(&out)
+        .assert()
+        .success();
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+    assert_eq!(json["features"][0]["properties"]["value"], 0.5);
+    assert_eq!(json["features"][0]["geometry"]["coordinates"][0][0][0], serde_json::json!([140., 25.]));
+}
+
+#[test]
+fn csv_from_stdin() {
+    let output = contour().args(["-", "--thresholds", "0.5"]).write_stdin(CSV).assert().success().get_output().clone();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["features"].as_array().unwrap().len(), 1);
+}
+
+// Runs the tool on the given arguments, expecting it to exit with the status 1
+// and an error message containing `message`.
+fn fails(args: &[&str], stdin: &str, message: &str) {
+    let output = contour().args(args).write_stdin(stdin).assert().failure().code(1).get_output().clone();
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("error: "), "{}", stderr);
+    assert!(stderr.contains(message), "{}", stderr);
+}
+
+#[test]
+fn bad_dimensions() {
+    let dir = TempDir::new().unwrap();
+    let input = fixture(&dir, "short.f64", &[0; 24]);
+    let input = input.to_str().unwrap();
+    fails(
+        &[input, "--format", "f64", "--width", "2", "--height", "2", "--thresholds", "0.5"],
+        "",
+        "the input holds 24 bytes but a 2x2 grid of 8-byte floats needs 32",
+    );
+}
+
+#[test]
+fn unreadable_file() {
+    fails(&["/nonexistent/grid.csv", "--thresholds", "0.5"], "", "cannot read '/nonexistent/grid.csv'");
+}
+
+#[test]
+fn ragged_csv() {
+    fails(&["-", "--thresholds", "0.5"], "0,0,0\n0,1\n", "Row 2 has 2 columns, expected 3");
+}
+
+#[test]
+fn bad_csv_value() {
+    fails(&["-", "--thresholds", "0.5"], "0,0,0\n0,x,0\n", "Invalid number \"x\" at row 2, column 2");
+}
+
+#[test]
+fn bad_threshold() {
+    fails(&["-", "--thresholds", "0.5,high"], CSV, "invalid value 'high' for --thresholds");
+}
+
+#[test]
+fn missing_thresholds() {
+    fails(&["-"], CSV, "one of --thresholds or --levels is required");
+}
+
+#[test]
+fn unknown_option() {
+    fails(&["-", "--thresholds", "0.5", "--color"], CSV, "unknown option --color");
+}